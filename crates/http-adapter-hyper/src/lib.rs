@@ -0,0 +1,153 @@
+#![deny(
+    unreachable_pub,
+    clippy::dbg_macro,
+    clippy::print_stdout,
+    clippy::print_stderr
+)]
+
+//! A [`plex_api::HttpTransport`] that sends requests using hyper and rustls
+//! instead of isahc and curl.
+//!
+//! ```no_run
+//! use http_adapter_hyper::HyperTransport;
+//! use plex_api::HttpClientBuilder;
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let client = HttpClientBuilder::default()
+//!     .set_transport(HyperTransport::new()?)
+//!     .build()
+//!     .unwrap();
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! hyper needs to run within a tokio runtime.
+use bytes::Bytes;
+use futures::{future::BoxFuture, io::AsyncReadExt, stream, TryStreamExt};
+use http_body_util::{combinators::UnsyncBoxBody, BodyDataStream, BodyExt, Empty, StreamBody};
+use hyper::body::Frame;
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use hyper_util::{
+    client::legacy::{connect::HttpConnector, Client},
+    rt::TokioExecutor,
+};
+use isahc::{AsyncBody, Request as IsahcRequest, Response as IsahcResponse};
+use plex_api::{Error, HttpTransport, Result};
+use std::{io, time::Duration};
+
+type Body = UnsyncBoxBody<Bytes, io::Error>;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Sends requests using a hyper client. Clones share the connection pool.
+#[derive(Clone, Debug)]
+pub struct HyperTransport {
+    client: Client<HttpsConnector<HttpConnector>, Body>,
+    timeout: Option<Duration>,
+}
+
+impl HyperTransport {
+    /// Creates a transport trusting the platform's root certificates.
+    pub fn new() -> io::Result<Self> {
+        let connector = HttpsConnectorBuilder::new()
+            .with_native_roots()?
+            .https_or_http()
+            .enable_http1()
+            .enable_http2()
+            .build();
+
+        Ok(Self::from_client(
+            Client::builder(TokioExecutor::new()).build(connector),
+        ))
+    }
+
+    /// Uses an existing hyper client, e.g. one with custom TLS settings.
+    pub fn from_client(client: Client<HttpsConnector<HttpConnector>, Body>) -> Self {
+        Self {
+            client,
+            timeout: None,
+        }
+    }
+
+    /// How long to wait for the response headers. The timeouts configured on
+    /// plex-api's requests are specific to isahc, so they don't apply here.
+    #[must_use]
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    async fn execute(&self, request: IsahcRequest<AsyncBody>) -> Result<IsahcResponse<AsyncBody>> {
+        let request = convert_request(request)?;
+
+        let response = self.client.request(request);
+        let response = match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, response)
+                .await
+                .map_err(|_| Error::Timeout)?,
+            None => response.await,
+        }
+        .map_err(io::Error::other)?;
+
+        convert_response(response)
+    }
+}
+
+impl HttpTransport for HyperTransport {
+    fn send(
+        &self,
+        request: IsahcRequest<AsyncBody>,
+    ) -> BoxFuture<'_, Result<IsahcResponse<AsyncBody>>> {
+        Box::pin(self.execute(request))
+    }
+}
+
+/// plex-api uses the `http` 0.2 types re-exported by isahc, while hyper uses
+/// `http` 1.x, so requests are rebuilt part by part.
+fn convert_request(request: IsahcRequest<AsyncBody>) -> Result<http::Request<Body>> {
+    let (parts, body) = request.into_parts();
+
+    let mut builder = http::Request::builder()
+        .method(parts.method.as_str())
+        .uri(parts.uri.to_string());
+    for (name, value) in &parts.headers {
+        builder = builder.header(name.as_str(), value.as_bytes());
+    }
+    if let Some(len) = body.len().filter(|len| *len > 0) {
+        if !parts.headers.contains_key("content-length") {
+            builder = builder.header("content-length", len);
+        }
+    }
+
+    let body = if body.is_empty() {
+        Empty::new().map_err(|never| match never {}).boxed_unsync()
+    } else {
+        let chunks = stream::try_unfold(body, |mut body| async move {
+            let mut chunk = vec![0; CHUNK_SIZE];
+            let read = body.read(&mut chunk).await?;
+            chunk.truncate(read);
+            Ok::<_, io::Error>((read > 0).then(|| (Frame::data(Bytes::from(chunk)), body)))
+        });
+        BodyExt::boxed_unsync(StreamBody::new(chunks))
+    };
+
+    Ok(builder.body(body)?)
+}
+
+fn convert_response(
+    response: http::Response<hyper::body::Incoming>,
+) -> Result<IsahcResponse<AsyncBody>> {
+    let (parts, body) = response.into_parts();
+
+    let mut builder = IsahcResponse::builder().status(parts.status.as_u16());
+    for (name, value) in &parts.headers {
+        builder = builder.header(name.as_str(), value.as_bytes());
+    }
+
+    let body = BodyDataStream::new(body)
+        .map_err(io::Error::other)
+        .into_async_read();
+    Ok(builder.body(AsyncBody::from_reader(body))?)
+}
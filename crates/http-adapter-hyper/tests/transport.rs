@@ -0,0 +1,79 @@
+use http_adapter_hyper::HyperTransport;
+use httpmock::{
+    Method::{GET, POST},
+    MockServer,
+};
+use plex_api::{Error, HttpClientBuilder};
+use std::time::Duration;
+
+#[tokio::test]
+async fn requests() {
+    let mock_server = MockServer::start_async().await;
+    let client = HttpClientBuilder::new(mock_server.base_url())
+        .set_transport(HyperTransport::new().unwrap())
+        .set_x_plex_token("token")
+        .build()
+        .unwrap();
+
+    let m = mock_server.mock(|when, then| {
+        when.method(GET)
+            .path("/identity")
+            .header("X-Plex-Token", "token")
+            .header("Accept", "application/json");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{"MediaContainer": {"size": 0}}"#);
+    });
+    let response: serde_json::Value = client.get("/identity").json().await.unwrap();
+    assert_eq!(response["MediaContainer"]["size"], 0);
+    m.assert();
+
+    let m = mock_server.mock(|when, then| {
+        when.method(POST)
+            .path("/form")
+            .header("Content-Length", "7")
+            .body("a=1&b=2");
+        then.status(200);
+    });
+    client
+        .post("/form")
+        .form(&[("a", "1"), ("b", "2")])
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
+    m.assert();
+
+    mock_server.mock(|when, then| {
+        when.method(GET).path("/missing");
+        then.status(404);
+    });
+    let error = client.get("/missing").consume().await.unwrap_err();
+    assert!(matches!(
+        error,
+        Error::UnexpectedApiResponse {
+            status_code: 404,
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn timeout() {
+    let mock_server = MockServer::start_async().await;
+    let client = HttpClientBuilder::new(mock_server.base_url())
+        .set_transport(
+            HyperTransport::new()
+                .unwrap()
+                .timeout(Duration::from_millis(100)),
+        )
+        .build()
+        .unwrap();
+
+    mock_server.mock(|when, then| {
+        when.method(GET).path("/slow");
+        then.status(200).delay(Duration::from_secs(2));
+    });
+    let error = client.get("/slow").consume().await.unwrap_err();
+    assert!(matches!(error, Error::Timeout));
+}
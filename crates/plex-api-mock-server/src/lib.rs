@@ -0,0 +1,207 @@
+#![deny(
+    unreachable_pub,
+    clippy::dbg_macro,
+    clippy::print_stdout,
+    clippy::print_stderr
+)]
+
+//! A local HTTP server that answers like plex.tv and a Plex Media Server, for
+//! the integration tests of applications built on `plex-api`.
+//!
+//! Both plex.tv and the media server are served from the same address, so a
+//! client pointed at [`MockPlexServer::base_url`] can be used for either.
+//! The canned responses are kept in the crate's `mocks` folder, which
+//! `plex-api` runs its own tests against too, and [`httpmock`] can be used
+//! directly for anything else.
+//!
+//! ```
+//! use plex_api::{HttpClientBuilder, Server};
+//! use plex_api_mock_server::{MockPlexServer, ServerKind};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let mock = MockPlexServer::start_async().await;
+//! mock.media_providers(ServerKind::Unclaimed);
+//! let movies = mock.movie_library();
+//!
+//! let client = HttpClientBuilder::new(mock.base_url()).build().unwrap();
+//! let server = Server::new(mock.base_url(), client).await.unwrap();
+//! let library = &server.libraries()[0];
+//! assert_eq!(library.title(), "Movies");
+//! # let plex_api::library::Library::Movie(library) = library else { panic!() };
+//! # library.movies().await.unwrap();
+//! movies.assert();
+//! # }
+//! ```
+
+use httpmock::{Method, Mock, MockServer};
+use std::ops::Deref;
+
+/// The token the canned accounts sign in with.
+pub const AUTH_TOKEN: &str = "auth_token";
+
+/// The plex.tv subscription of the account signing in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Account {
+    /// An account without Plex Pass.
+    Free,
+    /// An account with an active Plex Pass subscription.
+    PlexPass,
+}
+
+impl Account {
+    fn user_info(self) -> &'static str {
+        match self {
+            Self::Free => {
+                include_str!("../mocks/myplex/api/v2/user/user_info_free.json")
+            }
+            Self::PlexPass => include_str!("../mocks/myplex/api/v2/user/user_info_plexpass.json"),
+        }
+    }
+}
+
+/// Who the mocked media server belongs to, which decides the features it
+/// offers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerKind {
+    /// Not claimed by any account yet.
+    Unclaimed,
+    /// Claimed by an account without Plex Pass.
+    Free,
+    /// Claimed by an account with Plex Pass.
+    PlexPass,
+}
+
+impl ServerKind {
+    fn media_providers(self) -> &'static str {
+        match self {
+            Self::Unclaimed => {
+                include_str!("../mocks/server/media/providers_unclaimed.json")
+            }
+            Self::Free => {
+                include_str!("../mocks/server/media/providers_free.json")
+            }
+            Self::PlexPass => {
+                include_str!("../mocks/server/media/providers_plexpass.json")
+            }
+        }
+    }
+}
+
+/// The mocked plex.tv and media server.
+///
+/// Every method adds the mock for one behaviour and returns it, so tests can
+/// check it was used with [`Mock::assert`] or remove it with [`Mock::delete`].
+/// Derefs to the underlying [`MockServer`] for adding other mocks.
+pub struct MockPlexServer {
+    server: MockServer,
+}
+
+impl From<MockServer> for MockPlexServer {
+    fn from(server: MockServer) -> Self {
+        Self { server }
+    }
+}
+
+impl Deref for MockPlexServer {
+    type Target = MockServer;
+
+    fn deref(&self) -> &Self::Target {
+        &self.server
+    }
+}
+
+impl MockPlexServer {
+    /// Starts a server without any mocks, blocking the current thread until
+    /// it is ready. Use [`MockPlexServer::start_async`] inside async code.
+    pub fn start() -> Self {
+        MockServer::start().into()
+    }
+
+    /// Starts a server without any mocks.
+    pub async fn start_async() -> Self {
+        MockServer::start_async().await.into()
+    }
+
+    /// Returns the underlying [`MockServer`], keeping the mocks added so far.
+    pub fn into_inner(self) -> MockServer {
+        self.server
+    }
+
+    /// Answers signing in with a username and password.
+    pub fn sign_in(&self, account: Account) -> Mock<'_> {
+        self.json(
+            Some(Method::POST),
+            "/api/v2/users/signin",
+            account.user_info(),
+        )
+    }
+
+    /// Answers the request for the account's details, which is also how
+    /// tokens are checked. Any method is accepted.
+    pub fn account(&self, account: Account) -> Mock<'_> {
+        self.json(None, "/api/v2/user", account.user_info())
+    }
+
+    /// Answers the request a server is loaded with. It offers libraries of
+    /// movies (ID 1), TV shows (2), photos (3) and music (5).
+    pub fn media_providers(&self, kind: ServerKind) -> Mock<'_> {
+        self.json(
+            Some(Method::GET),
+            "/media/providers",
+            kind.media_providers(),
+        )
+    }
+
+    /// Answers the request for the server's identity, which is also how
+    /// `plex-api` probes a server's connections when reconnecting.
+    pub fn identity(&self) -> Mock<'_> {
+        self.json(
+            Some(Method::GET),
+            "/identity",
+            include_str!("../mocks/server/identity.json"),
+        )
+    }
+
+    /// Answers listing the movies of library 1.
+    pub fn movie_library(&self) -> Mock<'_> {
+        self.json(
+            Some(Method::GET),
+            "/library/sections/1/all",
+            include_str!("../mocks/server/media/movie_library.json"),
+        )
+    }
+
+    /// Answers loading the movie with the rating key `159637`, which can be
+    /// transcoded, see [`MockPlexServer::transcode_decision`].
+    pub fn movie(&self) -> Mock<'_> {
+        self.json(
+            Some(Method::GET),
+            "/library/metadata/159637",
+            include_str!("../mocks/transcode/metadata_159637.json"),
+        )
+    }
+
+    /// Answers the transcode decision for any item by transcoding its video
+    /// to H.264 and its audio to MP3 in a DASH stream.
+    pub fn transcode_decision(&self) -> Mock<'_> {
+        self.json(
+            Some(Method::GET),
+            "/video/:/transcode/universal/decision",
+            include_str!("../mocks/transcode/video_dash_h264_mp3.json"),
+        )
+    }
+
+    fn json(&self, method: Option<Method>, path: &str, body: &str) -> Mock<'_> {
+        self.server.mock(|when, then| {
+            let when = match method {
+                Some(method) => when.method(method),
+                None => when,
+            };
+            when.path(path);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body(body);
+        })
+    }
+}
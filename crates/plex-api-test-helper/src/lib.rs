@@ -7,6 +7,7 @@ fn rewrite_single_test(
     args: TokenStream,
     input: TokenStream,
     extra_attr: TokenStream2,
+    record_responses: bool,
 ) -> TokenStream {
     if !args.is_empty() {
         return Error::new(
@@ -23,6 +24,21 @@ fn rewrite_single_test(
     let fn_vis = fn_type.vis;
     let fn_attrs = fn_type.attrs;
     let fn_block = fn_type.block;
+    let fn_name = &fn_signature.ident;
+
+    // With `tests_record_mocks` the responses received by online tests are
+    // saved as mocks, see `tests/fixtures/online/recorder.rs` in plex-api.
+    let recording = if record_responses {
+        quote! {
+            #[cfg(feature = "tests_record_mocks")]
+            let _recording = crate::fixtures::online::recorder::Recording::start(
+                module_path!(),
+                stringify!(#fn_name),
+            );
+        }
+    } else {
+        quote! {}
+    };
 
     let async_attr = if fn_signature.asyncness.is_some() {
         quote! {
@@ -39,6 +55,7 @@ fn rewrite_single_test(
         #extra_attr
         #async_attr
         #fn_vis #fn_signature {
+            #recording
             #fn_block
         }
     })
@@ -53,7 +70,7 @@ pub fn offline_test(args: TokenStream, input: TokenStream) -> TokenStream {
             ignore = "Skipping offline tests.",
         )]
     };
-    rewrite_single_test(args, input, ignore)
+    rewrite_single_test(args, input, ignore, false)
 }
 
 #[proc_macro_attribute]
@@ -65,7 +82,7 @@ pub fn online_test_unclaimed_server(args: TokenStream, input: TokenStream) -> To
             ignore = "Skipping tests requiring an unclaimed server.",
         )]
     };
-    rewrite_single_test(args, input, ignore)
+    rewrite_single_test(args, input, ignore, true)
 }
 
 #[proc_macro_attribute]
@@ -78,7 +95,7 @@ pub fn online_test_claimed_server(args: TokenStream, input: TokenStream) -> Toke
             ignore = "Skipping tests requiring a claimed server.",
         )]
     };
-    rewrite_single_test(args, input, ignore)
+    rewrite_single_test(args, input, ignore, true)
 }
 
 #[proc_macro_attribute]
@@ -94,7 +111,7 @@ pub fn online_test_myplex(args: TokenStream, input: TokenStream) -> TokenStream
             ignore = "Skipping tests requiring myplex access.",
         )]
     };
-    rewrite_single_test(args, input, ignore)
+    rewrite_single_test(args, input, ignore, true)
 }
 
 #[proc_macro_attribute]
@@ -111,7 +128,7 @@ pub fn online_test_non_shared_server(args: TokenStream, input: TokenStream) -> T
         )]
     };
 
-    rewrite_single_test(args, input, extra_attrs)
+    rewrite_single_test(args, input, extra_attrs, true)
 }
 
 #[proc_macro_attribute]
@@ -125,5 +142,5 @@ pub fn online_test(args: TokenStream, input: TokenStream) -> TokenStream {
         )]
     };
 
-    rewrite_single_test(args, input, extra_attrs)
+    rewrite_single_test(args, input, extra_attrs, true)
 }
@@ -2,7 +2,7 @@ use plex_api::{
     device::Device,
     library::{Item, MetadataItem, Transcodable},
     media_container::server::library::{AudioCodec, ContainerFormat, VideoCodec},
-    transcode::{QueueItemStatus, VideoTranscodeOptions},
+    transcode::VideoTranscodeOptions,
     HttpClientBuilder, MyPlexBuilder,
 };
 use rpassword::prompt_password;
@@ -10,11 +10,7 @@ use std::{
     io::{stdin, stdout, BufRead, Write},
     path::PathBuf,
 };
-use tokio::{
-    fs::OpenOptions,
-    io::BufWriter,
-    time::{sleep, Duration},
-};
+use tokio::{fs::OpenOptions, io::BufWriter};
 use tokio_util::compat::TokioAsyncReadCompatExt;
 
 async fn download<M>(media: M)
@@ -37,30 +33,11 @@ where
         .await
         .unwrap();
 
-    loop {
-        match entry.status() {
-            QueueItemStatus::Deciding | QueueItemStatus::Waiting => {
-                sleep(Duration::from_millis(200)).await;
-                entry.update().await.unwrap();
-            }
-            QueueItemStatus::Processing => {
-                sleep(Duration::from_millis(1000)).await;
-                entry.update().await.unwrap();
-            }
-            QueueItemStatus::Available => {
-                println!("\nDownload available!:\n{:#?}\n", entry);
-                break;
-            }
-            QueueItemStatus::Error => {
-                println!("\nTranscode errored\n");
-                break;
-            }
-            QueueItemStatus::Expired => {
-                println!("\nTranscode expired\n");
-                break;
-            }
-        }
+    if let Err(error) = entry.wait_until_available(None).await {
+        println!("\nTranscode failed: {error}\n");
+        return;
     }
+    println!("\nDownload available!:\n{:#?}\n", entry);
 
     let target = PathBuf::from(format!(
         "{}.{}",
@@ -148,9 +125,7 @@ async fn main() {
     let queue = server.download_queue().await.unwrap();
 
     if pargs.contains("--clear") {
-        for item in queue.items().await.unwrap() {
-            item.delete().await.unwrap();
-        }
+        queue.clear().await.unwrap();
     }
 
     eprintln!("Current queue: {:#?}", queue.items().await.unwrap());
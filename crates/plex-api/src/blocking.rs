@@ -0,0 +1,201 @@
+//! Synchronous versions of the main types for code that isn't async, like
+//! command line tools and scripts.
+//!
+//! The wrappers dereference to the async types, so everything that doesn't
+//! need to wait for the network is available as usual. For the async methods
+//! of other types, e.g. the items of a library, use [`Wait::wait`]:
+//!
+//! ```no_run
+//! use plex_api::{
+//!     blocking::{Server, Wait},
+//!     library::{MetadataItem, Show},
+//!     HttpClientBuilder,
+//! };
+//!
+//! let client = HttpClientBuilder::default().build().unwrap();
+//! let server = Server::new("http://127.0.0.1:32400", client).unwrap();
+//! for library in server.libraries() {
+//!     for item in library.items().unwrap() {
+//!         let show: Result<Show, _> = item.try_into();
+//!         if let Ok(show) = show {
+//!             let seasons = show.seasons().wait().unwrap();
+//!             println!("{}: {} seasons", show.title(), seasons.len());
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Requests are driven on the calling thread, which is blocked until they
+//! complete. Don't use these from within an async runtime.
+use crate::{
+    library::Item,
+    media_container::server::Identity,
+    myplex::{announcements::AnnouncementsManager, claim_token::ClaimToken, server::ServerInfo},
+    transcode::TranscodeSession,
+    HttpClient, MyPlexBuilder, RemoteAccess, Result,
+};
+use futures::executor::block_on;
+use std::{fmt::Debug, future::Future, ops::Deref};
+
+/// Blocks the current thread until the future completes.
+pub trait Wait: Future + Sized {
+    fn wait(self) -> Self::Output {
+        block_on(self)
+    }
+}
+
+impl<F: Future> Wait for F {}
+
+/// A blocking version of [`crate::MyPlex`].
+#[derive(Debug, Clone)]
+pub struct MyPlex(crate::MyPlex);
+
+impl MyPlex {
+    /// Builds the MyPlex client, signing in if the builder was given a
+    /// username and password.
+    pub fn build(builder: MyPlexBuilder<'_>) -> Result<Self> {
+        builder.build().wait().map(Self)
+    }
+
+    pub fn into_async(self) -> crate::MyPlex {
+        self.0
+    }
+
+    pub fn refresh(self) -> Result<Self> {
+        self.0.refresh().wait().map(Self)
+    }
+
+    pub fn claim_token(&self) -> Result<ClaimToken> {
+        self.0.claim_token().wait()
+    }
+
+    pub fn server_info(&self, machine_identifier: &str) -> Result<ServerInfo> {
+        self.0.server_info(machine_identifier).wait()
+    }
+
+    pub fn announcements(&self) -> Result<AnnouncementsManager> {
+        self.0.announcements().wait()
+    }
+
+    pub fn signout(self) -> Result {
+        self.0.signout().wait()
+    }
+}
+
+impl Deref for MyPlex {
+    type Target = crate::MyPlex;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<crate::MyPlex> for MyPlex {
+    fn from(myplex: crate::MyPlex) -> Self {
+        Self(myplex)
+    }
+}
+
+/// A blocking version of [`crate::Server`].
+#[derive(Debug, Clone)]
+pub struct Server(crate::Server);
+
+impl Server {
+    pub fn new<U>(url: U, client: HttpClient) -> Result<Self>
+    where
+        U: Debug,
+        http::Uri: TryFrom<U>,
+        <http::Uri as TryFrom<U>>::Error: Into<http::Error>,
+    {
+        crate::Server::new(url, client).wait().map(Self)
+    }
+
+    pub fn into_async(self) -> crate::Server {
+        self.0
+    }
+
+    pub fn refresh(self) -> Result<Self> {
+        self.0.refresh().wait().map(Self)
+    }
+
+    pub fn libraries(&self) -> Vec<Library> {
+        self.0.libraries().into_iter().map(Library).collect()
+    }
+
+    pub fn item_by_id(&self, rating_key: &str) -> Result<Item> {
+        self.0.item_by_id(rating_key).wait()
+    }
+
+    pub fn identity(&self) -> Result<Identity> {
+        self.0.identity().wait()
+    }
+
+    pub fn myplex(&self) -> Result<MyPlex> {
+        self.0.myplex().map(MyPlex)
+    }
+
+    pub fn claim(self, claim_token: &str) -> Result<Self> {
+        self.0.claim(claim_token).wait().map(Self)
+    }
+
+    pub fn unclaim(self) -> Result<Self> {
+        self.0.unclaim().wait().map(Self)
+    }
+
+    pub fn transcode_sessions(&self) -> Result<Vec<TranscodeSession>> {
+        self.0.transcode_sessions().wait()
+    }
+
+    pub fn remote_access(&self) -> Result<RemoteAccess> {
+        self.0.remote_access().wait()
+    }
+}
+
+impl Deref for Server {
+    type Target = crate::Server;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<crate::Server> for Server {
+    fn from(server: crate::Server) -> Self {
+        Self(server)
+    }
+}
+
+/// A blocking version of [`crate::library::Library`].
+#[derive(Debug, Clone)]
+pub struct Library(crate::library::Library);
+
+impl Library {
+    pub fn into_async(self) -> crate::library::Library {
+        self.0
+    }
+
+    /// Retrieves the top level items of this library: movies, shows, artists
+    /// or photos depending on its type.
+    pub fn items(&self) -> Result<Vec<Item>> {
+        use crate::library::Library::*;
+
+        fn items<T: Into<Item>>(items: Result<Vec<T>>) -> Result<Vec<Item>> {
+            Ok(items?.into_iter().map(Into::into).collect())
+        }
+
+        match &self.0 {
+            Movie(library) | Video(library) => items(library.movies().wait()),
+            TV(library) => items(library.shows().wait()),
+            Music(library) => items(library.artists().wait()),
+            Photo(library) => items(library.photos().wait()),
+        }
+    }
+}
+
+impl Deref for Library {
+    type Target = crate::library::Library;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
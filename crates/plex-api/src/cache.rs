@@ -0,0 +1,212 @@
+use futures::io::{AsyncReadExt, Cursor};
+use isahc::{
+    http::{header, HeaderMap, Method, StatusCode},
+    AsyncBody, Request as HttpRequest, Response as HttpResponse,
+};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fmt,
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
+const DEFAULT_MAX_BODY_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CacheKey {
+    uri: String,
+    accept: Option<String>,
+    // Different accounts see different content at the same URL. Only a hash
+    // of the token is kept so that it doesn't linger in memory.
+    token: u64,
+}
+
+struct CacheEntry {
+    headers: HeaderMap,
+    body: Arc<[u8]>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<CacheKey, CacheEntry>,
+    clock: u64,
+}
+
+/// Remembers responses that carry an `ETag` or `Last-Modified` header so
+/// that repeated `GET` requests can be sent as conditional requests. When
+/// the server answers `304 Not Modified` the remembered body is returned as
+/// if the server had sent it again.
+///
+/// Clones share the same cached responses.
+#[derive(Clone)]
+pub struct ResponseCache {
+    state: Arc<Mutex<CacheState>>,
+    max_entries: usize,
+    max_body_size: usize,
+}
+
+impl fmt::Debug for ResponseCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseCache")
+            .field("entries", &self.len())
+            .field("max_entries", &self.max_entries)
+            .field("max_body_size", &self.max_body_size)
+            .finish()
+    }
+}
+
+impl ResponseCache {
+    /// Creates a cache holding up to `max_entries` responses. The least
+    /// recently used response is dropped when it's full.
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            state: Default::default(),
+            max_entries,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+
+    /// Responses with bigger bodies aren't cached. Defaults to 1 MiB.
+    #[must_use]
+    pub fn max_body_size(self, max_body_size: usize) -> Self {
+        Self {
+            max_body_size,
+            ..self
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forgets all the cached responses.
+    pub fn clear(&self) {
+        self.state.lock().unwrap().entries.clear();
+    }
+
+    /// Adds the validators of a cached response to the request, returning
+    /// the key to store the response under if it can be cached.
+    pub(crate) fn prepare(&self, request: &mut HttpRequest<AsyncBody>) -> Option<CacheKey> {
+        if request.method() != Method::GET || self.max_entries == 0 {
+            return None;
+        }
+
+        let header = |name| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(ToOwned::to_owned)
+        };
+        let mut hasher = DefaultHasher::new();
+        header("X-Plex-Token").hash(&mut hasher);
+        let key = CacheKey {
+            uri: request.uri().to_string(),
+            accept: header(header::ACCEPT.as_str()),
+            token: hasher.finish(),
+        };
+
+        let state = self.state.lock().unwrap();
+        if let Some(entry) = state.entries.get(&key) {
+            let headers = request.headers_mut();
+            for (validator, condition) in [
+                (header::ETAG, header::IF_NONE_MATCH),
+                (header::LAST_MODIFIED, header::IF_MODIFIED_SINCE),
+            ] {
+                if let Some(value) = entry.headers.get(&validator) {
+                    headers.insert(condition, value.clone());
+                }
+            }
+        }
+
+        Some(key)
+    }
+
+    /// Stores a response that can be validated later, or replaces a
+    /// `304 Not Modified` response with the cached one.
+    pub(crate) async fn handle(
+        &self,
+        key: CacheKey,
+        response: HttpResponse<AsyncBody>,
+    ) -> crate::Result<HttpResponse<AsyncBody>> {
+        match response.status() {
+            StatusCode::NOT_MODIFIED => {
+                let mut state = self.state.lock().unwrap();
+                state.clock += 1;
+                let clock = state.clock;
+                match state.entries.get_mut(&key) {
+                    Some(entry) => {
+                        entry.last_used = clock;
+                        let mut cached = HttpResponse::new(AsyncBody::from(entry.body.to_vec()));
+                        *cached.headers_mut() = entry.headers.clone();
+                        Ok(cached)
+                    }
+                    None => Ok(response),
+                }
+            }
+            StatusCode::OK
+                if response.headers().contains_key(header::ETAG)
+                    || response.headers().contains_key(header::LAST_MODIFIED) =>
+            {
+                let too_big = response
+                    .headers()
+                    .get(header::CONTENT_LENGTH)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.parse::<usize>().ok())
+                    .is_some_and(|length| length > self.max_body_size);
+                if too_big {
+                    return Ok(response);
+                }
+
+                let (parts, mut body) = response.into_parts();
+                let mut buffer = Vec::new();
+                (&mut body)
+                    .take(self.max_body_size as u64 + 1)
+                    .read_to_end(&mut buffer)
+                    .await?;
+
+                if buffer.len() > self.max_body_size {
+                    // Hand the part that was read back along with the rest.
+                    return Ok(HttpResponse::from_parts(
+                        parts,
+                        AsyncBody::from_reader(Cursor::new(buffer).chain(body)),
+                    ));
+                }
+
+                self.store(key, parts.headers.clone(), buffer.as_slice().into());
+                Ok(HttpResponse::from_parts(parts, AsyncBody::from(buffer)))
+            }
+            _ => Ok(response),
+        }
+    }
+
+    fn store(&self, key: CacheKey, headers: HeaderMap, body: Arc<[u8]>) {
+        let mut state = self.state.lock().unwrap();
+        if !state.entries.contains_key(&key) && state.entries.len() >= self.max_entries {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+
+        state.clock += 1;
+        let last_used = state.clock;
+        state.entries.insert(
+            key,
+            CacheEntry {
+                headers,
+                body,
+                last_used,
+            },
+        );
+    }
+}
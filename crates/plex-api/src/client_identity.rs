@@ -0,0 +1,75 @@
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::{fs, io::ErrorKind, path::Path};
+use uuid::Uuid;
+
+/// How the client introduces itself to plex.tv and the servers.
+///
+/// Plex lists every client identifier it sees as a separate device in the
+/// account's settings, so applications should generate the identity once and
+/// keep using it. [`ClientIdentity::load_or_create`] takes care of that.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientIdentity {
+    pub client_identifier: String,
+    pub product: String,
+    pub version: String,
+    pub platform: String,
+    pub platform_version: String,
+    pub device: String,
+    pub device_name: String,
+    pub model: String,
+}
+
+impl ClientIdentity {
+    /// A new identity with a random client identifier, describing this
+    /// library and the system it runs on.
+    pub fn generate() -> Self {
+        let platform = sysinfo::System::name().unwrap_or("unknown".to_string());
+
+        Self {
+            client_identifier: Uuid::new_v4().to_string(),
+            product: option_env!("CARGO_PKG_NAME")
+                .unwrap_or("plex-api")
+                .to_string(),
+            version: option_env!("CARGO_PKG_VERSION")
+                .unwrap_or("unknown")
+                .to_string(),
+            platform_version: sysinfo::System::os_version().unwrap_or("unknown".to_string()),
+            device: platform.clone(),
+            platform,
+            device_name: sysinfo::System::host_name().unwrap_or("unknown".to_string()),
+            model: String::from("hosted"),
+        }
+    }
+
+    /// Reads an identity previously written by [`ClientIdentity::save`].
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let data = fs::read(path)?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// Writes the identity as JSON, creating the parent directories if needed.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Loads the identity from the file or, if it doesn't exist yet,
+    /// generates a new one and saves it there.
+    pub fn load_or_create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        match Self::load(path) {
+            Err(crate::Error::StdIoError { source }) if source.kind() == ErrorKind::NotFound => {
+                let identity = Self::generate();
+                identity.save(path)?;
+                Ok(identity)
+            }
+            result => result,
+        }
+    }
+}
@@ -0,0 +1,540 @@
+use std::{
+    io::{self, SeekFrom},
+    ops::{Bound, RangeBounds},
+    time::Duration,
+};
+
+use futures::{
+    stream, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt, StreamExt,
+};
+use futures_timer::Delay;
+use http::StatusCode;
+use isahc::http::header::{CONTENT_LENGTH, CONTENT_RANGE};
+
+use crate::{
+    isahc_compat::StatusCodeExt, progress::ProgressTracker, DownloadVerificationError, Error,
+    HttpClient, ProgressObserver, Result,
+};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+const PARALLEL_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Controls how files are downloaded from the server.
+#[derive(Debug, Clone, Copy)]
+pub struct DownloadOptions {
+    /// How many times the download will be resumed after the connection to
+    /// the server fails. Zero disables resuming.
+    pub max_retries: u32,
+    /// How long to wait before resuming a failed download.
+    pub retry_delay: Duration,
+    /// The number of concurrent connections to use. When greater than one the
+    /// file is split into chunks which are fetched in parallel and written
+    /// out in order. Only used when the size of the download is known.
+    pub connections: usize,
+    /// The size of each chunk in a parallel download. Up to `connections`
+    /// chunks are held in memory at once.
+    pub chunk_size: u64,
+    /// Whether to check that the downloaded size matches the size of the
+    /// file, taken from the media's metadata or else from the server's
+    /// response. A mismatch fails the download with
+    /// [`DownloadVerificationError`]. The server doesn't expose checksums of
+    /// media files, so the contents can't be checked.
+    pub verify: bool,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            retry_delay: Duration::from_secs(1),
+            connections: 1,
+            chunk_size: PARALLEL_CHUNK_SIZE,
+            verify: false,
+        }
+    }
+}
+
+/// A file that a download writes into at the offsets the server reports,
+/// rather than appending to it.
+///
+/// The file is grown to the full size of the download as soon as the server
+/// reports it, and the number of bytes downloaded from the start of the file
+/// is tracked. A failed download can be continued from there with the same
+/// `SparseFile`, or with [`SparseFile::resume`] after a restart.
+///
+/// ```
+/// use futures::io::Cursor;
+/// use plex_api::SparseFile;
+///
+/// // The first 1000 bytes were downloaded before the application quit.
+/// let file = SparseFile::resume(Cursor::new(vec![0; 4096]), 1000);
+/// assert_eq!(file.downloaded(), 1000);
+/// assert!(!file.is_complete());
+/// ```
+#[derive(Debug)]
+pub struct SparseFile<F> {
+    file: F,
+    downloaded: u64,
+    size: Option<u64>,
+}
+
+impl<F> SparseFile<F>
+where
+    F: AsyncWrite + AsyncSeek + Unpin,
+{
+    /// Downloads into an empty file.
+    pub fn new(file: F) -> Self {
+        Self::resume(file, 0)
+    }
+
+    /// Continues a download of which the first `downloaded` bytes are
+    /// already in the file.
+    pub fn resume(file: F, downloaded: u64) -> Self {
+        Self {
+            file,
+            downloaded,
+            size: None,
+        }
+    }
+
+    /// The number of bytes downloaded from the start of the file.
+    pub fn downloaded(&self) -> u64 {
+        self.downloaded
+    }
+
+    /// The full size of the download, once the server has reported it.
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    /// Whether the whole file has been downloaded.
+    pub fn is_complete(&self) -> bool {
+        self.size.is_some_and(|size| self.downloaded >= size)
+    }
+
+    pub fn into_inner(self) -> F {
+        self.file
+    }
+
+    /// Grows the file to its full size by writing its last byte.
+    async fn allocate(&mut self, size: u64) -> io::Result<()> {
+        if self.size.is_some() {
+            return Ok(());
+        }
+        self.size = Some(size);
+        if size > self.downloaded {
+            self.file.seek(SeekFrom::Start(size - 1)).await?;
+            self.file.write_all(&[0]).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the first byte and the full size from a `Content-Range` header, e.g.
+/// `bytes 100-199/1000`. The size is `*` when the server doesn't know it.
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, size) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let (start, _) = range.split_once('-')?;
+    let size = match size {
+        "*" => None,
+        size => Some(size.parse().ok()?),
+    };
+    Some((start.parse().ok()?, size))
+}
+
+/// Checks the number of bytes received against the expected size if
+/// verification was requested.
+fn verify<O: ProgressObserver>(tracker: &ProgressTracker<O>, options: DownloadOptions) -> Result {
+    if !options.verify {
+        return Ok(());
+    }
+
+    let received = tracker.transferred();
+    match tracker.total() {
+        None => Err(DownloadVerificationError::UnknownSize.into()),
+        Some(expected) if expected != received => {
+            Err(DownloadVerificationError::SizeMismatch { expected, received }.into())
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+/// Converts a range into the first byte and (inclusive) last byte to request.
+/// The last byte is before the first one for empty ranges, e.g. `10..5`.
+pub(crate) fn byte_range<R: RangeBounds<u64>>(range: R) -> (u64, Option<u64>) {
+    let start = match range.start_bound() {
+        Bound::Included(v) => *v,
+        Bound::Excluded(v) => v.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+
+    let end = match range.end_bound() {
+        Bound::Included(v) => Some(*v),
+        // Nothing comes before the first byte.
+        Bound::Excluded(0) => return (start.max(1), Some(0)),
+        Bound::Excluded(v) => Some(v - 1),
+        Bound::Unbounded => None,
+    };
+
+    (start, end)
+}
+
+/// Why a single attempt at downloading failed.
+enum Failure {
+    /// The connection broke, it's worth trying again from where we stopped.
+    Transient(Error),
+    /// Anything else, e.g. the server refused the request or the writer
+    /// failed.
+    Fatal(Error),
+}
+
+/// Describes a file being downloaded from the server.
+pub(crate) struct Download<'a> {
+    pub(crate) client: &'a HttpClient,
+    pub(crate) path: &'a str,
+    /// The first byte to download.
+    pub(crate) start: u64,
+    /// The last byte to download, if not the end of the file.
+    pub(crate) end: Option<u64>,
+    /// The full size of the file if known.
+    pub(crate) size: Option<u64>,
+    /// The request timeout, usually disabled as large files take a while.
+    pub(crate) timeout: Option<Duration>,
+    /// Whether `503 Service Unavailable` means the file is still being
+    /// transcoded, see [`Error::TranscodeIncomplete`].
+    pub(crate) transcoding: bool,
+}
+
+impl Download<'_> {
+    /// Whether no bytes are requested, because the range ends before it
+    /// starts or starts past the end of the file.
+    fn is_empty(&self) -> bool {
+        self.end.is_some_and(|end| end < self.start)
+            || self.size.is_some_and(|size| self.start >= size)
+    }
+
+    /// Downloads the requested bytes into the writer, resuming from the last
+    /// received byte if the connection drops. Nothing is requested when the
+    /// range is empty.
+    #[tracing::instrument(level = "trace", skip_all, fields(path = self.path, start = self.start, end = self.end))]
+    pub(crate) async fn run<W, O>(
+        &self,
+        mut writer: W,
+        options: DownloadOptions,
+        observer: O,
+    ) -> Result
+    where
+        W: AsyncWrite + Unpin,
+        O: ProgressObserver,
+    {
+        if self.is_empty() {
+            writer.flush().await?;
+            return Ok(());
+        }
+
+        if options.connections > 1 {
+            let last = self
+                .end
+                .or_else(|| self.size.filter(|size| *size > 0).map(|size| size - 1));
+            if let Some(last) = last {
+                return self.run_parallel(last, writer, options, observer).await;
+            }
+        }
+
+        let total = match (self.end, self.size) {
+            (Some(end), _) => Some((end - self.start).saturating_add(1)),
+            (None, Some(size)) => Some(size.saturating_sub(self.start)),
+            _ => None,
+        };
+
+        let mut tracker = ProgressTracker::new(observer, total);
+        let mut position = self.start;
+        let mut attempt = 0;
+        loop {
+            let result = self.fetch(&mut position, &mut writer, &mut tracker).await;
+
+            match result {
+                Ok(()) => break,
+                Err(Failure::Transient(error)) if attempt < options.max_retries => {
+                    attempt += 1;
+                    tracing::debug!(%error, attempt, position, "Download interrupted, resuming");
+                    Delay::new(options.retry_delay).await;
+                }
+                Err(Failure::Transient(error) | Failure::Fatal(error)) => return Err(error),
+            }
+        }
+
+        writer.flush().await?;
+        verify(&tracker, options)
+    }
+
+    /// Splits the range up to `last` into chunks and downloads several of
+    /// them at once. Each chunk is resumed individually if it fails.
+    async fn run_parallel<W, O>(
+        &self,
+        last: u64,
+        mut writer: W,
+        options: DownloadOptions,
+        observer: O,
+    ) -> Result
+    where
+        W: AsyncWrite + Unpin,
+        O: ProgressObserver,
+    {
+        let mut tracker =
+            ProgressTracker::new(observer, Some((last - self.start).saturating_add(1)));
+        let chunk_size = options.chunk_size.max(1);
+        let chunk_options = DownloadOptions {
+            connections: 1,
+            ..options
+        };
+
+        let mut chunks = stream::iter((self.start..=last).step_by(chunk_size as usize))
+            .map(|start| {
+                let end = last.min(start.saturating_add(chunk_size - 1));
+                async move {
+                    let mut buf = Vec::with_capacity((end - start + 1) as usize);
+                    Download {
+                        client: self.client,
+                        path: self.path,
+                        start,
+                        end: Some(end),
+                        size: self.size,
+                        timeout: self.timeout,
+                        transcoding: self.transcoding,
+                    }
+                    .run(&mut buf, chunk_options, ())
+                    .await?;
+                    Ok::<_, Error>(buf)
+                }
+            })
+            .buffered(options.connections);
+
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk?;
+            writer.write_all(&chunk).await?;
+            tracker.advance(chunk.len() as u64);
+        }
+
+        writer.flush().await?;
+        verify(&tracker, options)
+    }
+
+    /// Downloads the rest of the file into a [`SparseFile`], starting from
+    /// its downloaded bytes rather than [`Download::start`] and resuming if
+    /// the connection drops.
+    #[tracing::instrument(level = "trace", skip_all, fields(path = self.path, downloaded = file.downloaded))]
+    pub(crate) async fn run_sparse<F, O>(
+        &self,
+        file: &mut SparseFile<F>,
+        options: DownloadOptions,
+        observer: O,
+    ) -> Result
+    where
+        F: AsyncWrite + AsyncSeek + Unpin,
+        O: ProgressObserver,
+    {
+        let total = file.size.map(|size| size.saturating_sub(file.downloaded));
+        let mut tracker = ProgressTracker::new(observer, total);
+        let mut attempt = 0;
+        loop {
+            match self.fetch_sparse(file, &mut tracker).await {
+                Ok(()) => break,
+                Err(Failure::Transient(error)) if attempt < options.max_retries => {
+                    attempt += 1;
+                    tracing::debug!(%error, attempt, downloaded = file.downloaded, "Download interrupted, resuming");
+                    Delay::new(options.retry_delay).await;
+                }
+                Err(Failure::Transient(error) | Failure::Fatal(error)) => return Err(error),
+            }
+        }
+
+        file.file.flush().await?;
+        verify(&tracker, options)
+    }
+
+    async fn fetch_sparse<F, O>(
+        &self,
+        file: &mut SparseFile<F>,
+        tracker: &mut ProgressTracker<O>,
+    ) -> std::result::Result<(), Failure>
+    where
+        F: AsyncWrite + AsyncSeek + Unpin,
+        O: ProgressObserver,
+    {
+        let response = self
+            .client
+            .get(self.path)
+            .timeout(self.timeout)
+            .header("Range", format!("bytes={}-", file.downloaded))
+            .send()
+            .await
+            .map_err(Failure::Transient)?;
+
+        // A server that doesn't support ranges sends the whole file, which
+        // is written over the start of the file again.
+        let (start, size) = match response.status().as_http_status() {
+            StatusCode::OK => (
+                0,
+                response
+                    .headers()
+                    .get(CONTENT_LENGTH)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<u64>().ok()),
+            ),
+            StatusCode::PARTIAL_CONTENT => response
+                .headers()
+                .get(CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_content_range)
+                .unwrap_or((file.downloaded, None)),
+            StatusCode::SERVICE_UNAVAILABLE if self.transcoding => {
+                return Err(Failure::Fatal(Error::TranscodeIncomplete))
+            }
+            _ => return Err(Failure::Fatal(Error::from_response(response).await)),
+        };
+        if start > file.downloaded {
+            return Err(Failure::Fatal(Error::UnexpectedDownloadOffset {
+                requested: file.downloaded,
+                received: start,
+            }));
+        }
+
+        if let Some(size) = size {
+            if file.size.is_none() {
+                tracker.discover_total(size.saturating_sub(file.downloaded));
+            }
+            file.allocate(size)
+                .await
+                .map_err(|e| Failure::Fatal(e.into()))?;
+        }
+        file.file
+            .seek(SeekFrom::Start(start))
+            .await
+            .map_err(|e| Failure::Fatal(e.into()))?;
+
+        let mut position = start;
+        let mut body = response.into_body();
+        let mut buf = vec![0; CHUNK_SIZE];
+        loop {
+            let read = body
+                .read(&mut buf)
+                .await
+                .map_err(|e| Failure::Transient(e.into()))?;
+            if read == 0 {
+                break;
+            }
+
+            file.file
+                .write_all(&buf[..read])
+                .await
+                .map_err(|e| Failure::Fatal(e.into()))?;
+
+            position += read as u64;
+            if position > file.downloaded {
+                tracker.advance(position - file.downloaded);
+                file.downloaded = position;
+            }
+        }
+
+        match file.size {
+            Some(size) if file.downloaded < size => Err(Failure::Transient(
+                io::Error::from(io::ErrorKind::UnexpectedEof).into(),
+            )),
+            Some(_) => Ok(()),
+            None => {
+                file.size = Some(file.downloaded);
+                Ok(())
+            }
+        }
+    }
+
+    async fn fetch<W, O>(
+        &self,
+        position: &mut u64,
+        writer: &mut W,
+        tracker: &mut ProgressTracker<O>,
+    ) -> std::result::Result<(), Failure>
+    where
+        W: AsyncWrite + Unpin,
+        O: ProgressObserver,
+    {
+        let mut builder = self.client.get(self.path).timeout(self.timeout);
+        if *position != 0 || self.end.is_some() {
+            // We're requesting part of the file.
+            let end = self.end.map(|v| v.to_string()).unwrap_or_default();
+            builder = builder.header("Range", format!("bytes={}-{end}", *position))
+        }
+
+        let response = builder.send().await.map_err(Failure::Transient)?;
+        // Not every endpoint supports ranges (transcodes in progress for
+        // example). In that case the whole file is sent again and everything
+        // before the current position has to be thrown away.
+        let mut skip = match response.status().as_http_status() {
+            StatusCode::OK => *position,
+            StatusCode::PARTIAL_CONTENT => 0,
+            StatusCode::SERVICE_UNAVAILABLE if self.transcoding => {
+                return Err(Failure::Fatal(Error::TranscodeIncomplete))
+            }
+            _ => return Err(Failure::Fatal(Error::from_response(response).await)),
+        };
+        let limit = self.end.map(|end| end + 1);
+
+        if *position == 0 && limit.is_none() {
+            if let Some(length) = response
+                .headers()
+                .get(CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+            {
+                tracker.discover_total(length);
+            }
+        }
+
+        let mut body = response.into_body();
+        let mut buf = vec![0; CHUNK_SIZE];
+        loop {
+            let read = body
+                .read(&mut buf)
+                .await
+                .map_err(|e| Failure::Transient(e.into()))?;
+            if read == 0 {
+                break;
+            }
+
+            let mut data = &buf[..read];
+            if skip > 0 {
+                let skipped = skip.min(data.len() as u64);
+                data = &data[skipped as usize..];
+                skip -= skipped;
+            }
+            if let Some(limit) = limit {
+                let remaining = limit.saturating_sub(*position);
+                data = &data[..data.len().min(remaining as usize)];
+            }
+            if data.is_empty() {
+                continue;
+            }
+
+            writer
+                .write_all(data)
+                .await
+                .map_err(|e| Failure::Fatal(e.into()))?;
+
+            *position += data.len() as u64;
+            tracker.advance(data.len() as u64);
+
+            if limit.is_some_and(|limit| *position >= limit) {
+                break;
+            }
+        }
+
+        let expected_end = self.end.map(|end| end + 1).or(self.size);
+        match expected_end {
+            Some(expected_end) if *position < expected_end => Err(Failure::Transient(
+                io::Error::from(io::ErrorKind::UnexpectedEof).into(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Builds the paths of API endpoints from the templates in [`crate::url`],
+//! also for endpoints the crate doesn't wrap yet:
+//!
+//! ```
+//! use plex_api::{endpoints::Endpoint, url::SERVER_LIBRARY_SECTION_ALL};
+//!
+//! let endpoint = Endpoint::new(SERVER_LIBRARY_SECTION_ALL)
+//!     .segment("sectionId", 1)
+//!     .query("title", "Alien: Covenant");
+//! assert_eq!(
+//!     endpoint.to_string(),
+//!     "/library/sections/1/all?title=Alien%3A+Covenant"
+//! );
+//! ```
+//!
+//! An [`Endpoint`] can be passed to [`HttpClient::get`](crate::HttpClient::get)
+//! and the other request methods in place of a path.
+
+use http::uri::{InvalidUri, PathAndQuery};
+use std::fmt::{self, Display, Write};
+
+/// The path of an endpoint with its placeholders filled in, plus a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    path: String,
+    query: Vec<(String, String)>,
+}
+
+impl Endpoint {
+    /// Starts from a path, where parts in braces, e.g. `{sectionId}`, are
+    /// placeholders for [`Endpoint::segment`].
+    pub fn new<P: Into<String>>(template: P) -> Self {
+        Self {
+            path: template.into(),
+            query: Vec::new(),
+        }
+    }
+
+    /// Replaces the `{name}` placeholder with the value. Characters that
+    /// would change the meaning of the path, like `/` or `?`, are
+    /// percent-encoded.
+    #[must_use]
+    pub fn segment<V: Display>(mut self, name: &str, value: V) -> Self {
+        let value = encode_segment(&value.to_string());
+        self.path = self.path.replace(&format!("{{{name}}}"), &value);
+        self
+    }
+
+    /// Adds a query parameter. Parameters are kept in the order they were
+    /// added, and adding one twice sends both values.
+    #[must_use]
+    pub fn query<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.query.push((name.into(), value.into()));
+        self
+    }
+
+    /// The path without the query.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path)?;
+        if !self.query.is_empty() {
+            f.write_char('?')?;
+            f.write_str(&serde_urlencoded::to_string(&self.query).map_err(|_| fmt::Error)?)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Endpoint> for String {
+    fn from(endpoint: Endpoint) -> Self {
+        endpoint.to_string()
+    }
+}
+
+impl TryFrom<Endpoint> for PathAndQuery {
+    type Error = InvalidUri;
+
+    fn try_from(endpoint: Endpoint) -> Result<Self, Self::Error> {
+        endpoint.to_string().try_into()
+    }
+}
+
+/// Percent-encodes everything that isn't allowed in a path segment by
+/// RFC 3986, and the dots of a `.` or `..` segment so they aren't resolved
+/// as the current or parent directory.
+fn encode_segment(value: &str) -> String {
+    if value == "." || value == ".." {
+        return value.replace('.', "%2E");
+    }
+
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~'
+            | b'!'
+            | b'$'
+            | b'&'
+            | b'\''
+            | b'('
+            | b')'
+            | b'*'
+            | b'+'
+            | b','
+            | b';'
+            | b'='
+            | b':'
+            | b'@' => encoded.push(byte as char),
+            _ => {
+                let _ = write!(encoded, "%{byte:02X}");
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_are_encoded() {
+        let endpoint = Endpoint::new("/library/metadata/{ratingKey}/station/{stationId}")
+            .segment("ratingKey", "../1?a=b")
+            .segment("stationId", "a b/ü");
+        assert_eq!(
+            endpoint.path(),
+            "/library/metadata/..%2F1%3Fa=b/station/a%20b%2F%C3%BC"
+        );
+        assert_eq!(endpoint.to_string(), endpoint.path());
+
+        let endpoint = Endpoint::new("/library/metadata/{ratingKey}/station/{stationId}")
+            .segment("ratingKey", "..")
+            .segment("stationId", ".");
+        assert_eq!(endpoint.path(), "/library/metadata/%2E%2E/station/%2E");
+
+        let endpoint = Endpoint::new("/playlists")
+            .query("type", "video")
+            .query("uri", "server://x/com.plexapp.plugins.library/1&2");
+        assert_eq!(
+            endpoint.to_string(),
+            "/playlists?type=video&uri=server%3A%2F%2Fx%2Fcom.plexapp.plugins.library%2F1%262"
+        );
+        assert!(PathAndQuery::try_from(endpoint).is_ok());
+    }
+}
@@ -1,6 +1,9 @@
-use crate::media_container::server::Feature;
+use crate::{
+    media_container::server::Feature, redact::redact, retry::retry_after, server::ServerFeature,
+};
 use isahc::{AsyncBody, AsyncReadResponseExt, Response as HttpResponse};
 use serde::Deserialize;
+use std::{fmt, time::Duration};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -46,8 +49,21 @@ pub enum Error {
     },
     #[error("Error while communicating with MyPlexApi: {errors:?}.")]
     MyPlexErrorResponse { errors: Vec<Self> },
-    #[error("Error occurred while communicating to MyPlex API: #{code} - {message}.")]
-    MyPlexApiError { code: i32, message: String },
+    #[error("Plex API error: {0}.")]
+    PlexApiError(PlexApiError),
+    #[error("Too many requests, slow down.")]
+    RateLimited {
+        /// How long the server asked to wait before trying again.
+        retry_after: Option<Duration>,
+    },
+    /// The error the server sent along, if any, is returned by
+    /// [`Error::api_error`].
+    #[error("The account isn't allowed to access this item or library section.")]
+    NotAuthorized(Option<PlexApiError>),
+    /// The error the server sent along, if any, is returned by
+    /// [`Error::api_error`].
+    #[error("The item is locked and can't be changed right now.")]
+    ItemLocked(Option<PlexApiError>),
     #[error("Failed to get claim token: {0}.")]
     FailedToGetClaimToken(String),
     #[error("Unexpected API response: HTTP {status_code}, content: {content}.")]
@@ -56,6 +72,10 @@ pub enum Error {
     WebhookNotFound(String),
     #[error("The mandatory feature is not available: {0}.")]
     SubscriptionFeatureNotAvailable(Feature),
+    #[error("The server doesn't support {0}.")]
+    ServerFeatureNotAvailable(ServerFeature),
+    #[error("Unable to parse the server version: {0}.")]
+    InvalidServerVersion(String),
     #[error("OTP is required for the authentication.")]
     OtpRequired,
     #[error("OTP is provided, but no username/password.")]
@@ -68,12 +88,16 @@ pub enum Error {
     RequestedSettingNotFound(String),
     #[error("You can't set setting to a value of a different type.")]
     IncompatibleSettingValues,
+    #[error("Invalid value for setting {0}.")]
+    InvalidSettingValue(String),
     #[error("Provided pin is already expired.")]
     PinExpired,
     #[error("Provided pin is not linked yet.")]
     PinNotLinked,
     #[error("Item requested was not found on the server.")]
     ItemNotFound,
+    #[error("Only downloaded or uploaded subtitle streams can be deleted.")]
+    SubtitleStreamNotExternal,
     #[error("The requested transcode parameters were invalid.")]
     InvalidTranscodeSettings,
     #[error("The transcode request failed: {0}.")]
@@ -82,6 +106,24 @@ pub enum Error {
     TranscodeRefused,
     #[error("Transcoding is incomplete.")]
     TranscodeIncomplete,
+    #[error("The queued item has expired and is no longer available.")]
+    QueueItemExpired,
+    #[error("Timed out waiting for the server.")]
+    Timeout,
+    #[error("The download was resumed from byte {requested}, but the server sent data from byte {received}.")]
+    UnexpectedDownloadOffset { requested: u64, received: u64 },
+    #[error("The downloaded file failed verification: {0}.")]
+    DownloadVerification(#[from] DownloadVerificationError),
+    #[error("Unable to parse the playlist: {0}.")]
+    InvalidPlaylist(String),
+    #[error("Unable to parse the preview thumbnails: {0}.")]
+    InvalidPreviewThumbnails(String),
+    #[error("Only episodes of a show can be recorded as a series.")]
+    NotAnEpisode,
+    #[error("Tags can't be edited on this kind of item.")]
+    TagsNotSupported,
+    #[error("Smart collections can only hold movies or shows.")]
+    SmartCollectionNotSupported,
     #[error("Invalid header value.")]
     InvalidHeaderValue,
     #[error("Unknown container format.")]
@@ -97,6 +139,12 @@ const PLEX_API_ERROR_CODE_AUTH_OTP_REQUIRED: i32 = 1029;
 impl Error {
     pub async fn from_response(mut response: HttpResponse<AsyncBody>) -> Self {
         let status_code = response.status().as_u16();
+        if status_code == 429 {
+            return Self::RateLimited {
+                retry_after: retry_after(&response),
+            };
+        }
+
         let response_body = match response.text().await {
             Ok(body) => body,
             Err(err) => {
@@ -104,25 +152,25 @@ impl Error {
             }
         };
 
-        let err: Result<MyPlexApiErrorResponse, Error>;
-        if let Some(content_type) = response.headers().get("Content-type") {
-            match content_type.to_str().unwrap().split("; ").next().unwrap() {
-                "application/xml" => {
-                    err = quick_xml::de::from_str::<MyPlexApiErrorResponse>(&response_body)
-                        .map_err(|e| e.into())
-                }
-                _ => {
-                    err = serde_json::from_str::<MyPlexApiErrorResponse>(&response_body)
-                        .map_err(|e| e.into())
-                }
-            }
+        let is_xml = response
+            .headers()
+            .get("Content-type")
+            .and_then(|content_type| content_type.to_str().ok())
+            .and_then(|content_type| content_type.split(';').next())
+            .is_some_and(|media_type| media_type.trim().ends_with("/xml"));
+
+        let envelope = if is_xml {
+            // quick-xml can't deserialize attributes into untagged enums.
+            quick_xml::de::from_str(&response_body)
+                .map(ErrorEnvelope::MyPlex)
+                .or_else(|_| quick_xml::de::from_str(&response_body).map(ErrorEnvelope::Response))
+                .ok()
         } else {
-            err = serde_json::from_str::<MyPlexApiErrorResponse>(&response_body)
-                .map_err(|e| e.into());
-        }
+            serde_json::from_str::<ErrorEnvelope>(&response_body).ok()
+        };
 
-        match err {
-            Ok(r) => {
+        match (envelope, status_code) {
+            (Some(ErrorEnvelope::MyPlex(r)), _) => {
                 if r.errors.len() == 1 && r.errors[0].code == PLEX_API_ERROR_CODE_AUTH_OTP_REQUIRED
                 {
                     Self::OtpRequired
@@ -130,44 +178,115 @@ impl Error {
                     r.into()
                 }
             }
-            Err(_) => Self::UnexpectedApiResponse {
+            (envelope, 403) => Self::NotAuthorized(envelope.map(|e| e.into_api_error(status_code))),
+            (envelope, 423) => Self::ItemLocked(envelope.map(|e| e.into_api_error(status_code))),
+            (Some(envelope), _) => Self::PlexApiError(envelope.into_api_error(status_code)),
+            (None, _) => Self::UnexpectedApiResponse {
                 status_code,
-                content: response_body,
+                content: redact(&response_body).into_owned(),
             },
         }
     }
+
+    /// The error reported by the Plex API, if the response contained one.
+    /// When plex.tv reports several errors the first one is returned.
+    pub fn api_error(&self) -> Option<&PlexApiError> {
+        match self {
+            Self::PlexApiError(error) => Some(error),
+            Self::NotAuthorized(error) | Self::ItemLocked(error) => error.as_ref(),
+            Self::MyPlexErrorResponse { errors } => errors.iter().find_map(Self::api_error),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Deserialize, Debug, Clone)]
+/// An error reported by plex.tv or a Plex Media Server.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
-struct MyPlexApiError {
-    code: i32,
-    message: String,
+pub struct PlexApiError {
+    /// Plex's own error code, e.g. `1029` when a one-time password is
+    /// required. Servers that don't have one report the HTTP status code.
+    pub code: i32,
+    pub message: String,
+    /// HTTP status code of the response.
+    pub status: u16,
+}
+
+impl fmt::Display for PlexApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} - {}", self.code, self.message)
+    }
+}
 
-    /// HTTP status code for the error. Not used, keeping it here to reflect the complete API response.
-    #[allow(dead_code)]
-    status: u16,
+/// How a downloaded file differs from what the server said it would be, see
+/// [`DownloadOptions::verify`](crate::DownloadOptions::verify).
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum DownloadVerificationError {
+    #[error("expected {expected} bytes but received {received}")]
+    SizeMismatch { expected: u64, received: u64 },
+    #[error("the server didn't report the size of the file")]
+    UnknownSize,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 #[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
 pub(crate) struct MyPlexApiErrorResponse {
-    errors: Vec<MyPlexApiError>,
+    errors: Vec<PlexApiError>,
+}
+
+/// The different ways Plex APIs wrap errors.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum ErrorEnvelope {
+    /// `{"errors": [{"code": 1001, "message": "...", "status": 401}]}` from
+    /// plex.tv.
+    MyPlex(MyPlexApiErrorResponse),
+    /// `<Response code="400" status="Bad Request"/>` from the older APIs.
+    Response(ResponseEnvelope),
+    /// `{"error": "Invalid authentication token."}`.
+    Message { error: String },
 }
 
-impl From<MyPlexApiError> for Error {
-    fn from(error: MyPlexApiError) -> Self {
-        Self::MyPlexApiError {
-            code: error.code,
-            message: error.message,
+impl ErrorEnvelope {
+    fn into_api_error(self, status: u16) -> PlexApiError {
+        match self {
+            Self::MyPlex(response) => {
+                response
+                    .errors
+                    .into_iter()
+                    .next()
+                    .unwrap_or_else(|| PlexApiError {
+                        code: i32::from(status),
+                        message: String::new(),
+                        status,
+                    })
+            }
+            Self::Response(response) => PlexApiError {
+                code: i32::from(response.code),
+                message: response.status,
+                status,
+            },
+            Self::Message { error } => PlexApiError {
+                code: i32::from(status),
+                message: error,
+                status,
+            },
         }
     }
 }
 
+#[derive(Deserialize, Debug)]
+struct ResponseEnvelope {
+    #[serde(rename = "@code", alias = "code")]
+    code: u16,
+    #[serde(rename = "@status", alias = "status")]
+    status: String,
+}
+
 impl From<MyPlexApiErrorResponse> for Error {
     fn from(r: MyPlexApiErrorResponse) -> Self {
         Self::MyPlexErrorResponse {
-            errors: r.errors.into_iter().map(|e| e.into()).collect(),
+            errors: r.errors.into_iter().map(Self::PlexApiError).collect(),
         }
     }
 }
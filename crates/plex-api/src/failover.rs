@@ -0,0 +1,93 @@
+use http::Uri;
+use isahc::http::Uri as RequestUri;
+use std::sync::{Arc, Mutex};
+
+/// The addresses a server can be reached at, in the order they're tried,
+/// and which one requests currently go to.
+///
+/// Clones of a client share the current address, so items loaded before a
+/// failover follow the server to its new address.
+#[derive(Debug, Clone)]
+pub(crate) struct Failover {
+    connections: Arc<Vec<Uri>>,
+    current: Arc<Mutex<usize>>,
+}
+
+impl Failover {
+    /// Returns `None` unless there's an address to fail over to.
+    pub(crate) fn new(connections: Vec<Uri>, current: &Uri) -> Option<Self> {
+        if connections.len() < 2 {
+            return None;
+        }
+
+        let current = connections
+            .iter()
+            .position(|uri| origin(uri) == origin(current))
+            .unwrap_or_default();
+        Some(Self {
+            connections: Arc::new(connections),
+            current: Arc::new(Mutex::new(current)),
+        })
+    }
+
+    /// Whether these are the addresses, in the same order.
+    pub(crate) fn has_connections(&self, connections: &[Uri]) -> bool {
+        *self.connections == connections
+    }
+
+    /// Sends the requests to `uri` from now on, when it's one of the
+    /// addresses.
+    pub(crate) fn move_to(&self, uri: &Uri) {
+        if let Some(position) = self
+            .connections
+            .iter()
+            .position(|connection| origin(connection) == origin(uri))
+        {
+            *self.current.lock().unwrap() = position;
+        }
+    }
+
+    /// The address requests should be sent to.
+    pub(crate) fn current(&self) -> Uri {
+        self.connections[*self.current.lock().unwrap()].clone()
+    }
+
+    /// Moves on from the address a request failed to reach and returns the
+    /// request's URI on the next one, `None` once every address has been
+    /// tried. When another request already moved on, its address is used.
+    pub(crate) fn next(&self, failed: &RequestUri, tried: usize) -> Option<RequestUri> {
+        let failed_origin = (
+            failed.scheme_str().unwrap_or_default(),
+            failed.authority().map(|authority| authority.as_str()),
+        );
+        if tried + 1 >= self.connections.len()
+            || !self
+                .connections
+                .iter()
+                .any(|uri| origin(uri) == failed_origin)
+        {
+            return None;
+        }
+
+        let mut current = self.current.lock().unwrap();
+        if origin(&self.connections[*current]) == failed_origin {
+            *current = (*current + 1) % self.connections.len();
+        }
+
+        let (scheme, authority) = origin(&self.connections[*current]);
+        let path_and_query = failed
+            .path_and_query()
+            .map(|path_and_query| path_and_query.as_str())
+            .unwrap_or("/");
+        format!("{scheme}://{}{path_and_query}", authority?)
+            .parse()
+            .ok()
+    }
+}
+
+fn origin(uri: &Uri) -> (&str, Option<&str>) {
+    (
+        uri.scheme_str().unwrap_or_default(),
+        uri.authority().map(|authority| authority.as_str()),
+    )
+}
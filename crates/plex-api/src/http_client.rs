@@ -1,15 +1,42 @@
-use crate::{isahc_compat::StatusCodeExt, url::MYPLEX_DEFAULT_API_URL, Result};
+use crate::{
+    cache::ResponseCache,
+    client_identity::ClientIdentity,
+    failover::Failover,
+    isahc_compat::StatusCodeExt,
+    media_container::MediaContainerWrapper,
+    middleware::{
+        FailoverEvent, FailoverObserver, Middleware, RequestEvent, RequestInterceptor,
+        RequestMetrics, ResponseInspector,
+    },
+    proxy::{Proxies, Proxy},
+    rate_limit::{RateLimit, RateLimiter},
+    redact::{redacted_path, redacted_uri},
+    transcode::ClientProfile,
+    transport::{HttpTransport, Transport},
+    url::MYPLEX_DEFAULT_API_URL,
+    xml, Result, RetryPolicy,
+};
+use futures_timer::Delay;
 use http::{uri::PathAndQuery, StatusCode, Uri};
 use isahc::{
     config::{Configurable, RedirectPolicy},
-    http::{request::Builder, HeaderValue as IsahcHeaderValue},
+    http::{
+        header::CONTENT_TYPE,
+        request::{Builder, Parts as RequestParts},
+        HeaderValue as IsahcHeaderValue, Uri as RequestUri,
+    },
     AsyncBody, AsyncReadResponseExt, HttpClient as IsahcHttpClient, Request as HttpRequest,
     Response as HttpResponse,
 };
 use secrecy::{ExposeSecret, SecretString};
 use serde::{de::DeserializeOwned, Serialize};
-use std::time::Duration;
-use uuid::Uuid;
+use std::{
+    io::ErrorKind,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::Instrument;
 
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
 const DEFAULT_CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
@@ -86,6 +113,72 @@ pub struct HttpClient {
     ///
     /// Used when proxying a client request via a server.
     pub x_plex_target_client_identifier: String,
+
+    /// `X-Plex-Language` header value.
+    ///
+    /// Language for the metadata titles and summaries, e.g. `de` or `pt-BR`.
+    /// Left to the account's settings when empty.
+    pub x_plex_language: String,
+
+    /// `X-Plex-Country-Code` header value.
+    ///
+    /// Country used for ratings and regional metadata, e.g. `US`. Not sent
+    /// when empty.
+    pub x_plex_country_code: String,
+
+    /// `X-Plex-Device-Screen-Resolution` header value.
+    ///
+    /// Plex Web sends the window and screen sizes, e.g.
+    /// `1440x789,1440x900`. The server takes it into account when choosing
+    /// the quality of adaptive streams. Not sent when empty.
+    pub x_plex_device_screen_resolution: String,
+
+    /// `X-Plex-Device-Vendor` header value.
+    ///
+    /// The manufacturer of the device, e.g. `Apple`. Not sent when empty.
+    pub x_plex_device_vendor: String,
+
+    /// `X-Plex-Session-Identifier` header value.
+    ///
+    /// The server groups the requests with the same identifier into one
+    /// session, e.g. on its dashboard. Not sent when empty.
+    pub x_plex_session_identifier: String,
+
+    /// `X-Plex-Playback-Session-Id` header value.
+    ///
+    /// Identifies one playback, so the server can tell its transcode and
+    /// timeline requests belong together. Not sent when empty, see
+    /// [`Server::with_playback_session`](crate::Server::with_playback_session).
+    pub x_plex_playback_session_id: String,
+
+    /// How failed requests are retried, not at all if `None`.
+    retry_policy: Option<RetryPolicy>,
+
+    rate_limiter: RateLimiter,
+
+    middleware: Middleware,
+
+    cache: Option<ResponseCache>,
+
+    /// Whether responses may be sent compressed.
+    compression: bool,
+
+    /// Sends the requests instead of `http_client` when set.
+    transport: Option<Transport>,
+
+    proxies: Proxies,
+
+    /// How long a request may take in total, unless overridden with
+    /// [`RequestBuilder::timeout`].
+    timeout: Option<Duration>,
+
+    connect_timeout: Option<Duration>,
+
+    /// How long to wait when no data arrives.
+    read_timeout: Option<Duration>,
+
+    /// The other addresses of the server at `api_url`, if it has any.
+    failover: Option<Failover>,
 }
 
 impl HttpClient {
@@ -114,6 +207,36 @@ impl HttpClient {
             );
         }
 
+        if !self.x_plex_language.is_empty() {
+            request = request.header("X-Plex-Language", &self.x_plex_language);
+        }
+
+        if !self.x_plex_country_code.is_empty() {
+            request = request.header("X-Plex-Country-Code", &self.x_plex_country_code);
+        }
+
+        if !self.x_plex_device_screen_resolution.is_empty() {
+            request = request.header(
+                "X-Plex-Device-Screen-Resolution",
+                &self.x_plex_device_screen_resolution,
+            );
+        }
+
+        if !self.x_plex_device_vendor.is_empty() {
+            request = request.header("X-Plex-Device-Vendor", &self.x_plex_device_vendor);
+        }
+
+        if !self.x_plex_session_identifier.is_empty() {
+            request = request.header("X-Plex-Session-Identifier", &self.x_plex_session_identifier);
+        }
+
+        if !self.x_plex_playback_session_id.is_empty() {
+            request = request.header(
+                "X-Plex-Playback-Session-Id",
+                &self.x_plex_playback_session_id,
+            );
+        }
+
         if !self.x_plex_token.expose_secret().is_empty() {
             request = request.header("X-Plex-Token", self.x_plex_token.expose_secret());
         }
@@ -121,6 +244,94 @@ impl HttpClient {
         request
     }
 
+    /// Applies the settings that isahc takes per request.
+    fn configure(
+        &self,
+        builder: Builder,
+        host: Option<&str>,
+        timeout: Option<Duration>,
+    ) -> Builder {
+        let mut builder = self
+            .proxies
+            .configure(builder, host)
+            .automatic_decompression(self.compression);
+        if let Some(timeout) = timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(read_timeout) = self.read_timeout {
+            builder = builder.low_speed_timeout(1, read_timeout);
+        }
+        builder
+    }
+
+    /// The address requests are sent to. This is `api_url` unless the server
+    /// stopped responding there and requests moved to another of its
+    /// addresses.
+    pub fn base_url(&self) -> Uri {
+        match &self.failover {
+            Some(failover) => failover.current(),
+            None => self.api_url.clone(),
+        }
+    }
+
+    /// Lets requests move to the next of the addresses when the current one
+    /// can't be reached. The clones sharing the current address keep doing
+    /// so when the addresses haven't changed.
+    pub(crate) fn with_failover(mut self, connections: Vec<Uri>) -> Self {
+        if !self
+            .failover
+            .as_ref()
+            .is_some_and(|failover| failover.has_connections(&connections))
+        {
+            self.failover = Failover::new(connections, &self.base_url());
+        }
+        self
+    }
+
+    /// Returns a copy of this client sending its requests to `api_url`. The
+    /// clones sharing the current address move along when it's one of the
+    /// addresses to fail over to.
+    pub(crate) fn moved_to(&self, api_url: Uri) -> Self {
+        if let Some(failover) = &self.failover {
+            failover.move_to(&api_url);
+        }
+        Self {
+            api_url,
+            ..self.clone()
+        }
+    }
+
+    /// The time limit for requests, see [`HttpClientBuilder::set_timeout`].
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns a copy of this client using a different time limit for its
+    /// requests.
+    pub fn with_timeout(&self, timeout: Option<Duration>) -> Self {
+        Self {
+            timeout,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a copy of the client whose requests belong to the given
+    /// playback. The playback's ID also becomes the session identifier
+    /// unless the client already has one.
+    pub(crate) fn with_playback_session(&self, playback_session_id: String) -> Self {
+        let mut client = self.clone();
+        if client.x_plex_session_identifier.is_empty() {
+            client
+                .x_plex_session_identifier
+                .clone_from(&playback_session_id);
+        }
+        client.x_plex_playback_session_id = playback_session_id;
+        client
+    }
+
     /// Verifies that this client has an authentication token.
     pub fn is_authenticated(&self) -> bool {
         !self.x_plex_token.expose_secret().is_empty()
@@ -133,11 +344,11 @@ impl HttpClient {
         <PathAndQuery as TryFrom<T>>::Error: Into<http::Error>,
     {
         RequestBuilder {
-            http_client: &self.http_client,
-            base_url: self.api_url.clone(),
+            client: self,
+            base_url: self.base_url(),
             path_and_query: path,
             request_builder: self.prepare_request().method("POST"),
-            timeout: Some(DEFAULT_TIMEOUT),
+            timeout: self.timeout,
         }
     }
 
@@ -149,11 +360,11 @@ impl HttpClient {
         <PathAndQuery as TryFrom<T>>::Error: Into<http::Error>,
     {
         RequestBuilder {
-            http_client: &self.http_client,
-            base_url: self.api_url.clone(),
+            client: self,
+            base_url: self.base_url(),
             path_and_query: path,
             request_builder: self.prepare_request_min().method("POST"),
-            timeout: Some(DEFAULT_TIMEOUT),
+            timeout: self.timeout,
         }
     }
 
@@ -164,11 +375,11 @@ impl HttpClient {
         <PathAndQuery as TryFrom<T>>::Error: Into<http::Error>,
     {
         RequestBuilder {
-            http_client: &self.http_client,
-            base_url: self.api_url.clone(),
+            client: self,
+            base_url: self.base_url(),
             path_and_query: path,
             request_builder: self.prepare_request().method("HEAD"),
-            timeout: Some(DEFAULT_TIMEOUT),
+            timeout: self.timeout,
         }
     }
 
@@ -179,11 +390,11 @@ impl HttpClient {
         <PathAndQuery as TryFrom<T>>::Error: Into<http::Error>,
     {
         RequestBuilder {
-            http_client: &self.http_client,
-            base_url: self.api_url.clone(),
+            client: self,
+            base_url: self.base_url(),
             path_and_query: path,
             request_builder: self.prepare_request().method("GET"),
-            timeout: Some(DEFAULT_TIMEOUT),
+            timeout: self.timeout,
         }
     }
 
@@ -195,11 +406,11 @@ impl HttpClient {
         <PathAndQuery as TryFrom<T>>::Error: Into<http::Error>,
     {
         RequestBuilder {
-            http_client: &self.http_client,
-            base_url: self.api_url.clone(),
+            client: self,
+            base_url: self.base_url(),
             path_and_query: path,
             request_builder: self.prepare_request_min().method("GET"),
-            timeout: Some(DEFAULT_TIMEOUT),
+            timeout: self.timeout,
         }
     }
 
@@ -210,11 +421,11 @@ impl HttpClient {
         <PathAndQuery as TryFrom<T>>::Error: Into<http::Error>,
     {
         RequestBuilder {
-            http_client: &self.http_client,
-            base_url: self.api_url.clone(),
+            client: self,
+            base_url: self.base_url(),
             path_and_query: path,
             request_builder: self.prepare_request().method("PUT"),
-            timeout: Some(DEFAULT_TIMEOUT),
+            timeout: self.timeout,
         }
     }
 
@@ -226,11 +437,11 @@ impl HttpClient {
         <PathAndQuery as TryFrom<T>>::Error: Into<http::Error>,
     {
         RequestBuilder {
-            http_client: &self.http_client,
-            base_url: self.api_url.clone(),
+            client: self,
+            base_url: self.base_url(),
             path_and_query: path,
             request_builder: self.prepare_request_min().method("PUT"),
-            timeout: Some(DEFAULT_TIMEOUT),
+            timeout: self.timeout,
         }
     }
 
@@ -241,11 +452,11 @@ impl HttpClient {
         <PathAndQuery as TryFrom<T>>::Error: Into<http::Error>,
     {
         RequestBuilder {
-            http_client: &self.http_client,
-            base_url: self.api_url.clone(),
+            client: self,
+            base_url: self.base_url(),
             path_and_query: path,
             request_builder: self.prepare_request().method("DELETE"),
-            timeout: Some(DEFAULT_TIMEOUT),
+            timeout: self.timeout,
         }
     }
 
@@ -257,11 +468,11 @@ impl HttpClient {
         <PathAndQuery as TryFrom<T>>::Error: Into<http::Error>,
     {
         RequestBuilder {
-            http_client: &self.http_client,
-            base_url: self.api_url.clone(),
+            client: self,
+            base_url: self.base_url(),
             path_and_query: path,
             request_builder: self.prepare_request_min().method("DELETE"),
-            timeout: Some(DEFAULT_TIMEOUT),
+            timeout: self.timeout,
         }
     }
 
@@ -276,10 +487,36 @@ impl HttpClient {
         }
     }
 
+    /// The identity the client sends, e.g. to save it for the next run.
+    pub fn identity(&self) -> ClientIdentity {
+        ClientIdentity {
+            client_identifier: self.x_plex_client_identifier.clone(),
+            product: self.x_plex_product.clone(),
+            version: self.x_plex_version.clone(),
+            platform: self.x_plex_platform.clone(),
+            platform_version: self.x_plex_platform_version.clone(),
+            device: self.x_plex_device.clone(),
+            device_name: self.x_plex_device_name.clone(),
+            model: self.x_plex_model.clone(),
+        }
+    }
+
     /// Get a reference to the client's authentication token.
     pub fn x_plex_token(&self) -> &str {
         self.x_plex_token.expose_secret()
     }
+
+    /// The cache of `GET` responses, see
+    /// [`HttpClientBuilder::set_response_cache`].
+    pub fn response_cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_ref()
+    }
+
+    /// How failed requests are retried, see
+    /// [`HttpClientBuilder::set_retry_policy`].
+    pub fn retry_policy(&self) -> Option<&RetryPolicy> {
+        self.retry_policy.as_ref()
+    }
 }
 
 impl From<&HttpClient> for HttpClient {
@@ -293,7 +530,7 @@ where
     PathAndQuery: TryFrom<P>,
     <PathAndQuery as TryFrom<P>>::Error: Into<http::Error>,
 {
-    http_client: &'a IsahcHttpClient,
+    client: &'a HttpClient,
     base_url: Uri,
     path_and_query: P,
     request_builder: Builder,
@@ -309,7 +546,7 @@ where
     #[must_use]
     pub fn timeout(self, timeout: Option<Duration>) -> Self {
         Self {
-            http_client: self.http_client,
+            client: self.client,
             base_url: self.base_url,
             path_and_query: self.path_and_query,
             request_builder: self.request_builder,
@@ -328,14 +565,16 @@ where
         let uri = Uri::from_parts(uri_parts).map_err(Into::<http::Error>::into)?;
         let uri_string = uri.to_string();
 
-        let mut builder = self.request_builder.uri(uri_string);
-        if let Some(timeout) = self.timeout {
-            builder = builder.timeout(timeout);
-        }
+        let builder = self.client.configure(
+            self.request_builder.uri(uri_string),
+            uri.host(),
+            self.timeout,
+        );
 
         Ok(Request {
-            http_client: self.http_client,
+            client: self.client,
             request: builder.body(body)?,
+            timeout: self.timeout,
         })
     }
 
@@ -357,6 +596,44 @@ where
             .body(body)
     }
 
+    /// Asks for the metadata in this request's response in a different
+    /// language than the client's.
+    #[must_use]
+    pub fn language(self, language: &str) -> Self {
+        self.replace_header("X-Plex-Language", language)
+    }
+
+    /// Overrides the client's screen resolution for this request, e.g. when
+    /// the player moved to another screen.
+    #[must_use]
+    pub fn screen_resolution(self, screen_resolution: &str) -> Self {
+        self.replace_header("X-Plex-Device-Screen-Resolution", screen_resolution)
+    }
+
+    /// Overrides the client's device vendor for this request.
+    #[must_use]
+    pub fn device_vendor(self, device_vendor: &str) -> Self {
+        self.replace_header("X-Plex-Device-Vendor", device_vendor)
+    }
+
+    /// Groups this request into a different session than the client's.
+    #[must_use]
+    pub fn session_identifier(self, session_identifier: &str) -> Self {
+        self.replace_header("X-Plex-Session-Identifier", session_identifier)
+    }
+
+    /// Sets a header the client may have already set, unlike
+    /// [`RequestBuilder::header`] which would send both values.
+    fn replace_header(mut self, name: &'static str, value: &str) -> Self {
+        if let (Some(headers), Ok(value)) = (
+            self.request_builder.headers_mut(),
+            IsahcHeaderValue::from_str(value),
+        ) {
+            headers.insert(name, value);
+        }
+        self
+    }
+
     /// Adds a request header.
     #[must_use]
     pub fn header<K, V>(self, key: K, value: V) -> Self
@@ -367,7 +644,7 @@ where
         <isahc::http::header::HeaderValue as TryFrom<V>>::Error: Into<isahc::http::Error>,
     {
         Self {
-            http_client: self.http_client,
+            client: self.client,
             base_url: self.base_url,
             path_and_query: self.path_and_query,
             request_builder: self.request_builder.header(key, value),
@@ -390,6 +667,16 @@ where
         self.body(())?.xml().await
     }
 
+    /// Sends this request and decodes the contents of the
+    /// [`MediaContainerWrapper`] most JSON responses come in. `T` can be any
+    /// type that deserializes from the `MediaContainer` object, usually a
+    /// struct declaring the fields of the endpoint it needs, e.g. `size` and
+    /// the list of items, or `serde_json::Value` to keep all of them.
+    pub async fn media_container<T: DeserializeOwned + Unpin>(self) -> Result<T> {
+        let wrapper: MediaContainerWrapper<T> = self.json().await?;
+        Ok(wrapper.media_container)
+    }
+
     /// Sends this request, verifies success and then consumes any response.
     pub async fn consume(self) -> Result<()> {
         let mut response = self.header("Accept", "application/json").send().await?;
@@ -405,8 +692,9 @@ where
 }
 
 pub struct Request<'a, T> {
-    http_client: &'a IsahcHttpClient,
+    client: &'a HttpClient,
     request: HttpRequest<T>,
+    timeout: Option<Duration>,
 }
 
 impl<'a, T> Request<'a, T>
@@ -415,10 +703,55 @@ where
 {
     /// Sends this request generating a response.
     pub async fn send(self) -> Result<HttpResponse<AsyncBody>> {
-        Ok(self.http_client.send_async(self.request).await?)
+        let Request {
+            client,
+            request,
+            timeout,
+        } = self;
+        let request = request.map(Into::<AsyncBody>::into);
+        // Bodies can't be replayed, so only requests without one are retried
+        // or sent to another address.
+        if !request.body().is_empty()
+            || (client.retry_policy.is_none() && client.failover.is_none())
+        {
+            return execute(client, request).await;
+        }
+
+        let (parts, _) = request.into_parts();
+        let mut uri = parts.uri.clone();
+        let mut tried = 0;
+        loop {
+            let result = send_with_retries(client, &parts, &uri, timeout).await;
+
+            let next = match (&result, &client.failover) {
+                (Err(error), Some(failover)) if is_network_error(error) => {
+                    failover.next(&uri, tried).map(|next| (next, error))
+                }
+                _ => None,
+            };
+            let Some((next, error)) = next else {
+                return result;
+            };
+
+            tracing::debug!(
+                "Failing over from {} to {}: {error}",
+                redacted_uri(&uri),
+                redacted_uri(&next)
+            );
+            client.middleware.failed_over(&FailoverEvent {
+                from: &uri,
+                to: &next,
+                error,
+            });
+            uri = next;
+            tried += 1;
+        }
     }
 
     /// Sends this request and attempts to decode the response as JSON.
+    ///
+    /// Endpoints that only answer in XML are read as if they had sent the
+    /// equivalent JSON.
     pub async fn json<R: DeserializeOwned + Unpin>(mut self) -> Result<R> {
         let headers = self.request.headers_mut();
         headers.insert("Accept", IsahcHeaderValue::from_static("application/json"));
@@ -427,17 +760,27 @@ where
 
         match response.status().as_http_status() {
             StatusCode::OK | StatusCode::CREATED | StatusCode::ACCEPTED => {
-                let body = response.text().await?;
-                match serde_json::from_str(&body) {
+                let is_xml = response
+                    .headers()
+                    .get(CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .is_some_and(|content_type| content_type.contains("xml"));
+                let body = response.bytes().await?;
+                let result = if is_xml {
+                    xml::to_json(&body).and_then(|value| Ok(serde_json::from_value(value)?))
+                } else {
+                    serde_json::from_slice(&body).map_err(Into::into)
+                };
+                match result {
                     Ok(response) => Ok(response),
                     Err(error) => {
                         #[cfg(feature = "tests_deny_unknown_fields")]
                         // We're in tests, so it's fine to print
                         #[allow(clippy::print_stdout)]
                         {
-                            println!("Received body: {body}");
+                            println!("Received body: {}", String::from_utf8_lossy(&body));
                         }
-                        Err(error.into())
+                        Err(error)
                     }
                 }
             }
@@ -454,15 +797,15 @@ where
 
         match response.status().as_http_status() {
             StatusCode::OK | StatusCode::CREATED | StatusCode::ACCEPTED => {
-                let body = response.text().await?;
-                match quick_xml::de::from_str(&body) {
+                let body = response.bytes().await?;
+                match quick_xml::de::from_reader(body.as_slice()) {
                     Ok(response) => Ok(response),
                     Err(error) => {
                         #[cfg(feature = "tests_deny_unknown_fields")]
                         // We're in tests, so it's fine to print
                         #[allow(clippy::print_stdout)]
                         {
-                            println!("Received body: {body}");
+                            println!("Received body: {}", String::from_utf8_lossy(&body));
                         }
                         Err(error.into())
                     }
@@ -473,17 +816,138 @@ where
     }
 }
 
+/// Sends a request without a body to the URI, retrying it according to the
+/// client's retry policy.
+async fn send_with_retries(
+    client: &HttpClient,
+    parts: &RequestParts,
+    uri: &RequestUri,
+    timeout: Option<Duration>,
+) -> Result<HttpResponse<AsyncBody>> {
+    let attempts = client
+        .retry_policy
+        .as_ref()
+        .map_or(1, RetryPolicy::attempts);
+    let mut retry = 0;
+    loop {
+        let mut builder = client.configure(
+            HttpRequest::builder()
+                .method(parts.method.clone())
+                .uri(uri.clone())
+                .version(parts.version),
+            uri.host(),
+            timeout,
+        );
+        if let Some(headers) = builder.headers_mut() {
+            headers.extend(parts.headers.clone());
+        }
+
+        let result = execute(client, builder.body(AsyncBody::empty())?).await;
+
+        let delay = match (&result, &client.retry_policy) {
+            (_, None) => None,
+            _ if retry + 1 >= attempts => None,
+            (Ok(response), Some(policy)) => policy.retry_response(&parts.method, response, retry),
+            (Err(error), Some(policy)) => policy.retry_error(&parts.method, error, retry),
+        };
+        let Some(delay) = delay else {
+            return result;
+        };
+
+        if let Ok(mut response) = result {
+            tracing::debug!(
+                "Retrying {} after HTTP {} in {delay:?}",
+                redacted_uri(uri),
+                response.status()
+            );
+            response.consume().await?;
+        } else {
+            tracing::debug!(
+                "Retrying {} after a timeout in {delay:?}",
+                redacted_uri(uri)
+            );
+        }
+
+        Delay::new(delay).await;
+        retry += 1;
+    }
+}
+
+/// Whether the server couldn't be reached at all, as opposed to answering
+/// with an error.
+fn is_network_error(error: &crate::Error) -> bool {
+    matches!(error, crate::Error::IsahcError { source } if source.is_network())
+}
+
+/// Sends a single request through the client's middleware, cache and rate
+/// limiter.
+async fn execute(
+    client: &HttpClient,
+    request: HttpRequest<AsyncBody>,
+) -> Result<HttpResponse<AsyncBody>> {
+    let mut request = client.middleware.intercept(request);
+    let method = request.method().clone();
+    let uri = request.uri().clone();
+    let span = tracing::debug_span!(
+        "http_request",
+        method = %method,
+        path = %redacted_path(&uri),
+        status = tracing::field::Empty,
+        duration_ms = tracing::field::Empty,
+    );
+
+    async {
+        let cache_key = client
+            .cache
+            .as_ref()
+            .and_then(|cache| Some((cache, cache.prepare(&mut request)?)));
+
+        client.rate_limiter.wait(&uri).await;
+        let started = Instant::now();
+        let result = match &client.transport {
+            Some(transport) => transport.0.send(request).await,
+            None => client
+                .http_client
+                .send_async(request)
+                .await
+                .map_err(Into::into),
+        };
+        let duration = started.elapsed();
+
+        let status = result
+            .as_ref()
+            .ok()
+            .map(|response| response.status().as_u16());
+        let span = tracing::Span::current();
+        span.record("duration_ms", duration.as_millis() as u64);
+        if let Some(status) = status {
+            span.record("status", status);
+        }
+        client.middleware.record(&RequestEvent {
+            method: &method,
+            host: uri.host(),
+            path: uri.path(),
+            status,
+            duration,
+        });
+
+        let mut response = result?;
+        if let Some((cache, key)) = cache_key {
+            response = cache.handle(key, response).await?;
+        }
+        client.middleware.inspect(&method, &uri, response).await
+    }
+    .instrument(span)
+    .await
+}
+
 pub struct HttpClientBuilder {
     client: Result<HttpClient>,
 }
 
 impl Default for HttpClientBuilder {
     fn default() -> Self {
-        let sys_platform = sysinfo::System::name().unwrap_or("unknown".to_string());
-        let sys_version = sysinfo::System::os_version().unwrap_or("unknown".to_string());
-        let sys_hostname = sysinfo::System::host_name().unwrap_or("unknown".to_string());
-
-        let random_uuid = Uuid::new_v4();
+        let identity = ClientIdentity::generate();
 
         let client = HttpClient {
             api_url: Uri::from_static(MYPLEX_DEFAULT_API_URL),
@@ -493,22 +957,35 @@ impl Default for HttpClientBuilder {
                 .build()
                 .expect("failed to create default http client"),
             x_plex_provides: String::from("controller"),
-            x_plex_product: option_env!("CARGO_PKG_NAME")
-                .unwrap_or("plex-api")
-                .to_string(),
-            x_plex_platform: sys_platform.clone(),
-            x_plex_platform_version: sys_version,
-            x_plex_version: option_env!("CARGO_PKG_VERSION")
-                .unwrap_or("unknown")
-                .to_string(),
-            x_plex_device: sys_platform,
-            x_plex_device_name: sys_hostname,
-            x_plex_client_identifier: random_uuid.to_string(),
+            x_plex_product: identity.product,
+            x_plex_platform: identity.platform,
+            x_plex_platform_version: identity.platform_version,
+            x_plex_version: identity.version,
+            x_plex_device: identity.device,
+            x_plex_device_name: identity.device_name,
+            x_plex_client_identifier: identity.client_identifier,
             x_plex_sync_version: String::from("2"),
             x_plex_token: SecretString::new("".into()),
-            x_plex_model: String::from("hosted"),
+            x_plex_model: identity.model,
             x_plex_features: String::from("external-media,indirect-media,hub-style-list"),
             x_plex_target_client_identifier: String::from(""),
+            x_plex_language: String::from(""),
+            x_plex_country_code: String::from(""),
+            x_plex_device_screen_resolution: String::from(""),
+            x_plex_device_vendor: String::from(""),
+            x_plex_session_identifier: String::from(""),
+            x_plex_playback_session_id: String::from(""),
+            retry_policy: None,
+            rate_limiter: RateLimiter::default(),
+            middleware: Middleware::default(),
+            cache: None,
+            compression: true,
+            transport: None,
+            proxies: Proxies::default(),
+            timeout: Some(DEFAULT_TIMEOUT),
+            connect_timeout: None,
+            read_timeout: None,
+            failover: None,
         };
 
         Self { client: Ok(client) }
@@ -522,10 +999,207 @@ impl HttpClientBuilder {
         Self::default().set_x_plex_platform("Generic")
     }
 
+    /// Identifies the client as one of the common device types so that the
+    /// server transcodes media using its built-in profile for that device.
+    pub fn set_client_profile(self, profile: ClientProfile) -> Self {
+        self.set_x_plex_platform(profile.platform())
+    }
+
     pub fn build(self) -> Result<HttpClient> {
         self.client
     }
 
+    /// Retries requests that failed because the server was busy or
+    /// unavailable. Requests are not retried by default.
+    pub fn set_retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.retry_policy = Some(retry_policy);
+                client
+            }),
+        }
+    }
+
+    /// Limits how often plex.tv is requested. All the plex.tv hosts count
+    /// towards the same limit. There's no limit by default.
+    pub fn set_myplex_rate_limit(self, rate_limit: RateLimit) -> Self {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.rate_limiter.myplex = Some(rate_limit);
+                client
+            }),
+        }
+    }
+
+    /// Limits how often each server is requested. Every host has its own
+    /// limit. There's no limit by default.
+    pub fn set_server_rate_limit(self, rate_limit: RateLimit) -> Self {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.rate_limiter.server = Some(rate_limit);
+                client
+            }),
+        }
+    }
+
+    /// Caches responses to `GET` requests so that they can be revalidated
+    /// instead of being downloaded again. Nothing is cached by default.
+    pub fn set_response_cache(self, cache: ResponseCache) -> Self {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.cache = Some(cache);
+                client
+            }),
+        }
+    }
+
+    /// Asks for gzip or deflate compressed responses and decompresses them
+    /// as they arrive. Enabled by default.
+    pub fn set_compression(self, compression: bool) -> Self {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.compression = compression;
+                client
+            }),
+        }
+    }
+
+    /// Gets told when requests to a server move to another of its addresses
+    /// because the current one couldn't be reached. This happens for servers
+    /// connected through [`Device::connect`](crate::device::Device::connect),
+    /// trying local addresses first, then remote ones and relays last.
+    pub fn set_failover_observer<O>(self, observer: O) -> Self
+    where
+        O: FailoverObserver + 'static,
+    {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.middleware.failover_observer = Some(Arc::new(observer));
+                client
+            }),
+        }
+    }
+
+    /// Reports every request made by the client, including its status and
+    /// how long it took.
+    pub fn set_metrics<M>(self, metrics: M) -> Self
+    where
+        M: RequestMetrics + 'static,
+    {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.middleware.metrics = Some(Arc::new(metrics));
+                client
+            }),
+        }
+    }
+
+    /// Adds an interceptor that can change every request before it's sent.
+    /// Interceptors run in the order they were added.
+    pub fn add_request_interceptor<I>(self, interceptor: I) -> Self
+    where
+        I: RequestInterceptor + 'static,
+    {
+        Self {
+            client: self.client.map(move |mut client| {
+                client
+                    .middleware
+                    .request_interceptors
+                    .push(Arc::new(interceptor));
+                client
+            }),
+        }
+    }
+
+    /// Adds an inspector that sees every response before the client handles
+    /// it. Inspectors run in the order they were added.
+    pub fn add_response_inspector<I>(self, inspector: I) -> Self
+    where
+        I: ResponseInspector + 'static,
+    {
+        Self {
+            client: self.client.map(move |mut client| {
+                client
+                    .middleware
+                    .response_inspectors
+                    .push(Arc::new(inspector));
+                client
+            }),
+        }
+    }
+
+    /// How long a request may take in total before it's aborted, `None` to
+    /// wait forever. Defaults to 30 seconds. Downloads aren't limited.
+    pub fn set_timeout(self, timeout: Option<Duration>) -> Self {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.timeout = timeout;
+                client
+            }),
+        }
+    }
+
+    /// How long to wait for a connection to be established. Defaults to 5
+    /// seconds.
+    pub fn set_connect_timeout(self, connect_timeout: Duration) -> Self {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.connect_timeout = Some(connect_timeout);
+                client
+            }),
+        }
+    }
+
+    /// Aborts requests when no data arrives for this long, which also applies
+    /// to downloads. Not set by default.
+    pub fn set_read_timeout(self, read_timeout: Duration) -> Self {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.read_timeout = Some(read_timeout);
+                client
+            }),
+        }
+    }
+
+    /// Sends all requests through the proxy.
+    pub fn set_proxy(self, proxy: Proxy) -> Self {
+        self.set_myplex_proxy(proxy.clone()).set_server_proxy(proxy)
+    }
+
+    /// Sends the requests to plex.tv through the proxy.
+    pub fn set_myplex_proxy(self, proxy: Proxy) -> Self {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.proxies.myplex = Some(proxy);
+                client
+            }),
+        }
+    }
+
+    /// Sends the requests to servers, i.e. anything but plex.tv, through the
+    /// proxy.
+    pub fn set_server_proxy(self, proxy: Proxy) -> Self {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.proxies.server = Some(proxy);
+                client
+            }),
+        }
+    }
+
+    /// Sends requests using the given transport rather than the isahc client.
+    pub fn set_transport<T>(self, transport: T) -> Self
+    where
+        T: HttpTransport + 'static,
+    {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.transport = Some(Transport(Arc::new(transport)));
+                client
+            }),
+        }
+    }
+
     pub fn set_http_client(self, http_client: IsahcHttpClient) -> Self {
         Self {
             client: self.client.map(move |mut client| {
@@ -555,11 +1229,52 @@ impl HttpClientBuilder {
         Self {
             client: self.client.and_then(move |mut client| {
                 client.api_url = Uri::try_from(api_url).map_err(Into::into)?;
+                // The other addresses belonged to the previous server.
+                client.failover = None;
                 Ok(client)
             }),
         }
     }
 
+    /// Uses a previously saved identity instead of the randomly generated one.
+    pub fn set_identity(self, identity: ClientIdentity) -> Self {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.x_plex_client_identifier = identity.client_identifier;
+                client.x_plex_product = identity.product;
+                client.x_plex_version = identity.version;
+                client.x_plex_platform = identity.platform;
+                client.x_plex_platform_version = identity.platform_version;
+                client.x_plex_device = identity.device;
+                client.x_plex_device_name = identity.device_name;
+                client.x_plex_model = identity.model;
+                client
+            }),
+        }
+    }
+
+    /// Uses the identity stored in the file, creating the file with the
+    /// client's current identity on the first run. See
+    /// [`ClientIdentity::load_or_create`].
+    pub fn load_identity<P: AsRef<Path>>(self, path: P) -> Self {
+        let path = path.as_ref();
+        let client = match self.client {
+            Ok(client) => client,
+            Err(error) => return Self { client: Err(error) },
+        };
+
+        match ClientIdentity::load(path) {
+            Ok(identity) => Self::from(client).set_identity(identity),
+            Err(crate::Error::StdIoError { source }) if source.kind() == ErrorKind::NotFound => {
+                let result = client.identity().save(path);
+                Self {
+                    client: result.map(|_| client),
+                }
+            }
+            Err(error) => Self { client: Err(error) },
+        }
+    }
+
     pub fn set_x_plex_token<S: Into<SecretString>>(self, token: S) -> Self {
         Self {
             client: self.client.map(move |mut client| {
@@ -650,6 +1365,67 @@ impl HttpClientBuilder {
         }
     }
 
+    /// Requests metadata in the given language, e.g. `de` or `pt-BR`. This
+    /// also applies to Discover and the other plex.tv metadata providers.
+    pub fn set_x_plex_language<S: Into<String>>(self, language: S) -> Self {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.x_plex_language = language.into();
+                client
+            }),
+        }
+    }
+
+    pub fn set_x_plex_country_code<S: Into<String>>(self, country_code: S) -> Self {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.x_plex_country_code = country_code.into();
+                client
+            }),
+        }
+    }
+
+    pub fn set_x_plex_device_screen_resolution<S: Into<String>>(
+        self,
+        screen_resolution: S,
+    ) -> Self {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.x_plex_device_screen_resolution = screen_resolution.into();
+                client
+            }),
+        }
+    }
+
+    pub fn set_x_plex_device_vendor<S: Into<String>>(self, device_vendor: S) -> Self {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.x_plex_device_vendor = device_vendor.into();
+                client
+            }),
+        }
+    }
+
+    pub fn set_x_plex_session_identifier<S: Into<String>>(self, session_identifier: S) -> Self {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.x_plex_session_identifier = session_identifier.into();
+                client
+            }),
+        }
+    }
+
+    /// Continues a playback started earlier, e.g. by another instance of the
+    /// app.
+    pub fn set_x_plex_playback_session_id<S: Into<String>>(self, playback_session_id: S) -> Self {
+        Self {
+            client: self.client.map(move |mut client| {
+                client.x_plex_playback_session_id = playback_session_id.into();
+                client
+            }),
+        }
+    }
+
     pub fn set_x_plex_features(self, features: &[&str]) -> Self {
         Self {
             client: self.client.map(move |mut client| {
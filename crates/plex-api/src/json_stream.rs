@@ -0,0 +1,287 @@
+use crate::Result;
+use futures::{
+    io::{AsyncRead, AsyncReadExt},
+    stream::{self, Stream},
+};
+use serde::de::DeserializeOwned;
+use std::io;
+
+const CHUNK_SIZE: usize = 16 * 1024;
+
+#[derive(Debug, Default)]
+struct Scanner {
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    string_start: usize,
+    last_string: (usize, usize),
+}
+
+impl Scanner {
+    /// Feeds the byte at `pos` to the scanner, returning true if it was part
+    /// of a string.
+    fn feed(&mut self, byte: u8, pos: usize) -> bool {
+        if self.in_string {
+            if self.escaped {
+                self.escaped = false;
+            } else if byte == b'\\' {
+                self.escaped = true;
+            } else if byte == b'"' {
+                self.in_string = false;
+                self.last_string = (self.string_start, pos + 1);
+            }
+            return true;
+        }
+
+        match byte {
+            b'"' => {
+                self.in_string = true;
+                self.string_start = pos;
+                return true;
+            }
+            b'{' | b'[' => self.depth += 1,
+            b'}' | b']' => self.depth = self.depth.saturating_sub(1),
+            _ => {}
+        }
+        false
+    }
+}
+
+#[derive(Debug)]
+enum State {
+    /// Looking for the array, which starts after `pending_key` when set.
+    Header {
+        pending_key: Option<usize>,
+    },
+    Items,
+    Done,
+}
+
+/// Reads the elements of an array inside a JSON document one by one, so that
+/// only a single element has to be held in memory at a time.
+///
+/// The array has to be a member of the object nested in the root object,
+/// which is where Plex puts the items of a `MediaContainer`.
+pub(crate) struct JsonArrayReader<R> {
+    reader: R,
+    key: String,
+    buffer: Vec<u8>,
+    pos: usize,
+    scanner: Scanner,
+    state: State,
+}
+
+impl<R: AsyncRead + Unpin> JsonArrayReader<R> {
+    pub(crate) fn new(reader: R, key: &str) -> Self {
+        Self {
+            reader,
+            key: format!("\"{key}\""),
+            buffer: Vec::new(),
+            pos: 0,
+            scanner: Scanner::default(),
+            state: State::Header { pending_key: None },
+        }
+    }
+
+    /// Reads more of the body, returning false once it has all been read.
+    async fn fill(&mut self) -> Result<bool> {
+        let start = self.buffer.len();
+        self.buffer.resize(start + CHUNK_SIZE, 0);
+        let read = self.reader.read(&mut self.buffer[start..]).await?;
+        self.buffer.truncate(start + read);
+        Ok(read > 0)
+    }
+
+    /// Reads everything up to the start of the array and deserializes it as
+    /// if the array wasn't there. Must be called before [`Self::next`].
+    pub(crate) async fn header<H: DeserializeOwned>(&mut self) -> Result<H> {
+        loop {
+            while self.pos < self.buffer.len() {
+                let State::Header { pending_key } = self.state else {
+                    unreachable!("the header has already been read");
+                };
+                let byte = self.buffer[self.pos];
+
+                if let Some(key_start) = pending_key {
+                    if byte.is_ascii_whitespace() {
+                        self.pos += 1;
+                        continue;
+                    }
+                    if byte == b'[' {
+                        let mut header = self.buffer[..key_start].trim_ascii_end().to_vec();
+                        if header.last() == Some(&b',') {
+                            header.pop();
+                        }
+                        header.extend(std::iter::repeat_n(b'}', self.scanner.depth));
+
+                        self.buffer.drain(..=self.pos);
+                        self.pos = 0;
+                        self.scanner = Scanner::default();
+                        self.state = State::Items;
+                        return Ok(serde_json::from_slice(&header)?);
+                    }
+                    self.state = State::Header { pending_key: None };
+                }
+
+                let pos = self.pos;
+                self.pos += 1;
+                if !self.scanner.feed(byte, pos) && byte == b':' && self.scanner.depth == 2 {
+                    let (start, end) = self.scanner.last_string;
+                    if self.buffer[start..end] == *self.key.as_bytes() {
+                        self.state = State::Header {
+                            pending_key: Some(start),
+                        };
+                    }
+                }
+            }
+
+            if !self.fill().await? {
+                // There's no array, e.g. because the container is empty.
+                self.state = State::Done;
+                return Ok(serde_json::from_slice(&self.buffer)?);
+            }
+        }
+    }
+
+    /// Reads the next element of the array.
+    pub(crate) async fn next<T: DeserializeOwned>(&mut self) -> Result<Option<T>> {
+        loop {
+            match self.state {
+                State::Done => return Ok(None),
+                State::Header { .. } => unreachable!("the header hasn't been read"),
+                State::Items => {}
+            }
+
+            if self.scanner.depth == 0 {
+                // Skip to the start of the next element.
+                let skip = self.buffer[self.pos..]
+                    .iter()
+                    .take_while(|b| b.is_ascii_whitespace() || **b == b',')
+                    .count();
+                self.buffer.drain(..self.pos + skip);
+                self.pos = 0;
+                if self.buffer.first() == Some(&b']') {
+                    self.state = State::Done;
+                    return Ok(None);
+                }
+            }
+
+            while self.pos < self.buffer.len() {
+                let byte = self.buffer[self.pos];
+                let pos = self.pos;
+                let depth = self.scanner.depth;
+                self.pos += 1;
+
+                let end = if self.scanner.feed(byte, pos) {
+                    None
+                } else {
+                    match byte {
+                        b'}' | b']' if depth == 1 => Some(self.pos),
+                        // The end of a scalar element.
+                        b',' | b']' if depth == 0 => Some(pos),
+                        _ => None,
+                    }
+                };
+
+                if let Some(end) = end {
+                    let item = serde_json::from_slice(&self.buffer[..end])?;
+                    self.buffer.drain(..end);
+                    self.pos = 0;
+                    return Ok(Some(item));
+                }
+            }
+
+            if !self.fill().await? {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+        }
+    }
+
+    /// Turns the reader into a stream of the remaining elements.
+    pub(crate) fn into_stream<T: DeserializeOwned>(self) -> impl Stream<Item = Result<T>> {
+        stream::try_unfold(self, |mut reader| async move {
+            Ok(reader.next().await?.map(|item| (item, reader)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{executor::block_on, io::Cursor, TryStreamExt};
+    use serde::Deserialize;
+    use std::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    /// Hands out the data a single byte at a time.
+    struct Trickle(Cursor<Vec<u8>>);
+
+    impl AsyncRead for Trickle {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let len = buf.len().min(1);
+            Pin::new(&mut self.0).poll_read(cx, &mut buf[..len])
+        }
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Container {
+        #[serde(rename = "MediaContainer")]
+        media_container: Header,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Header {
+        size: u32,
+        title: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item {
+        title: String,
+    }
+
+    fn read(json: &str) -> Result<(Container, Vec<Item>)> {
+        block_on(async {
+            let mut reader =
+                JsonArrayReader::new(Trickle(Cursor::new(json.as_bytes().to_vec())), "Metadata");
+            let header = reader.header().await?;
+            let items = reader.into_stream().try_collect().await?;
+            Ok((header, items))
+        })
+    }
+
+    #[test]
+    fn read_array() {
+        let (header, items) = read(
+            r#"{"MediaContainer": {"size": 2, "Metadata": [
+                {"title": "A \"quoted\" ] title", "Metadata": []},
+                {"title": "B"}
+            ], "title": "After"}}"#,
+        )
+        .unwrap();
+        assert_eq!(header.media_container.size, 2);
+        assert_eq!(
+            items,
+            vec![
+                Item {
+                    title: "A \"quoted\" ] title".to_owned()
+                },
+                Item {
+                    title: "B".to_owned()
+                }
+            ]
+        );
+
+        let (header, items) = read(r#"{"MediaContainer": {"size": 0, "title": "Empty"}}"#).unwrap();
+        assert_eq!(header.media_container.title.as_deref(), Some("Empty"));
+        assert!(items.is_empty());
+
+        assert!(read(r#"{"MediaContainer": {"size": 1, "Metadata": [{"title": "#).is_err());
+    }
+}
@@ -7,22 +7,60 @@
 
 //! TODO
 //!
+pub mod blocking;
+mod cache;
+mod client_identity;
+mod download;
+pub mod endpoints;
 mod error;
+mod failover;
 mod http_client;
 mod isahc_compat;
+mod json_stream;
 pub mod media_container;
+mod middleware;
 mod myplex;
 mod player;
+mod progress;
+mod proxy;
+mod rate_limit;
+mod redact;
+mod retry;
 mod server;
+mod transport;
 pub mod url;
 pub mod webhook;
+mod xml;
 
-pub use error::Error;
-pub use http_client::{HttpClient, HttpClientBuilder};
+pub use cache::ResponseCache;
+pub use client_identity::ClientIdentity;
+pub use download::{DownloadOptions, SparseFile};
+pub use error::{DownloadVerificationError, Error, PlexApiError};
+pub use http_client::{HttpClient, HttpClientBuilder, RequestBuilder};
+pub use middleware::{
+    FailoverEvent, FailoverObserver, RequestEvent, RequestInterceptor, RequestMetrics,
+    ResponseInspector,
+};
 pub use myplex::{
-    account::RestrictionProfile, device, discover, pin::PinManager, sharing, MyPlex, MyPlexBuilder,
+    account::{self, RestrictionProfile},
+    device, diagnostics, discover,
+    pin::PinManager,
+    privacy::{MetricStatus, Privacy, PrivacyMetric},
+    sharing, sync,
+    webhook::WebhookManager,
+    MyPlex, MyPlexBuilder,
 };
 pub use player::Player;
-pub use server::{library, prefs::Preferences, transcode, Server};
+pub use progress::{Progress, ProgressObserver};
+pub use proxy::Proxy;
+pub use rate_limit::RateLimit;
+pub use retry::RetryPolicy;
+pub use server::{
+    butler, content_rating, library, livetv, optimize, prefs::Preferences, transcode,
+    DeleteWatched, EpisodeSort, Health, ItemSettings, KeepUnwatched, MappingState,
+    PreviewThumbnails, RemoteAccess, SeasonsDisplay, Server, ServerCapabilities, ServerFeature,
+    ServerVersion, TranscoderSettings, X264Preset,
+};
+pub use transport::HttpTransport;
 
 pub type Result<T = (), E = error::Error> = std::result::Result<T, E>;
@@ -4,6 +4,10 @@ use serde_plain::derive_fromstr_from_deserialize;
 use serde_with::{formats::CommaSeparator, serde_as, NoneAsEmptyString, StringWithSeparator};
 use time::OffsetDateTime;
 
+use super::helpers::deserialize_datetime_from_timestamp_or_rfc3339;
+
+/// The devices from `/devices.xml` or `/api/resources`. The resources from
+/// `/api/v2/resources` are a JSON array of [`Device`] instead.
 #[derive(Debug, Deserialize, Clone)]
 #[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
@@ -23,80 +27,94 @@ pub struct DevicesMediaContainer {
     // pub media_container: MediaContainer,
 }
 
+/// A device as plex.tv lists it, either from the XML attributes of the older
+/// endpoints or the fields of `/api/v2/resources`.
 #[serde_as]
 #[derive(Debug, Deserialize, Clone)]
 #[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
 pub struct Device {
-    #[serde(rename = "@name")]
+    #[serde(rename = "@name", alias = "name")]
     pub name: String,
-    #[serde(rename = "@product")]
+    #[serde(rename = "@product", alias = "product")]
     pub product: String,
-    #[serde(rename = "@publicAddress")]
+    #[serde(rename = "@publicAddress", alias = "publicAddress")]
     pub public_address: String,
-    #[serde(rename = "@productVersion")]
+    #[serde(rename = "@productVersion", alias = "productVersion")]
     pub product_version: String,
-    #[serde(rename = "@platform")]
+    #[serde(rename = "@platform", alias = "platform")]
     pub platform: String,
-    #[serde(rename = "@platformVersion")]
+    #[serde(rename = "@platformVersion", alias = "platformVersion")]
     pub platform_version: String,
-    #[serde(rename = "@device")]
+    #[serde(rename = "@device", alias = "device")]
     pub device: String,
-    #[serde(rename = "@model")]
+    #[serde(rename = "@model", alias = "model")]
     pub model: Option<String>,
-    #[serde(rename = "@vendor")]
+    #[serde(rename = "@vendor", alias = "vendor")]
     pub vendor: Option<String>,
     #[serde_as(as = "StringWithSeparator::<CommaSeparator, Feature>")]
-    #[serde(rename = "@provides")]
+    #[serde(rename = "@provides", alias = "provides")]
     pub provides: Vec<Feature>,
-    #[serde(rename = "@clientIdentifier")]
+    #[serde(rename = "@clientIdentifier", alias = "clientIdentifier")]
     pub client_identifier: String,
-    #[serde(rename = "@version")]
+    #[serde(rename = "@version", alias = "version")]
     pub version: Option<String>,
-    #[serde(rename = "@id")]
+    #[serde(rename = "@id", alias = "id")]
     pub id: Option<u32>,
-    #[serde(rename = "@token")]
+    #[serde(rename = "@token", alias = "token")]
     pub token: Option<SecretString>,
-    #[serde(rename = "@accessToken")]
+    #[serde(rename = "@accessToken", alias = "accessToken")]
     pub access_token: Option<SecretString>,
-    #[serde(with = "time::serde::timestamp", rename = "@createdAt")]
+    #[serde(
+        deserialize_with = "deserialize_datetime_from_timestamp_or_rfc3339",
+        rename = "@createdAt",
+        alias = "createdAt"
+    )]
     pub created_at: OffsetDateTime,
-    #[serde(with = "time::serde::timestamp", rename = "@lastSeenAt")]
+    #[serde(
+        deserialize_with = "deserialize_datetime_from_timestamp_or_rfc3339",
+        rename = "@lastSeenAt",
+        alias = "lastSeenAt"
+    )]
     pub last_seen_at: OffsetDateTime,
     #[serde_as(as = "StringWithSeparator::<CommaSeparator, String>")]
-    #[serde(default, rename = "@screenResolution")]
+    #[serde(default, rename = "@screenResolution", alias = "screenResolution")]
     pub screen_resolution: Vec<String>,
     #[serde_as(as = "NoneAsEmptyString")]
-    #[serde(default, rename = "@screenDensity")]
+    #[serde(default, rename = "@screenDensity", alias = "screenDensity")]
     pub screen_density: Option<u16>,
-    #[serde(rename = "Connection", default)]
+    #[serde(rename = "Connection", alias = "connections", default)]
     pub connections: Vec<Connection>,
-    #[serde(rename = "@httpsRequired")]
+    #[serde(rename = "@httpsRequired", alias = "httpsRequired")]
     pub https_required: Option<bool>,
-    #[serde(rename = "@synced")]
+    #[serde(rename = "@synced", alias = "synced")]
     pub synced: Option<bool>,
-    #[serde(rename = "@relay")]
+    #[serde(rename = "@relay", alias = "relay")]
     pub relay: Option<bool>,
-    #[serde(rename = "@publicAddressMatches")]
+    #[serde(rename = "@publicAddressMatches", alias = "publicAddressMatches")]
     pub public_address_matches: Option<bool>,
-    #[serde(rename = "@presence")]
+    #[serde(rename = "@presence", alias = "presence")]
     pub presence: Option<bool>,
-    #[serde(rename = "@owned")]
+    #[serde(rename = "@owned", alias = "owned")]
     pub owned: Option<bool>,
-    #[serde(rename = "@ownerId")]
+    #[serde(rename = "@ownerId", alias = "ownerId")]
     pub owner_id: Option<u64>,
-    #[serde(rename = "@home")]
+    #[serde(rename = "@home", alias = "home")]
     pub home: Option<bool>,
-    #[serde(rename = "@sourceTitle")]
+    #[serde(rename = "@sourceTitle", alias = "sourceTitle")]
     pub source_title: Option<String>,
     #[serde(rename = "SyncList")]
     pub sync_list: Option<SyncList>,
-    #[serde(default = "create_empty_secret_string", rename = "@authToken")]
+    #[serde(
+        default = "create_empty_secret_string",
+        rename = "@authToken",
+        alias = "authToken"
+    )]
     pub auth_token: SecretString,
-    #[serde(rename = "@dnsRebindingProtection")]
+    #[serde(rename = "@dnsRebindingProtection", alias = "dnsRebindingProtection")]
     pub dns_rebinding_protection: Option<bool>,
-    #[serde(rename = "@natLoopbackSupported")]
+    #[serde(rename = "@natLoopbackSupported", alias = "natLoopbackSupported")]
     pub nat_loopback_supported: Option<bool>,
-    #[serde(rename = "@searchEnabled")]
+    #[serde(rename = "@searchEnabled", alias = "searchEnabled")]
     pub search_enabled: Option<bool>,
 }
 
@@ -107,29 +125,31 @@ fn create_empty_secret_string() -> SecretString {
 #[derive(Debug, Deserialize, Clone)]
 #[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
 pub struct SyncList {
-    #[serde(rename = "@itemsCompleteCount")]
+    #[serde(rename = "@itemsCompleteCount", alias = "itemsCompleteCount")]
     pub items_complete_count: u32,
-    #[serde(rename = "@totalSize")]
+    #[serde(rename = "@totalSize", alias = "totalSize")]
     pub total_size: u64,
-    #[serde(rename = "@version")]
+    #[serde(rename = "@version", alias = "version")]
     pub version: u32,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 #[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
 pub struct Connection {
-    #[serde(with = "http_serde::uri", rename = "@uri")]
+    #[serde(with = "http_serde::uri", rename = "@uri", alias = "uri")]
     pub uri: http::Uri,
-    #[serde(rename = "@protocol")]
+    #[serde(rename = "@protocol", alias = "protocol")]
     pub protocol: Option<String>,
-    #[serde(rename = "@address")]
+    #[serde(rename = "@address", alias = "address")]
     pub address: Option<String>,
-    #[serde(rename = "@port")]
+    #[serde(rename = "@port", alias = "port")]
     pub port: Option<u32>,
-    #[serde(rename = "@local")]
+    #[serde(rename = "@local", alias = "local")]
     pub local: Option<bool>,
-    #[serde(rename = "@relay")]
+    #[serde(rename = "@relay", alias = "relay")]
     pub relay: Option<bool>,
+    #[serde(rename = "@IPv6", alias = "IPv6")]
+    pub ipv6: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
@@ -1,18 +1,19 @@
 use serde::{
     de::{Error as DeError, SeqAccess, Visitor},
-    Deserializer,
+    Deserialize, Deserializer,
 };
 use serde_aux::prelude::{
     deserialize_bool_from_anything, deserialize_option_number_from_string,
     deserialize_string_from_number,
 };
+use serde_json::Number;
 use serde_with::{formats::Separator, DeserializeAs};
 use std::{
     fmt::{self, Display},
     marker::PhantomData,
     str::FromStr,
 };
-use time::OffsetDateTime;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 
 pub(crate) fn optional_boolish<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
 where
@@ -42,6 +43,95 @@ where
         .map_err(DeError::custom)
 }
 
+/// Reads a Unix timestamp, also as a string like in XML attributes, or an
+/// RFC 3339 date like plex.tv's newer JSON APIs return.
+pub(crate) fn deserialize_datetime_from_timestamp_or_rfc3339<'de, D>(
+    deserializer: D,
+) -> Result<OffsetDateTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct Helper;
+
+    impl Visitor<'_> for Helper {
+        type Value = OffsetDateTime;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(formatter, "a timestamp or an RFC 3339 date")
+        }
+
+        fn visit_i64<E: DeError>(self, value: i64) -> Result<Self::Value, E> {
+            OffsetDateTime::from_unix_timestamp(value).map_err(DeError::custom)
+        }
+
+        fn visit_u64<E: DeError>(self, value: u64) -> Result<Self::Value, E> {
+            let value = i64::try_from(value).map_err(DeError::custom)?;
+            self.visit_i64(value)
+        }
+
+        fn visit_str<E: DeError>(self, value: &str) -> Result<Self::Value, E> {
+            match value.parse::<i64>() {
+                Ok(timestamp) => self.visit_i64(timestamp),
+                Err(_) => OffsetDateTime::parse(value, &Rfc3339).map_err(DeError::custom),
+            }
+        }
+    }
+
+    deserializer.deserialize_any(Helper)
+}
+
+/// Reads free text like a title. XML attributes that look like numbers are
+/// read as numbers, e.g. a library called `2024`, so those are accepted too.
+pub(crate) fn deserialize_text<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct Helper;
+
+    impl Visitor<'_> for Helper {
+        type Value = String;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(formatter, "a string or a number")
+        }
+
+        fn visit_str<E: DeError>(self, value: &str) -> Result<Self::Value, E> {
+            Ok(value.to_owned())
+        }
+
+        fn visit_string<E: DeError>(self, value: String) -> Result<Self::Value, E> {
+            Ok(value)
+        }
+
+        fn visit_i64<E: DeError>(self, value: i64) -> Result<Self::Value, E> {
+            Ok(value.to_string())
+        }
+
+        fn visit_u64<E: DeError>(self, value: u64) -> Result<Self::Value, E> {
+            Ok(value.to_string())
+        }
+
+        // Formatted like JSON does, which keeps e.g. the `.0` of `2.0`.
+        fn visit_f64<E: DeError>(self, value: f64) -> Result<Self::Value, E> {
+            Ok(Number::from_f64(value)
+                .map(|number| number.to_string())
+                .unwrap_or_else(|| value.to_string()))
+        }
+    }
+
+    deserializer.deserialize_any(Helper)
+}
+
+pub(crate) fn deserialize_option_text<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Text(#[serde(deserialize_with = "deserialize_text")] String);
+
+    Ok(Option::<Text>::deserialize(deserializer)?.map(|Text(text)| text))
+}
+
 pub(crate) struct StringWithSeparatorOrList<Sep, T>(PhantomData<(Sep, T)>);
 
 impl<'de, SEPARATOR, I, T> DeserializeAs<'de, I> for StringWithSeparatorOrList<SEPARATOR, T>
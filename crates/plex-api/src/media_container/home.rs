@@ -2,34 +2,38 @@ use serde::Deserialize;
 
 use crate::RestrictionProfile;
 
+/// A member of a Plex Home, from the XML attributes of `/api/home/users` or
+/// the fields of `/api/v2/home/users`.
 #[derive(Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 #[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
 pub struct User {
-    #[serde(rename = "@id")]
+    #[serde(rename = "@id", alias = "id")]
     pub id: u64,
-    #[serde(rename = "@uuid")]
+    #[serde(rename = "@uuid", alias = "uuid")]
     pub uuid: String,
-    #[serde(rename = "@admin")]
+    #[serde(rename = "@admin", alias = "admin")]
     pub admin: bool,
-    #[serde(rename = "@guest")]
+    #[serde(rename = "@guest", alias = "guest")]
     pub guest: bool,
-    #[serde(rename = "@restricted")]
+    #[serde(rename = "@restricted", alias = "restricted")]
     pub restricted: bool,
-    #[serde(rename = "@restrictionProfile")]
+    #[serde(rename = "@restrictionProfile", alias = "restrictionProfile")]
     pub restriction_profile: Option<RestrictionProfile>,
-    #[serde(rename = "@hasPassword")]
+    #[serde(rename = "@hasPassword", alias = "hasPassword")]
     pub has_password: bool,
-    #[serde(rename = "@protected")]
+    #[serde(rename = "@protected", alias = "protected")]
     pub protected: bool,
-    #[serde(rename = "@title")]
+    #[serde(rename = "@title", alias = "title")]
     pub title: String,
-    #[serde(rename = "@username")]
+    #[serde(rename = "@username", alias = "username")]
     pub username: Option<String>,
-    #[serde(rename = "@email")]
+    #[serde(rename = "@email", alias = "email")]
     pub email: Option<String>,
-    #[serde(with = "http_serde::uri", rename = "@thumb")]
+    #[serde(with = "http_serde::uri", rename = "@thumb", alias = "thumb")]
     pub thumb: http::Uri,
+    /// Only returned by `/api/v2/home/users`.
+    pub friendly_name: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -51,3 +55,19 @@ pub struct UsersResponse {
     #[serde(rename = "@guestUserID")]
     pub guest_user_id: u64,
 }
+
+/// The response of `/api/v2/home/users`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+pub struct HomeUsersResponse {
+    pub id: u64,
+    pub name: String,
+    #[serde(rename = "guestUserID")]
+    pub guest_user_id: Option<u64>,
+    #[serde(rename = "guestUserUUID")]
+    pub guest_user_uuid: Option<String>,
+    pub guest_enabled: Option<bool>,
+    pub subscription: Option<bool>,
+    pub users: Vec<User>,
+}
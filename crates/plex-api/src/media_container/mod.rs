@@ -20,10 +20,15 @@ pub struct MediaContainer {
     pub identifier: Option<String>,
 }
 
+/// The object JSON responses wrap their content in. XML responses have the
+/// `MediaContainer` element at their root instead, so they don't need it.
+///
+/// Mostly useful with [`RequestBuilder::media_container`](crate::RequestBuilder::media_container)
+/// for endpoints the crate doesn't wrap yet.
 #[derive(Debug, Deserialize, Clone)]
 #[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct MediaContainerWrapper<T> {
+pub struct MediaContainerWrapper<T> {
     #[serde(rename = "MediaContainer")]
-    pub(crate) media_container: T,
+    pub media_container: T,
 }
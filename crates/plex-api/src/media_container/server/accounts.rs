@@ -0,0 +1,32 @@
+use crate::{media_container::MediaContainer, myplex::account::AutoSelectSubtitleMode};
+use serde::Deserialize;
+
+/// An account known to the server: its owner, the accounts of the Plex Home
+/// and the users it's shared with. The IDs are the ones used by the server's
+/// watch history and play queues, which differ from the plex.tv user IDs for
+/// everyone but the owner.
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct ServerAccount {
+    pub id: u32,
+    pub key: String,
+    /// Empty for the system account with the ID `0`.
+    pub name: String,
+    pub thumb: Option<String>,
+    pub default_audio_language: Option<String>,
+    #[serde(default)]
+    pub auto_select_audio: bool,
+    pub default_subtitle_language: Option<String>,
+    pub subtitle_mode: Option<AutoSelectSubtitleMode>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct AccountsMediaContainer {
+    #[serde(default, rename = "Account")]
+    pub accounts: Vec<ServerAccount>,
+    #[serde(flatten)]
+    pub media_container: MediaContainer,
+}
@@ -0,0 +1,39 @@
+use crate::media_container::MediaContainer;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Something the server is busy with, like scanning a library or running a
+/// [butler task](crate::butler::Butler).
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct Activity {
+    pub uuid: String,
+    /// What kind of work this is, e.g. `library.update.section`.
+    #[serde(rename = "type")]
+    pub activity_type: String,
+    #[serde(default)]
+    pub cancellable: bool,
+    /// The server account that started the activity.
+    #[serde(rename = "userID")]
+    pub user_id: Option<u32>,
+    pub title: String,
+    pub subtitle: Option<String>,
+    /// The percentage done.
+    #[serde(default)]
+    pub progress: u8,
+    /// Details that depend on the type, e.g. the `librarySectionID` of a
+    /// scan.
+    #[serde(rename = "Context", default)]
+    pub context: Map<String, Value>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct ActivitiesMediaContainer {
+    #[serde(default, rename = "Activity")]
+    pub activities: Vec<Activity>,
+    #[serde(flatten)]
+    pub media_container: MediaContainer,
+}
@@ -0,0 +1,85 @@
+use super::library::SearchType;
+use crate::media_container::MediaContainer;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct AgentMediaType {
+    pub media_type: SearchType,
+    pub name: Option<String>,
+    pub language_code: Option<String>,
+}
+
+/// A metadata agent that libraries can use to match their items.
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct Agent {
+    pub identifier: String,
+    pub name: String,
+    /// Primary agents can be selected for a library, the others only
+    /// contribute to the metadata of a primary agent.
+    #[serde(default)]
+    pub primary: bool,
+    #[serde(default)]
+    pub has_prefs: bool,
+    #[serde(default)]
+    pub has_attribution: bool,
+    #[serde(default, rename = "MediaType")]
+    pub media_types: Vec<AgentMediaType>,
+}
+
+/// An agent adding to the metadata of a legacy primary agent, in the order
+/// they're consulted.
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct AgentContributor {
+    pub identifier: String,
+    pub name: String,
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub has_prefs: bool,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct AgentsMediaContainer {
+    #[serde(default, rename = "Agent")]
+    pub agents: Vec<Agent>,
+    #[serde(flatten)]
+    pub media_container: MediaContainer,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct AgentContributorsMediaContainer {
+    #[serde(default, rename = "Agent")]
+    pub contributors: Vec<AgentContributor>,
+    #[serde(flatten)]
+    pub media_container: MediaContainer,
+}
+
+/// A scanner that libraries can use to find media in their folders.
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct Scanner {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub media_type: Option<SearchType>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct ScannersMediaContainer {
+    #[serde(default, rename = "Scanner")]
+    pub scanners: Vec<Scanner>,
+    #[serde(flatten)]
+    pub media_container: MediaContainer,
+}
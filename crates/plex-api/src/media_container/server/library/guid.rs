@@ -1,4 +1,4 @@
-use std::fmt::Formatter;
+use std::fmt::{Display, Formatter};
 
 use serde::{
     de::{MapAccess, Visitor},
@@ -23,6 +23,45 @@ pub enum Guid {
     Unknown(String),
 }
 
+/// An item's id in one of the external metadata databases.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ExternalId {
+    Imdb(String),
+    Tmdb(u32),
+    Tvdb(u32),
+}
+
+impl Display for ExternalId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Imdb(id) => write!(f, "imdb://{id}"),
+            Self::Tmdb(id) => write!(f, "tmdb://{id}"),
+            Self::Tvdb(id) => write!(f, "tvdb://{id}"),
+        }
+    }
+}
+
+impl Guid {
+    /// The external database id this guid refers to, if any.
+    ///
+    /// Guids of the legacy agents carry extra parts after the id (e.g.
+    /// `?lang=en` or episode numbers), which are dropped. Only guids
+    /// identifying a whole item are considered.
+    pub fn external_id(&self) -> Option<ExternalId> {
+        fn strip(id: &str) -> Option<&str> {
+            let id = id.split_once('?').map_or(id, |(id, _)| id);
+            (!id.is_empty() && !id.contains('/')).then_some(id)
+        }
+
+        match self {
+            Self::Imdb(id) => strip(id).map(|id| ExternalId::Imdb(id.to_owned())),
+            Self::Tmdb(id) => strip(id)?.parse().ok().map(ExternalId::Tmdb),
+            Self::Tvdb(id) => strip(id)?.parse().ok().map(ExternalId::Tvdb),
+            _ => None,
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for Guid {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -53,7 +92,9 @@ impl<'de> Deserialize<'de> for Guid {
                     ["imdb", id] | ["com.plexapp.agents.imdb", id] => Guid::Imdb(id.to_owned()),
                     ["local", id] => Guid::Local(id.to_owned()),
                     ["tvdb", id] | ["com.plexapp.agents.thetvdb", id] => Guid::Tvdb(id.to_owned()),
-                    ["tmdb", id] => Guid::Tmdb(id.to_owned()),
+                    ["tmdb", id] | ["com.plexapp.agents.themoviedb", id] => {
+                        Guid::Tmdb(id.to_owned())
+                    }
                     ["collection", id] => Guid::Collection(id.to_owned()),
                     ["com.plexapp.agents.lastfm", id] => Guid::LastFm(id.to_owned()),
                     ["mbid", id] => Guid::Mbid(id.to_owned()),
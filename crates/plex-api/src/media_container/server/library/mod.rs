@@ -3,11 +3,14 @@ mod metadata_type;
 
 use crate::media_container::{
     helpers::deserialize_option_string_from_number,
-    helpers::{deserialize_option_datetime_from_timestamp, optional_boolish},
+    helpers::{
+        deserialize_option_datetime_from_timestamp, deserialize_option_text, deserialize_text,
+        optional_boolish,
+    },
     preferences::Preferences,
     MediaContainer,
 };
-pub use guid::Guid;
+pub use guid::{ExternalId, Guid};
 pub use metadata_type::*;
 use monostate::MustBe;
 use serde::{Deserialize, Deserializer, Serialize};
@@ -256,6 +259,39 @@ pub struct AudioStream {
     pub stream_identifier: Option<String>,
 }
 
+/// The results of the server's loudness analysis of an audio stream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Loudness {
+    /// The ReplayGain adjustment for the track in dB.
+    pub gain: f32,
+    /// The track's peak sample, 1.0 being full scale.
+    pub peak: f32,
+    /// The integrated loudness in LUFS.
+    pub loudness: Option<f32>,
+    /// The loudness range in LU.
+    pub range: Option<f32>,
+    pub album_gain: Option<f32>,
+    pub album_peak: Option<f32>,
+    pub album_range: Option<f32>,
+}
+
+impl AudioStream {
+    /// The loudness values, `None` until the server has analyzed the stream.
+    pub fn loudness(&self) -> Option<Loudness> {
+        let parse = |value: &Option<String>| value.as_deref().and_then(|v| v.parse().ok());
+
+        Some(Loudness {
+            gain: parse(&self.gain)?,
+            peak: parse(&self.peak)?,
+            loudness: parse(&self.loudness),
+            range: parse(&self.lra),
+            album_gain: parse(&self.album_gain),
+            album_peak: parse(&self.album_peak),
+            album_range: parse(&self.album_range),
+        })
+    }
+}
+
 #[serde_as]
 #[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -312,6 +348,65 @@ pub struct LyricStream {
     pub provider: Option<String>,
 }
 
+/// A subtitle found by one of the server's subtitle agents (e.g. OpenSubtitles).
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+pub struct SubtitleSearchResult {
+    #[serde(default, deserialize_with = "deserialize_option_string_from_number")]
+    pub id: Option<String>,
+    pub stream_type: Option<MustBe!(3)>,
+    pub codec: SubtitleCodec,
+    pub key: String,
+    pub source_key: Option<String>,
+    pub title: Option<String>,
+    pub display_title: Option<String>,
+    pub extended_display_title: Option<String>,
+    pub language: Option<String>,
+    pub language_code: Option<String>,
+    pub language_tag: Option<String>,
+    pub provider_title: Option<String>,
+    pub score: Option<f32>,
+    pub hearing_impaired: Option<bool>,
+    pub forced: Option<bool>,
+    pub perfect_match: Option<bool>,
+    pub downloaded: Option<bool>,
+    #[serde(rename = "userID")]
+    pub user_id: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+pub struct SubtitleSearchMediaContainer {
+    #[serde(default, rename = "Stream")]
+    pub streams: Vec<SubtitleSearchResult>,
+    #[serde(flatten)]
+    pub media_container: MediaContainer,
+}
+
+/// A loudness measurement of a section of an audio stream.
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+pub struct Level {
+    /// The loudness in LUFS.
+    #[serde(rename = "v")]
+    pub value: f32,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+pub struct LevelsMediaContainer {
+    #[serde(default, rename = "Level")]
+    pub levels: Vec<Level>,
+    /// The number of measurements the server has for the whole stream.
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    pub total_samples: Option<u32>,
+    #[serde(flatten)]
+    pub media_container: MediaContainer,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[cfg_attr(not(feature = "tests_deny_unknown_fields"), serde(untagged))]
 #[cfg_attr(feature = "tests_deny_unknown_fields", serde(try_from = "Value"))]
@@ -323,6 +418,19 @@ pub enum Stream {
     Unknown(Value),
 }
 
+impl Stream {
+    /// The stream's bitrate in kbps, known once the server has analyzed the
+    /// file.
+    pub fn bitrate(&self) -> Option<u32> {
+        match self {
+            Self::Video(stream) => stream.bitrate,
+            Self::Audio(stream) => stream.bitrate,
+            Self::Subtitle(stream) => stream.bitrate,
+            Self::Lyric(_) | Self::Unknown(_) => None,
+        }
+    }
+}
+
 // This generates much saner errors in tests than an untagged enum.
 #[cfg(feature = "tests_deny_unknown_fields")]
 impl TryFrom<Value> for Stream {
@@ -443,6 +551,15 @@ pub struct Media {
     pub premium: Option<bool>,
     #[serde(default, deserialize_with = "optional_boolish")]
     pub has_voice_activity: Option<bool>,
+
+    // EXIF data, only available for photos.
+    pub aperture: Option<String>,
+    pub exposure: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    pub iso: Option<u32>,
+    pub lens: Option<String>,
+    pub make: Option<String>,
+    pub model: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -545,7 +662,9 @@ pub struct ParentMetadata {
     pub parent_rating_key: Option<String>,
     pub parent_guid: Option<Guid>,
 
+    #[serde(default, deserialize_with = "deserialize_option_text")]
     pub parent_title: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_option_text")]
     pub parent_studio: Option<String>,
     pub parent_year: Option<u32>,
     pub parent_content_rating: Option<String>,
@@ -565,7 +684,9 @@ pub struct GrandParentMetadata {
     pub grandparent_rating_key: Option<String>,
     pub grandparent_guid: Option<Guid>,
 
+    #[serde(default, deserialize_with = "deserialize_option_text")]
     pub grandparent_title: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_option_text")]
     pub grandparent_studio: Option<String>,
     pub grandparent_year: Option<u32>,
     pub grandparent_content_rating: Option<String>,
@@ -679,9 +800,16 @@ pub struct Metadata {
     #[serde(default, deserialize_with = "optional_boolish")]
     pub allow_sync: Option<bool>,
 
+    #[serde(deserialize_with = "deserialize_text")]
     pub title: String,
+    #[serde(default, deserialize_with = "deserialize_option_text")]
     pub title_sort: Option<String>,
+    /// The edition of a movie, e.g. `Director's Cut`.
+    #[serde(default, deserialize_with = "deserialize_option_text")]
+    pub edition_title: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_option_text")]
     pub original_title: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_option_text")]
     pub studio: Option<String>,
     #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
     pub year: Option<u32>,
@@ -690,6 +818,7 @@ pub struct Metadata {
     #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
     pub max_year: Option<u32>,
     pub content_rating: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_option_text")]
     pub summary: Option<String>,
     pub rating: Option<f32>,
     pub rating_count: Option<u32>,
@@ -703,6 +832,7 @@ pub struct Metadata {
         deserialize_with = "deserialize_option_datetime_from_timestamp"
     )]
     pub last_rated_at: Option<OffsetDateTime>,
+    #[serde(default, deserialize_with = "deserialize_option_text")]
     pub tagline: Option<String>,
     pub duration: Option<u64>,
     pub originally_available_at: Option<Date>,
@@ -755,6 +885,7 @@ pub struct Metadata {
     #[serde(rename = "librarySectionID")]
     #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
     pub library_section_id: Option<u32>,
+    #[serde(default, deserialize_with = "deserialize_option_text")]
     pub library_section_title: Option<String>,
     pub library_section_key: Option<String>,
 
@@ -801,6 +932,9 @@ pub struct Metadata {
     pub chapters: Vec<Chapter>,
     #[serde(default, rename = "Label")]
     pub labels: Vec<Tag>,
+    /// Tags assigned to photos, e.g. places and people.
+    #[serde(default, rename = "Tag")]
+    pub tags: Vec<Tag>,
 
     #[serde(rename = "Preferences")]
     pub preferences: Option<Box<Preferences>>,
@@ -851,6 +985,29 @@ pub struct Link {
     pub url: String,
 }
 
+/// A folder listed when browsing a library section by its folders.
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct FolderDirectory {
+    pub key: String,
+    pub title: String,
+    pub fast_key: Option<String>,
+}
+
+/// A tag (e.g. a label or genre) used by items in a library section.
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct TagDirectory {
+    /// The tag's id, used when filtering the section by the tag.
+    pub key: String,
+    pub title: String,
+    pub fast_key: Option<String>,
+    #[serde(rename = "type")]
+    pub tag_type: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
@@ -860,9 +1017,13 @@ pub struct MetadataMediaContainer {
     pub rating_key: Option<String>,
     pub augmentation_key: Option<String>,
 
+    #[serde(default, deserialize_with = "deserialize_option_text")]
     pub title: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_option_text")]
     pub title1: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_option_text")]
     pub title2: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_option_text")]
     pub summary: Option<String>,
     pub duration: Option<u64>,
 
@@ -882,6 +1043,7 @@ pub struct MetadataMediaContainer {
 
     #[serde(rename = "librarySectionID")]
     pub library_section_id: Option<u32>,
+    #[serde(default, deserialize_with = "deserialize_option_text")]
     pub library_section_title: Option<String>,
     #[serde(rename = "librarySectionUUID")]
     pub library_section_uuid: Option<String>,
@@ -974,6 +1136,12 @@ pub struct ServerLibrary {
     #[serde(with = "time::serde::timestamp")]
     pub updated_at: OffsetDateTime,
     pub uuid: String,
+    /// Total duration of the section's media in milliseconds, only included
+    /// when requested with `includeStorage=1`.
+    pub duration_total: Option<u64>,
+    /// Total size of the section's media files in bytes, only included when
+    /// requested with `includeStorage=1`.
+    pub storage_total: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -1114,7 +1282,7 @@ impl TryFrom<Value> for ContentDirectory {
     }
 }
 
-#[derive(Debug, Deserialize_repr, Clone, Copy, Serialize_repr)]
+#[derive(Debug, Deserialize_repr, Clone, Copy, Serialize_repr, PartialEq, Eq, Hash)]
 #[repr(u16)]
 pub enum SearchType {
     Movie = 1,
@@ -1,13 +1,16 @@
+pub mod accounts;
+pub mod activities;
+pub mod agents;
 mod feature;
 pub mod library;
 
 pub use self::feature::Feature;
 use self::library::ContentDirectory;
-use crate::media_container::helpers::StringWithSeparatorOrList;
+use crate::{media_container::helpers::StringWithSeparatorOrList, server::ServerVersion};
 use semver::Version;
 use serde::Deserialize;
 use serde_plain::derive_fromstr_from_deserialize;
-use serde_with::{formats::CommaSeparator, serde_as, StringWithSeparator};
+use serde_with::{formats::CommaSeparator, serde_as, DisplayFromStr, StringWithSeparator};
 use time::OffsetDateTime;
 
 #[derive(Debug, Deserialize, Clone)]
@@ -240,6 +243,22 @@ pub struct Server {
     pub media_providers: Vec<MediaProvider>,
 }
 
+/// The server's basic details, available without authenticating.
+#[serde_as]
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct Identity {
+    #[serde(rename = "size")]
+    _size: Option<u32>,
+    pub api_version: Option<Version>,
+    /// Whether the server has been claimed by a Plex account.
+    pub claimed: bool,
+    pub machine_identifier: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub version: ServerVersion,
+}
+
 #[derive(Debug, Deserialize, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
 pub enum Diagnostics {
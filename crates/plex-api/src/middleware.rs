@@ -0,0 +1,159 @@
+use crate::{Error, Result};
+use futures::future::BoxFuture;
+use isahc::{
+    http::{Method, Uri},
+    AsyncBody, Request as HttpRequest, Response as HttpResponse,
+};
+use std::{fmt, sync::Arc, time::Duration};
+
+/// Changes requests before they are sent, e.g. to add headers.
+///
+/// Any `Fn(Request<AsyncBody>) -> Request<AsyncBody>` closure can be used as
+/// an interceptor:
+///
+/// ```
+/// use plex_api::HttpClientBuilder;
+///
+/// let client = HttpClientBuilder::default()
+///     .add_request_interceptor(|mut request: isahc::Request<isahc::AsyncBody>| {
+///         request
+///             .headers_mut()
+///             .insert("X-Plex-Language", "de".parse().unwrap());
+///         request
+///     })
+///     .build()
+///     .unwrap();
+/// ```
+pub trait RequestInterceptor: Send + Sync {
+    fn intercept(&self, request: HttpRequest<AsyncBody>) -> HttpRequest<AsyncBody>;
+}
+
+impl<F> RequestInterceptor for F
+where
+    F: Fn(HttpRequest<AsyncBody>) -> HttpRequest<AsyncBody> + Send + Sync,
+{
+    fn intercept(&self, request: HttpRequest<AsyncBody>) -> HttpRequest<AsyncBody> {
+        self(request)
+    }
+}
+
+/// Looks at responses as they arrive, before the client handles them.
+///
+/// Inspectors receive the response and hand back the one the client should
+/// use, so besides logging or collecting metrics they can also read the
+/// body to record it, or swap the response for a recorded one.
+pub trait ResponseInspector: Send + Sync {
+    fn inspect<'a>(
+        &'a self,
+        method: &'a Method,
+        uri: &'a Uri,
+        response: HttpResponse<AsyncBody>,
+    ) -> BoxFuture<'a, Result<HttpResponse<AsyncBody>>>;
+}
+
+/// A finished HTTP request, as reported to [`RequestMetrics`].
+#[derive(Debug)]
+pub struct RequestEvent<'a> {
+    pub method: &'a Method,
+    pub host: Option<&'a str>,
+    /// The path without the query, which may contain the token.
+    pub path: &'a str,
+    /// `None` if no response was received.
+    pub status: Option<u16>,
+    /// How long it took to receive the response headers.
+    pub duration: Duration,
+}
+
+impl RequestEvent<'_> {
+    /// Whether the request failed or the server answered with an error.
+    pub fn is_error(&self) -> bool {
+        self.status.is_none_or(|status| status >= 400)
+    }
+}
+
+/// Receives every request made by the client, e.g. to export them as
+/// Prometheus metrics.
+pub trait RequestMetrics: Send + Sync {
+    fn record(&self, event: &RequestEvent<'_>);
+}
+
+/// A server that stopped responding at one of its addresses, as reported to
+/// [`FailoverObserver`].
+#[derive(Debug)]
+pub struct FailoverEvent<'a> {
+    /// The address that failed.
+    pub from: &'a Uri,
+    /// The address requests are sent to from now on.
+    pub to: &'a Uri,
+    pub error: &'a Error,
+}
+
+/// Learns when requests to a server move to another of its addresses after
+/// a network error.
+///
+/// Any `Fn(&FailoverEvent)` closure can be used as an observer.
+pub trait FailoverObserver: Send + Sync {
+    fn failed_over(&self, event: &FailoverEvent<'_>);
+}
+
+impl<F> FailoverObserver for F
+where
+    F: Fn(&FailoverEvent<'_>) + Send + Sync,
+{
+    fn failed_over(&self, event: &FailoverEvent<'_>) {
+        self(event)
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct Middleware {
+    pub(crate) request_interceptors: Vec<Arc<dyn RequestInterceptor>>,
+    pub(crate) response_inspectors: Vec<Arc<dyn ResponseInspector>>,
+    pub(crate) metrics: Option<Arc<dyn RequestMetrics>>,
+    pub(crate) failover_observer: Option<Arc<dyn FailoverObserver>>,
+}
+
+impl fmt::Debug for Middleware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Middleware")
+            .field("request_interceptors", &self.request_interceptors.len())
+            .field("response_inspectors", &self.response_inspectors.len())
+            .field("metrics", &self.metrics.is_some())
+            .field("failover_observer", &self.failover_observer.is_some())
+            .finish()
+    }
+}
+
+impl Middleware {
+    pub(crate) fn intercept(&self, request: HttpRequest<AsyncBody>) -> HttpRequest<AsyncBody> {
+        self.request_interceptors
+            .iter()
+            .fold(request, |request, interceptor| {
+                interceptor.intercept(request)
+            })
+    }
+
+    pub(crate) fn record(&self, event: &RequestEvent<'_>) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record(event);
+        }
+    }
+
+    pub(crate) fn failed_over(&self, event: &FailoverEvent<'_>) {
+        if let Some(observer) = &self.failover_observer {
+            observer.failed_over(event);
+        }
+    }
+
+    pub(crate) async fn inspect(
+        &self,
+        method: &Method,
+        uri: &Uri,
+        mut response: HttpResponse<AsyncBody>,
+    ) -> Result<HttpResponse<AsyncBody>> {
+        for inspector in &self.response_inspectors {
+            response = inspector.inspect(method, uri, response).await?;
+        }
+        Ok(response)
+    }
+}
@@ -19,7 +19,7 @@ pub struct SubscriptionSummary {
     pub features: Vec<crate::media_container::server::Feature>,
 }
 
-#[derive(Deserialize, Debug, Clone, Copy)]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SubscriptionStatus {
     Active,
     Inactive,
@@ -194,6 +194,60 @@ pub struct MyPlexAccount {
     pub attribution_partner: Option<String>,
 }
 
+impl MyPlexAccount {
+    /// Whether the account has an active Plex Pass subscription. Plex Pass
+    /// features stay available until a cancelled subscription runs out.
+    pub fn has_plex_pass(&self) -> bool {
+        self.subscription.active
+            && matches!(
+                self.subscription.status,
+                SubscriptionStatus::Active | SubscriptionStatus::PendingCancellation
+            )
+    }
+
+    /// The Plex Pass plan, e.g. `monthly`, `yearly` or `lifetime`.
+    pub fn plan(&self) -> Option<&str> {
+        self.subscription.plan.as_deref()
+    }
+
+    /// Checks whether a feature is enabled for the account.
+    pub fn has_feature(&self, feature: &crate::media_container::server::Feature) -> bool {
+        self.subscription.features.contains(feature)
+    }
+
+    /// Checks whether the account is entitled to use a platform's apps, e.g.
+    /// `android` or `roku`. The `all` entitlement covers every platform.
+    pub fn has_entitlement(&self, entitlement: &str) -> bool {
+        self.entitlements
+            .iter()
+            .any(|e| e == entitlement || e == "all")
+    }
+
+    /// Checks whether the account has been granted a role, e.g. `plexpass`.
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles
+            .as_ref()
+            .is_some_and(|roles| roles.iter().any(|r| r == role))
+    }
+
+    /// Whether this is a managed user of a Plex Home rather than a full
+    /// account.
+    pub fn is_managed(&self) -> bool {
+        self.restricted
+    }
+
+    /// The token used to authenticate as this account.
+    pub fn token(&self) -> &SecretString {
+        &self.auth_token
+    }
+
+    /// When the authentication token stops being remembered, after which
+    /// the user has to sign in again.
+    pub fn remember_expires_at(&self) -> Option<OffsetDateTime> {
+        self.remember_expires_at
+    }
+}
+
 #[derive(Deserialize, Debug, Clone)]
 #[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
 pub struct CustomRestrictions {
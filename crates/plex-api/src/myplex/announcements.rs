@@ -1,4 +1,4 @@
-use crate::{url::MYPLEX_ANNOUNCEMENTS, HttpClient, Result};
+use crate::{url::MYPLEX_ANNOUNCEMENTS, HttpClient, PlexApiError, Result};
 use serde::Deserialize;
 use time::OffsetDateTime;
 
@@ -102,6 +102,42 @@ impl AnnouncementsManager {
     pub fn announcements_mut(&mut self) -> &mut Vec<Announcement> {
         &mut self.container.announcements
     }
+
+    /// Return the announcements that haven't been read and haven't expired,
+    /// i.e. the ones official clients would show.
+    pub fn unread_announcements(&self) -> impl Iterator<Item = &Announcement> {
+        self.container
+            .announcements
+            .iter()
+            .filter(|a| !a.read && !a.is_expired())
+    }
+
+    /// Mark every unread announcement as read
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn mark_all_read(&mut self) -> Result<()> {
+        for announcement in &mut self.container.announcements {
+            if !announcement.read {
+                announcement.read().await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Dismiss an announcement: it's marked as read and removed from the list
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn dismiss(&mut self, id: u32) -> Result<()> {
+        let index = self
+            .container
+            .announcements
+            .iter()
+            .position(|a| a.id == id)
+            .ok_or(crate::Error::ItemNotFound)?;
+
+        self.container.announcements[index].read().await?;
+        self.container.announcements.remove(index);
+        Ok(())
+    }
 }
 
 #[derive(Deserialize)]
@@ -114,47 +150,52 @@ struct AnnouncementApiResponce {
 
 impl From<AnnouncementApiResponce> for crate::Error {
     fn from(error: AnnouncementApiResponce) -> Self {
-        Self::MyPlexApiError {
+        // The envelope's code is the HTTP status the request would have had.
+        Self::PlexApiError(PlexApiError {
             code: i32::from(error.code),
             message: error.status,
-        }
+            status: error.code,
+        })
     }
 }
 
 impl Announcement {
-    /// Mark the announcement as read
-    #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn read(&mut self) -> Result<()> {
+    /// Whether the announcement has passed its expiry time. Announcements
+    /// without an expiry time never expire.
+    pub fn is_expired(&self) -> bool {
+        self.expire_at.unix_timestamp() != 0 && self.expire_at < OffsetDateTime::now_utc()
+    }
+
+    async fn set_read(&mut self, read: bool) -> Result<()> {
         let response: AnnouncementApiResponce = self
             .client
             .as_ref()
             .unwrap()
-            .put(format!("{MYPLEX_ANNOUNCEMENTS}/{}?read=1", self.id))
+            .put(format!(
+                "{MYPLEX_ANNOUNCEMENTS}/{}?read={}",
+                self.id,
+                u8::from(read)
+            ))
             .xml()
             .await?;
 
         if response.code == 200 {
+            self.read = read;
             Ok(())
         } else {
             Err(response.into())
         }
     }
 
+    /// Mark the announcement as read
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn read(&mut self) -> Result<()> {
+        self.set_read(true).await
+    }
+
     /// Mark the announcement as unread
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn unread(&mut self) -> Result<()> {
-        let response: AnnouncementApiResponce = self
-            .client
-            .as_ref()
-            .unwrap()
-            .put(format!("{MYPLEX_ANNOUNCEMENTS}/{}?read=0", self.id))
-            .xml()
-            .await?;
-
-        if response.code == 200 {
-            Ok(())
-        } else {
-            Err(response.into())
-        }
+        self.set_read(false).await
     }
 }
@@ -0,0 +1,75 @@
+use super::account::MyPlexAccount;
+use crate::media_container::devices::Device;
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Default)]
+struct CacheState {
+    account: Option<(Instant, MyPlexAccount)>,
+    resources: Option<(Instant, Vec<Device>)>,
+}
+
+/// Keeps the account and the resources plex.tv returned for a while, since
+/// apps ask for them often and plex.tv rate limits them. Nothing is kept
+/// unless a TTL is set.
+///
+/// Clones share the cached responses.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MyPlexCache {
+    ttl: Option<Duration>,
+    state: Arc<Mutex<CacheState>>,
+}
+
+impl MyPlexCache {
+    pub(crate) fn new(ttl: Option<Duration>) -> Self {
+        Self {
+            ttl,
+            state: Default::default(),
+        }
+    }
+
+    /// An empty cache with the same TTL, e.g. for another account.
+    pub(crate) fn detached(&self) -> Self {
+        Self::new(self.ttl)
+    }
+
+    pub(crate) fn account(&self) -> Option<MyPlexAccount> {
+        let state = self.state.lock().unwrap();
+        self.fresh(state.account.as_ref())
+    }
+
+    pub(crate) fn set_account(&self, account: &MyPlexAccount) {
+        if self.ttl.is_some() {
+            self.state.lock().unwrap().account = Some((Instant::now(), account.clone()));
+        }
+    }
+
+    pub(crate) fn resources(&self) -> Option<Vec<Device>> {
+        let state = self.state.lock().unwrap();
+        self.fresh(state.resources.as_ref())
+    }
+
+    pub(crate) fn set_resources(&self, resources: &[Device]) {
+        if self.ttl.is_some() {
+            self.state.lock().unwrap().resources = Some((Instant::now(), resources.to_vec()));
+        }
+    }
+
+    /// Forgets the resources, e.g. after a device was renamed or removed.
+    pub(crate) fn invalidate_resources(&self) {
+        self.state.lock().unwrap().resources = None;
+    }
+
+    pub(crate) fn invalidate(&self) {
+        *self.state.lock().unwrap() = CacheState::default();
+    }
+
+    fn fresh<T: Clone>(&self, entry: Option<&(Instant, T)>) -> Option<T> {
+        let ttl = self.ttl?;
+        entry
+            .filter(|(cached_at, _)| cached_at.elapsed() < ttl)
+            .map(|(_, value)| value.clone())
+    }
+}
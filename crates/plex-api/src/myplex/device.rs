@@ -1,48 +1,283 @@
+use super::{cache::MyPlexCache, fetch_v2};
 use crate::{
+    endpoints::Endpoint,
     http_client::HttpClient,
-    media_container::devices::{DevicesMediaContainer, Feature},
-    url::{MYPLEX_DEVICES, MYPLEX_RESOURCES},
+    media_container::devices::{Connection, Device as DeviceInfo, DevicesMediaContainer, Feature},
+    url::{MYPLEX_DEVICE, MYPLEX_DEVICES, MYPLEX_RESOURCES, MYPLEX_RESOURCES_V2},
     Error, Player, Result, Server,
 };
-use futures::{future::select_ok, FutureExt};
+use futures::{
+    future::{join_all, select_ok},
+    FutureExt,
+};
+use futures_timer::Delay;
 use secrecy::ExposeSecret;
+use std::time::{Duration, Instant};
+use time::OffsetDateTime;
 use tracing::{debug, error, trace};
 
+/// Selects devices by what they provide and who owns them.
+///
+/// ```
+/// use plex_api::{device::DeviceFilter, media_container::devices::Feature};
+///
+/// let shared_servers = DeviceFilter::servers().shared();
+/// let sync_targets = DeviceFilter::providing(Feature::SyncTarget);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeviceFilter {
+    feature: Option<Feature>,
+    owned: Option<bool>,
+}
+
+impl DeviceFilter {
+    /// Matches every device.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Matches devices providing a feature.
+    pub fn providing(feature: Feature) -> Self {
+        Self {
+            feature: Some(feature),
+            owned: None,
+        }
+    }
+
+    pub fn servers() -> Self {
+        Self::providing(Feature::Server)
+    }
+
+    pub fn players() -> Self {
+        Self::providing(Feature::Player)
+    }
+
+    /// Only matches devices owned by the account.
+    pub fn owned(self) -> Self {
+        Self {
+            owned: Some(true),
+            ..self
+        }
+    }
+
+    /// Only matches devices that other accounts have shared with this one.
+    pub fn shared(self) -> Self {
+        Self {
+            owned: Some(false),
+            ..self
+        }
+    }
+
+    pub fn matches(&self, device: &Device<'_>) -> bool {
+        self.feature.is_none_or(|feature| device.provides(feature))
+            && self.owned.is_none_or(|owned| device.is_owned() == owned)
+    }
+}
+
+/// The outcome of [`DeviceManager::revoke_all_except_current`].
+#[derive(Debug, Default)]
+pub struct RevokedDevices {
+    /// The client identifiers of the devices that were signed out.
+    pub revoked: Vec<String>,
+    /// The devices that are still signed in, by client identifier, and why.
+    pub failed: Vec<(String, Error)>,
+}
+
+/// How often [`DeviceManager::wait_until_online`] asks plex.tv again.
+const PRESENCE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
 pub struct DeviceManager {
     pub client: HttpClient,
+    cache: MyPlexCache,
 }
 
 impl DeviceManager {
     pub fn new(client: HttpClient) -> Self {
-        Self { client }
+        Self {
+            client,
+            cache: MyPlexCache::default(),
+        }
+    }
+
+    pub(crate) fn with_cache(self, cache: MyPlexCache) -> Self {
+        Self { cache, ..self }
+    }
+
+    fn wrap(&self, devices: Vec<DeviceInfo>) -> Vec<Device<'_>> {
+        devices
+            .into_iter()
+            .map(|device| Device {
+                inner: device,
+                client: &self.client,
+                cache: &self.cache,
+            })
+            .collect()
     }
 
-    async fn devices_internal<'a>(&'a self, url: &str) -> Result<Vec<Device<'a>>> {
+    async fn fetch_xml(&self, url: &str) -> Result<Vec<DeviceInfo>> {
         let container: DevicesMediaContainer = self
             .client
             .get(url)
             .header("Accept", "application/xml")
             .xml()
             .await?;
+        Ok(container.devices)
+    }
 
-        Ok(container
-            .devices
-            .into_iter()
-            .map(|device| Device {
-                inner: device,
-                client: &self.client,
-            })
-            .collect())
+    /// Requests the resources from `/api/v2/resources`, or the older XML
+    /// endpoint if that isn't available.
+    async fn fetch_resources(&self) -> Result<Vec<DeviceInfo>> {
+        let resources = fetch_v2(
+            &self.client,
+            Endpoint::new(MYPLEX_RESOURCES_V2)
+                .query("includeHttps", "1")
+                .query("includeRelay", "1"),
+        )
+        .await?;
+
+        match resources {
+            Some(resources) => Ok(resources),
+            None => {
+                debug!("Falling back to {MYPLEX_RESOURCES}");
+                self.fetch_xml(MYPLEX_RESOURCES).await
+            }
+        }
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn devices(&self) -> Result<Vec<Device<'_>>> {
-        self.devices_internal(MYPLEX_DEVICES).await
+        Ok(self.wrap(self.fetch_xml(MYPLEX_DEVICES).await?))
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn resources(&self) -> Result<Vec<Device<'_>>> {
-        self.devices_internal(MYPLEX_RESOURCES).await
+        let resources = match self.cache.resources() {
+            Some(resources) => resources,
+            None => {
+                let resources = self.fetch_resources().await?;
+                self.cache.set_resources(&resources);
+                resources
+            }
+        };
+        Ok(self.wrap(resources))
+    }
+
+    /// Lists the account's devices that match the filter.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn devices_matching(&self, filter: DeviceFilter) -> Result<Vec<Device<'_>>> {
+        let mut devices = self.devices().await?;
+        devices.retain(|device| filter.matches(device));
+        Ok(devices)
+    }
+
+    /// Lists the resources available to the account that match the filter.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn resources_matching(&self, filter: DeviceFilter) -> Result<Vec<Device<'_>>> {
+        let mut resources = self.resources().await?;
+        resources.retain(|device| filter.matches(device));
+        Ok(resources)
+    }
+
+    /// Finds one of the account's devices by its client identifier.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn device(&self, identifier: &str) -> Result<Device<'_>> {
+        self.devices()
+            .await?
+            .into_iter()
+            .find(|device| device.identifier() == identifier)
+            .ok_or(Error::ItemNotFound)
+    }
+
+    /// Revokes the token of one of the account's devices, by its client
+    /// identifier. The device is removed from the account and has to sign in
+    /// again, e.g. after it was lost or when an application is uninstalled.
+    /// Use [`MyPlex::signout`](crate::MyPlex::signout) to revoke the token
+    /// this client is using.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn revoke(&self, identifier: &str) -> Result {
+        self.device(identifier).await?.delete().await
+    }
+
+    /// Signs out every device of the account except this client, e.g. after
+    /// the account's token leaked. Servers are kept, since revoking their
+    /// token would also remove them from the account. A device that can't be
+    /// signed out doesn't stop the others from being signed out.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn revoke_all_except_current(&self) -> Result<RevokedDevices> {
+        let current = self.client.x_plex_client_identifier.as_str();
+        let devices = self
+            .devices()
+            .await?
+            .into_iter()
+            .filter(|device| device.identifier() != current && !device.is_server())
+            .map(|device| async move {
+                let identifier = device.identifier().to_owned();
+                (identifier, device.delete().await)
+            });
+
+        let mut revoked = RevokedDevices::default();
+        for (identifier, result) in join_all(devices).await {
+            match result {
+                Ok(()) => revoked.revoked.push(identifier),
+                Err(error) => revoked.failed.push((identifier, error)),
+            }
+        }
+        Ok(revoked)
+    }
+
+    /// Fetches the token to use with a server or other resource, by its
+    /// client identifier. Servers that other accounts share with this one
+    /// don't accept the account's own token, only the one issued for the
+    /// share, which can be set with [`HttpClient::set_x_plex_token`].
+    ///
+    /// Unlike [`DeviceManager::resources`] this always asks plex.tv, so a
+    /// token that changed, e.g. because the server was shared again, is
+    /// picked up.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn access_token(&self, identifier: &str) -> Result<String> {
+        self.fetch_resources()
+            .await?
+            .into_iter()
+            .find(|resource| resource.client_identifier == identifier)
+            .and_then(|resource| resource.access_token)
+            .map(|token| token.expose_secret().to_owned())
+            .ok_or(Error::ItemNotFound)
+    }
+
+    /// Waits for plex.tv to report the resource as online, e.g. for a server
+    /// that is still booting. Resources that aren't listed yet are treated as
+    /// offline. Fails with [`Error::Timeout`] if the resource doesn't come
+    /// online in time.
+    ///
+    /// Every poll asks plex.tv, the cached resources are updated instead of
+    /// being used.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn wait_until_online(
+        &self,
+        identifier: &str,
+        timeout: Duration,
+    ) -> Result<Device<'_>> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let resources = self.fetch_resources().await?;
+            self.cache.set_resources(&resources);
+            let resource = self
+                .wrap(resources)
+                .into_iter()
+                .find(|device| device.identifier() == identifier);
+            match resource {
+                Some(resource) if resource.is_present() == Some(true) => return Ok(resource),
+                Some(_) => trace!("Resource {identifier} is offline"),
+                None => trace!("Resource {identifier} isn't listed"),
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(Error::Timeout);
+            }
+            Delay::new(remaining.min(PRESENCE_POLL_INTERVAL)).await;
+        }
     }
 }
 
@@ -50,6 +285,7 @@ impl DeviceManager {
 pub struct Device<'a> {
     inner: crate::media_container::devices::Device,
     client: &'a HttpClient,
+    cache: &'a MyPlexCache,
 }
 
 impl Device<'_> {
@@ -66,6 +302,75 @@ impl Device<'_> {
         &self.inner.name
     }
 
+    /// The device's ID on plex.tv. Only known for devices listed by
+    /// [`DeviceManager::devices`].
+    pub fn id(&self) -> Option<u32> {
+        self.inner.id
+    }
+
+    /// The app running on the device, e.g. `Plex for iOS`.
+    pub fn product(&self) -> &str {
+        &self.inner.product
+    }
+
+    pub fn product_version(&self) -> &str {
+        &self.inner.product_version
+    }
+
+    pub fn platform(&self) -> &str {
+        &self.inner.platform
+    }
+
+    pub fn platform_version(&self) -> &str {
+        &self.inner.platform_version
+    }
+
+    /// The features provided by the device.
+    pub fn features(&self) -> &[Feature] {
+        &self.inner.provides
+    }
+
+    /// The addresses the device can be reached at.
+    pub fn connections(&self) -> &[Connection] {
+        &self.inner.connections
+    }
+
+    pub fn created_at(&self) -> OffsetDateTime {
+        self.inner.created_at
+    }
+
+    /// When plex.tv last heard from the device.
+    pub fn last_seen_at(&self) -> OffsetDateTime {
+        self.inner.last_seen_at
+    }
+
+    /// Whether the device belongs to the account rather than being shared
+    /// with it.
+    pub fn is_owned(&self) -> bool {
+        self.inner.owned.unwrap_or(true)
+    }
+
+    /// The address plex.tv last saw the device connecting from.
+    pub fn public_address(&self) -> &str {
+        &self.inner.public_address
+    }
+
+    /// Whether the device shares its public address with the client asking
+    /// plex.tv about it, i.e. they're most likely on the same network.
+    pub fn public_address_matches(&self) -> Option<bool> {
+        self.inner.public_address_matches
+    }
+
+    /// Whether plex.tv considers the device to be online.
+    pub fn is_present(&self) -> Option<bool> {
+        self.inner.presence
+    }
+
+    /// Whether the server only accepts secure connections.
+    pub fn https_required(&self) -> Option<bool> {
+        self.inner.https_required
+    }
+
     /// Syntax sugar method for checking if the current device provides [`Feature::Server`]
     pub fn is_server(&self) -> bool {
         self.provides(Feature::Server)
@@ -76,6 +381,38 @@ impl Device<'_> {
         self.provides(Feature::Controller)
     }
 
+    /// Syntax sugar method for checking if the current device provides [`Feature::Player`]
+    pub fn is_player(&self) -> bool {
+        self.provides(Feature::Player)
+    }
+
+    fn device_path(&self) -> Result<Endpoint> {
+        let id = self.inner.id.ok_or(Error::ItemNotFound)?;
+        Ok(Endpoint::new(MYPLEX_DEVICE).segment("deviceId", id))
+    }
+
+    /// Changes the name the device is listed with.
+    #[tracing::instrument(level = "debug", skip(self), fields(device_name = self.inner.name))]
+    pub async fn rename(&mut self, name: &str) -> Result {
+        let uri = format!(
+            "{}?{}",
+            self.device_path()?,
+            serde_urlencoded::to_string([("name", name)])?
+        );
+        self.client.put(uri).consume().await?;
+        self.inner.name = name.to_owned();
+        self.cache.invalidate_resources();
+        Ok(())
+    }
+
+    /// Removes the device from the account, signing it out.
+    #[tracing::instrument(level = "debug", skip(self), fields(device_name = self.inner.name))]
+    pub async fn delete(self) -> Result {
+        self.client.delete(self.device_path()?).consume().await?;
+        self.cache.invalidate_resources();
+        Ok(())
+    }
+
     /// Returns the authentication token that should be used when connecting to the device.
     /// If it's a shared device, the main authentication token will no be accepted.
     pub fn access_token(&self) -> Option<&str> {
@@ -117,6 +454,21 @@ impl Device<'_> {
 
                 let (server, _) = select_ok(futures).await?;
                 trace!("Connected via {address}", address = server.client().api_url);
+                // Local addresses are tried first, then remote ones and
+                // relays last.
+                let mut connections = self.inner.connections.iter().collect::<Vec<_>>();
+                connections.sort_by_key(|connection| {
+                    (
+                        connection.relay == Some(true),
+                        connection.local != Some(true),
+                    )
+                });
+                let server = server.with_connections(
+                    connections
+                        .into_iter()
+                        .map(|connection| connection.uri.clone())
+                        .collect(),
+                );
                 Ok(DeviceConnection::Server(Box::new(server)))
             } else {
                 trace!(
@@ -0,0 +1,360 @@
+//! Helpers for working out why a server can't be reached, e.g. when remote
+//! access isn't working.
+//!
+//! A [`DiagnosticsReport`] combines what plex.tv knows about the client's
+//! location with a check of every published connection of the account's
+//! servers.
+
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    time::{Duration, Instant},
+};
+
+use futures::future::join_all;
+use http::Uri;
+use isahc::AsyncReadResponseExt;
+use serde::Deserialize;
+
+use super::device::{Device, DeviceFilter, DeviceManager};
+use crate::{
+    http_client::{HttpClient, HttpClientBuilder},
+    url::{MYPLEX_GEOIP, MYPLEX_PUBLIC_IP, SERVER_IDENTITY},
+    Error, Result,
+};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where plex.tv thinks an address is located.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct GeoLocation {
+    /// Two letter country code.
+    pub code: String,
+    pub continent_code: Option<String>,
+    pub country: Option<String>,
+    pub city: Option<String>,
+    pub european_union_member: Option<bool>,
+    pub time_zone: Option<String>,
+    pub postal_code: Option<String>,
+    /// Whether privacy laws restrict what Plex may collect in the country.
+    pub in_privacy_restricted_country: Option<bool>,
+    pub in_privacy_restricted_region: Option<bool>,
+    pub subdivisions: Option<String>,
+    /// Latitude and longitude, e.g. `52.3759, 4.8975`.
+    pub coordinates: Option<String>,
+}
+
+/// How a connection reaches the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionKind {
+    /// An address on the server's own network.
+    Local,
+    /// A direct connection from outside the server's network, which needs
+    /// the router to forward the port.
+    Remote,
+    /// Plex's relay, used when nothing else works. Relayed connections are
+    /// bandwidth limited.
+    Relay,
+}
+
+/// The outcome of trying one of a server's connections.
+#[derive(Debug)]
+pub struct ConnectionReport {
+    pub uri: Uri,
+    pub kind: ConnectionKind,
+    /// How long the server took to answer, if it did.
+    pub latency: Option<Duration>,
+    /// Why the server couldn't be reached.
+    pub error: Option<Error>,
+}
+
+impl ConnectionReport {
+    pub fn is_reachable(&self) -> bool {
+        self.latency.is_some()
+    }
+}
+
+/// What could be worked out about the NAT in front of a server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NatStatus {
+    /// The server can be reached directly on its public address.
+    Open,
+    /// The server is behind a NAT without a working port forward, so only
+    /// the relay reaches it from outside.
+    RelayOnly,
+    /// The server's public address is itself a private or carrier-grade NAT
+    /// address, so there's another NAT between the router and the internet.
+    /// Port forwarding on the server's router can't work in this case.
+    DoubleNat,
+    /// No remote connection could be reached.
+    Unknown,
+}
+
+/// The result of checking one server.
+#[derive(Debug)]
+pub struct ServerReport {
+    pub name: String,
+    pub identifier: String,
+    /// The address plex.tv last saw the server connecting from.
+    pub public_address: Option<IpAddr>,
+    /// Whether the server and the client share their public address, i.e.
+    /// local connections are expected to work.
+    pub same_network: Option<bool>,
+    pub connections: Vec<ConnectionReport>,
+    pub nat: NatStatus,
+}
+
+impl ServerReport {
+    /// The reachable connection with the lowest latency.
+    pub fn fastest(&self) -> Option<&ConnectionReport> {
+        self.connections
+            .iter()
+            .filter(|c| c.latency.is_some())
+            .min_by_key(|c| c.latency)
+    }
+
+    /// Whether the server can be reached from outside its network without
+    /// going through the relay.
+    pub fn remote_access_works(&self) -> bool {
+        self.connections
+            .iter()
+            .any(|c| c.kind == ConnectionKind::Remote && c.is_reachable())
+    }
+}
+
+/// Everything found by [`Diagnostics::run`].
+#[derive(Debug)]
+pub struct DiagnosticsReport {
+    /// The client's public address as seen by plex.tv.
+    pub public_address: IpAddr,
+    pub location: Option<GeoLocation>,
+    pub servers: Vec<ServerReport>,
+}
+
+/// Runs connectivity checks against plex.tv and the account's servers.
+#[derive(Debug, Clone)]
+pub struct Diagnostics {
+    client: HttpClient,
+    timeout: Duration,
+}
+
+impl Diagnostics {
+    pub(crate) fn new(client: HttpClient) -> Self {
+        Self {
+            client,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// How long to wait for each connection before considering it
+    /// unreachable. Defaults to 5 seconds.
+    #[must_use]
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// The client's public address as seen by plex.tv.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn public_address(&self) -> Result<IpAddr> {
+        let mut response = self.client.get(MYPLEX_PUBLIC_IP).send().await?;
+        if !response.status().is_success() {
+            return Err(Error::from_response(response).await);
+        }
+
+        let content = response.text().await?;
+        content
+            .trim()
+            .parse()
+            .map_err(|_| Error::UnexpectedApiResponse {
+                status_code: response.status().as_u16(),
+                content,
+            })
+    }
+
+    /// Looks up where an address is located.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn geoip(&self, address: IpAddr) -> Result<GeoLocation> {
+        let uri = format!(
+            "{}?{}",
+            MYPLEX_GEOIP,
+            serde_urlencoded::to_string([("ip_address", address.to_string())])?
+        );
+        self.client.get(uri).json().await
+    }
+
+    /// Tries every connection the server publishes and works out whether
+    /// remote access is possible.
+    #[tracing::instrument(level = "debug", skip(self, device), fields(device_name = device.name()))]
+    pub async fn check_server(&self, device: &Device<'_>) -> Result<ServerReport> {
+        if !device.is_server() {
+            return Err(Error::DeviceConnectionNotSupported);
+        }
+
+        let mut client = self.client.clone();
+        if let Some(access_token) = device.access_token() {
+            if access_token != client.x_plex_token() {
+                client = client.set_x_plex_token(access_token.to_owned());
+            }
+        }
+
+        let connections = join_all(device.connections().iter().map(|connection| {
+            let kind = if connection.relay == Some(true) {
+                ConnectionKind::Relay
+            } else if connection.local == Some(true) {
+                ConnectionKind::Local
+            } else {
+                ConnectionKind::Remote
+            };
+            self.check_connection(&client, connection.uri.clone(), kind)
+        }))
+        .await;
+
+        let public_address = device.public_address().parse().ok();
+        let nat = nat_status(public_address, device, &connections);
+
+        Ok(ServerReport {
+            name: device.name().to_owned(),
+            identifier: device.identifier().to_owned(),
+            public_address,
+            same_network: device.public_address_matches(),
+            connections,
+            nat,
+        })
+    }
+
+    async fn check_connection(
+        &self,
+        client: &HttpClient,
+        uri: Uri,
+        kind: ConnectionKind,
+    ) -> ConnectionReport {
+        let started = Instant::now();
+        let result = match HttpClientBuilder::from(client.clone())
+            .set_api_url(uri.clone())
+            .build()
+        {
+            Ok(client) => {
+                client
+                    .get(SERVER_IDENTITY)
+                    .timeout(Some(self.timeout))
+                    .consume()
+                    .await
+            }
+            Err(error) => Err(error),
+        };
+
+        match result {
+            Ok(()) => ConnectionReport {
+                uri,
+                kind,
+                latency: Some(started.elapsed()),
+                error: None,
+            },
+            Err(error) => ConnectionReport {
+                uri,
+                kind,
+                latency: None,
+                error: Some(error),
+            },
+        }
+    }
+
+    /// Finds the client's public address and location, then checks every
+    /// server available to the account.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn run(&self) -> Result<DiagnosticsReport> {
+        let public_address = self.public_address().await?;
+        // Not knowing the location doesn't stop the rest from being useful.
+        let location = self.geoip(public_address).await.ok();
+
+        let device_manager = DeviceManager::new(self.client.clone());
+        let devices = device_manager
+            .resources_matching(DeviceFilter::servers())
+            .await?;
+
+        let mut servers = Vec::with_capacity(devices.len());
+        for device in &devices {
+            servers.push(self.check_server(device).await?);
+        }
+
+        Ok(DiagnosticsReport {
+            public_address,
+            location,
+            servers,
+        })
+    }
+}
+
+fn nat_status(
+    public_address: Option<IpAddr>,
+    device: &Device<'_>,
+    connections: &[ConnectionReport],
+) -> NatStatus {
+    // A port forward pointing at another private network shows up as a
+    // remote connection with a private address.
+    let private_remote = device.connections().iter().any(|connection| {
+        connection.local != Some(true)
+            && connection.relay != Some(true)
+            && connection
+                .address
+                .as_deref()
+                .and_then(|address| address.parse().ok())
+                .is_some_and(is_private)
+    });
+
+    if public_address.is_some_and(is_private) || private_remote {
+        NatStatus::DoubleNat
+    } else if connections
+        .iter()
+        .any(|c| c.kind == ConnectionKind::Remote && c.is_reachable())
+    {
+        NatStatus::Open
+    } else if connections
+        .iter()
+        .any(|c| c.kind == ConnectionKind::Relay && c.is_reachable())
+    {
+        NatStatus::RelayOnly
+    } else {
+        NatStatus::Unknown
+    }
+}
+
+fn is_private(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(address) => is_private_v4(address),
+        IpAddr::V6(address) => is_private_v6(address),
+    }
+}
+
+fn is_private_v4(address: Ipv4Addr) -> bool {
+    let [first, second, ..] = address.octets();
+    // 100.64.0.0/10 is reserved for carrier-grade NAT.
+    address.is_private() || address.is_link_local() || (first == 100 && second & 0xc0 == 64)
+}
+
+fn is_private_v6(address: Ipv6Addr) -> bool {
+    // Unique local addresses, fc00::/7.
+    address.segments()[0] & 0xfe00 == 0xfc00 || address.to_ipv4_mapped().is_some_and(is_private_v4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn private_addresses() {
+        for address in [
+            "10.1.2.3",
+            "172.20.0.1",
+            "192.168.1.1",
+            "100.64.0.1",
+            "fd00::1",
+        ] {
+            assert!(is_private(address.parse().unwrap()), "{address}");
+        }
+        for address in ["1.0.0.2", "100.128.0.1", "2001:db8::1"] {
+            assert!(!is_private(address.parse().unwrap()), "{address}");
+        }
+    }
+}
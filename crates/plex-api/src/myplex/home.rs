@@ -1,10 +1,16 @@
 use crate::{
-    media_container::home::{User, UsersResponse},
-    url::{MYPLEX_USERS, MYPLEX_USER_SWITCH},
+    endpoints::Endpoint,
+    media_container::home::{HomeUsersResponse, User, UsersResponse},
+    url::{MYPLEX_USER, MYPLEX_USERS, MYPLEX_USERS_V2, MYPLEX_USER_SWITCH},
     HttpClient, MyPlex, Result,
 };
+use tracing::debug;
 
-use super::account::MyPlexAccount;
+use super::{
+    account::MyPlexAccount,
+    fetch_v2,
+    sharing::{Friend, InviteStatus},
+};
 
 pub struct HomeManager {
     pub(crate) client: HttpClient,
@@ -56,13 +62,53 @@ impl From<u64> for SwitchableUser {
 }
 
 impl HomeManager {
+    /// Lists the members of the home. Falls back to the older XML endpoint
+    /// when `/api/v2/home/users` isn't available.
     pub async fn users(&self) -> Result<Vec<User>> {
-        Ok(self
-            .client
-            .get(MYPLEX_USERS)
-            .xml::<UsersResponse>()
-            .await?
-            .users)
+        match fetch_v2::<_, HomeUsersResponse>(&self.client, MYPLEX_USERS_V2).await? {
+            Some(response) => Ok(response.users),
+            None => {
+                debug!("Falling back to {MYPLEX_USERS}");
+                Ok(self
+                    .client
+                    .get(MYPLEX_USERS)
+                    .xml::<UsersResponse>()
+                    .await?
+                    .users)
+            }
+        }
+    }
+
+    /// Invites an existing Plex account into the home by its username or
+    /// email. The invitation shows up in [`HomeManager::invitations`] until
+    /// it's accepted.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn invite(&self, identifier: &str) -> Result {
+        self.client
+            .post(Endpoint::new(MYPLEX_USERS).query("invitedEmail", identifier))
+            .consume()
+            .await
+    }
+
+    /// The pending invitations into a home, both the ones sent by this
+    /// account and the ones it received. Accept or cancel them with
+    /// [`Friend::accept`] and [`Friend::delete`].
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn invitations(&self) -> Result<Vec<Friend>> {
+        let mut invitations = Friend::list(&self.client, InviteStatus::PendingSent).await?;
+        invitations.extend(Friend::list(&self.client, InviteStatus::PendingReceived).await?);
+        invitations.retain(|friend| friend.home);
+        Ok(invitations)
+    }
+
+    /// Removes a user from the home. Managed users are deleted along with
+    /// their account.
+    #[tracing::instrument(level = "debug", skip(self, user), fields(user.id = user.id))]
+    pub async fn remove_user(&self, user: &User) -> Result {
+        self.client
+            .delete(Endpoint::new(MYPLEX_USER).segment("userId", user.id))
+            .consume()
+            .await
     }
 
     pub async fn switch_user(
@@ -76,12 +122,15 @@ impl HomeManager {
             .client
             .post(format!(
                 "{}?pin={}",
-                MYPLEX_USER_SWITCH.replace("{uuid}", &user.into().id()),
+                Endpoint::new(MYPLEX_USER_SWITCH).segment("uuid", user.into().id()),
                 pin.unwrap_or_default()
             ))
             .json()
             .await?;
         myplex.client = myplex.client.set_x_plex_token(account.auth_token.clone());
+        // Other accounts see different resources.
+        myplex.cache = myplex.cache.detached();
+        myplex.cache.set_account(&account);
         myplex.account = Some(account);
 
         Ok(myplex)
@@ -1,19 +1,23 @@
-pub(crate) mod account;
+pub mod account;
 pub(crate) mod announcements;
+mod cache;
 pub(crate) mod claim_token;
 pub mod device;
+pub mod diagnostics;
 pub mod discover;
 pub(crate) mod home;
 pub(crate) mod pin;
 pub(crate) mod privacy;
 pub(crate) mod server;
 pub mod sharing;
+pub mod sync;
 pub(crate) mod webhook;
 
 use self::{
-    account::MyPlexAccount, announcements::AnnouncementsManager, claim_token::ClaimToken,
-    device::DeviceManager, discover::Discover, home::HomeManager, pin::PinManager,
-    privacy::Privacy, sharing::Sharing, webhook::WebhookManager,
+    account::MyPlexAccount, announcements::AnnouncementsManager, cache::MyPlexCache,
+    claim_token::ClaimToken, device::DeviceManager, diagnostics::Diagnostics, discover::Discover,
+    home::HomeManager, pin::PinManager, privacy::Privacy, sharing::Sharing, sync::SyncManager,
+    webhook::WebhookManager,
 };
 use crate::{
     http_client::{HttpClient, HttpClientBuilder, Request},
@@ -22,14 +26,50 @@ use crate::{
     url::{MYPLEX_SERVERS, MYPLEX_SIGNIN_PATH, MYPLEX_SIGNOUT_PATH, MYPLEX_USER_INFO_PATH},
     Error, Result,
 };
-use http::StatusCode;
-use isahc::AsyncBody;
+use http::{uri::PathAndQuery, StatusCode};
+use isahc::{http::header::CONTENT_TYPE, AsyncBody, AsyncReadResponseExt};
 use secrecy::{ExposeSecret, SecretString};
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+/// Requests one of plex.tv's `/api/v2` endpoints. Returns `None` when the
+/// endpoint isn't available, i.e. it doesn't exist or answers with something
+/// other than JSON, so the older XML endpoint can be used instead. Responses
+/// that are JSON but can't be read are errors like everywhere else.
+pub(crate) async fn fetch_v2<T, R>(client: &HttpClient, path: T) -> Result<Option<R>>
+where
+    PathAndQuery: TryFrom<T>,
+    <PathAndQuery as TryFrom<T>>::Error: Into<http::Error>,
+    R: DeserializeOwned,
+{
+    let mut response = client
+        .get(path)
+        .header("Accept", "application/json")
+        .send()
+        .await?;
+
+    match response.status().as_http_status() {
+        StatusCode::OK => {
+            let is_json = response
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|content_type| content_type.contains("json"));
+            if !is_json {
+                return Ok(None);
+            }
+            Ok(Some(serde_json::from_slice(&response.bytes().await?)?))
+        }
+        StatusCode::NOT_FOUND | StatusCode::GONE => Ok(None),
+        _ => Err(Error::from_response(response).await),
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct MyPlex {
     client: HttpClient,
     account: Option<MyPlexAccount>,
+    cache: MyPlexCache,
 }
 
 impl MyPlex {
@@ -37,6 +77,7 @@ impl MyPlex {
         Self {
             client,
             account: None,
+            cache: MyPlexCache::default(),
         }
     }
 
@@ -45,6 +86,7 @@ impl MyPlex {
         password: &str,
         client: HttpClient,
         extra_params: &[(&str, &str)],
+        cache: MyPlexCache,
     ) -> Result<Self> {
         if client.is_authenticated() {
             return Err(Error::ClientAuthenticated);
@@ -59,44 +101,66 @@ impl MyPlex {
             params.push((key, value));
         }
 
-        Self::build_from_signin_response(&client, client.post(MYPLEX_SIGNIN_PATH).form(&params)?)
-            .await
+        Self::build_from_signin_response(
+            &client,
+            client.post(MYPLEX_SIGNIN_PATH).form(&params)?,
+            cache,
+        )
+        .await
     }
 
-    #[tracing::instrument(level = "debug", skip(password, client))]
-    async fn login(username: &str, password: &str, client: HttpClient) -> Result<Self> {
-        Self::login_internal(username, password, client, &[]).await
+    #[tracing::instrument(level = "debug", skip(password, client, cache))]
+    async fn login(
+        username: &str,
+        password: &str,
+        client: HttpClient,
+        cache: MyPlexCache,
+    ) -> Result<Self> {
+        Self::login_internal(username, password, client, &[], cache).await
     }
 
     #[tracing::instrument(
         name = "MyPlex::login_with_otp",
         level = "debug",
-        skip(password, client)
+        skip(password, client, cache)
     )]
     async fn login_with_otp(
         username: &str,
         password: &str,
         verification_code: &str,
         client: HttpClient,
+        cache: MyPlexCache,
     ) -> Result<Self> {
         Self::login_internal(
             username,
             password,
             client,
             &[("verificationCode", verification_code)],
+            cache,
         )
         .await
     }
 
+    /// Loads the account's details again. The cached details are used
+    /// instead while they're younger than the cache TTL, see
+    /// [`MyPlexBuilder::set_cache_ttl`].
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn refresh(self) -> Result<Self> {
         if !self.client.is_authenticated() {
             return Err(Error::ClientNotAuthenticated);
         }
 
+        if let Some(account) = self.cache.account() {
+            return Ok(Self {
+                account: Some(account),
+                ..self
+            });
+        }
+
         Self::build_from_signin_response(
             &self.client,
             self.client.get(MYPLEX_USER_INFO_PATH).body(())?,
+            self.cache,
         )
         .await
     }
@@ -104,21 +168,45 @@ impl MyPlex {
     async fn build_from_signin_response<B>(
         client: &HttpClient,
         request: Request<'_, B>,
+        cache: MyPlexCache,
     ) -> Result<Self>
     where
         B: Into<AsyncBody>,
     {
         let account: account::MyPlexAccount = request.json().await?;
+        cache.set_account(&account);
         Ok(Self {
             client: client.clone().set_x_plex_token(account.auth_token.clone()),
             account: Some(account),
+            cache,
         })
     }
 
+    /// Forgets the cached account details and resources so that they're
+    /// loaded from plex.tv the next time they're needed.
+    pub fn invalidate(&self) {
+        self.cache.invalidate();
+    }
+
     pub fn client(&self) -> &HttpClient {
         &self.client
     }
 
+    /// The client for talking to plex.tv directly, authenticated as this
+    /// account, see [`Server::raw`](crate::Server::raw).
+    pub fn raw(&self) -> &HttpClient {
+        &self.client
+    }
+
+    /// Returns a copy whose requests use a different time limit.
+    pub fn with_timeout(&self, timeout: Option<Duration>) -> Self {
+        Self {
+            client: self.client.with_timeout(timeout),
+            account: self.account.clone(),
+            cache: self.cache.clone(),
+        }
+    }
+
     /// Get a claim token from the API, which can be used for attaching a server to your account.
     /// See <https://hub.docker.com/r/plexinc/pms-docker> for details, look for "PLEX_CLAIM".
     pub async fn claim_token(&self) -> Result<ClaimToken> {
@@ -169,6 +257,14 @@ impl MyPlex {
         self.account.as_ref()
     }
 
+    /// Whether the signed in account has an active Plex Pass subscription.
+    pub fn has_plex_pass(&self) -> bool {
+        self.account
+            .as_ref()
+            .map(MyPlexAccount::has_plex_pass)
+            .unwrap_or_default()
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn webhook_manager(&self) -> Result<WebhookManager> {
         if !self.client.is_authenticated() {
@@ -189,7 +285,17 @@ impl MyPlex {
             return Err(Error::ClientNotAuthenticated);
         }
 
-        Ok(DeviceManager::new(self.client.clone()))
+        Ok(DeviceManager::new(self.client.clone()).with_cache(self.cache.clone()))
+    }
+
+    /// Connectivity checks for debugging why the account's servers can't be
+    /// reached.
+    pub fn diagnostics(&self) -> Result<Diagnostics> {
+        if !self.client.is_authenticated() {
+            return Err(Error::ClientNotAuthenticated);
+        }
+
+        Ok(Diagnostics::new(self.client.clone()))
     }
 
     pub fn pin_manager(&self) -> Result<PinManager> {
@@ -200,6 +306,15 @@ impl MyPlex {
         Ok(PinManager::new(self.client.clone()))
     }
 
+    /// Manage the mobile sync items of the account's devices.
+    pub fn sync(&self) -> Result<SyncManager> {
+        if !self.client.is_authenticated() {
+            return Err(Error::ClientNotAuthenticated);
+        }
+
+        Ok(SyncManager::new(self.client.clone()))
+    }
+
     pub async fn announcements(&self) -> Result<AnnouncementsManager> {
         AnnouncementsManager::new(self.client.clone()).await
     }
@@ -254,6 +369,7 @@ pub struct MyPlexBuilder<'a> {
     password: Option<SecretString>,
     otp: Option<SecretString>,
     test_token_auth: bool,
+    cache_ttl: Option<Duration>,
 }
 
 impl<'a> Default for MyPlexBuilder<'a> {
@@ -265,6 +381,7 @@ impl<'a> Default for MyPlexBuilder<'a> {
             password: None,
             otp: None,
             test_token_auth: true,
+            cache_ttl: None,
         }
     }
 }
@@ -278,6 +395,7 @@ impl MyPlexBuilder<'_> {
             password: self.password,
             otp: self.otp,
             test_token_auth: self.test_token_auth,
+            cache_ttl: self.cache_ttl,
         }
     }
 
@@ -289,6 +407,16 @@ impl MyPlexBuilder<'_> {
             password: self.password,
             otp: self.otp,
             test_token_auth,
+            cache_ttl: self.cache_ttl,
+        }
+    }
+
+    /// Keeps the account's details and its resources for this long before
+    /// asking plex.tv for them again. Nothing is cached by default.
+    pub fn set_cache_ttl(self, cache_ttl: Duration) -> Self {
+        Self {
+            cache_ttl: Some(cache_ttl),
+            ..self
         }
     }
 
@@ -298,6 +426,7 @@ impl MyPlexBuilder<'_> {
         } else {
             HttpClientBuilder::default().build()?
         };
+        let cache = MyPlexCache::new(self.cache_ttl);
 
         if let (Some(username), Some(password)) = (self.username, self.password) {
             if let Some(otp) = self.otp {
@@ -306,10 +435,11 @@ impl MyPlexBuilder<'_> {
                     password.expose_secret(),
                     otp.expose_secret(),
                     client,
+                    cache,
                 )
                 .await;
             } else {
-                return MyPlex::login(username, password.expose_secret(), client).await;
+                return MyPlex::login(username, password.expose_secret(), client, cache).await;
             }
         }
 
@@ -321,7 +451,10 @@ impl MyPlexBuilder<'_> {
             client = client.set_x_plex_token(token);
         }
 
-        let mut plex = MyPlex::new(client);
+        let mut plex = MyPlex {
+            cache,
+            ..MyPlex::new(client)
+        };
 
         if self.test_token_auth {
             plex = plex.refresh().await?;
@@ -343,6 +476,7 @@ impl<'a> MyPlexBuilder<'a> {
             password: self.password,
             otp: self.otp,
             test_token_auth: self.test_token_auth,
+            cache_ttl: self.cache_ttl,
         }
     }
 
@@ -357,6 +491,7 @@ impl<'a> MyPlexBuilder<'a> {
             password: Some(password.into()),
             otp: self.otp,
             test_token_auth: self.test_token_auth,
+            cache_ttl: self.cache_ttl,
         }
     }
 
@@ -371,6 +506,7 @@ impl<'a> MyPlexBuilder<'a> {
             password: self.password,
             otp: Some(otp.into()),
             test_token_auth: self.test_token_auth,
+            cache_ttl: self.cache_ttl,
         }
     }
 }
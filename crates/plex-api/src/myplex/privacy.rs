@@ -13,23 +13,46 @@ struct PrivacyApiResponse {
     opt_out_library_stats: bool,
     domain: String,
     base_url: String,
-    metrics: Vec<Metric>,
+    metrics: Vec<PrivacyMetric>,
 }
 
+/// The account's data sharing settings.
 #[derive(Debug)]
 pub struct Privacy {
+    /// Whether playback data, i.e. what is watched or listened to, isn't
+    /// shared with Plex.
     pub opt_out_playback: bool,
+    /// Whether statistics about the account's libraries aren't shared with
+    /// Plex.
     pub opt_out_library_stats: bool,
+    /// What plex.tv collects for each kind of event, given the settings
+    /// above.
+    pub metrics: Vec<PrivacyMetric>,
 
     client: HttpClient,
 }
 
-#[derive(Debug, Deserialize)]
+/// How the events of a kind are collected.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricStatus {
+    /// Collected along with the account they came from.
+    Identifiable,
+    /// Collected without the account.
+    Anonymous,
+    /// Not collected.
+    Off,
+    #[cfg(not(feature = "tests_deny_unknown_fields"))]
+    #[serde(other)]
+    Unknown,
+}
+
+/// A kind of event reported to Plex's analytics, e.g. `playback:itemstart`.
+#[derive(Debug, Deserialize, Clone)]
 #[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
-#[allow(dead_code)]
-struct Metric {
-    event: String,
-    status: String,
+pub struct PrivacyMetric {
+    pub event: String,
+    pub status: MetricStatus,
 }
 
 impl Privacy {
@@ -40,11 +63,34 @@ impl Privacy {
         Ok(Self {
             opt_out_playback: p.opt_out_playback,
             opt_out_library_stats: p.opt_out_library_stats,
+            metrics: p.metrics,
             client,
         })
     }
 
+    /// The events that are collected along with the account, for auditing
+    /// what is shared.
+    pub fn identifiable_events(&self) -> impl Iterator<Item = &str> {
+        self.metrics
+            .iter()
+            .filter(|metric| metric.status == MetricStatus::Identifiable)
+            .map(|metric| metric.event.as_str())
+    }
+
+    /// Changes whether playback data is shared, keeping the library setting.
+    pub async fn set_opt_out_playback(&mut self, opt_out: bool) -> crate::Result<()> {
+        self.update(opt_out, self.opt_out_library_stats).await
+    }
+
+    /// Changes whether library statistics are shared, keeping the playback
+    /// setting.
+    pub async fn set_opt_out_library_stats(&mut self, opt_out: bool) -> crate::Result<()> {
+        self.update(self.opt_out_playback, opt_out).await
+    }
+
     /// Changes privacy settings, see [Privacy Preferences on plex.tv](https://www.plex.tv/about/privacy-legal/privacy-preferences/#opd).
+    /// The [metrics](Privacy::metrics) aren't updated, load the settings
+    /// again to see how the change affects them.
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn update(
         &mut self,
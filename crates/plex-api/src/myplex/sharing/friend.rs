@@ -65,6 +65,20 @@ pub struct Friend {
 }
 
 impl Friend {
+    /// Lists the friends with the given status, including managed users.
+    pub(crate) async fn list(client: &HttpClient, status: InviteStatus) -> Result<Vec<Friend>> {
+        let mut friends: Vec<Friend> = client
+            .get(format!("{}?includeSharedServers=true&includeSharedSources=true&includeSharingSettings=true&status={}", MYPLEX_INVITES_FRIENDS, status))
+            .json()
+            .await?;
+
+        for friend in &mut friends {
+            friend.client = Some(client.clone())
+        }
+
+        Ok(friends)
+    }
+
     /// Accept friendship.
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn accept(self) -> Result<Friend> {
@@ -5,7 +5,7 @@ mod shareables;
 
 use crate::{
     media_container::users::AllowTuners,
-    url::{MYPLEX_INVITES_FRIENDS, MYPLEX_INVITES_INVITE, MYPLEX_INVITES_SHARED_SERVERS},
+    url::{MYPLEX_INVITES_INVITE, MYPLEX_INVITES_SHARED_SERVERS},
     MyPlex, Result,
 };
 use serde::{Deserialize, Serialize};
@@ -66,6 +66,13 @@ struct ShareServerRequest {
     machine_identifier: String,
 }
 
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct UpdateSharedServerRequest {
+    settings: server::Settings,
+    library_section_ids: Vec<u32>,
+}
+
 impl<'a> Sharing<'a> {
     pub fn new(myplex: &'a MyPlex) -> Self {
         Sharing { myplex }
@@ -95,36 +102,10 @@ impl<'a> Sharing<'a> {
         permissions: Permissions,
         filters: Filters,
     ) -> Result<SharedServer> {
-        let server_info = self.myplex.server_info(server.id()).await?;
-        let sections: Vec<u32> = sections
-            .iter()
-            .filter_map(|s| s.id().parse().ok())
-            .collect();
         let request = ShareServerRequest {
             invited_email: user.id().to_owned(),
-            settings: server::Settings {
-                allow_channels: permissions.allow_channels,
-                allow_subtitle_admin: permissions.allow_subtitle_admin,
-                allow_sync: permissions.allow_sync,
-                allow_tuners: permissions.allow_tuners,
-                allow_camera_upload: permissions.allow_camera_upload,
-                filter_movies: Some(filters.movies),
-                filter_television: Some(filters.television),
-                filter_music: Some(filters.music),
-                filter_photos: filters.photos,
-                ..Default::default()
-            },
-            library_section_ids: server_info
-                .library_sections
-                .iter()
-                .filter_map(|s| {
-                    if sections.contains(&s.key) {
-                        Some(s.id)
-                    } else {
-                        None
-                    }
-                })
-                .collect(),
+            settings: server::Settings::new(permissions, filters),
+            library_section_ids: self.section_ids(server.id(), sections).await?,
             machine_identifier: server.id().to_owned(),
         };
 
@@ -136,20 +117,62 @@ impl<'a> Sharing<'a> {
             .await
     }
 
-    /// Returns a list of friends with the requested status, including managed users.
-    #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn friends(&self, status: InviteStatus) -> Result<Vec<friend::Friend>> {
-        let mut friends: Vec<friend::Friend> = self
-            .myplex
+    /// Changes an existing share: which libraries the user can access and
+    /// the restrictions applied to them, e.g. only allowing items with a
+    /// particular label for a managed user. Use [`SharedServer::permissions`]
+    /// and [`SharedServer::filters`] to only change part of the settings.
+    #[tracing::instrument(level = "debug", skip_all, fields(share.id = share.id))]
+    pub async fn update_share<'b>(
+        &self,
+        share: &SharedServer,
+        sections: &[ShareableLibrary<'b>],
+        permissions: Permissions,
+        filters: Filters,
+    ) -> Result<SharedServer> {
+        let request = UpdateSharedServerRequest {
+            settings: server::Settings::new(permissions, filters),
+            library_section_ids: self
+                .section_ids(&share.machine_identifier, sections)
+                .await?,
+        };
+
+        self.myplex
             .client()
-            .get(format!("{}?includeSharedServers=true&includeSharedSources=true&includeSharingSettings=true&status={}", MYPLEX_INVITES_FRIENDS, status))
+            .put(format!("{MYPLEX_INVITES_SHARED_SERVERS}/{}", share.id))
+            .json_body(&request)?
             .json()
-            .await?;
+            .await
+    }
 
-        for friend in &mut friends {
-            friend.client = Some(self.myplex.client().clone())
-        }
+    /// plex.tv identifies the libraries of a server by its own ids rather
+    /// than the ones the server uses.
+    async fn section_ids<'b>(
+        &self,
+        machine_identifier: &str,
+        sections: &[ShareableLibrary<'b>],
+    ) -> Result<Vec<u32>> {
+        let server_info = self.myplex.server_info(machine_identifier).await?;
+        let sections: Vec<u32> = sections
+            .iter()
+            .filter_map(|s| s.id().parse().ok())
+            .collect();
+
+        Ok(server_info
+            .library_sections
+            .iter()
+            .filter_map(|s| {
+                if sections.contains(&s.key) {
+                    Some(s.id)
+                } else {
+                    None
+                }
+            })
+            .collect())
+    }
 
-        Ok(friends)
+    /// Returns a list of friends with the requested status, including managed users.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn friends(&self, status: InviteStatus) -> Result<Vec<friend::Friend>> {
+        friend::Friend::list(self.myplex.client(), status).await
     }
 }
@@ -1,4 +1,4 @@
-use super::{friend::Friend, SharingFilter};
+use super::{friend::Friend, Filters, Permissions, SharingFilter};
 use crate::media_container::users::AllowTuners;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
@@ -36,6 +36,23 @@ impl Default for Settings {
     }
 }
 
+impl Settings {
+    pub(super) fn new(permissions: Permissions, filters: Filters) -> Self {
+        Self {
+            allow_channels: permissions.allow_channels,
+            allow_subtitle_admin: permissions.allow_subtitle_admin,
+            allow_sync: permissions.allow_sync,
+            allow_tuners: permissions.allow_tuners,
+            allow_camera_upload: permissions.allow_camera_upload,
+            filter_movies: Some(filters.movies),
+            filter_television: Some(filters.television),
+            filter_music: Some(filters.music),
+            filter_photos: filters.photos,
+            ..Default::default()
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 #[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
@@ -64,3 +81,38 @@ pub struct SharedServer {
     pub all_libraries: bool,
     pub invited: Option<Friend>,
 }
+
+impl SharedServer {
+    /// The user's current permissions on the server.
+    pub fn permissions(&self) -> Permissions {
+        let settings = &self.sharing_settings;
+        Permissions {
+            allow_sync: settings.allow_sync,
+            allow_channels: settings.allow_channels,
+            allow_tuners: settings.allow_tuners,
+            allow_subtitle_admin: settings.allow_subtitle_admin,
+            allow_camera_upload: settings.allow_camera_upload,
+        }
+    }
+
+    /// The restrictions currently applied to the shared libraries.
+    pub fn filters(&self) -> Filters {
+        let settings = &self.sharing_settings;
+        Filters {
+            movies: settings.filter_movies.clone().unwrap_or_default(),
+            music: settings.filter_music.clone().unwrap_or_default(),
+            television: settings.filter_television.clone().unwrap_or_default(),
+            photos: settings.filter_photos.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// The server's ids of the libraries included in the share, for passing
+    /// to [`Sharing::update_share`](super::Sharing::update_share).
+    pub fn library_ids(&self) -> Vec<String> {
+        self.libraries
+            .iter()
+            .map(|library| library.key.to_string())
+            .collect()
+    }
+}
@@ -0,0 +1,369 @@
+//! Mobile sync, the predecessor of download queues.
+//!
+//! Sync items are stored on plex.tv against the device that should receive
+//! the media. The servers holding the media poll for new items, transcode
+//! them according to the item's media settings and the device then fetches
+//! the results. Many servers and older apps still rely on this rather than
+//! on [`DownloadQueue`](crate::transcode::DownloadQueue).
+
+use crate::{
+    endpoints::Endpoint,
+    http_client::HttpClient,
+    isahc_compat::StatusCodeExt,
+    media_container::devices::Feature,
+    myplex::device::DeviceManager,
+    url::{MYPLEX_SYNC_ITEM, MYPLEX_SYNC_ITEMS},
+    Error, Result,
+};
+use futures::future::try_join_all;
+use http::StatusCode;
+use isahc::AsyncReadResponseExt;
+use serde::{Deserialize, Serialize};
+use serde_plain::derive_display_from_serialize;
+
+/// How much of the content a sync item should keep on the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncScope {
+    /// Everything matching the item's location.
+    All,
+    /// Only the first [`SyncPolicy::value`] items.
+    Count,
+    #[cfg(not(feature = "tests_deny_unknown_fields"))]
+    #[serde(other)]
+    Unknown,
+}
+
+derive_display_from_serialize!(SyncScope);
+
+/// Decides which of the items matching a sync item's location are synced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+pub struct SyncPolicy {
+    #[serde(rename = "@scope")]
+    pub scope: SyncScope,
+    /// The number of items to sync when the scope is [`SyncScope::Count`].
+    #[serde(rename = "@value", default)]
+    pub value: u32,
+    /// Whether to only sync items that haven't been watched yet.
+    #[serde(rename = "@unwatched", default)]
+    pub unwatched: bool,
+}
+
+impl Default for SyncPolicy {
+    fn default() -> Self {
+        Self {
+            scope: SyncScope::All,
+            value: 0,
+            unwatched: false,
+        }
+    }
+}
+
+/// The transcode settings applied to the media of a sync item. Anything left
+/// unset uses the server's default.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+pub struct MediaSettings {
+    /// Maximum video bitrate in kbps.
+    #[serde(rename = "@maxVideoBitrate")]
+    pub max_video_bitrate: Option<u32>,
+    /// Video quality between 0 and 100.
+    #[serde(rename = "@videoQuality")]
+    pub video_quality: Option<u32>,
+    /// Maximum video resolution, e.g. `1280x720`.
+    #[serde(rename = "@videoResolution")]
+    pub video_resolution: Option<String>,
+    /// Audio volume boost as a percentage.
+    #[serde(rename = "@audioBoost")]
+    pub audio_boost: Option<u32>,
+    /// Music bitrate in kbps.
+    #[serde(rename = "@musicBitrate")]
+    pub music_bitrate: Option<u32>,
+    /// Photo quality between 0 and 100.
+    #[serde(rename = "@photoQuality")]
+    pub photo_quality: Option<u32>,
+    /// Maximum photo resolution, e.g. `1920x1080`.
+    #[serde(rename = "@photoResolution")]
+    pub photo_resolution: Option<String>,
+    /// Size of burnt-in subtitles as a percentage.
+    #[serde(rename = "@subtitleSize")]
+    pub subtitle_size: Option<u32>,
+}
+
+impl MediaSettings {
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let numbers = [
+            ("maxVideoBitrate", self.max_video_bitrate),
+            ("videoQuality", self.video_quality),
+            ("audioBoost", self.audio_boost),
+            ("musicBitrate", self.music_bitrate),
+            ("photoQuality", self.photo_quality),
+            ("subtitleSize", self.subtitle_size),
+        ];
+        let strings = [
+            ("videoResolution", &self.video_resolution),
+            ("photoResolution", &self.photo_resolution),
+        ];
+
+        numbers
+            .into_iter()
+            .filter_map(|(name, value)| value.map(|v| (name, v.to_string())))
+            .chain(
+                strings
+                    .into_iter()
+                    .filter_map(|(name, value)| value.clone().map(|v| (name, v))),
+            )
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncState {
+    Pending,
+    Processing,
+    Complete,
+    Failed,
+    #[cfg(not(feature = "tests_deny_unknown_fields"))]
+    #[serde(other)]
+    Unknown,
+}
+
+/// The progress of a sync item.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+pub struct SyncStatus {
+    #[serde(rename = "@state")]
+    pub state: SyncState,
+    #[serde(rename = "@failureCode")]
+    pub failure_code: Option<String>,
+    #[serde(rename = "@failure")]
+    pub failure: Option<String>,
+    /// The number of media items covered by the sync item.
+    #[serde(rename = "@itemsCount", default)]
+    pub items_count: u32,
+    #[serde(rename = "@itemsCompleteCount", default)]
+    pub items_complete_count: u32,
+    #[serde(rename = "@itemsDownloadedCount", default)]
+    pub items_downloaded_count: u32,
+    #[serde(rename = "@itemsReadyCount", default)]
+    pub items_ready_count: u32,
+    #[serde(rename = "@itemsSuccessfulCount", default)]
+    pub items_successful_count: u32,
+    /// The total size of the synced media in bytes.
+    #[serde(rename = "@totalSize", default)]
+    pub total_size: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+pub struct SyncServer {
+    #[serde(rename = "@machineIdentifier")]
+    pub machine_identifier: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+pub struct SyncLocation {
+    #[serde(rename = "@uri")]
+    pub uri: String,
+}
+
+/// A piece of content that a device is asked to keep a copy of.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+pub struct SyncItem {
+    #[serde(rename = "@id")]
+    pub id: u32,
+    #[serde(rename = "@version")]
+    pub version: u32,
+    #[serde(rename = "@rootTitle")]
+    pub root_title: Option<String>,
+    #[serde(rename = "@title")]
+    pub title: String,
+    #[serde(rename = "@metadataType")]
+    pub metadata_type: Option<String>,
+    #[serde(rename = "@contentType")]
+    pub content_type: Option<String>,
+    #[serde(rename = "Server")]
+    pub server: Option<SyncServer>,
+    #[serde(rename = "Status")]
+    pub status: SyncStatus,
+    #[serde(rename = "MediaSettings", default)]
+    pub media_settings: MediaSettings,
+    #[serde(rename = "Policy", default)]
+    pub policy: SyncPolicy,
+    #[serde(rename = "Location")]
+    pub location: SyncLocation,
+    /// The device the item is synced to.
+    #[serde(skip)]
+    pub client_identifier: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+struct SyncItemsContainer {
+    #[serde(rename = "@clientIdentifier")]
+    _client_identifier: Option<String>,
+    #[serde(rename = "@status")]
+    _status: Option<String>,
+    #[serde(rename = "@itemsCount")]
+    _items_count: Option<u32>,
+    #[serde(rename = "SyncItem", default)]
+    items: Vec<SyncItem>,
+}
+
+/// Describes a sync item to create.
+#[derive(Debug, Clone, Default)]
+pub struct NewSyncItem {
+    /// The title shown to the user for the sync item.
+    pub title: String,
+    /// Usually the title of the library section or show the item belongs to.
+    pub root_title: Option<String>,
+    /// E.g. `movie` or `episode`.
+    pub metadata_type: Option<String>,
+    /// E.g. `video`, `audio` or `photo`.
+    pub content_type: Option<String>,
+    /// The machine identifier of the server holding the media.
+    pub machine_identifier: String,
+    /// A `library://` URI locating the content on the server.
+    pub uri: String,
+    pub policy: SyncPolicy,
+    pub media_settings: MediaSettings,
+}
+
+impl NewSyncItem {
+    fn params(&self) -> Vec<(String, String)> {
+        let mut params = vec![
+            ("SyncItem[title]".to_owned(), self.title.clone()),
+            (
+                "SyncItem[machineIdentifier]".to_owned(),
+                self.machine_identifier.clone(),
+            ),
+            ("SyncItem[Location][uri]".to_owned(), self.uri.clone()),
+            (
+                "SyncItem[Policy][scope]".to_owned(),
+                self.policy.scope.to_string(),
+            ),
+            (
+                "SyncItem[Policy][value]".to_owned(),
+                self.policy.value.to_string(),
+            ),
+            (
+                "SyncItem[Policy][unwatched]".to_owned(),
+                (self.policy.unwatched as u8).to_string(),
+            ),
+        ];
+
+        let optional = [
+            ("rootTitle", &self.root_title),
+            ("metadataType", &self.metadata_type),
+            ("contentType", &self.content_type),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                params.push((format!("SyncItem[{name}]"), value.clone()));
+            }
+        }
+
+        for (name, value) in self.media_settings.params() {
+            params.push((format!("SyncItem[MediaSettings][{name}]"), value));
+        }
+
+        params
+    }
+}
+
+/// Lists, creates and removes the sync items of the account's devices.
+pub struct SyncManager {
+    client: HttpClient,
+}
+
+impl SyncManager {
+    pub fn new(client: HttpClient) -> Self {
+        Self { client }
+    }
+
+    /// Lists the items being synced to a device.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn items(&self, client_identifier: &str) -> Result<Vec<SyncItem>> {
+        let container: SyncItemsContainer = self
+            .client
+            .get(Endpoint::new(MYPLEX_SYNC_ITEMS).segment("clientId", client_identifier))
+            .header("Accept", "application/xml")
+            .xml()
+            .await?;
+
+        Ok(with_client_identifier(container.items, client_identifier))
+    }
+
+    /// Lists the items being synced to any of the account's devices that
+    /// can act as a sync target.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn all_items(&self) -> Result<Vec<SyncItem>> {
+        let device_manager = DeviceManager::new(self.client.clone());
+        let devices = device_manager.devices().await?;
+
+        let items = try_join_all(
+            devices
+                .iter()
+                .filter(|device| device.provides(Feature::SyncTarget))
+                .map(|device| self.items(device.identifier())),
+        )
+        .await?;
+
+        Ok(items.into_iter().flatten().collect())
+    }
+
+    /// Asks the server identified in the item to start syncing content to a
+    /// device.
+    #[tracing::instrument(level = "debug", skip(self, item), fields(title = item.title))]
+    pub async fn create(&self, client_identifier: &str, item: &NewSyncItem) -> Result<SyncItem> {
+        let uri = format!(
+            "{}?{}",
+            Endpoint::new(MYPLEX_SYNC_ITEMS).segment("clientId", client_identifier),
+            serde_urlencoded::to_string(item.params())?
+        );
+
+        let container: SyncItemsContainer = self.client.post(uri).xml().await?;
+
+        with_client_identifier(container.items, client_identifier)
+            .into_iter()
+            .next()
+            .ok_or(Error::ItemNotFound)
+    }
+
+    /// Stops syncing an item to a device. The device removes the synced
+    /// media the next time it checks in.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn delete(&self, client_identifier: &str, id: u32) -> Result {
+        let mut response = self
+            .client
+            .delete(
+                Endpoint::new(MYPLEX_SYNC_ITEM)
+                    .segment("clientId", client_identifier)
+                    .segment("syncId", id),
+            )
+            .send()
+            .await?;
+
+        match response.status().as_http_status() {
+            StatusCode::OK | StatusCode::NO_CONTENT => {
+                response.consume().await?;
+                Ok(())
+            }
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+}
+
+fn with_client_identifier(items: Vec<SyncItem>, client_identifier: &str) -> Vec<SyncItem> {
+    items
+        .into_iter()
+        .map(|mut item| {
+            item.client_identifier = client_identifier.to_owned();
+            item
+        })
+        .collect()
+}
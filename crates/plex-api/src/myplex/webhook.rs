@@ -5,6 +5,8 @@ use http::{StatusCode, Uri};
 use serde::Deserialize;
 use std::fmt::Debug;
 
+/// Lists and changes the URLs that plex.tv sends the account's webhooks to.
+/// The payloads delivered to them are described in [`crate::webhook`].
 pub struct WebhookManager {
     webhooks: Vec<Webhook>,
     client: HttpClient,
@@ -16,6 +18,12 @@ pub struct Webhook {
     pub url: Uri,
 }
 
+impl From<Uri> for Webhook {
+    fn from(url: Uri) -> Self {
+        Self { url }
+    }
+}
+
 impl WebhookManager {
     #[tracing::instrument(level = "debug", skip(client))]
     pub async fn new(client: HttpClient) -> Result<Self> {
@@ -70,7 +78,8 @@ impl WebhookManager {
         }
     }
 
-    /// Add a new webhook with the provided URL.
+    /// Add a new webhook with the provided URL. Does nothing if the URL is
+    /// already configured.
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn add<U>(&mut self, url: U) -> Result
     where
@@ -78,10 +87,13 @@ impl WebhookManager {
         Uri: TryFrom<U>,
         <Uri as TryFrom<U>>::Error: Into<http::Error>,
     {
+        let url = Uri::try_from(url).map_err(Into::into)?;
+        if self.webhooks.iter().any(|webhook| webhook.url == url) {
+            return Ok(());
+        }
+
         let mut webhooks = self.webhooks.clone();
-        webhooks.push(Webhook {
-            url: Uri::try_from(url).map_err(Into::into)?,
-        });
+        webhooks.push(url.into());
         self.set(webhooks).await
     }
 
@@ -0,0 +1,149 @@
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use futures::AsyncRead;
+
+/// How often the transfer rate is re-measured.
+const RATE_WINDOW: Duration = Duration::from_millis(500);
+
+/// A snapshot of how far along a transfer is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// The number of bytes transferred so far.
+    pub transferred: u64,
+    /// The total number of bytes to be transferred, if known.
+    pub total: Option<u64>,
+    /// The current transfer rate in bytes per second.
+    pub rate: f64,
+}
+
+/// Receives updates as data is transferred to or from the server.
+///
+/// Implemented for any `FnMut(Progress)` closure. Pass `()` when progress
+/// reporting isn't needed.
+pub trait ProgressObserver: Send + Sync {
+    fn update(&mut self, progress: Progress);
+}
+
+impl ProgressObserver for () {
+    fn update(&mut self, _progress: Progress) {}
+}
+
+impl<F> ProgressObserver for F
+where
+    F: FnMut(Progress) + Send + Sync,
+{
+    fn update(&mut self, progress: Progress) {
+        self(progress)
+    }
+}
+
+/// Counts the bytes of a transfer, measures its rate and passes the results
+/// on to an observer.
+pub(crate) struct ProgressTracker<O> {
+    observer: O,
+    transferred: u64,
+    total: Option<u64>,
+    sample_at: Instant,
+    sample_transferred: u64,
+    rate: Option<f64>,
+}
+
+impl<O: ProgressObserver> ProgressTracker<O> {
+    pub(crate) fn new(observer: O, total: Option<u64>) -> Self {
+        Self {
+            observer,
+            transferred: 0,
+            total,
+            sample_at: Instant::now(),
+            sample_transferred: 0,
+            rate: None,
+        }
+    }
+
+    /// Sets the total size of the transfer if it wasn't known up front.
+    pub(crate) fn discover_total(&mut self, total: u64) {
+        self.total.get_or_insert(total);
+    }
+
+    pub(crate) fn transferred(&self) -> u64 {
+        self.transferred
+    }
+
+    pub(crate) fn total(&self) -> Option<u64> {
+        self.total
+    }
+
+    /// Records that more bytes were transferred and notifies the observer.
+    pub(crate) fn advance(&mut self, bytes: u64) {
+        self.transferred += bytes;
+
+        // The rate is measured over a short window rather than per chunk
+        // which would make it jump around wildly. Until the first window
+        // passes the average since the start is used instead.
+        let elapsed = self.sample_at.elapsed();
+        let measured = (self.transferred - self.sample_transferred) as f64;
+        if elapsed >= RATE_WINDOW {
+            self.rate = Some(measured / elapsed.as_secs_f64());
+            self.sample_at = Instant::now();
+            self.sample_transferred = self.transferred;
+        }
+
+        let rate = self.rate.unwrap_or_else(|| {
+            let secs = elapsed.as_secs_f64();
+            if secs > 0.0 {
+                measured / secs
+            } else {
+                0.0
+            }
+        });
+
+        self.observer.update(Progress {
+            transferred: self.transferred,
+            total: self.total,
+            rate,
+        });
+    }
+}
+
+/// Wraps a reader, notifying an observer about every chunk read from it.
+pub(crate) struct ProgressReader<R, O> {
+    inner: R,
+    tracker: ProgressTracker<O>,
+}
+
+impl<R, O: ProgressObserver> ProgressReader<R, O> {
+    pub(crate) fn new(inner: R, total: Option<u64>, observer: O) -> Self {
+        Self {
+            inner,
+            tracker: ProgressTracker::new(observer, total),
+        }
+    }
+}
+
+impl<R, O> AsyncRead for ProgressReader<R, O>
+where
+    R: AsyncRead + Unpin,
+    O: ProgressObserver + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let result = Pin::new(&mut this.inner).poll_read(cx, buf);
+
+        if let Poll::Ready(Ok(read)) = result {
+            if read > 0 {
+                this.tracker.advance(read as u64);
+            }
+        }
+
+        result
+    }
+}
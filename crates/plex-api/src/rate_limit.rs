@@ -0,0 +1,114 @@
+use crate::{redact::redacted_uri, url::is_myplex_host};
+use futures_timer::Delay;
+use isahc::http::Uri;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// How many requests may be sent to a host, as a token bucket: the bucket
+/// holds up to `burst` requests and refills at `requests_per_second`.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    requests_per_second: f64,
+    burst: u32,
+}
+
+impl RateLimit {
+    /// Allows the given number of requests per second, with bursts of up to
+    /// a second's worth of requests. A rate that isn't a positive finite
+    /// number, e.g. zero, disables the limit.
+    pub fn per_second(requests_per_second: f64) -> Self {
+        let requests_per_second = if requests_per_second.is_finite() && requests_per_second > 0.0 {
+            requests_per_second
+        } else {
+            0.0
+        };
+        Self {
+            requests_per_second,
+            burst: requests_per_second.ceil().max(1.0) as u32,
+        }
+    }
+
+    /// How many requests can be sent at once after a quiet period.
+    #[must_use]
+    pub fn burst(self, burst: u32) -> Self {
+        Self {
+            burst: burst.max(1),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Keeps a separate bucket for every host. All the plex.tv hosts share one
+/// bucket since plex.tv limits the account rather than a single host.
+///
+/// Clones of a client share their buckets.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RateLimiter {
+    pub(crate) myplex: Option<RateLimit>,
+    pub(crate) server: Option<RateLimit>,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+impl RateLimiter {
+    /// Waits until a request to the URI may be sent.
+    pub(crate) async fn wait(&self, uri: &Uri) {
+        let delay = self.reserve(uri);
+        if !delay.is_zero() {
+            tracing::trace!(
+                "Rate limited, waiting {delay:?} before requesting {}",
+                redacted_uri(uri)
+            );
+            Delay::new(delay).await;
+        }
+    }
+
+    /// Takes a token for a request to the URI, returning how long to wait
+    /// before sending it.
+    fn reserve(&self, uri: &Uri) -> Duration {
+        let host = uri.host().unwrap_or_default();
+        let (key, limit) = if is_myplex_host(host) {
+            ("plex.tv".to_owned(), self.myplex)
+        } else {
+            (
+                uri.authority().map(ToString::to_string).unwrap_or_default(),
+                self.server,
+            )
+        };
+        let Some(limit) = limit else {
+            return Duration::ZERO;
+        };
+
+        let now = Instant::now();
+        let burst = f64::from(limit.burst);
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: burst,
+            updated_at: now,
+        });
+
+        let elapsed = now.saturating_duration_since(bucket.updated_at);
+        bucket.tokens =
+            (bucket.tokens + elapsed.as_secs_f64() * limit.requests_per_second).min(burst);
+        bucket.updated_at = now;
+        // Going below zero reserves a token that hasn't been refilled yet, so
+        // concurrent requests queue up behind each other.
+        bucket.tokens -= 1.0;
+
+        if bucket.tokens >= 0.0 || limit.requests_per_second <= 0.0 {
+            Duration::ZERO
+        } else {
+            // Very low rates can ask for longer than a Duration holds.
+            Duration::try_from_secs_f64(-bucket.tokens / limit.requests_per_second)
+                .unwrap_or(Duration::MAX)
+        }
+    }
+}
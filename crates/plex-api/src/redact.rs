@@ -0,0 +1,154 @@
+use isahc::http::Uri;
+use std::borrow::Cow;
+
+/// Names of the query parameters, attributes and fields that hold tokens.
+const SECRET_NAMES: &[&str] = &[
+    "X-Plex-Token",
+    "authToken",
+    "accessToken",
+    "authenticationToken",
+];
+
+const REDACTED: &str = "REDACTED";
+
+/// Hides the tokens in a URL, query string or response body so that it can
+/// be logged or included in an error. Handles `name=value` query parameters,
+/// `name="value"` XML attributes and `"name": "value"` JSON fields.
+pub(crate) fn redact(text: &str) -> Cow<'_, str> {
+    let mut redacted = String::new();
+    let mut copied = 0;
+    let mut pos = 0;
+
+    while let Some((name_end, value_start, value_end)) = find_secret(text, pos) {
+        redacted.push_str(&text[copied..value_start]);
+        redacted.push_str(REDACTED);
+        copied = value_end;
+        pos = value_end.max(name_end);
+    }
+
+    if copied == 0 {
+        Cow::Borrowed(text)
+    } else {
+        redacted.push_str(&text[copied..]);
+        Cow::Owned(redacted)
+    }
+}
+
+/// Finds the next secret value at or after `from`, returning where its name
+/// ends and where the value starts and ends.
+fn find_secret(text: &str, from: usize) -> Option<(usize, usize, usize)> {
+    let bytes = text.as_bytes();
+    let mut pos = from;
+
+    while pos < bytes.len() {
+        let found = SECRET_NAMES.iter().find(|name| {
+            bytes.len() - pos >= name.len()
+                && bytes[pos..pos + name.len()].eq_ignore_ascii_case(name.as_bytes())
+                && (pos == 0 || !is_name_byte(bytes[pos - 1]))
+        });
+        let Some(name) = found else {
+            pos += 1;
+            continue;
+        };
+
+        let name_end = pos + name.len();
+        let mut cursor = name_end;
+        let skip = |cursor: &mut usize, allowed: &[u8]| {
+            while *cursor < bytes.len() && allowed.contains(&bytes[*cursor]) {
+                *cursor += 1;
+            }
+        };
+
+        // A closing quote for JSON keys, then the separator.
+        skip(&mut cursor, b"\"");
+        skip(&mut cursor, b" \t");
+        if cursor >= bytes.len() || !matches!(bytes[cursor], b'=' | b':') {
+            pos = name_end;
+            continue;
+        }
+        cursor += 1;
+        skip(&mut cursor, b" \t");
+
+        let quote = bytes
+            .get(cursor)
+            .copied()
+            .filter(|b| matches!(b, b'"' | b'\''));
+        if quote.is_some() {
+            cursor += 1;
+        }
+        let value_start = cursor;
+        while cursor < bytes.len() {
+            let byte = bytes[cursor];
+            let end = match quote {
+                Some(quote) => byte == quote,
+                None => {
+                    matches!(byte, b'&' | b'"' | b'\'' | b'<' | b',' | b'}' | b';')
+                        || byte.is_ascii_whitespace()
+                }
+            };
+            if end {
+                break;
+            }
+            cursor += 1;
+        }
+
+        if cursor > value_start {
+            return Some((name_end, value_start, cursor));
+        }
+        pos = name_end;
+    }
+
+    None
+}
+
+fn is_name_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_')
+}
+
+/// Formats the path and query of a URI with any token hidden.
+pub(crate) fn redacted_path(uri: &Uri) -> String {
+    let path = uri
+        .path_and_query()
+        .map(|path| path.as_str())
+        .unwrap_or_else(|| uri.path());
+    redact(path).into_owned()
+}
+
+/// Formats a whole URI with any token hidden.
+pub(crate) fn redacted_uri(uri: &Uri) -> String {
+    redact(&uri.to_string()).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redact_token() {
+        let uri: Uri =
+            "http://127.0.0.1:32400/library/parts/1/file.mkv?download=1&X-Plex-Token=secret"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            redacted_path(&uri),
+            "/library/parts/1/file.mkv?download=1&X-Plex-Token=REDACTED"
+        );
+
+        let uri: Uri = "http://127.0.0.1:32400/identity".parse().unwrap();
+        assert_eq!(redacted_path(&uri), "/identity");
+
+        assert_eq!(
+            redact(r#"<MyPlex authToken="secret" username="user" />"#),
+            r#"<MyPlex authToken="REDACTED" username="user" />"#
+        );
+        assert_eq!(
+            redact(r#"{"id": 1, "authToken" : "secret", "accessToken":"other"}"#),
+            r#"{"id": 1, "authToken" : "REDACTED", "accessToken":"REDACTED"}"#
+        );
+        assert_eq!(
+            redact("x-plex-token=a&myX-Plex-Token=b"),
+            "x-plex-token=REDACTED&myX-Plex-Token=b"
+        );
+        assert!(matches!(redact("nothing to hide"), Cow::Borrowed(_)));
+    }
+}
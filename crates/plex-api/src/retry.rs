@@ -0,0 +1,145 @@
+use crate::Error;
+use isahc::{http::Method, AsyncBody, Response as HttpResponse};
+use std::time::Duration;
+
+/// Decides whether and when failed requests are sent again.
+///
+/// Requests are retried when the server answers with `429 Too Many
+/// Requests`, `502 Bad Gateway`, `503 Service Unavailable` or `504 Gateway
+/// Timeout`, or when the request times out. Only requests without a body are
+/// retried, and apart from `429` (which means the request wasn't processed)
+/// only if their method is idempotent.
+///
+/// ```
+/// use plex_api::{HttpClientBuilder, RetryPolicy};
+/// use std::time::Duration;
+///
+/// let client = HttpClientBuilder::default()
+///     .set_retry_policy(
+///         RetryPolicy::default()
+///             .max_attempts(5)
+///             .initial_backoff(Duration::from_secs(1)),
+///     )
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, waiting half a second before the first retry and
+    /// doubling that every time, with jitter.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// How many times a request is sent in total, including the first
+    /// attempt.
+    #[must_use]
+    pub fn max_attempts(self, max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..self
+        }
+    }
+
+    /// How long to wait before the first retry. The wait doubles with every
+    /// following retry.
+    #[must_use]
+    pub fn initial_backoff(self, initial_backoff: Duration) -> Self {
+        Self {
+            initial_backoff,
+            ..self
+        }
+    }
+
+    /// The longest wait between two attempts, unless the server asks for a
+    /// longer one using `Retry-After`.
+    #[must_use]
+    pub fn max_backoff(self, max_backoff: Duration) -> Self {
+        Self {
+            max_backoff,
+            ..self
+        }
+    }
+
+    /// Randomly shortens each wait by up to a half, so that clients that
+    /// failed at the same time don't all retry at the same time.
+    #[must_use]
+    pub fn jitter(self, jitter: bool) -> Self {
+        Self { jitter, ..self }
+    }
+
+    pub(crate) fn attempts(&self) -> u32 {
+        self.max_attempts.max(1)
+    }
+
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff);
+
+        if self.jitter {
+            backoff.mul_f64(1.0 - fastrand::f64() / 2.0)
+        } else {
+            backoff
+        }
+    }
+
+    /// Works out how long to wait before sending the request again, `None`
+    /// if it shouldn't be retried.
+    pub(crate) fn retry_response(
+        &self,
+        method: &Method,
+        response: &HttpResponse<AsyncBody>,
+        retry: u32,
+    ) -> Option<Duration> {
+        match response.status().as_u16() {
+            429 => {}
+            502..=504 if is_idempotent(method) => {}
+            _ => return None,
+        }
+
+        Some(retry_after(response).unwrap_or_else(|| self.backoff(retry)))
+    }
+
+    pub(crate) fn retry_error(
+        &self,
+        method: &Method,
+        error: &Error,
+        retry: u32,
+    ) -> Option<Duration> {
+        let timed_out = matches!(error, Error::IsahcError { source } if source.is_timeout());
+        (timed_out && is_idempotent(method)).then(|| self.backoff(retry))
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+    )
+}
+
+/// Reads the number of seconds from a `Retry-After` header.
+pub(crate) fn retry_after<T>(response: &HttpResponse<T>) -> Option<Duration> {
+    response
+        .headers()
+        .get("Retry-After")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .map(Duration::from_secs)
+}
@@ -0,0 +1,270 @@
+//! The butler, which runs the server's scheduled maintenance tasks like
+//! backing up the database or refreshing metadata.
+//!
+//! Enabled tasks run once per interval during the maintenance window. While
+//! a task runs, its progress is reported as one of the server's
+//! [activities](crate::Server::activities). The server doesn't report when a
+//! task last ran, although for database backups the date is part of the
+//! [backup's](DatabaseBackup) name.
+
+use crate::{
+    endpoints::Endpoint,
+    media_container::{
+        preferences::Value,
+        server::activities::{ActivitiesMediaContainer, Activity},
+        MediaContainerWrapper,
+    },
+    url::{SERVER_ACTIVITIES, SERVER_BROWSE, SERVER_BUTLER, SERVER_BUTLER_TASK},
+    Error, HttpClient, Preferences, Result,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures_timer::Delay;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use time::{format_description::BorrowedFormatItem, macros::format_description, Date};
+
+const START_HOUR: &str = "ButlerStartHour";
+const END_HOUR: &str = "ButlerEndHour";
+const BACKUP_PATH: &str = "ButlerDatabaseBackupPath";
+const BACKUP_TASK: &str = "BackupDatabase";
+/// Backups are copies of the library database with the date appended, e.g.
+/// `com.plexapp.plugins.library.db-2024-03-17`.
+const BACKUP_PREFIX: &str = "com.plexapp.plugins.library.db-";
+const BACKUP_DATE: &[BorrowedFormatItem<'_>] = format_description!("[year]-[month]-[day]");
+/// The activity reported while the backup task runs.
+const BACKUP_ACTIVITY: &str = "butler.backup";
+const BACKUP_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// How long the backup activity may take to show up once the task was
+/// started. A quick backup can come and go between two polls, after this
+/// long without seeing it the backup is taken to be done.
+const BACKUP_START_GRACE: Duration = Duration::from_secs(3);
+
+/// A scheduled task and its schedule.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct ButlerTask {
+    /// The name used to start or stop the task, e.g. `BackupDatabase`.
+    pub name: String,
+    pub title: String,
+    pub description: Option<String>,
+    /// How many days pass between runs.
+    pub interval: u32,
+    /// Whether the task starts at a random time within the maintenance
+    /// window rather than at its start.
+    #[serde(default)]
+    pub schedule_randomized: bool,
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ButlerTasks {
+    #[serde(rename = "ButlerTask", default)]
+    tasks: Vec<ButlerTask>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ButlerTasksWrapper {
+    #[serde(rename = "ButlerTasks")]
+    butler_tasks: ButlerTasks,
+}
+
+/// The hours of the day, in the server's time zone, during which scheduled
+/// tasks run. The window wraps around midnight when the end is before the
+/// start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ButlerWindow {
+    pub start_hour: u8,
+    pub end_hour: u8,
+}
+
+/// A copy of the library database made by the backup task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseBackup {
+    /// The file's path on the server.
+    pub path: String,
+    pub date: Date,
+}
+
+impl DatabaseBackup {
+    fn from_entry(entry: BrowseEntry) -> Option<Self> {
+        let file_name = entry.path.rsplit(['/', '\\']).next()?;
+        let date = Date::parse(file_name.strip_prefix(BACKUP_PREFIX)?, BACKUP_DATE).ok()?;
+        Some(Self {
+            path: entry.path,
+            date,
+        })
+    }
+}
+
+/// A file or folder listed by the server's file browser.
+#[derive(Debug, Deserialize)]
+struct BrowseEntry {
+    path: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BrowseMediaContainer {
+    #[serde(rename = "File", default)]
+    files: Vec<BrowseEntry>,
+}
+
+/// Runs and inspects the server's scheduled tasks, see
+/// [`crate::Server::butler`]. Only the server's owner can use it.
+#[derive(Debug, Clone)]
+pub struct Butler {
+    client: HttpClient,
+}
+
+impl Butler {
+    pub(crate) fn new(client: HttpClient) -> Self {
+        Self { client }
+    }
+
+    /// Lists the scheduled tasks.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn tasks(&self) -> Result<Vec<ButlerTask>> {
+        let wrapper: ButlerTasksWrapper = self.client.get(SERVER_BUTLER).json().await?;
+        Ok(wrapper.butler_tasks.tasks)
+    }
+
+    /// The maintenance window the tasks run in.
+    pub async fn window(&self) -> Result<ButlerWindow> {
+        let prefs = Preferences::new(&self.client).await?;
+        let hour = |id: &str| match prefs.get(id).map(|setting| &setting.value) {
+            Some(Value::Int(hour)) => {
+                u8::try_from(*hour).map_err(|_| Error::InvalidSettingValue(id.to_owned()))
+            }
+            Some(_) => Err(Error::IncompatibleSettingValues),
+            None => Err(Error::RequestedSettingNotFound(id.to_owned())),
+        };
+
+        Ok(ButlerWindow {
+            start_hour: hour(START_HOUR)?,
+            end_hour: hour(END_HOUR)?,
+        })
+    }
+
+    /// Runs a task now, outside of its schedule.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn start(&self, name: &str) -> Result {
+        self.client
+            .post(Endpoint::new(SERVER_BUTLER_TASK).segment("taskName", name))
+            .consume()
+            .await
+    }
+
+    /// Runs every enabled task now.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn start_all(&self) -> Result {
+        self.client.post(SERVER_BUTLER).consume().await
+    }
+
+    /// Stops a running task.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn stop(&self, name: &str) -> Result {
+        self.client
+            .delete(Endpoint::new(SERVER_BUTLER_TASK).segment("taskName", name))
+            .consume()
+            .await
+    }
+
+    /// Stops every running task.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn stop_all(&self) -> Result {
+        self.client.delete(SERVER_BUTLER).consume().await
+    }
+
+    /// Lists the database backups in the backup directory configured on the
+    /// server, oldest first.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn database_backups(&self) -> Result<Vec<DatabaseBackup>> {
+        let prefs = Preferences::new(&self.client).await?;
+        let directory = match prefs.get(BACKUP_PATH).map(|setting| &setting.value) {
+            Some(Value::Text(directory)) => directory.clone(),
+            Some(_) => return Err(Error::IncompatibleSettingValues),
+            None => return Err(Error::RequestedSettingNotFound(BACKUP_PATH.to_owned())),
+        };
+
+        let wrapper: MediaContainerWrapper<BrowseMediaContainer> = self
+            .client
+            .get(
+                Endpoint::new(SERVER_BROWSE)
+                    .segment("path", STANDARD.encode(directory))
+                    .query("includeFiles", "1"),
+            )
+            .json()
+            .await?;
+        let mut backups: Vec<DatabaseBackup> = wrapper
+            .media_container
+            .files
+            .into_iter()
+            .filter_map(DatabaseBackup::from_entry)
+            .collect();
+        backups.sort_by_key(|backup| backup.date);
+        Ok(backups)
+    }
+
+    /// Backs up the database now and waits for the backup task to finish,
+    /// returning the backup it wrote.
+    ///
+    /// The backup is done once its `butler.backup` activity went away again
+    /// or a backup newer than the ones present before shows up, whichever is
+    /// noticed first. Backups are named by day, so a backup replacing one
+    /// from earlier the same day is only noticed through the activity: when
+    /// that isn't seen within a few seconds of starting the task, the newest
+    /// backup is returned. Returns [`Error::Timeout`] if the backup isn't done
+    /// within the timeout, if one is given.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn backup_database(&self, timeout: Option<Duration>) -> Result<DatabaseBackup> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let previous = self.database_backups().await?.pop();
+        self.start(BACKUP_TASK).await?;
+        let started = Instant::now();
+
+        let mut seen_running = false;
+        loop {
+            let running = self
+                .activities()
+                .await?
+                .iter()
+                .any(|activity| activity.activity_type == BACKUP_ACTIVITY);
+            seen_running |= running;
+
+            if !running {
+                let done = seen_running || started.elapsed() >= BACKUP_START_GRACE;
+                let newest = self.database_backups().await?.pop();
+                match newest {
+                    Some(newest) if done || is_newer(&newest, previous.as_ref()) => {
+                        return Ok(newest)
+                    }
+                    None if done => return Err(Error::ItemNotFound),
+                    _ => (),
+                }
+            }
+
+            let sleep = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Err(Error::Timeout);
+                    }
+                    BACKUP_POLL_INTERVAL.min(remaining)
+                }
+                None => BACKUP_POLL_INTERVAL,
+            };
+            Delay::new(sleep).await;
+        }
+    }
+
+    async fn activities(&self) -> Result<Vec<Activity>> {
+        let wrapper: MediaContainerWrapper<ActivitiesMediaContainer> =
+            self.client.get(SERVER_ACTIVITIES).json().await?;
+        Ok(wrapper.media_container.activities)
+    }
+}
+
+fn is_newer(backup: &DatabaseBackup, previous: Option<&DatabaseBackup>) -> bool {
+    previous.is_none_or(|previous| backup.date > previous.date)
+}
@@ -0,0 +1,201 @@
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use crate::{
+    media_container::server::{Feature, MediaProviderProtocol, Server as ServerMediaContainer},
+    Error,
+};
+
+use super::LIBRARY_PROVIDER;
+
+/// A Plex Media Server version, e.g. `1.25.3.5409-f11334058`.
+///
+/// Versions compare by their numeric parts, the trailing revision is only
+/// kept for display.
+#[derive(Debug, Clone)]
+pub struct ServerVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    pub build: u32,
+    revision: Option<String>,
+}
+
+impl ServerVersion {
+    /// The source revision the server was built from.
+    pub fn revision(&self) -> Option<&str> {
+        self.revision.as_deref()
+    }
+
+    fn numbers(&self) -> (u32, u32, u32, u32) {
+        (self.major, self.minor, self.patch, self.build)
+    }
+}
+
+impl PartialEq for ServerVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.numbers() == other.numbers()
+    }
+}
+
+impl Eq for ServerVersion {}
+
+impl PartialOrd for ServerVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ServerVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.numbers().cmp(&other.numbers())
+    }
+}
+
+impl FromStr for ServerVersion {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (numbers, revision) = match s.split_once('-') {
+            Some((numbers, revision)) => (numbers, Some(revision.to_owned())),
+            None => (s, None),
+        };
+
+        let mut parts = numbers.split('.').map(str::parse::<u32>);
+        let mut next = || match parts.next() {
+            Some(Ok(number)) => Ok(number),
+            None => Ok(0),
+            Some(Err(_)) => Err(Error::InvalidServerVersion(s.to_owned())),
+        };
+
+        Ok(Self {
+            major: next()?,
+            minor: next()?,
+            patch: next()?,
+            build: next()?,
+            revision,
+        })
+    }
+}
+
+impl fmt::Display for ServerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}.{}",
+            self.major, self.minor, self.patch, self.build
+        )?;
+        if let Some(revision) = &self.revision {
+            write!(f, "-{revision}")?;
+        }
+        Ok(())
+    }
+}
+
+/// What a server is able to do, worked out from its version, the features
+/// granted to its owner's account and the media providers it offers.
+#[derive(Debug, Clone)]
+pub struct ServerCapabilities {
+    version: Option<ServerVersion>,
+    features: Vec<Feature>,
+    library: bool,
+    downloads: bool,
+    live_tv: bool,
+    tuners: bool,
+    sync: bool,
+    transcoder_video: bool,
+    transcoder_audio: bool,
+}
+
+impl ServerCapabilities {
+    pub(crate) fn new(media_container: &ServerMediaContainer) -> Self {
+        let library = media_container
+            .media_providers
+            .iter()
+            .find(|p| p.identifier == LIBRARY_PROVIDER);
+
+        Self {
+            version: media_container.version.parse().ok(),
+            features: media_container.owner_features.clone(),
+            library: library.is_some(),
+            downloads: library
+                .map(|provider| {
+                    provider
+                        .protocols
+                        .iter()
+                        .any(|p| matches!(p, MediaProviderProtocol::Download))
+                })
+                .unwrap_or_default(),
+            live_tv: media_container.media_providers.iter().any(|provider| {
+                provider
+                    .protocols
+                    .iter()
+                    .any(|p| matches!(p, MediaProviderProtocol::LiveTv))
+            }),
+            tuners: media_container.allow_tuners,
+            sync: media_container.allow_sync,
+            transcoder_video: media_container.transcoder_video,
+            transcoder_audio: media_container.transcoder_audio,
+        }
+    }
+
+    /// The server's version, if it could be parsed.
+    pub fn version(&self) -> Option<&ServerVersion> {
+        self.version.as_ref()
+    }
+
+    /// Checks that the server is at least the given version. Servers with a
+    /// version that couldn't be parsed are assumed to be too old.
+    pub fn version_at_least(&self, major: u32, minor: u32, patch: u32) -> bool {
+        self.version
+            .as_ref()
+            .map(|v| (v.major, v.minor, v.patch) >= (major, minor, patch))
+            .unwrap_or_default()
+    }
+
+    /// The features granted by the server owner's Plex account.
+    pub fn features(&self) -> &[Feature] {
+        &self.features
+    }
+
+    pub fn has_feature(&self, feature: &Feature) -> bool {
+        self.features.contains(feature)
+    }
+
+    pub fn supports_library(&self) -> bool {
+        self.library
+    }
+
+    /// Whether items can be queued to be transcoded for download.
+    pub fn supports_download_queue(&self) -> bool {
+        self.downloads
+    }
+
+    /// Whether the server detects the intros of TV episodes so they can be
+    /// skipped.
+    pub fn supports_intro_markers(&self) -> bool {
+        self.has_feature(&Feature::IntroMarkers)
+    }
+
+    /// Whether the server has a program guide for Live TV.
+    pub fn supports_live_tv(&self) -> bool {
+        self.live_tv
+    }
+
+    /// Whether the server allows tuner devices to be set up for DVRs.
+    pub fn supports_tuners(&self) -> bool {
+        self.tuners
+    }
+
+    /// Whether the server allows items to be synced to mobile devices.
+    pub fn supports_sync(&self) -> bool {
+        self.sync
+    }
+
+    pub fn supports_video_transcoding(&self) -> bool {
+        self.transcoder_video
+    }
+
+    pub fn supports_audio_transcoding(&self) -> bool {
+        self.transcoder_audio
+    }
+}
@@ -0,0 +1,186 @@
+//! Content ratings like `PG-13` or `gb/15`, typed so they can be compared
+//! across the rating systems of different countries.
+//!
+//! The server keeps ratings as plain strings. US ratings have no prefix while
+//! the ratings of other countries are prefixed with the country's code, e.g.
+//! `de/12`. Every rating is mapped to the minimum age it stands for, which is
+//! what restriction profiles compare against as well:
+//!
+//! ```
+//! use plex_api::{content_rating::ContentRating, RestrictionProfile};
+//!
+//! let pg13 = ContentRating::parse("PG-13");
+//! assert!(pg13 < ContentRating::parse("gb/15"));
+//! assert!(RestrictionProfile::Teen.allows(&pg13));
+//! assert!(!RestrictionProfile::OlderKid.allows(&pg13));
+//! ```
+
+use super::library::MetadataItem;
+use crate::RestrictionProfile;
+use std::{cmp::Ordering, fmt};
+
+/// A content rating from any of the supported rating systems.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContentRating {
+    country: Option<String>,
+    label: String,
+    age: Option<u8>,
+}
+
+impl ContentRating {
+    /// Reads a rating as the server sends it. Ratings that aren't known,
+    /// e.g. `NR`, are kept but have no minimum age.
+    pub fn parse(rating: &str) -> Self {
+        let rating = rating.trim();
+        let (country, label) = match rating.split_once('/') {
+            Some((country, label)) => (Some(country.to_lowercase()), label.trim()),
+            None => (None, rating),
+        };
+        let age = minimum_age(country.as_deref().unwrap_or("us"), label);
+
+        Self {
+            country,
+            label: label.to_owned(),
+            age,
+        }
+    }
+
+    /// The country of the rating system, `None` for US ratings.
+    pub fn country(&self) -> Option<&str> {
+        self.country.as_deref()
+    }
+
+    /// The rating within its system, e.g. `15` for `gb/15`.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// The age the rating recommends the content for, which makes ratings of
+    /// different systems comparable.
+    pub fn age(&self) -> Option<u8> {
+        self.age
+    }
+}
+
+/// Ratings are ordered by their [age](ContentRating::age), so ratings
+/// without one can't be compared to others.
+impl PartialOrd for ContentRating {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self == other {
+            return Some(Ordering::Equal);
+        }
+        match (self.age, other.age) {
+            (Some(age), Some(other_age)) if age != other_age => Some(age.cmp(&other_age)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ContentRating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.country {
+            Some(country) => write!(f, "{country}/{}", self.label),
+            None => f.write_str(&self.label),
+        }
+    }
+}
+
+impl RestrictionProfile {
+    /// The highest age of the ratings the profile allows.
+    pub fn max_age(&self) -> Option<u8> {
+        match self {
+            Self::LittleKid => Some(6),
+            Self::OlderKid => Some(10),
+            Self::Teen => Some(14),
+            #[cfg(not(feature = "tests_deny_unknown_fields"))]
+            Self::Unknown => None,
+        }
+    }
+
+    /// Whether items with the rating are visible to users with the profile.
+    /// Like on the server, unrated items are hidden.
+    pub fn allows(&self, rating: &ContentRating) -> bool {
+        matches!((rating.age(), self.max_age()), (Some(age), Some(max_age)) if age <= max_age)
+    }
+}
+
+/// Keeps the items rated for the given age or younger, e.g. to filter a
+/// library's listing with a [`RestrictionProfile::max_age`]. Unrated items
+/// are left out.
+pub fn rated_at_most<M, I>(items: I, max_age: u8) -> Vec<M>
+where
+    M: MetadataItem,
+    I: IntoIterator<Item = M>,
+{
+    items
+        .into_iter()
+        .filter(|item| {
+            item.content_rating()
+                .and_then(|rating| rating.age())
+                .is_some_and(|age| age <= max_age)
+        })
+        .collect()
+}
+
+fn minimum_age(country: &str, label: &str) -> Option<u8> {
+    let label = label.to_uppercase();
+    let age = match (country, label.as_str()) {
+        ("us", "NR" | "NOT RATED" | "UNRATED") => return None,
+        ("us", "G" | "TV-Y" | "TV-G") => 0,
+        ("us", "TV-Y7" | "TV-Y7-FV") => 7,
+        ("us", "PG" | "TV-PG") => 10,
+        ("us", "PG-13") => 13,
+        ("us", "TV-14") => 14,
+        ("us", "R" | "TV-MA") => 17,
+        ("us", "NC-17" | "X") => 18,
+        ("gb", "U" | "UC") => 0,
+        ("gb", "PG") => 8,
+        ("gb", "R18") => 18,
+        ("au", "E" | "G" | "C" | "P") => 0,
+        ("au", "PG") => 8,
+        ("au", "M" | "MA15+") => 15,
+        ("au", "R18+" | "X18+") => 18,
+        ("ca", "E" | "G" | "C") => 0,
+        ("ca", "PG" | "C8") => 8,
+        ("ca", "14A" | "14+") => 14,
+        ("ca", "18A" | "18+" | "R") => 18,
+        ("nz", "G") => 0,
+        ("nz", "PG") => 8,
+        ("nz", "M") => 16,
+        ("fr" | "de" | "nl", "TP" | "U" | "AL") => 0,
+        // Most other systems are named after the age, e.g. `12A`, `-16` or
+        // `FSK 18`.
+        _ => label
+            .split(|c: char| !c.is_ascii_digit())
+            .find(|digits| !digits.is_empty())?
+            .parse()
+            .ok()?,
+    };
+    Some(age)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ratings() {
+        let rating = ContentRating::parse("de/12");
+        assert_eq!(rating.country(), Some("de"));
+        assert_eq!(rating.label(), "12");
+        assert_eq!(rating.age(), Some(12));
+        assert_eq!(rating.to_string(), "de/12");
+
+        assert_eq!(ContentRating::parse("TV-MA").age(), Some(17));
+        assert_eq!(ContentRating::parse("au/MA15+").age(), Some(15));
+        assert_eq!(ContentRating::parse("fr/-16").age(), Some(16));
+        assert_eq!(ContentRating::parse("NR").age(), None);
+
+        assert!(ContentRating::parse("G") < ContentRating::parse("gb/12A"));
+        assert!(ContentRating::parse("R") > ContentRating::parse("TV-14"));
+        assert_eq!(
+            ContentRating::parse("NR").partial_cmp(&ContentRating::parse("G")),
+            None
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use crate::{
+    endpoints::Endpoint,
+    media_container::{
+        preferences::{Setting, Value},
+        server::library::MetadataMediaContainer,
+        MediaContainerWrapper,
+    },
+    url::SERVER_LIBRARY_PREFS,
+    Error, HttpClient, Result,
+};
+
+const EPISODE_SORT: &str = "episodeSort";
+const SEASONS_DISPLAY: &str = "flattenSeasons";
+const KEEP_UNWATCHED: &str = "autoDeletionItemPolicyUnwatchedLibrary";
+const DELETE_WATCHED: &str = "autoDeletionItemPolicyWatchedLibrary";
+const AUDIO_LANGUAGE: &str = "audioLanguage";
+const SUBTITLE_LANGUAGE: &str = "subtitleLanguage";
+
+/// The order episodes are listed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EpisodeSort {
+    LibraryDefault,
+    OldestFirst,
+    NewestFirst,
+}
+
+impl EpisodeSort {
+    fn from_value(value: i64) -> Option<Self> {
+        match value {
+            -1 => Some(Self::LibraryDefault),
+            0 => Some(Self::OldestFirst),
+            1 => Some(Self::NewestFirst),
+            _ => None,
+        }
+    }
+
+    fn value(self) -> i64 {
+        match self {
+            Self::LibraryDefault => -1,
+            Self::OldestFirst => 0,
+            Self::NewestFirst => 1,
+        }
+    }
+}
+
+/// Whether a show's episodes are grouped into seasons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeasonsDisplay {
+    LibraryDefault,
+    Show,
+    /// Lists all of the episodes directly under the show.
+    Hide,
+}
+
+impl SeasonsDisplay {
+    fn from_value(value: i64) -> Option<Self> {
+        match value {
+            -1 => Some(Self::LibraryDefault),
+            0 => Some(Self::Show),
+            1 => Some(Self::Hide),
+            _ => None,
+        }
+    }
+
+    fn value(self) -> i64 {
+        match self {
+            Self::LibraryDefault => -1,
+            Self::Show => 0,
+            Self::Hide => 1,
+        }
+    }
+}
+
+/// Which unwatched episodes the server keeps, deleting the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepUnwatched {
+    All,
+    /// Only the given number of the latest episodes.
+    Latest(u32),
+    /// Only the episodes added within the given number of days.
+    AddedInLastDays(u32),
+}
+
+impl KeepUnwatched {
+    fn from_value(value: i64) -> Option<Self> {
+        Some(match value {
+            0 => Self::All,
+            1.. => Self::Latest(u32::try_from(value).ok()?),
+            _ => Self::AddedInLastDays(u32::try_from(-value).ok()?),
+        })
+    }
+
+    fn value(self) -> i64 {
+        match self {
+            Self::All => 0,
+            Self::Latest(count) => count.into(),
+            Self::AddedInLastDays(days) => -i64::from(days),
+        }
+    }
+}
+
+/// When the server deletes episodes after they were watched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeleteWatched {
+    Never,
+    AfterADay,
+    AfterAWeek,
+    OnNextRefresh,
+}
+
+impl DeleteWatched {
+    fn from_value(value: i64) -> Option<Self> {
+        match value {
+            0 => Some(Self::Never),
+            1 => Some(Self::AfterADay),
+            7 => Some(Self::AfterAWeek),
+            100 => Some(Self::OnNextRefresh),
+            _ => None,
+        }
+    }
+
+    fn value(self) -> i64 {
+        match self {
+            Self::Never => 0,
+            Self::AfterADay => 1,
+            Self::AfterAWeek => 7,
+            Self::OnNextRefresh => 100,
+        }
+    }
+}
+
+/// The advanced settings of a show or season, overriding the ones of its
+/// library.
+///
+/// Fields are `None` when the server doesn't offer the setting for the item,
+/// and such settings are left untouched when saving.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ItemSettings {
+    pub episode_sort: Option<EpisodeSort>,
+    pub seasons_display: Option<SeasonsDisplay>,
+    pub keep_unwatched: Option<KeepUnwatched>,
+    pub delete_watched: Option<DeleteWatched>,
+    /// The preferred audio language as an ISO 639 code, empty to use the
+    /// account's preference.
+    pub audio_language: Option<String>,
+    /// The preferred subtitle language, see `audio_language`.
+    pub subtitle_language: Option<String>,
+}
+
+impl ItemSettings {
+    pub(crate) async fn load(client: &HttpClient, rating_key: &str) -> Result<Self> {
+        let wrapper: MediaContainerWrapper<MetadataMediaContainer> = client
+            .get(format!(
+                "/library/metadata/{rating_key}?includePreferences=1"
+            ))
+            .json()
+            .await?;
+        let settings = wrapper
+            .media_container
+            .metadata
+            .into_iter()
+            .next()
+            .ok_or(Error::ItemNotFound)?
+            .preferences
+            .map(|preferences| preferences.settings)
+            .unwrap_or_default();
+
+        let setting = |id: &str| settings.iter().find(|setting| setting.id == id);
+
+        Ok(Self {
+            episode_sort: typed_value(setting(EPISODE_SORT), EpisodeSort::from_value)?,
+            seasons_display: typed_value(setting(SEASONS_DISPLAY), SeasonsDisplay::from_value)?,
+            keep_unwatched: typed_value(setting(KEEP_UNWATCHED), KeepUnwatched::from_value)?,
+            delete_watched: typed_value(setting(DELETE_WATCHED), DeleteWatched::from_value)?,
+            audio_language: setting(AUDIO_LANGUAGE).map(|setting| setting.value.to_string()),
+            subtitle_language: setting(SUBTITLE_LANGUAGE).map(|setting| setting.value.to_string()),
+        })
+    }
+
+    pub(crate) async fn save(&self, client: &HttpClient, rating_key: &str) -> Result {
+        let params = [
+            (
+                EPISODE_SORT,
+                self.episode_sort.map(|v| v.value().to_string()),
+            ),
+            (
+                SEASONS_DISPLAY,
+                self.seasons_display.map(|v| v.value().to_string()),
+            ),
+            (
+                KEEP_UNWATCHED,
+                self.keep_unwatched.map(|v| v.value().to_string()),
+            ),
+            (
+                DELETE_WATCHED,
+                self.delete_watched.map(|v| v.value().to_string()),
+            ),
+            (AUDIO_LANGUAGE, self.audio_language.clone()),
+            (SUBTITLE_LANGUAGE, self.subtitle_language.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(id, value)| Some((id, value?)))
+        .collect::<Vec<_>>();
+
+        if params.is_empty() {
+            return Ok(());
+        }
+
+        let uri = format!(
+            "{}?{}",
+            Endpoint::new(SERVER_LIBRARY_PREFS).segment("ratingKey", rating_key),
+            serde_urlencoded::to_string(params)?
+        );
+        client.put(uri).consume().await
+    }
+}
+
+/// Integer settings sometimes come back typed as text, so both are accepted.
+fn typed_value<T>(setting: Option<&Setting>, parse: fn(i64) -> Option<T>) -> Result<Option<T>> {
+    let Some(setting) = setting else {
+        return Ok(None);
+    };
+
+    let value = match &setting.value {
+        Value::Int(value) => Some(*value),
+        Value::Text(value) => value.parse().ok(),
+        _ => None,
+    };
+    value
+        .and_then(parse)
+        .map(Some)
+        .ok_or_else(|| Error::InvalidSettingValue(setting.id.clone()))
+}
@@ -1,26 +1,60 @@
-use std::{future::Future, marker::PhantomData, ops::RangeBounds};
+use std::{
+    collections::HashMap, fmt::Display, future::Future, marker::PhantomData, ops::RangeBounds,
+    time::Duration,
+};
 
 use enum_dispatch::enum_dispatch;
-use futures::AsyncWrite;
-use http::StatusCode;
-use isahc::AsyncReadResponseExt;
+use futures::{
+    stream::{self, Stream as FuturesStream},
+    AsyncRead, AsyncWrite, StreamExt, TryStreamExt,
+};
+use http::{StatusCode, Uri};
+use isahc::{AsyncBody, AsyncReadResponseExt};
+use time::Date;
+use uuid::Uuid;
 
 use crate::{
+    download::{byte_range, Download},
+    endpoints::Endpoint,
     isahc_compat::StatusCodeExt,
+    json_stream::JsonArrayReader,
     media_container::{
-        server::library::{
-            CollectionMetadataSubtype, LibraryType, Media as MediaMetadata, Metadata,
-            MetadataMediaContainer, MetadataType, Part as PartMetadata, PlaylistMetadataType,
-            Protocol, SearchType, ServerLibrary,
+        preferences::{Preferences as MediaContainerPreferences, Setting, Value},
+        server::{
+            library::{
+                AudioStream, CollectionMetadataSubtype, ContainerFormat, ContentDirectory,
+                ExternalId, FolderDirectory, Guid, LevelsMediaContainer, LibraryType, Loudness,
+                LyricStream, Media as MediaMetadata, Metadata, MetadataMediaContainer,
+                MetadataType, Part as PartMetadata, PlaylistMetadataType, Protocol, SearchType,
+                ServerLibrary, Stream, SubtitleSearchMediaContainer, SubtitleSearchResult,
+                SubtitleStream, Tag, TagDirectory,
+            },
+            MediaProviderFeature, Server as ServerMediaContainer,
         },
         MediaContainerWrapper,
     },
+    progress::ProgressReader,
+    server::{
+        authenticated_url, content_rating::ContentRating, PreviewThumbnails, Query,
+        LIBRARY_PROVIDER,
+    },
     transcode::{
+        bs,
         download_queue::{DownloadQueue, QueueItem},
         session::{create_transcode_session, TranscodeSession},
-        Context, MusicTranscodeOptions, TranscodeOptions, VideoTranscodeOptions,
+        transcode_artwork, ArtTranscodeOptions, AudioOnly, Context, MusicTranscodeOptions,
+        TranscodeOptions, VideoTranscodeOptions,
+    },
+    url::{
+        SERVER_COLLECTIONS, SERVER_COLLECTION_ITEMS, SERVER_LIBRARY_NEAREST,
+        SERVER_LIBRARY_PART_INDEX, SERVER_LIBRARY_SECTION, SERVER_LIBRARY_SECTION_ALL,
+        SERVER_LIBRARY_SECTION_ANALYZE, SERVER_LIBRARY_SECTION_FOLDER,
+        SERVER_LIBRARY_SECTION_NEWEST, SERVER_LIBRARY_SECTION_PREFS,
+        SERVER_LIBRARY_SECTION_RECENTLY_ADDED, SERVER_LIBRARY_SECTION_TAGS, SERVER_LIBRARY_SIMILAR,
+        SERVER_LIBRARY_STATION, SERVER_LIBRARY_STREAM, SERVER_LIBRARY_STREAM_LEVELS,
+        SERVER_LIBRARY_SUBTITLES, SERVER_LIBRARY_UPLOAD, SERVER_MEDIA_PROVIDERS,
     },
-    Error, HttpClient, Result,
+    DownloadOptions, Error, HttpClient, ItemSettings, ProgressObserver, Result,
 };
 
 pub trait FromMetadata {
@@ -61,6 +95,33 @@ pub trait MetadataItem {
     fn title(&self) -> &str {
         &self.metadata().title
     }
+
+    /// The item's content rating, e.g. `PG-13`.
+    fn content_rating(&self) -> Option<ContentRating> {
+        self.metadata()
+            .content_rating
+            .as_deref()
+            .map(ContentRating::parse)
+    }
+
+    /// The ids of this item in external metadata databases. The newer agents
+    /// only list them when the item was retrieved with `includeGuids=1`,
+    /// which [`Server::item_by_id`](crate::Server::item_by_id) does.
+    fn external_ids(&self) -> Vec<ExternalId> {
+        let metadata = self.metadata();
+        let mut ids = Vec::new();
+        for id in metadata
+            .guid
+            .iter()
+            .chain(&metadata.guids)
+            .filter_map(Guid::external_id)
+        {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
 }
 
 /// Implements MetadataItem for the given struct which must contain `client`
@@ -97,9 +158,16 @@ where
     T: FromMetadata,
 {
     let wrapper: MediaContainerWrapper<MetadataMediaContainer> = client.get(path).json().await?;
+    Ok(container_items(client, wrapper.media_container))
+}
 
-    let media = wrapper
-        .media_container
+/// Creates the items from a container's metadata, filling in the library
+/// details that the server only sets on the container.
+fn container_items<T>(client: &HttpClient, container: MetadataMediaContainer) -> Vec<T>
+where
+    T: FromMetadata,
+{
+    container
         .metadata
         .into_iter()
         .map(|metadata| {
@@ -108,16 +176,81 @@ where
                 Metadata {
                     library_section_id: metadata
                         .library_section_id
-                        .or(wrapper.media_container.library_section_id),
+                        .or(container.library_section_id),
                     library_section_title: metadata
                         .library_section_title
-                        .or(wrapper.media_container.library_section_title.clone()),
+                        .or(container.library_section_title.clone()),
                     ..metadata
                 },
             )
         })
-        .collect();
-    Ok(media)
+        .collect()
+}
+
+/// Retrieves the folders and items at a level of a library's folder tree.
+#[tracing::instrument(level = "trace", skip(client))]
+async fn folder_contents(client: &HttpClient, path: &str) -> Result<FolderContents> {
+    let mut wrapper: MediaContainerWrapper<MetadataMediaContainer> =
+        client.get(path).json().await?;
+
+    let folders = std::mem::take(&mut wrapper.media_container.directories)
+        .into_iter()
+        .map(|directory| {
+            Ok(Folder {
+                client: client.clone(),
+                directory: serde_json::from_value(directory)?,
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(FolderContents {
+        folders,
+        items: container_items(client, wrapper.media_container),
+    })
+}
+
+/// Streams the metadata items found at the lookup key as they are received,
+/// without holding the whole response in memory.
+pub(crate) fn metadata_items_stream<T>(
+    client: &HttpClient,
+    path: &str,
+) -> impl FuturesStream<Item = Result<T>> + Send + 'static
+where
+    T: FromMetadata + Send + 'static,
+{
+    let client = client.clone();
+    let path = path.to_owned();
+
+    stream::once(async move {
+        let response = client
+            .get(path)
+            .header("Accept", "application/json")
+            .send()
+            .await?;
+        if response.status().as_http_status() != StatusCode::OK {
+            return Err(Error::from_response(response).await);
+        }
+
+        let mut reader = JsonArrayReader::new(response.into_body(), "Metadata");
+        let container: MediaContainerWrapper<MetadataMediaContainer> = reader.header().await?;
+        let container = container.media_container;
+
+        Ok(reader.into_stream().map_ok(move |metadata: Metadata| {
+            T::from_metadata(
+                client.clone(),
+                Metadata {
+                    library_section_id: metadata
+                        .library_section_id
+                        .or(container.library_section_id),
+                    library_section_title: metadata
+                        .library_section_title
+                        .or(container.library_section_title.clone()),
+                    ..metadata
+                },
+            )
+        }))
+    })
+    .try_flatten()
 }
 
 /// Attempts to retrieve the parent of this item.
@@ -134,6 +267,80 @@ where
     }
 }
 
+async fn grandparent<T, P>(item: &T, client: &HttpClient) -> Result<Option<P>>
+where
+    T: MetadataItem,
+    P: FromMetadata,
+{
+    if let Some(ref grandparent_key) = item.metadata().grand_parent.grandparent_key {
+        Ok(metadata_items(client, grandparent_key)
+            .await?
+            .into_iter()
+            .next())
+    } else {
+        Ok(None)
+    }
+}
+
+/// How many items are requested at once when paging through them.
+const PAGE_SIZE: u32 = 100;
+
+/// Streams the metadata items found at the lookup key, requesting them a page
+/// at a time.
+fn paged_items_stream<T>(
+    client: &HttpClient,
+    path: &str,
+) -> impl FuturesStream<Item = Result<T>> + Send + 'static
+where
+    T: FromMetadata + Send + 'static,
+{
+    let client = client.clone();
+    let path = path.to_owned();
+
+    stream::try_unfold(Some(0), move |offset| {
+        let client = client.clone();
+        let path = path.clone();
+        async move {
+            let Some(offset) = offset else {
+                return Ok::<_, Error>(None);
+            };
+
+            let query = Query::new()
+                .param("X-Plex-Container-Start", offset.to_string())
+                .param("X-Plex-Container-Size", PAGE_SIZE.to_string());
+            let wrapper: MediaContainerWrapper<MetadataMediaContainer> =
+                client.get(format!("{path}?{query}")).json().await?;
+            let total = wrapper.media_container.media_container.total_size;
+            let items: Vec<T> = container_items(&client, wrapper.media_container);
+
+            let received = items.len() as u32;
+            let next = offset + received;
+            let more = received > 0 && total.map_or(received == PAGE_SIZE, |total| next < total);
+
+            Ok(Some((
+                stream::iter(items.into_iter().map(Ok)),
+                more.then_some(next),
+            )))
+        }
+    })
+    .try_flatten()
+}
+
+/// Streams the metadata items from a pivot from a library.
+fn pivot_items_stream<M>(
+    client: &HttpClient,
+    directory: &ServerLibrary,
+    context: &str,
+) -> impl FuturesStream<Item = Result<M>> + Send + 'static
+where
+    M: FromMetadata + Send + 'static,
+{
+    match directory.pivots.iter().find(|p| p.context == context) {
+        Some(pivot) => metadata_items_stream(client, &pivot.key).left_stream(),
+        None => stream::empty().right_stream(),
+    }
+}
+
 /// Retrieves the metadata items from a pivot from a library.
 #[tracing::instrument(level = "trace", skip(client, directory), fields(directory.key = directory.key))]
 async fn pivot_items<M>(
@@ -151,6 +358,168 @@ where
     }
 }
 
+/// Filters applied when searching for subtitles.
+#[derive(Debug, Clone, Default)]
+pub struct SubtitleSearchOptions {
+    /// Only return subtitles for the hearing impaired (SDH).
+    pub hearing_impaired: bool,
+    /// Only return forced subtitles.
+    pub forced: bool,
+}
+
+/// Asks the server's subtitle agents for subtitles matching the item.
+#[tracing::instrument(level = "trace", skip(client))]
+async fn search_subtitles(
+    client: &HttpClient,
+    rating_key: &str,
+    language: &str,
+    options: SubtitleSearchOptions,
+) -> Result<Vec<SubtitleSearchResult>> {
+    let query = Query::new()
+        .param("language", language)
+        .param("hearingImpaired", bs(options.hearing_impaired))
+        .param("forced", bs(options.forced));
+    let path = format!(
+        "{}?{query}",
+        Endpoint::new(SERVER_LIBRARY_SUBTITLES).segment("ratingKey", rating_key)
+    );
+
+    let wrapper: MediaContainerWrapper<SubtitleSearchMediaContainer> =
+        client.get(path).timeout(None).json().await?;
+    Ok(wrapper.media_container.streams)
+}
+
+/// Makes the server download the subtitle and attach it to the item.
+#[tracing::instrument(level = "trace", skip(client, subtitle), fields(subtitle.key = subtitle.key))]
+async fn download_subtitle(
+    client: &HttpClient,
+    rating_key: &str,
+    subtitle: &SubtitleSearchResult,
+) -> Result {
+    let mut query = Query::new()
+        .param("key", &subtitle.key)
+        .param("codec", subtitle.codec.to_string())
+        .param(
+            "hearingImpaired",
+            bs(subtitle.hearing_impaired.unwrap_or_default()),
+        )
+        .param("forced", bs(subtitle.forced.unwrap_or_default()));
+    if let Some(ref language) = subtitle.language_code {
+        query = query.param("language", language);
+    }
+    if let Some(ref provider) = subtitle.provider_title {
+        query = query.param("providerTitle", provider);
+    }
+    let path = format!(
+        "{}?{query}",
+        Endpoint::new(SERVER_LIBRARY_SUBTITLES).segment("ratingKey", rating_key)
+    );
+
+    let mut response = client.put(path).send().await?;
+    match response.status().as_http_status() {
+        StatusCode::OK | StatusCode::NO_CONTENT => {
+            response.consume().await?;
+            Ok(())
+        }
+        _ => Err(Error::from_response(response).await),
+    }
+}
+
+/// Removes a subtitle stream which was previously downloaded or uploaded to
+/// the server. Subtitles embedded into the media file can't be removed.
+#[tracing::instrument(level = "trace", skip(client, stream), fields(stream.id = stream.id))]
+async fn delete_subtitle(client: &HttpClient, stream: &SubtitleStream) -> Result {
+    if stream.key.is_none() {
+        return Err(Error::SubtitleStreamNotExternal);
+    }
+
+    let mut response = client
+        .delete(Endpoint::new(SERVER_LIBRARY_STREAM).segment("streamId", &stream.id))
+        .send()
+        .await?;
+    match response.status().as_http_status() {
+        StatusCode::OK | StatusCode::NO_CONTENT => {
+            response.consume().await?;
+            Ok(())
+        }
+        _ => Err(Error::from_response(response).await),
+    }
+}
+
+/// Limits applied when looking for sonically similar items.
+#[derive(Debug, Clone, Default)]
+pub struct SonicSimilarityOptions {
+    /// The maximum number of items to return.
+    pub limit: Option<u32>,
+    /// The maximum sonic distance between the items, 0.0 being identical and
+    /// 1.0 being completely different.
+    pub max_distance: Option<f32>,
+}
+
+/// Retrieves items that sound similar to the given one. Requires the server
+/// to have performed sonic analysis of the music library.
+#[tracing::instrument(level = "trace", skip(client))]
+async fn sonically_similar<T>(
+    client: &HttpClient,
+    rating_key: &str,
+    options: SonicSimilarityOptions,
+) -> Result<Vec<T>>
+where
+    T: FromMetadata,
+{
+    let mut query = Query::new();
+    if let Some(limit) = options.limit {
+        query = query.param("limit", limit.to_string());
+    }
+    if let Some(max_distance) = options.max_distance {
+        query = query.param("maxDistance", max_distance.to_string());
+    }
+
+    let path = format!(
+        "{}?{query}",
+        Endpoint::new(SERVER_LIBRARY_NEAREST).segment("ratingKey", rating_key)
+    );
+    metadata_items(client, &path).await
+}
+
+/// Streams one of an item's assets (artwork, theme music etc.) into the
+/// writer. Like part downloads the configured timeout is ignored.
+#[tracing::instrument(level = "trace", skip(client, writer))]
+async fn download_asset<W>(client: &HttpClient, path: Option<&str>, writer: W) -> Result
+where
+    W: AsyncWrite + Unpin,
+{
+    let path = path.ok_or(Error::ItemNotFound)?;
+
+    let mut response = client.get(path).timeout(None).send().await?;
+    match response.status().as_http_status() {
+        StatusCode::OK => {
+            response.copy_to(writer).await?;
+            Ok(())
+        }
+        _ => Err(Error::from_response(response).await),
+    }
+}
+
+/// Generates a radio station seeded by the given item.
+#[tracing::instrument(level = "trace", skip(client))]
+async fn station_tracks(client: &HttpClient, rating_key: &str) -> Result<Vec<Track>> {
+    let query = Query::new()
+        .param("type", SearchType::Track.to_string())
+        .param("maxDegreesOfSeparation", "-1");
+    let path = format!(
+        "{}?{query}",
+        Endpoint::new(SERVER_LIBRARY_STATION)
+            .segment("ratingKey", rating_key)
+            .segment("stationId", Uuid::new_v4())
+    );
+    metadata_items(client, &path).await
+}
+
+fn tag_names(tags: &[Tag]) -> Vec<&str> {
+    tags.iter().map(|t| t.tag.as_str()).collect()
+}
+
 /// A single media format for a `MediaItem`.
 #[derive(Debug, Clone)]
 pub struct Media<'a, M: MediaItem> {
@@ -197,7 +566,7 @@ impl<'a, M: Transcodable + MediaItem + Sync> Transcodable for Media<'a, M> {
     async fn create_download_session(&self, options: M::Options) -> Result<TranscodeSession> {
         create_transcode_session(
             self.client,
-            self.parent_metadata,
+            &self.parent_metadata.key,
             Context::Static,
             Protocol::Http,
             Some(self.media_index),
@@ -215,7 +584,7 @@ impl<'a, M: Transcodable + MediaItem + Sync> Transcodable for Media<'a, M> {
     ) -> Result<TranscodeSession> {
         create_transcode_session(
             self.client,
-            self.parent_metadata,
+            &self.parent_metadata.key,
             Context::Streaming,
             protocol,
             Some(self.media_index),
@@ -265,9 +634,81 @@ impl<'a, M: MediaItem> Part<'a, M> {
         self.part.duration
     }
 
+    /// The path of the file on the server.
+    pub fn file(&self) -> Option<&str> {
+        self.part.file.as_deref()
+    }
+
+    pub fn container(&self) -> Option<ContainerFormat> {
+        self.part.container
+    }
+
+    /// Whether the server has generated seek thumbnails for the file.
+    pub fn has_indexes(&self) -> bool {
+        self.part
+            .indexes
+            .as_deref()
+            .is_some_and(|indexes| !indexes.is_empty())
+    }
+
+    /// Fetches the seek thumbnails the server generated for the file, for
+    /// showing previews while scrubbing. See [`Part::has_indexes`].
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn preview_thumbnails(&self) -> Result<PreviewThumbnails> {
+        let (Some(id), Some(index)) = (
+            self.part.id.as_deref(),
+            self.part
+                .indexes
+                .as_deref()
+                .and_then(|indexes| indexes.split(',').next())
+                .filter(|index| !index.is_empty()),
+        ) else {
+            return Err(Error::ItemNotFound);
+        };
+
+        let mut response = self
+            .client
+            .get(
+                Endpoint::new(SERVER_LIBRARY_PART_INDEX)
+                    .segment("partId", id)
+                    .segment("index", index),
+            )
+            .send()
+            .await?;
+
+        match response.status().as_http_status() {
+            StatusCode::OK => PreviewThumbnails::parse(response.bytes().await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// The overall bitrate in kbps, known once the server has analyzed the
+    /// file.
+    pub fn bitrate(&self) -> Option<u32> {
+        self.part.bitrate
+    }
+
+    /// The video, audio and subtitle streams within the file. Only included
+    /// when the item was loaded on its own, e.g. with
+    /// [`crate::Server::item_by_id`], not in library listings.
+    pub fn streams(&self) -> &[Stream] {
+        self.part.streams.as_deref().unwrap_or_default()
+    }
+
+    /// Whether the server found the file and could read it the last time it
+    /// checked. `None` when the server didn't check.
+    pub fn is_accessible(&self) -> Option<bool> {
+        match (self.part.exists, self.part.accessible) {
+            (Some(false), _) | (_, Some(false)) => Some(false),
+            (Some(true), Some(true)) => Some(true),
+            _ => None,
+        }
+    }
+
     /// Downloads the original media file for this part writing the data into
     /// the provided writer. A range of bytes within the file can be requested
-    /// allowing for resumable transfers.
+    /// allowing for resumable transfers. An empty range, e.g. one starting
+    /// past the end of the file, downloads nothing.
     ///
     /// Configured timeout value will be ignored during downloading.
     #[tracing::instrument(level = "debug", skip_all)]
@@ -276,35 +717,41 @@ impl<'a, M: MediaItem> Part<'a, M> {
         W: AsyncWrite + Unpin,
         R: RangeBounds<u64>,
     {
-        let path = format!("{}?download=1", self.part.key.as_ref().unwrap());
-
-        let start = match range.start_bound() {
-            std::ops::Bound::Included(v) => *v,
-            std::ops::Bound::Excluded(v) => v + 1,
-            std::ops::Bound::Unbounded => 0,
-        };
-
-        let end = match range.end_bound() {
-            std::ops::Bound::Included(v) => Some(*v),
-            std::ops::Bound::Excluded(v) => Some(v - 1),
-            std::ops::Bound::Unbounded => None,
-        };
-
-        let mut builder = self.client.get(path).timeout(None);
-        if start != 0 || (end.is_some() && end != self.part.size) {
-            // We're requesting part of the file.
-            let end = end.map(|v| v.to_string()).unwrap_or_default();
-            builder = builder.header("Range", format!("bytes={start}-{end}"))
-        }
+        self.download_with(writer, range, DownloadOptions::default(), ())
+            .await
+    }
 
-        let mut response = builder.send().await?;
-        match response.status().as_http_status() {
-            StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
-                response.copy_to(writer).await?;
-                Ok(())
-            }
-            _ => Err(crate::Error::from_response(response).await),
+    /// Same as [`Part::download`] but allows controlling how interrupted
+    /// transfers are resumed and whether several connections are used (see
+    /// [`DownloadOptions`]). Progress is reported to the observer, counted
+    /// from the start of the requested range.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn download_with<W, R, O>(
+        &self,
+        writer: W,
+        range: R,
+        options: DownloadOptions,
+        observer: O,
+    ) -> Result
+    where
+        W: AsyncWrite + Unpin,
+        R: RangeBounds<u64>,
+        O: ProgressObserver,
+    {
+        let path = format!("{}?download=1", self.part.key.as_ref().unwrap());
+        let (start, end) = byte_range(range);
+
+        Download {
+            client: self.client,
+            path: &path,
+            start,
+            end,
+            size: self.part.size,
+            timeout: None,
+            transcoding: false,
         }
+        .run(writer, options, observer)
+        .await
     }
 
     /// The internal metadata for the media.
@@ -320,7 +767,7 @@ impl<'a, M: Transcodable + MediaItem + Sync> Transcodable for Part<'a, M> {
     async fn create_download_session(&self, options: M::Options) -> Result<TranscodeSession> {
         create_transcode_session(
             self.client,
-            self.parent_metadata,
+            &self.parent_metadata.key,
             Context::Static,
             Protocol::Http,
             Some(self.media_index),
@@ -338,7 +785,7 @@ impl<'a, M: Transcodable + MediaItem + Sync> Transcodable for Part<'a, M> {
     ) -> Result<TranscodeSession> {
         create_transcode_session(
             self.client,
-            self.parent_metadata,
+            &self.parent_metadata.key,
             Context::Streaming,
             protocol,
             Some(self.media_index),
@@ -394,6 +841,18 @@ pub trait MediaItem: MetadataItem + Sized {
             Vec::new()
         }
     }
+
+    /// The paths on the server of all the files making up this item, across
+    /// all of its media versions.
+    fn files(&self) -> Vec<&str> {
+        self.metadata()
+            .media
+            .iter()
+            .flatten()
+            .flat_map(|media| &media.parts)
+            .filter_map(|part| part.file.as_deref())
+            .collect()
+    }
 }
 
 pub trait Transcodable {
@@ -432,6 +891,88 @@ pub trait Transcodable {
         options: Self::Options,
         download_queue: Option<&DownloadQueue>,
     ) -> impl Future<Output = Result<QueueItem>> + Send;
+
+    /// Starts playback the way players do: asks the server whether the
+    /// original file can be played with the given options and falls back to
+    /// a streaming transcode using the protocol when it can't.
+    fn stream(
+        &self,
+        protocol: Protocol,
+        options: Self::Options,
+    ) -> impl Future<Output = Result<Playback>> + Send
+    where
+        Self: MediaItem + Sync,
+    {
+        async move {
+            match self.create_streaming_session(protocol, options).await {
+                Ok(session) => Ok(Playback::Transcode(Box::new(session))),
+                Err(Error::TranscodeRefused) => {
+                    let key = self
+                        .metadata()
+                        .media
+                        .iter()
+                        .flatten()
+                        .flat_map(|media| &media.parts)
+                        .find_map(|part| part.key.as_deref())
+                        .ok_or(Error::ItemNotFound)?;
+                    Ok(Playback::DirectPlay(authenticated_url(
+                        self.client(),
+                        key,
+                        Query::new(),
+                    )?))
+                }
+                Err(err) => Err(err),
+            }
+        }
+    }
+}
+
+/// Video items whose audio can be transcoded on its own, for listening to
+/// music videos or video podcasts without fetching the video.
+pub trait AudioTranscodable: MediaItem + Sync {
+    /// Starts an offline transcode of just the audio using the provided
+    /// music options. The original file is never offered as is since it
+    /// contains the video too.
+    fn create_audio_download_session(
+        &self,
+        options: MusicTranscodeOptions,
+    ) -> impl Future<Output = Result<TranscodeSession>> + Send {
+        create_transcode_session(
+            self.client(),
+            &self.metadata().key,
+            Context::Static,
+            Protocol::Http,
+            None,
+            None,
+            AudioOnly(options),
+        )
+    }
+
+    /// Starts a streaming transcode of just the audio using the streaming
+    /// protocol and provided music options.
+    fn create_audio_streaming_session(
+        &self,
+        protocol: Protocol,
+        options: MusicTranscodeOptions,
+    ) -> impl Future<Output = Result<TranscodeSession>> + Send {
+        create_transcode_session(
+            self.client(),
+            &self.metadata().key,
+            Context::Streaming,
+            protocol,
+            None,
+            None,
+            AudioOnly(options),
+        )
+    }
+}
+
+/// How an item gets played, see [`Transcodable::stream`].
+pub enum Playback {
+    /// The original file can be played as is from the URL, which includes
+    /// the authentication token.
+    DirectPlay(Uri),
+    Transcode(Box<TranscodeSession>),
 }
 
 /// A video that can be included in a video playlist.
@@ -453,6 +994,7 @@ impl FromMetadata for Video {
 }
 
 impl MediaItem for Video {}
+impl AudioTranscodable for Video {}
 impl Transcodable for Video {
     type Options = VideoTranscodeOptions;
 
@@ -460,7 +1002,7 @@ impl Transcodable for Video {
     async fn create_download_session(&self, options: Self::Options) -> Result<TranscodeSession> {
         create_transcode_session(
             self.client(),
-            self.metadata(),
+            &self.metadata().key,
             Context::Static,
             Protocol::Http,
             None,
@@ -478,7 +1020,7 @@ impl Transcodable for Video {
     ) -> Result<TranscodeSession> {
         create_transcode_session(
             self.client(),
-            self.metadata(),
+            &self.metadata().key,
             Context::Streaming,
             protocol,
             None,
@@ -562,6 +1104,75 @@ where
     }
 }
 
+impl<M> Collection<M> {
+    /// Whether the collection holds the items matching a filter, which the
+    /// server keeps up to date, rather than a fixed list of items.
+    pub fn is_smart(&self) -> bool {
+        self.metadata.smart.unwrap_or(false)
+    }
+
+    /// The filter of a smart collection, in the form of the query of a
+    /// library listing, e.g. `genre=5&year>>=2000`.
+    pub fn filter(&self) -> Option<String> {
+        if !self.is_smart() {
+            return None;
+        }
+        let (_, query) = self.metadata.content.as_deref()?.split_once('?')?;
+        Some(
+            query
+                .split('&')
+                .filter(|param| !param.starts_with("type="))
+                .collect::<Vec<_>>()
+                .join("&"),
+        )
+    }
+
+    /// Replaces the filter of a smart collection, see
+    /// [`Server::set_smart_collection_filter`](crate::Server::set_smart_collection_filter).
+    pub(crate) async fn set_filter(&self, machine_identifier: &str, filter: &str) -> Result {
+        let search_type = match &self.metadata.metadata_type {
+            Some(MetadataType::Collection(CollectionMetadataSubtype::Movie)) => SearchType::Movie,
+            Some(MetadataType::Collection(CollectionMetadataSubtype::Show)) => SearchType::Show,
+            _ => return Err(Error::SmartCollectionNotSupported),
+        };
+        let section_id = self
+            .metadata
+            .library_section_id
+            .ok_or(Error::ItemNotFound)?;
+
+        self.client
+            .put(
+                Endpoint::new(SERVER_COLLECTION_ITEMS)
+                    .segment("collectionId", &self.metadata.rating_key)
+                    .query(
+                        "uri",
+                        smart_filter_uri(machine_identifier, section_id, search_type, filter),
+                    ),
+            )
+            .consume()
+            .await
+    }
+}
+
+/// The URI the server expects for the filter of a smart collection.
+fn smart_filter_uri(
+    machine_identifier: &str,
+    section_id: impl Display,
+    search_type: SearchType,
+    filter: &str,
+) -> String {
+    let mut uri = format!(
+        "server://{machine_identifier}/{LIBRARY_PROVIDER}{}?type={}",
+        Endpoint::new(SERVER_LIBRARY_SECTION_ALL).segment("sectionId", section_id),
+        search_type as u16
+    );
+    if !filter.is_empty() {
+        uri.push('&');
+        uri.push_str(filter);
+    }
+    uri
+}
+
 #[derive(Debug, Clone)]
 pub struct Movie {
     client: HttpClient,
@@ -572,6 +1183,7 @@ derive_from_metadata!(Movie);
 derive_metadata_item!(Movie);
 
 impl MediaItem for Movie {}
+impl AudioTranscodable for Movie {}
 impl Transcodable for Movie {
     type Options = VideoTranscodeOptions;
 
@@ -579,7 +1191,7 @@ impl Transcodable for Movie {
     async fn create_download_session(&self, options: Self::Options) -> Result<TranscodeSession> {
         create_transcode_session(
             self.client(),
-            self.metadata(),
+            &self.metadata().key,
             Context::Static,
             Protocol::Http,
             None,
@@ -597,7 +1209,7 @@ impl Transcodable for Movie {
     ) -> Result<TranscodeSession> {
         create_transcode_session(
             self.client(),
-            self.metadata(),
+            &self.metadata().key,
             Context::Streaming,
             protocol,
             None,
@@ -623,6 +1235,33 @@ impl Transcodable for Movie {
     }
 }
 
+impl Movie {
+    /// Searches for subtitles in the given language (ISO 639-1 code) using
+    /// the server's subtitle agents. The search is performed by the server
+    /// and can take a while.
+    #[tracing::instrument(level = "debug", skip(self), fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn search_subtitles(
+        &self,
+        language: &str,
+        options: SubtitleSearchOptions,
+    ) -> Result<Vec<SubtitleSearchResult>> {
+        search_subtitles(&self.client, &self.metadata.rating_key, language, options).await
+    }
+
+    /// Downloads a subtitle found with `search_subtitles` and adds it to this
+    /// movie. Refetch the item to see the new subtitle stream.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn download_subtitle(&self, subtitle: &SubtitleSearchResult) -> Result {
+        download_subtitle(&self.client, &self.metadata.rating_key, subtitle).await
+    }
+
+    /// Deletes a previously downloaded subtitle stream from this movie.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn delete_subtitle(&self, stream: &SubtitleStream) -> Result {
+        delete_subtitle(&self.client, stream).await
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Show {
     client: HttpClient,
@@ -639,12 +1278,92 @@ impl Show {
         metadata_items(&self.client, &self.metadata.key).await
     }
 
+    /// Retrieves the season with the given number, `0` usually being the
+    /// specials.
+    #[tracing::instrument(level = "debug", skip(self), fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn season_by_number(&self, number: u32) -> Result<Option<Season>> {
+        Ok(self
+            .seasons()
+            .await?
+            .into_iter()
+            .find(|season| season.season_number() == Some(number)))
+    }
+
     /// Retrieves all of the episodes in all seasons of this show.
     #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.rating_key = self.metadata.rating_key))]
     pub async fn episodes(&self) -> Result<Vec<Episode>> {
         let path = format!("/library/metadata/{}/allLeaves", self.metadata.rating_key);
         metadata_items(&self.client, &path).await
     }
+
+    /// Streams all of the episodes in all seasons of this show, fetching
+    /// them from the server a page at a time. Better suited than
+    /// [`Show::episodes`] for long running shows.
+    pub fn all_episodes(&self) -> impl FuturesStream<Item = Result<Episode>> + Send + 'static {
+        let path = format!("/library/metadata/{}/allLeaves", self.metadata.rating_key);
+        paged_items_stream(&self.client, &path)
+    }
+
+    /// The path to the show's theme music.
+    pub fn theme(&self) -> Option<&str> {
+        self.metadata.theme.as_deref()
+    }
+
+    /// The path to the show's banner image.
+    pub fn banner(&self) -> Option<&str> {
+        self.metadata.banner.as_deref()
+    }
+
+    /// The path to the show's clear logo, a transparent image of the title.
+    pub fn clear_logo(&self) -> Option<&str> {
+        self.metadata
+            .image
+            .iter()
+            .flatten()
+            .find(|image| image.image_type == "clearLogo")
+            .map(|image| image.url.as_str())
+    }
+
+    /// Downloads the theme music writing the audio into the provided writer.
+    /// Fails with [`Error::ItemNotFound`] if the show has no theme.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn download_theme<W>(&self, writer: W) -> Result
+    where
+        W: AsyncWrite + Unpin,
+    {
+        download_asset(&self.client, self.theme(), writer).await
+    }
+
+    /// Downloads the original banner image into the provided writer.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn download_banner<W>(&self, writer: W) -> Result
+    where
+        W: AsyncWrite + Unpin,
+    {
+        download_asset(&self.client, self.banner(), writer).await
+    }
+
+    /// Downloads the original clear logo image into the provided writer.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn download_clear_logo<W>(&self, writer: W) -> Result
+    where
+        W: AsyncWrite + Unpin,
+    {
+        download_asset(&self.client, self.clear_logo(), writer).await
+    }
+
+    /// The show's advanced settings, such as the episode order and when
+    /// watched episodes get deleted.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn settings(&self) -> Result<ItemSettings> {
+        ItemSettings::load(&self.client, &self.metadata.rating_key).await
+    }
+
+    /// Stores the settings which are not `None`.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn set_settings(&self, settings: &ItemSettings) -> Result {
+        settings.save(&self.client, &self.metadata.rating_key).await
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -667,23 +1386,66 @@ impl Season {
         metadata_items(&self.client, &self.metadata.key).await
     }
 
+    /// Retrieves the episode with the given number within this season.
+    #[tracing::instrument(level = "debug", skip(self), fields(self.metadata.key = self.metadata.key))]
+    pub async fn episode_by_number(&self, number: u32) -> Result<Option<Episode>> {
+        Ok(self
+            .episodes()
+            .await?
+            .into_iter()
+            .find(|episode| episode.episode_number() == Some(number)))
+    }
+
     /// Retrieves the show that this season is from.
     #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.key = self.metadata.key))]
     pub async fn show(&self) -> Result<Option<Show>> {
         parent(self, &self.client).await
     }
-}
-
-#[derive(Debug, Clone)]
-pub struct Episode {
-    client: HttpClient,
-    metadata: Metadata,
-}
+
+    /// The season's advanced settings, overriding the ones of its show.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn settings(&self) -> Result<ItemSettings> {
+        ItemSettings::load(&self.client, &self.metadata.rating_key).await
+    }
+
+    /// Stores the settings which are not `None`.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn set_settings(&self, settings: &ItemSettings) -> Result {
+        settings.save(&self.client, &self.metadata.rating_key).await
+    }
+
+    /// Queues every episode in this season for download using the provided
+    /// download queue (or this client's queue if none is given). See
+    /// [`DownloadQueue::add_items`].
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.key = self.metadata.key))]
+    pub async fn queue_download_all(
+        &self,
+        options: VideoTranscodeOptions,
+        download_queue: Option<&DownloadQueue>,
+    ) -> Result<Vec<QueueItem>> {
+        let episodes = self.episodes().await?;
+
+        let queue = if let Some(q) = download_queue {
+            q.clone()
+        } else {
+            DownloadQueue::get_or_create(self.client.clone()).await?
+        };
+
+        queue.add_items(&episodes, options).await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Episode {
+    client: HttpClient,
+    metadata: Metadata,
+}
 
 derive_from_metadata!(Episode);
 derive_metadata_item!(Episode);
 
 impl MediaItem for Episode {}
+impl AudioTranscodable for Episode {}
 impl Transcodable for Episode {
     type Options = VideoTranscodeOptions;
 
@@ -691,7 +1453,7 @@ impl Transcodable for Episode {
     async fn create_download_session(&self, options: Self::Options) -> Result<TranscodeSession> {
         create_transcode_session(
             self.client(),
-            self.metadata(),
+            &self.metadata().key,
             Context::Static,
             Protocol::Http,
             None,
@@ -709,7 +1471,7 @@ impl Transcodable for Episode {
     ) -> Result<TranscodeSession> {
         create_transcode_session(
             self.client(),
-            self.metadata(),
+            &self.metadata().key,
             Context::Streaming,
             protocol,
             None,
@@ -751,6 +1513,37 @@ impl Episode {
     pub async fn season(&self) -> Result<Option<Season>> {
         parent(self, &self.client).await
     }
+
+    /// Retrieves the show that this episode is from.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.key = self.metadata.key))]
+    pub async fn show(&self) -> Result<Option<Show>> {
+        grandparent(self, &self.client).await
+    }
+
+    /// Searches for subtitles in the given language (ISO 639-1 code) using
+    /// the server's subtitle agents. The search is performed by the server
+    /// and can take a while.
+    #[tracing::instrument(level = "debug", skip(self), fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn search_subtitles(
+        &self,
+        language: &str,
+        options: SubtitleSearchOptions,
+    ) -> Result<Vec<SubtitleSearchResult>> {
+        search_subtitles(&self.client, &self.metadata.rating_key, language, options).await
+    }
+
+    /// Downloads a subtitle found with `search_subtitles` and adds it to this
+    /// episode. Refetch the item to see the new subtitle stream.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn download_subtitle(&self, subtitle: &SubtitleSearchResult) -> Result {
+        download_subtitle(&self.client, &self.metadata.rating_key, subtitle).await
+    }
+
+    /// Deletes a previously downloaded subtitle stream from this episode.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn delete_subtitle(&self, stream: &SubtitleStream) -> Result {
+        delete_subtitle(&self.client, stream).await
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -762,6 +1555,9 @@ pub struct Artist {
 derive_from_metadata!(Artist);
 derive_metadata_item!(Artist);
 
+/// How many tracks [`Artist::popular_tracks`] returns at most.
+const POPULAR_TRACKS: u32 = 100;
+
 impl Artist {
     /// Retrieves all of the fully-featured studio albums (skipping Lives, EPs, etc.) by this artist.
     #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.key = self.metadata.key))]
@@ -785,6 +1581,70 @@ impl Artist {
         );
         metadata_items(&self.client, &albums_search_path).await
     }
+
+    /// Retrieves all of the tracks on all albums by this artist.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn tracks(&self) -> Result<Vec<Track>> {
+        let path = format!("/library/metadata/{}/allLeaves", self.metadata.rating_key);
+        metadata_items(&self.client, &path).await
+    }
+
+    /// Retrieves the artist's most played tracks, most popular first. Live
+    /// and compilation albums are left out and each song is listed once.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn popular_tracks(&self) -> Result<Vec<Track>> {
+        let section_id = match &self.metadata.library_section_id {
+            Some(id) => id,
+            None => return Err(Error::UnexpectedError),
+        };
+
+        let query = Query::new()
+            .param("type", SearchType::Track.to_string())
+            .param("artist.id", &self.metadata.rating_key)
+            .param("album.subformat!", "Compilation,Live")
+            .param("group", "title")
+            .param("ratingCount>>", "0")
+            .param("sort", "ratingCount:desc")
+            .param("X-Plex-Container-Start", "0")
+            .param("X-Plex-Container-Size", POPULAR_TRACKS.to_string());
+        let path = format!(
+            "{}?{query}",
+            Endpoint::new(SERVER_LIBRARY_SECTION_ALL).segment("sectionId", section_id)
+        );
+        metadata_items(&self.client, &path).await
+    }
+
+    /// Retrieves the artists the metadata agent considers similar to this one.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn similar(&self) -> Result<Vec<Artist>> {
+        let path = Endpoint::new(SERVER_LIBRARY_SIMILAR)
+            .segment("ratingKey", &self.metadata.rating_key)
+            .to_string();
+        metadata_items(&self.client, &path).await
+    }
+
+    /// Retrieves artists that sound similar to this one.
+    #[tracing::instrument(level = "debug", skip(self), fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn sonically_similar(&self, options: SonicSimilarityOptions) -> Result<Vec<Artist>> {
+        sonically_similar(&self.client, &self.metadata.rating_key, options).await
+    }
+
+    /// Generates an artist radio: a list of tracks by this and related
+    /// artists, suitable for queueing up for playback.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn radio(&self) -> Result<Vec<Track>> {
+        station_tracks(&self.client, &self.metadata.rating_key).await
+    }
+
+    /// Returns the moods assigned to this artist.
+    pub fn moods(&self) -> Vec<&str> {
+        tag_names(&self.metadata.moods)
+    }
+
+    /// Returns the styles assigned to this artist.
+    pub fn styles(&self) -> Vec<&str> {
+        tag_names(&self.metadata.styles)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -808,6 +1668,25 @@ impl MusicAlbum {
     pub async fn artist(&self) -> Result<Option<Artist>> {
         parent(self, &self.client).await
     }
+
+    /// Retrieves albums that sound similar to this one.
+    #[tracing::instrument(level = "debug", skip(self), fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn sonically_similar(
+        &self,
+        options: SonicSimilarityOptions,
+    ) -> Result<Vec<MusicAlbum>> {
+        sonically_similar(&self.client, &self.metadata.rating_key, options).await
+    }
+
+    /// Returns the moods assigned to this album.
+    pub fn moods(&self) -> Vec<&str> {
+        tag_names(&self.metadata.moods)
+    }
+
+    /// Returns the styles assigned to this album.
+    pub fn styles(&self) -> Vec<&str> {
+        tag_names(&self.metadata.styles)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -827,7 +1706,7 @@ impl Transcodable for Track {
     async fn create_download_session(&self, options: Self::Options) -> Result<TranscodeSession> {
         create_transcode_session(
             self.client(),
-            self.metadata(),
+            &self.metadata().key,
             Context::Static,
             Protocol::Http,
             None,
@@ -845,7 +1724,7 @@ impl Transcodable for Track {
     ) -> Result<TranscodeSession> {
         create_transcode_session(
             self.client(),
-            self.metadata(),
+            &self.metadata().key,
             Context::Streaming,
             protocol,
             None,
@@ -882,6 +1761,120 @@ impl Track {
     pub async fn album(&self) -> Result<Option<MusicAlbum>> {
         parent(self, &self.client).await
     }
+
+    /// Retrieves the artist of this track's album.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.key = self.metadata.key))]
+    pub async fn artist(&self) -> Result<Option<Artist>> {
+        grandparent(self, &self.client).await
+    }
+
+    /// Retrieves tracks that sound similar to this one.
+    #[tracing::instrument(level = "debug", skip(self), fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn sonically_similar(&self, options: SonicSimilarityOptions) -> Result<Vec<Track>> {
+        sonically_similar(&self.client, &self.metadata.rating_key, options).await
+    }
+
+    /// Returns the lyric streams attached to this track, both sidecar files and
+    /// the ones provided by the lyrics agents.
+    pub fn lyric_streams(&self) -> Vec<&LyricStream> {
+        self.metadata
+            .media
+            .iter()
+            .flatten()
+            .flat_map(|media| media.parts.iter())
+            .flat_map(|part| part.streams.iter().flatten())
+            .filter_map(|stream| match stream {
+                Stream::Lyric(lyric) => Some(lyric),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The track's main audio stream.
+    fn audio_stream(&self) -> Option<&AudioStream> {
+        self.metadata
+            .media
+            .iter()
+            .flatten()
+            .flat_map(|media| media.parts.iter())
+            .flat_map(|part| part.streams.iter().flatten())
+            .find_map(|stream| match stream {
+                Stream::Audio(audio) => Some(audio),
+                _ => None,
+            })
+    }
+
+    /// The results of the loudness analysis, used for volume levelling.
+    /// Only included when the track was loaded on its own, e.g. with
+    /// [`crate::Server::item_by_id`].
+    pub fn loudness(&self) -> Option<Loudness> {
+        self.audio_stream().and_then(AudioStream::loudness)
+    }
+
+    /// Whether the server has performed sonic analysis of the track, which
+    /// is needed for [`Track::sonically_similar`] and radios.
+    pub fn has_sonic_analysis(&self) -> bool {
+        self.metadata.music_analysis_version.is_some()
+    }
+
+    /// Retrieves the loudness over the length of the track in LUFS, e.g. to
+    /// draw a waveform. The server averages the measurements down to the
+    /// requested number of samples.
+    #[tracing::instrument(level = "debug", skip(self), fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn loudness_levels(&self, samples: u32) -> Result<Vec<f32>> {
+        let stream = self.audio_stream().ok_or(Error::ItemNotFound)?;
+
+        let wrapper: MediaContainerWrapper<LevelsMediaContainer> = self
+            .client
+            .get(format!(
+                "{}?{}",
+                Endpoint::new(SERVER_LIBRARY_STREAM_LEVELS).segment("streamId", &stream.id),
+                Query::new().param("subsample", samples.to_string())
+            ))
+            .json()
+            .await?;
+        Ok(wrapper
+            .media_container
+            .levels
+            .into_iter()
+            .map(|level| level.value)
+            .collect())
+    }
+
+    /// Checks if any lyrics are available for this track.
+    pub fn has_lyrics(&self) -> bool {
+        !self.lyric_streams().is_empty()
+    }
+
+    /// Retrieves the lyrics body for one of the track's lyric streams. Timed
+    /// lyrics are returned in LRC format, the rest are plain text.
+    #[tracing::instrument(level = "debug", skip_all, fields(stream.id = stream.id))]
+    pub async fn lyrics(&self, stream: &LyricStream) -> Result<String> {
+        let key = stream.key.as_ref().ok_or(Error::ItemNotFound)?;
+
+        let mut response = self.client.get(key.as_str()).send().await?;
+        match response.status().as_http_status() {
+            StatusCode::OK => Ok(response.text().await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Generates a track radio: a list of tracks similar to this one,
+    /// suitable for queueing up for playback.
+    #[tracing::instrument(level = "debug", skip_all, fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn radio(&self) -> Result<Vec<Track>> {
+        station_tracks(&self.client, &self.metadata.rating_key).await
+    }
+
+    /// Returns the moods assigned to this track.
+    pub fn moods(&self) -> Vec<&str> {
+        tag_names(&self.metadata.moods)
+    }
+
+    /// Returns the styles assigned to this track.
+    pub fn styles(&self) -> Vec<&str> {
+        tag_names(&self.metadata.styles)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -901,6 +1894,41 @@ impl Photo {
     pub async fn album(&self) -> Result<Option<PhotoAlbum>> {
         parent(self, &self.client).await
     }
+
+    /// Returns the tags (places, people, etc.) assigned to this photo.
+    pub fn tags(&self) -> Vec<&str> {
+        tag_names(&self.metadata.tags)
+    }
+
+    /// Returns the date the photo was taken, according to its EXIF data.
+    pub fn taken_at(&self) -> Option<Date> {
+        self.metadata.originally_available_at
+    }
+
+    /// Requests a scaled version of this photo from the server, writing it to
+    /// the passed writer as a JPEG. The aspect ratio is always maintained.
+    #[tracing::instrument(level = "debug", skip(self, writer), fields(self.metadata.rating_key = self.metadata.rating_key))]
+    pub async fn transcode<W>(
+        &self,
+        width: u32,
+        height: u32,
+        options: ArtTranscodeOptions,
+        writer: W,
+    ) -> Result
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let key = self
+            .metadata
+            .media
+            .iter()
+            .flatten()
+            .flat_map(|media| media.parts.iter())
+            .find_map(|part| part.key.as_deref())
+            .ok_or(Error::ItemNotFound)?;
+
+        transcode_artwork(&self.client, key, width, height, options, writer).await
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1040,6 +2068,12 @@ impl MovieLibrary {
         pivot_items(&self.client, &self.directory, "content.library").await
     }
 
+    /// Streams all of the movies in this library as they are received, which
+    /// keeps memory use low for huge libraries.
+    pub fn movies_stream(&self) -> impl FuturesStream<Item = Result<Movie>> + Send + 'static {
+        pivot_items_stream(&self.client, &self.directory, "content.library")
+    }
+
     /// Retrieves all of the collections in this library.
     #[tracing::instrument(level = "debug", skip_all)]
     pub async fn collections(&self) -> Result<Vec<Collection<Movie>>> {
@@ -1071,6 +2105,11 @@ impl TVLibrary {
         pivot_items(&self.client, &self.directory, "content.library").await
     }
 
+    /// Streams all of the shows in this library as they are received.
+    pub fn shows_stream(&self) -> impl FuturesStream<Item = Result<Show>> + Send + 'static {
+        pivot_items_stream(&self.client, &self.directory, "content.library")
+    }
+
     /// Retrieves all of the collections in this library.
     #[tracing::instrument(level = "debug", skip_all)]
     pub async fn collections(&self) -> Result<Vec<Collection<Show>>> {
@@ -1102,6 +2141,11 @@ impl MusicLibrary {
         pivot_items(&self.client, &self.directory, "content.library").await
     }
 
+    /// Streams all of the artists in this library as they are received.
+    pub fn artists_stream(&self) -> impl FuturesStream<Item = Result<Artist>> + Send + 'static {
+        pivot_items_stream(&self.client, &self.directory, "content.library")
+    }
+
     /// Retrieves all of the playlists containing tracks from this library.
     #[tracing::instrument(level = "debug", skip_all)]
     pub async fn playlists(&self) -> Result<Vec<Playlist<Track>>> {
@@ -1127,6 +2171,30 @@ impl PhotoLibrary {
         pivot_items(&self.client, &self.directory, "content.library").await
     }
 
+    /// Retrieves all of the photos in this library, regardless of the album
+    /// they are in.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn photos(&self) -> Result<Vec<Photo>> {
+        let path = format!(
+            "/library/sections/{}/all?type={}",
+            self.directory.id,
+            SearchType::Photo
+        );
+        metadata_items(&self.client, &path).await
+    }
+
+    /// Retrieves the photo timeline: all of the photos in this library
+    /// starting with the most recently taken ones.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn timeline(&self) -> Result<Vec<Photo>> {
+        let path = format!(
+            "/library/sections/{}/all?type={}&sort=originallyAvailableAt:desc",
+            self.directory.id,
+            SearchType::Photo
+        );
+        metadata_items(&self.client, &path).await
+    }
+
     /// Retrieves all of the playlists containing photos from this library.
     #[tracing::instrument(level = "debug", skip_all)]
     pub async fn playlists(&self) -> Result<Vec<Playlist<Photo>>> {
@@ -1134,6 +2202,92 @@ impl PhotoLibrary {
     }
 }
 
+/// A folder on disk within a library, see [`Library::folders`].
+#[derive(Debug, Clone)]
+pub struct Folder {
+    client: HttpClient,
+    directory: FolderDirectory,
+}
+
+impl Folder {
+    /// The folder's name, or its full path for the library's root folders.
+    pub fn title(&self) -> &str {
+        &self.directory.title
+    }
+
+    /// Lists the folders and items within this folder.
+    #[tracing::instrument(level = "debug", skip(self), fields(folder.title = self.title()))]
+    pub async fn contents(&self) -> Result<FolderContents> {
+        folder_contents(&self.client, &self.directory.key).await
+    }
+}
+
+/// What a level of a library's folder tree contains.
+#[derive(Debug, Clone, Default)]
+pub struct FolderContents {
+    pub folders: Vec<Folder>,
+    /// The items whose media files are directly within the folder.
+    pub items: Vec<Item>,
+}
+
+/// The kinds of tags that can be edited on many items at once, see
+/// [`Library::add_tags`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagField {
+    Label,
+    Genre,
+    Collection,
+}
+
+impl TagField {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Label => "label",
+            Self::Genre => "genre",
+            Self::Collection => "collection",
+        }
+    }
+}
+
+/// The type the server expects when editing items in a section.
+fn edit_type(metadata_type: &MetadataType) -> Option<SearchType> {
+    Some(match metadata_type {
+        MetadataType::Movie => SearchType::Movie,
+        MetadataType::Show => SearchType::Show,
+        MetadataType::Season => SearchType::Season,
+        MetadataType::Episode => SearchType::Episode,
+        MetadataType::Artist => SearchType::Artist,
+        MetadataType::MusicAlbum => SearchType::Album,
+        MetadataType::Track => SearchType::Track,
+        MetadataType::Photo => SearchType::Photo,
+        MetadataType::Clip(_) => SearchType::Clip,
+        MetadataType::Collection(_) => SearchType::Collection,
+        _ => return None,
+    })
+}
+
+/// Counts and sizes for a whole library, see [`Library::totals`].
+#[derive(Debug, Clone, Default)]
+pub struct LibraryTotals {
+    /// Number of items of each type, e.g. shows, seasons and episodes for a
+    /// TV library.
+    pub counts: HashMap<SearchType, u32>,
+    /// Total size of the media files in bytes, if the server reported it.
+    pub size: Option<u64>,
+    /// Total duration of the media, if the server reported it.
+    pub duration: Option<Duration>,
+}
+
+impl LibraryTotals {
+    /// The number of items of the given type, zero for types the library
+    /// doesn't contain.
+    pub fn count(&self, item_type: SearchType) -> u32 {
+        self.counts.get(&item_type).copied().unwrap_or_default()
+    }
+}
+
+const PREVIEW_THUMBNAILS_SETTING: &str = "enableBIFGeneration";
+
 #[derive(Debug, Clone)]
 pub enum Library {
     Movie(MovieLibrary),
@@ -1163,6 +2317,16 @@ impl Library {
         }
     }
 
+    fn client(&self) -> &HttpClient {
+        match self {
+            Self::Movie(l) => &l.client,
+            Self::TV(l) => &l.client,
+            Self::Music(l) => &l.client,
+            Self::Video(l) => &l.client,
+            Self::Photo(l) => &l.client,
+        }
+    }
+
     fn directory(&self) -> &ServerLibrary {
         match self {
             Self::Movie(l) => &l.directory,
@@ -1173,6 +2337,43 @@ impl Library {
         }
     }
 
+    /// Creates a smart collection with the library's movies or shows that
+    /// match the filter, see
+    /// [`Server::create_smart_collection`](crate::Server::create_smart_collection).
+    pub(crate) async fn create_smart_collection(
+        &self,
+        machine_identifier: &str,
+        title: &str,
+        filter: &str,
+    ) -> Result<Item> {
+        let search_type = match self {
+            Self::Movie(_) => SearchType::Movie,
+            Self::TV(_) => SearchType::Show,
+            _ => return Err(Error::SmartCollectionNotSupported),
+        };
+
+        let wrapper: MediaContainerWrapper<MetadataMediaContainer> = self
+            .client()
+            .post(
+                Endpoint::new(SERVER_COLLECTIONS)
+                    .query("type", (search_type as u16).to_string())
+                    .query("title", title)
+                    .query("smart", "1")
+                    .query("sectionId", self.id())
+                    .query(
+                        "uri",
+                        smart_filter_uri(machine_identifier, self.id(), search_type, filter),
+                    ),
+            )
+            .json()
+            .await?;
+
+        container_items(self.client(), wrapper.media_container)
+            .into_iter()
+            .next()
+            .ok_or(Error::ItemNotFound)
+    }
+
     /// Returns the unique ID of this library.
     pub fn id(&self) -> &str {
         &self.directory().id
@@ -1186,4 +2387,375 @@ impl Library {
     pub fn library_type(&self) -> &LibraryType {
         &self.directory().library_type
     }
+
+    /// Asks the server to analyze the media in this library that it hasn't
+    /// analyzed yet. For music libraries this covers loudness analysis and,
+    /// when turned on in the server settings, sonic analysis. The analysis
+    /// runs in the background.
+    #[tracing::instrument(level = "debug", skip(self), fields(library.id = self.id()))]
+    pub async fn analyze(&self) -> Result {
+        self.client()
+            .put(Endpoint::new(SERVER_LIBRARY_SECTION_ANALYZE).segment("sectionId", self.id()))
+            .consume()
+            .await
+    }
+
+    /// The identifier of the metadata agent matching this library's items,
+    /// see [`crate::Server::agents`].
+    pub fn agent(&self) -> &str {
+        &self.directory().agent
+    }
+
+    /// The name of the scanner finding this library's items, see
+    /// [`crate::Server::scanners`].
+    pub fn scanner(&self) -> &str {
+        &self.directory().scanner
+    }
+
+    /// The library's advanced settings. Which ones are offered depends on
+    /// the library type and agent.
+    #[tracing::instrument(level = "debug", skip(self), fields(library.id = self.id()))]
+    pub async fn settings(&self) -> Result<Vec<Setting>> {
+        let wrapper: MediaContainerWrapper<MediaContainerPreferences> = self
+            .client()
+            .get(Endpoint::new(SERVER_LIBRARY_SECTION_PREFS).segment("sectionId", self.id()))
+            .json()
+            .await?;
+        Ok(wrapper.media_container.settings)
+    }
+
+    /// Changes some of the library's advanced settings, leaving the others
+    /// as they are.
+    #[tracing::instrument(level = "debug", skip(self), fields(library.id = self.id()))]
+    pub async fn set_settings(&self, settings: &[(&str, Value)]) -> Result {
+        let query = settings.iter().fold(Query::new(), |query, (id, value)| {
+            query.param(format!("prefs[{id}]"), value.to_string())
+        });
+        self.update(query).await
+    }
+
+    /// Switches the library to a different metadata agent. Existing items
+    /// keep their metadata until they're refreshed.
+    #[tracing::instrument(level = "debug", skip(self), fields(library.id = self.id()))]
+    pub async fn set_agent(&self, agent: &str) -> Result {
+        self.update(Query::new().param("agent", agent)).await
+    }
+
+    async fn update(&self, query: Query) -> Result {
+        // The server resets the agent when it's left out.
+        let query = if query.params.contains_key("agent") {
+            query
+        } else {
+            query.param("agent", self.agent())
+        };
+        self.client()
+            .put(format!(
+                "{}?{query}",
+                Endpoint::new(SERVER_LIBRARY_SECTION).segment("sectionId", self.id())
+            ))
+            .consume()
+            .await
+    }
+
+    /// Whether the server generates preview thumbnails for the videos in
+    /// this library, see [`Part::preview_thumbnails`].
+    #[tracing::instrument(level = "debug", skip(self), fields(library.id = self.id()))]
+    pub async fn preview_thumbnails_enabled(&self) -> Result<bool> {
+        let setting = self
+            .settings()
+            .await?
+            .into_iter()
+            .find(|setting| setting.id == PREVIEW_THUMBNAILS_SETTING)
+            .ok_or_else(|| {
+                Error::RequestedSettingNotFound(PREVIEW_THUMBNAILS_SETTING.to_owned())
+            })?;
+
+        match setting.value {
+            Value::Bool(enabled) => Ok(enabled),
+            _ => Err(Error::IncompatibleSettingValues),
+        }
+    }
+
+    /// Turns the generation of preview thumbnails on or off. Thumbnails are
+    /// generated by a scheduled task so they don't appear right away, and
+    /// existing ones are removed during the next library clean up once it
+    /// is turned off.
+    #[tracing::instrument(level = "debug", skip(self), fields(library.id = self.id()))]
+    pub async fn set_preview_thumbnails_enabled(&self, enabled: bool) -> Result {
+        self.set_settings(&[(PREVIEW_THUMBNAILS_SETTING, Value::Bool(enabled))])
+            .await
+    }
+
+    async fn section_items(&self, path: &str, offset: u32, limit: u32) -> Result<Vec<Item>> {
+        let query = Query::new()
+            .param("X-Plex-Container-Start", offset.to_string())
+            .param("X-Plex-Container-Size", limit.to_string());
+        metadata_items(
+            self.client(),
+            &format!(
+                "{}?{query}",
+                Endpoint::new(path).segment("sectionId", self.id())
+            ),
+        )
+        .await
+    }
+
+    /// Lists the library's root folders, i.e. the locations it was set up
+    /// with, and any items directly within them. Use [`Folder::contents`]
+    /// to go further down the tree.
+    #[tracing::instrument(level = "debug", skip(self), fields(library.id = self.id()))]
+    pub async fn folders(&self) -> Result<FolderContents> {
+        folder_contents(
+            self.client(),
+            &Endpoint::new(SERVER_LIBRARY_SECTION_FOLDER)
+                .segment("sectionId", self.id())
+                .to_string(),
+        )
+        .await
+    }
+
+    /// Retrieves the items most recently added to the library, newest first.
+    pub async fn recently_added(&self, limit: u32) -> Result<Vec<Item>> {
+        self.recently_added_page(0, limit).await
+    }
+
+    /// Retrieves a page of the recently added items, skipping the first
+    /// `offset` ones.
+    #[tracing::instrument(level = "debug", skip(self), fields(library.id = self.id()))]
+    pub async fn recently_added_page(&self, offset: u32, limit: u32) -> Result<Vec<Item>> {
+        self.section_items(SERVER_LIBRARY_SECTION_RECENTLY_ADDED, offset, limit)
+            .await
+    }
+
+    /// Retrieves the items with the most recent release dates.
+    pub async fn recently_released(&self, limit: u32) -> Result<Vec<Item>> {
+        self.recently_released_page(0, limit).await
+    }
+
+    /// Retrieves a page of the recently released items, skipping the first
+    /// `offset` ones.
+    #[tracing::instrument(level = "debug", skip(self), fields(library.id = self.id()))]
+    pub async fn recently_released_page(&self, offset: u32, limit: u32) -> Result<Vec<Item>> {
+        self.section_items(SERVER_LIBRARY_SECTION_NEWEST, offset, limit)
+            .await
+    }
+
+    /// Counts the items in the library by type and sums up the size and
+    /// duration of its media, without listing the items.
+    #[tracing::instrument(level = "debug", skip(self), fields(library.id = self.id()))]
+    pub async fn totals(&self) -> Result<LibraryTotals> {
+        let types: &[SearchType] = match self {
+            Self::Movie(_) => &[SearchType::Movie],
+            Self::Video(_) => &[SearchType::Clip],
+            Self::TV(_) => &[SearchType::Show, SearchType::Season, SearchType::Episode],
+            Self::Music(_) => &[SearchType::Artist, SearchType::Album, SearchType::Track],
+            Self::Photo(_) => &[SearchType::PhotoAlbum, SearchType::Photo],
+        };
+
+        let mut totals = LibraryTotals::default();
+        let path = Endpoint::new(SERVER_LIBRARY_SECTION_ALL).segment("sectionId", self.id());
+        for item_type in types {
+            let query = Query::new()
+                .param("type", item_type.to_string())
+                .param("X-Plex-Container-Start", "0")
+                .param("X-Plex-Container-Size", "0");
+            let wrapper: MediaContainerWrapper<MetadataMediaContainer> =
+                self.client().get(format!("{path}?{query}")).json().await?;
+            let container = wrapper.media_container.media_container;
+            totals.counts.insert(
+                *item_type,
+                container.total_size.or(container.size).unwrap_or_default(),
+            );
+        }
+
+        let providers: MediaContainerWrapper<ServerMediaContainer> = self
+            .client()
+            .get(format!("{SERVER_MEDIA_PROVIDERS}?includeStorage=1"))
+            .json()
+            .await?;
+        let directory = providers
+            .media_container
+            .media_providers
+            .into_iter()
+            .flat_map(|provider| provider.features)
+            .filter_map(|feature| match feature {
+                MediaProviderFeature::Content { directory, .. } => Some(directory),
+                _ => None,
+            })
+            .flatten()
+            .find_map(|directory| match directory {
+                ContentDirectory::Media(library) if library.id == self.id() => Some(library),
+                _ => None,
+            });
+        if let Some(directory) = directory {
+            totals.size = directory.storage_total;
+            totals.duration = directory.duration_total.map(Duration::from_millis);
+        }
+
+        Ok(totals)
+    }
+
+    /// Finds the library's top level items (e.g. movies or shows) with the
+    /// given external id. The server can't filter by these ids so this lists
+    /// the whole library.
+    #[tracing::instrument(level = "debug", skip(self), fields(library.id = self.id()))]
+    pub async fn find_by_external_id(&self, id: &ExternalId) -> Result<Vec<Item>> {
+        let items: Vec<Item> = metadata_items(
+            self.client(),
+            &format!(
+                "{}?includeGuids=1",
+                Endpoint::new(SERVER_LIBRARY_SECTION_ALL).segment("sectionId", self.id())
+            ),
+        )
+        .await?;
+
+        Ok(items
+            .into_iter()
+            .filter(|item| item.external_ids().contains(id))
+            .collect())
+    }
+
+    /// Lists the tags of the given kind used by the items in this library.
+    #[tracing::instrument(level = "debug", skip(self), fields(library.id = self.id()))]
+    pub async fn tags(&self, field: TagField) -> Result<Vec<TagDirectory>> {
+        let wrapper: MediaContainerWrapper<MetadataMediaContainer> = self
+            .client()
+            .get(
+                Endpoint::new(SERVER_LIBRARY_SECTION_TAGS)
+                    .segment("sectionId", self.id())
+                    .segment("field", field.name()),
+            )
+            .json()
+            .await?;
+
+        wrapper
+            .media_container
+            .directories
+            .into_iter()
+            .map(|directory| Ok(serde_json::from_value(directory)?))
+            .collect()
+    }
+
+    /// Adds the tags to all of the items with as few requests as possible,
+    /// one per type of item. The edited field is locked so the metadata
+    /// agents don't undo the change on the next refresh.
+    pub async fn add_tags<M: MetadataItem>(
+        &self,
+        field: TagField,
+        items: &[M],
+        tags: &[&str],
+    ) -> Result {
+        let params = tags
+            .iter()
+            .enumerate()
+            .map(|(index, tag)| {
+                (
+                    format!("{}[{index}].tag.tag", field.name()),
+                    tag.to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+        self.edit_items(field, items, params).await
+    }
+
+    /// Removes the tags from all of the items, see [`Library::add_tags`].
+    pub async fn remove_tags<M: MetadataItem>(
+        &self,
+        field: TagField,
+        items: &[M],
+        tags: &[&str],
+    ) -> Result {
+        if tags.is_empty() {
+            return Ok(());
+        }
+
+        let params = vec![(format!("{}[].tag.tag-", field.name()), tags.join(","))];
+        self.edit_items(field, items, params).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, items, params), fields(library.id = self.id()))]
+    async fn edit_items<M: MetadataItem>(
+        &self,
+        field: TagField,
+        items: &[M],
+        params: Vec<(String, String)>,
+    ) -> Result {
+        if params.is_empty() {
+            return Ok(());
+        }
+
+        let mut groups: Vec<(SearchType, Vec<&str>)> = Vec::new();
+        for item in items {
+            let item_type = item
+                .metadata()
+                .metadata_type
+                .as_ref()
+                .and_then(edit_type)
+                .ok_or(Error::TagsNotSupported)?;
+            match groups.iter_mut().find(|(t, _)| *t == item_type) {
+                Some((_, ids)) => ids.push(item.rating_key()),
+                None => groups.push((item_type, vec![item.rating_key()])),
+            }
+        }
+
+        let path = Endpoint::new(SERVER_LIBRARY_SECTION_ALL).segment("sectionId", self.id());
+        for (item_type, ids) in groups {
+            let mut query = Query::new()
+                .param("type", item_type.to_string())
+                .param("id", ids.join(","))
+                .param(format!("{}.locked", field.name()), "1");
+            for (name, value) in &params {
+                query = query.param(name, value);
+            }
+
+            self.client()
+                .put(format!("{path}?{query}"))
+                .consume()
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Uploads a file (e.g. a photo from a camera roll) into this library.
+    /// The server places it into the section's storage and scans it in.
+    ///
+    /// `length` must be the exact size of the data the reader produces. The
+    /// observer is notified as the data is sent, pass `()` to ignore progress.
+    ///
+    /// Configured timeout value will be ignored during uploading.
+    #[tracing::instrument(level = "debug", skip(self, reader, observer))]
+    pub async fn upload<R, O>(&self, file_name: &str, reader: R, length: u64, observer: O) -> Result
+    where
+        R: AsyncRead + Unpin + Send + Sync + 'static,
+        O: ProgressObserver + Unpin + 'static,
+    {
+        let path = format!(
+            "{}?{}",
+            Endpoint::new(SERVER_LIBRARY_UPLOAD).segment("sectionId", self.id()),
+            Query::new().param("filename", file_name)
+        );
+
+        let body = AsyncBody::from_reader_sized(
+            ProgressReader::new(reader, Some(length), observer),
+            length,
+        );
+
+        let mut response = self
+            .client()
+            .post(path)
+            .timeout(None)
+            .header("Content-Type", "application/octet-stream")
+            .body(body)?
+            .send()
+            .await?;
+
+        match response.status().as_http_status() {
+            StatusCode::OK | StatusCode::CREATED | StatusCode::NO_CONTENT => {
+                response.consume().await?;
+                Ok(())
+            }
+            _ => Err(Error::from_response(response).await),
+        }
+    }
 }
@@ -0,0 +1,323 @@
+use std::mem::discriminant;
+
+use serde::Deserialize;
+use serde_aux::prelude::{deserialize_bool_from_anything, deserialize_number_from_string};
+use time::OffsetDateTime;
+
+use crate::{
+    endpoints::Endpoint,
+    media_container::{
+        helpers::deserialize_option_datetime_from_timestamp,
+        preferences::{Setting, Value},
+        MediaContainerWrapper,
+    },
+    url::{
+        SERVER_LIVETV_DEVICE_CHANNELS, SERVER_LIVETV_DVR, SERVER_LIVETV_DVRS,
+        SERVER_LIVETV_DVR_PREFS, SERVER_LIVETV_LINEUP_CHANNELS,
+    },
+    Error, HttpClient, Result,
+};
+
+/// Maps one of the channels a tuner can receive to a channel in the lineup.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelMapping {
+    /// The channel's key in the lineup.
+    pub channel_key: String,
+    /// The channel's identifier on the tuner device.
+    pub device_identifier: String,
+    pub lineup_identifier: Option<String>,
+    /// Whether the channel is shown in the guide and can be recorded.
+    #[serde(deserialize_with = "deserialize_bool_from_anything")]
+    pub enabled: bool,
+}
+
+/// A tuner, or a network device holding several tuners, used by a DVR.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct TunerDevice {
+    pub key: String,
+    #[serde(rename = "parentID")]
+    pub parent_id: Option<u32>,
+    /// E.g. `device://tv.plex.grabbers.hdhomerun/1053C0CA`.
+    pub uuid: String,
+    pub uri: String,
+    pub protocol: Option<String>,
+    /// Whether the server can currently reach the device, e.g. `alive` or
+    /// `dead`.
+    pub status: Option<String>,
+    pub state: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_datetime_from_timestamp"
+    )]
+    pub last_seen_at: Option<OffsetDateTime>,
+    pub make: Option<String>,
+    pub model: Option<String>,
+    pub model_number: Option<String>,
+    /// The kind of signal received, e.g. `Antenna` or `Cable`.
+    pub source: Option<String>,
+    pub sources: Option<String>,
+    pub thumb: Option<String>,
+    pub title: Option<String>,
+    /// The number of channels that can be received at once.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub tuners: u32,
+    #[serde(rename = "ChannelMapping", default)]
+    pub channel_mappings: Vec<ChannelMapping>,
+    #[serde(rename = "Setting", default)]
+    pub settings: Vec<Setting>,
+}
+
+/// A channel found by a tuner device during its channel scan.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct TunerChannel {
+    /// The channel's identifier on the tuner device, e.g. `46.3`.
+    pub identifier: String,
+    pub channel_identifier: Option<String>,
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_bool_from_anything")]
+    pub hd: bool,
+    #[serde(default, deserialize_with = "deserialize_bool_from_anything")]
+    pub drm: bool,
+    pub signal_quality: Option<u32>,
+    pub signal_strength: Option<u32>,
+}
+
+/// A channel listed by the program guide.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct LineupChannel {
+    pub key: String,
+    pub identifier: Option<String>,
+    pub lineup_identifier: Option<String>,
+    pub call_sign: Option<String>,
+    pub title: Option<String>,
+    /// The channel number shown to viewers.
+    pub channel_vcn: Option<String>,
+    pub language: Option<String>,
+    pub thumb: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_bool_from_anything")]
+    pub hd: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+struct DvrState {
+    key: String,
+    uuid: String,
+    language: Option<String>,
+    lineup_title: Option<String>,
+    lineup: String,
+    country: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_datetime_from_timestamp"
+    )]
+    refreshed_at: Option<OffsetDateTime>,
+    epg_identifier: Option<String>,
+    #[serde(rename = "Device", default)]
+    devices: Vec<TunerDevice>,
+    #[serde(rename = "Setting", default)]
+    settings: Vec<Setting>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+struct DvrsContainer {
+    #[serde(rename = "size")]
+    _size: Option<u32>,
+    #[serde(rename = "Dvr", default)]
+    dvrs: Vec<DvrState>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+struct Lineup {
+    #[serde(rename = "uuid")]
+    _uuid: Option<String>,
+    #[serde(rename = "type")]
+    _type: Option<String>,
+    #[serde(rename = "title")]
+    _title: Option<String>,
+    #[serde(rename = "Channel", default)]
+    channels: Vec<LineupChannel>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+struct LineupsContainer {
+    #[serde(rename = "size")]
+    _size: Option<u32>,
+    #[serde(rename = "Lineup", default)]
+    lineups: Vec<Lineup>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+struct TunerChannelsContainer {
+    #[serde(rename = "size")]
+    _size: Option<u32>,
+    #[serde(rename = "DeviceChannel", default)]
+    channels: Vec<TunerChannel>,
+}
+
+/// A DVR configured on the server.
+#[derive(Debug, Clone)]
+pub struct Dvr {
+    client: HttpClient,
+    state: DvrState,
+}
+
+impl Dvr {
+    pub(crate) async fn all(client: HttpClient) -> Result<Vec<Self>> {
+        let wrapper: MediaContainerWrapper<DvrsContainer> =
+            client.get(SERVER_LIVETV_DVRS).json().await?;
+
+        Ok(wrapper
+            .media_container
+            .dvrs
+            .into_iter()
+            .map(|state| Self {
+                client: client.clone(),
+                state,
+            })
+            .collect())
+    }
+
+    async fn fetch(client: &HttpClient, key: &str) -> Result<DvrState> {
+        let wrapper: MediaContainerWrapper<DvrsContainer> = client
+            .get(Endpoint::new(SERVER_LIVETV_DVR).segment("dvrId", key))
+            .json()
+            .await?;
+
+        wrapper
+            .media_container
+            .dvrs
+            .into_iter()
+            .next()
+            .ok_or(Error::ItemNotFound)
+    }
+
+    pub(crate) fn client(&self) -> &HttpClient {
+        &self.client
+    }
+
+    pub fn key(&self) -> &str {
+        &self.state.key
+    }
+
+    pub fn uuid(&self) -> &str {
+        &self.state.uuid
+    }
+
+    /// The lineup providing the guide data, e.g.
+    /// `lineup://tv.plex.providers.epg.cloud/...`.
+    pub fn lineup(&self) -> &str {
+        &self.state.lineup
+    }
+
+    pub fn lineup_title(&self) -> Option<&str> {
+        self.state.lineup_title.as_deref()
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        self.state.language.as_deref()
+    }
+
+    pub fn country(&self) -> Option<&str> {
+        self.state.country.as_deref()
+    }
+
+    /// The identifier of the program guide media provider used by this DVR.
+    pub fn epg_identifier(&self) -> Option<&str> {
+        self.state.epg_identifier.as_deref()
+    }
+
+    /// When the guide data was last refreshed.
+    pub fn refreshed_at(&self) -> Option<OffsetDateTime> {
+        self.state.refreshed_at
+    }
+
+    /// The tuner devices used by this DVR.
+    pub fn devices(&self) -> &[TunerDevice] {
+        &self.state.devices
+    }
+
+    /// The DVR's recording settings, e.g. the quality to record at, how long
+    /// before and after the scheduled time to record and whether to remove
+    /// commercials.
+    pub fn settings(&self) -> &[Setting] {
+        &self.state.settings
+    }
+
+    /// Reloads the DVR's details from the server.
+    #[tracing::instrument(level = "debug", skip(self), fields(key = self.state.key))]
+    pub async fn refresh(&mut self) -> Result {
+        self.state = Self::fetch(&self.client, &self.state.key).await?;
+        Ok(())
+    }
+
+    /// Changes one of the DVR's [settings](Dvr::settings). The new value must
+    /// be of the same type as the current one.
+    #[tracing::instrument(level = "debug", skip(self), fields(key = self.state.key))]
+    pub async fn set_setting(&mut self, id: &str, value: Value) -> Result {
+        let setting = self
+            .state
+            .settings
+            .iter()
+            .find(|s| s.id == id)
+            .ok_or_else(|| Error::RequestedSettingNotFound(id.to_owned()))?;
+        if discriminant(&setting.value) != discriminant(&value) {
+            return Err(Error::IncompatibleSettingValues);
+        }
+
+        let uri = format!(
+            "{}?{}",
+            Endpoint::new(SERVER_LIVETV_DVR_PREFS).segment("dvrId", &self.state.key),
+            serde_urlencoded::to_string([(id, value.to_string())])?
+        );
+        self.client.put(uri).consume().await?;
+
+        self.refresh().await
+    }
+
+    /// Lists the channels in the DVR's lineup.
+    #[tracing::instrument(level = "debug", skip(self), fields(key = self.state.key))]
+    pub async fn lineup_channels(&self) -> Result<Vec<LineupChannel>> {
+        let uri = format!(
+            "{}?{}",
+            SERVER_LIVETV_LINEUP_CHANNELS,
+            serde_urlencoded::to_string([("lineup", &self.state.lineup)])?
+        );
+        let wrapper: MediaContainerWrapper<LineupsContainer> = self.client.get(uri).json().await?;
+
+        Ok(wrapper
+            .media_container
+            .lineups
+            .into_iter()
+            .flat_map(|lineup| lineup.channels)
+            .collect())
+    }
+
+    /// Lists the channels that one of the DVR's tuner devices can receive.
+    #[tracing::instrument(level = "debug", skip(self, device), fields(device = device.key))]
+    pub async fn tuner_channels(&self, device: &TunerDevice) -> Result<Vec<TunerChannel>> {
+        let wrapper: MediaContainerWrapper<TunerChannelsContainer> = self
+            .client
+            .get(Endpoint::new(SERVER_LIVETV_DEVICE_CHANNELS).segment("deviceId", &device.key))
+            .json()
+            .await?;
+
+        Ok(wrapper.media_container.channels)
+    }
+}
@@ -0,0 +1,190 @@
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+use crate::{
+    media_container::{
+        helpers::{deserialize_option_text, optional_boolish},
+        server::{
+            library::{deserialize_option_metadata_type, MetadataType},
+            GridChannelFilter, MediaProvider, MediaProviderFeature, MediaProviderProtocol,
+        },
+        MediaContainerWrapper,
+    },
+    HttpClient, Result,
+};
+
+use super::{ChannelMapping, Dvr, TunerDevice};
+
+/// A single broadcast of a program on a channel.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct Airing {
+    pub id: Option<u64>,
+    #[serde(with = "time::serde::timestamp")]
+    pub begins_at: OffsetDateTime,
+    #[serde(with = "time::serde::timestamp")]
+    pub ends_at: OffsetDateTime,
+    /// Identifies the channel in the lineup, matching
+    /// [`ChannelMapping::channel_key`].
+    pub channel_identifier: String,
+    pub channel_call_sign: Option<String>,
+    pub channel_short_title: Option<String>,
+    pub channel_title: Option<String>,
+    pub channel_thumb: Option<String>,
+    pub channel_art: Option<String>,
+    /// The channel number shown to viewers.
+    pub channel_vcn: Option<String>,
+    /// Whether the airing is currently being broadcast.
+    #[serde(default, deserialize_with = "optional_boolish")]
+    pub on_air: Option<bool>,
+    #[serde(default, deserialize_with = "optional_boolish")]
+    pub premiere: Option<bool>,
+    pub protocol: Option<String>,
+    pub video_resolution: Option<String>,
+}
+
+/// A program listed in the guide along with its airings.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct Program {
+    pub key: String,
+    pub rating_key: String,
+    pub guid: Option<String>,
+    #[serde(flatten, deserialize_with = "deserialize_option_metadata_type")]
+    pub metadata_type: Option<MetadataType>,
+    pub title: String,
+    pub grandparent_rating_key: Option<String>,
+    pub grandparent_guid: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_option_text")]
+    pub grandparent_title: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_option_text")]
+    pub parent_title: Option<String>,
+    pub parent_index: Option<u32>,
+    pub index: Option<u32>,
+    pub summary: Option<String>,
+    pub year: Option<u32>,
+    pub thumb: Option<String>,
+    pub grandparent_thumb: Option<String>,
+    /// Duration in milliseconds.
+    pub duration: Option<u64>,
+    pub originally_available_at: Option<String>,
+    #[serde(rename = "Media", default)]
+    pub airings: Vec<Airing>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+struct GridContainer {
+    #[serde(rename = "size")]
+    _size: Option<u32>,
+    #[serde(rename = "identifier")]
+    _identifier: Option<String>,
+    #[serde(rename = "Metadata", default)]
+    programs: Vec<Program>,
+}
+
+/// The program guide of one of the server's Live TV media providers.
+#[derive(Debug, Clone)]
+pub struct Guide {
+    client: HttpClient,
+    identifier: String,
+    title: String,
+    grid_key: String,
+    channel_filters: Vec<GridChannelFilter>,
+}
+
+impl Guide {
+    pub(crate) fn from_provider(client: &HttpClient, provider: &MediaProvider) -> Option<Self> {
+        if !provider
+            .protocols
+            .iter()
+            .any(|p| matches!(p, MediaProviderProtocol::LiveTv))
+        {
+            return None;
+        }
+
+        provider.features.iter().find_map(|feature| match feature {
+            MediaProviderFeature::Grid {
+                key,
+                grid_channel_filter,
+            } => Some(Self {
+                client: client.clone(),
+                identifier: provider.identifier.clone(),
+                title: provider.title.clone(),
+                grid_key: key.clone(),
+                channel_filters: grid_channel_filter.clone(),
+            }),
+            _ => None,
+        })
+    }
+
+    /// The media provider's identifier, e.g.
+    /// `tv.plex.providers.epg.cloud:30`. Matches [`Dvr::epg_identifier`].
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Genres that the guide's channels can be filtered by.
+    pub fn channel_filters(&self) -> &[GridChannelFilter] {
+        &self.channel_filters
+    }
+
+    /// Lists the programs airing at some point between `start` and `end`,
+    /// optionally only on the channel with the given identifier.
+    #[tracing::instrument(level = "debug", skip(self), fields(identifier = self.identifier))]
+    pub async fn grid(
+        &self,
+        start: OffsetDateTime,
+        end: OffsetDateTime,
+        channel_identifier: Option<&str>,
+    ) -> Result<Vec<Program>> {
+        // Plex reads the comparison operators from the end of the parameter
+        // names, e.g. `endsAt>=1700000000`.
+        let start = start.unix_timestamp().to_string();
+        let end = end.unix_timestamp().to_string();
+        let mut params = vec![
+            ("type", "1,4"),
+            ("sort", "beginsAt"),
+            ("endsAt>", start.as_str()),
+            ("beginsAt<", end.as_str()),
+        ];
+        if let Some(channel_identifier) = channel_identifier {
+            params.push(("channelIdentifier", channel_identifier));
+        }
+
+        let separator = if self.grid_key.contains('?') {
+            '&'
+        } else {
+            '?'
+        };
+        let uri = format!(
+            "{}{separator}{}",
+            self.grid_key,
+            serde_urlencoded::to_string(params)?
+        );
+
+        let wrapper: MediaContainerWrapper<GridContainer> = self.client.get(uri).json().await?;
+        Ok(wrapper.media_container.programs)
+    }
+}
+
+impl Dvr {
+    /// Finds the tuner device and enabled channel that an airing from this
+    /// DVR's guide can be recorded from.
+    pub fn channel_for(&self, airing: &Airing) -> Option<(&TunerDevice, &ChannelMapping)> {
+        self.devices().iter().find_map(|device| {
+            device
+                .channel_mappings
+                .iter()
+                .find(|mapping| mapping.enabled && mapping.channel_key == airing.channel_identifier)
+                .map(|mapping| (device, mapping))
+        })
+    }
+}
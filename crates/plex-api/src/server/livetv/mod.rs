@@ -0,0 +1,20 @@
+//! Live TV and DVR support.
+//!
+//! A server records and streams broadcast television through one or more
+//! [`Dvr`]s. Each DVR combines the tuner devices that receive the signal
+//! with a lineup from a program guide provider describing which channels
+//! are available. The [`Guide`] lists what is being broadcast on those
+//! channels, and [`Subscription`]s decide which of them get recorded. A
+//! channel can also be watched live through a [`LiveTvSession`].
+
+mod dvr;
+mod epg;
+mod session;
+mod subscription;
+
+pub use self::{
+    dvr::{ChannelMapping, Dvr, LineupChannel, TunerChannel, TunerDevice},
+    epg::{Airing, Guide, Program},
+    session::LiveTvSession,
+    subscription::{RecordSettings, Subscription, SubscriptionOptions},
+};
@@ -0,0 +1,187 @@
+use http::StatusCode;
+use isahc::AsyncReadResponseExt;
+use serde::Deserialize;
+
+use crate::{
+    endpoints::Endpoint,
+    isahc_compat::StatusCodeExt,
+    media_container::{server::library::Protocol, MediaContainerWrapper},
+    transcode::{
+        session::{create_transcode_session, TranscodeSession},
+        Context, VideoTranscodeOptions,
+    },
+    url::{SERVER_GRAB_OPERATION, SERVER_LIVETV_TUNE},
+    Error, HttpClient, Result,
+};
+
+use super::{ChannelMapping, Dvr, Program};
+
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+struct GrabOperation {
+    key: String,
+    #[serde(rename = "mediaSubscriptionID")]
+    _media_subscription_id: Option<u32>,
+    #[serde(rename = "mediaIndex")]
+    _media_index: Option<u32>,
+    grabber_identifier: Option<String>,
+    #[serde(rename = "grabberProtocol")]
+    _grabber_protocol: Option<String>,
+    #[serde(rename = "deviceID")]
+    device_id: Option<String>,
+    status: Option<String>,
+    #[serde(rename = "percent")]
+    _percent: Option<f32>,
+    #[serde(rename = "Metadata")]
+    metadata: Program,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+struct TunedSubscription {
+    #[serde(rename = "key")]
+    _key: Option<String>,
+    #[serde(rename = "type")]
+    _type: Option<u8>,
+    #[serde(rename = "targetLibrarySectionID")]
+    _target_library_section_id: Option<i32>,
+    #[serde(rename = "createdAt")]
+    _created_at: Option<i64>,
+    #[serde(rename = "title")]
+    _title: Option<String>,
+    #[serde(rename = "MediaGrabOperation", default)]
+    operations: Vec<GrabOperation>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+struct TuneContainer {
+    #[serde(rename = "size")]
+    _size: Option<u32>,
+    #[serde(rename = "MediaSubscription", default)]
+    subscriptions: Vec<TunedSubscription>,
+}
+
+/// A tuner held to watch a channel live.
+///
+/// The tuner stays in use until the session is [released](Self::release),
+/// dropping the session without releasing it leaves the server to notice
+/// that the stream is no longer being watched.
+pub struct LiveTvSession {
+    client: HttpClient,
+    operation: GrabOperation,
+    transcode: TranscodeSession,
+}
+
+impl LiveTvSession {
+    /// What is currently being broadcast on the channel.
+    pub fn program(&self) -> &Program {
+        &self.operation.metadata
+    }
+
+    /// The key of the [tuner device](super::TunerDevice) receiving the
+    /// channel.
+    pub fn device_key(&self) -> Option<&str> {
+        self.operation.device_id.as_deref()
+    }
+
+    /// E.g. `tv.plex.grabbers.hdhomerun`.
+    pub fn grabber_identifier(&self) -> Option<&str> {
+        self.operation.grabber_identifier.as_deref()
+    }
+
+    /// The state of the tuner, as reported when the channel was tuned.
+    pub fn status(&self) -> Option<&str> {
+        self.operation.status.as_deref()
+    }
+
+    /// The streaming transcode session delivering the channel.
+    pub fn transcode_session(&self) -> &TranscodeSession {
+        &self.transcode
+    }
+
+    /// Mutable access to the transcode session, e.g. to
+    /// [change its bitrate](TranscodeSession::set_bitrate).
+    pub fn transcode_session_mut(&mut self) -> &mut TranscodeSession {
+        &mut self.transcode
+    }
+
+    /// Stops streaming and frees the tuner for other sessions or recordings.
+    #[tracing::instrument(level = "debug", skip(self), fields(key = self.operation.key))]
+    pub async fn release(self) -> Result {
+        self.transcode.cancel().await?;
+        release_tuner(&self.client, &self.operation.key).await
+    }
+}
+
+async fn release_tuner(client: &HttpClient, operation_key: &str) -> Result {
+    let mut response = client
+        .delete(Endpoint::new(SERVER_GRAB_OPERATION).segment("operationId", operation_key))
+        .send()
+        .await?;
+
+    match response.status().as_http_status() {
+        // The server may already have released the tuner when the transcode
+        // stopped.
+        StatusCode::OK | StatusCode::NOT_FOUND => Ok(response.consume().await?),
+        _ => Err(Error::from_response(response).await),
+    }
+}
+
+impl Dvr {
+    /// Tunes one of the DVR's channels, found through
+    /// [`Dvr::channel_for`], and starts a streaming transcode of it.
+    #[tracing::instrument(level = "debug", skip(self, channel, options), fields(channel = channel.channel_key))]
+    pub async fn tune(
+        &self,
+        channel: &ChannelMapping,
+        protocol: Protocol,
+        options: VideoTranscodeOptions,
+    ) -> Result<LiveTvSession> {
+        let wrapper: MediaContainerWrapper<TuneContainer> = self
+            .client()
+            .post(
+                Endpoint::new(SERVER_LIVETV_TUNE)
+                    .segment("dvrId", self.key())
+                    .segment("channel", &channel.channel_key),
+            )
+            .json()
+            .await?;
+
+        let operation = wrapper
+            .media_container
+            .subscriptions
+            .into_iter()
+            .flat_map(|subscription| subscription.operations)
+            .next()
+            .ok_or(Error::ItemNotFound)?;
+
+        let transcode = match create_transcode_session(
+            self.client(),
+            &operation.metadata.key,
+            Context::Streaming,
+            protocol,
+            None,
+            None,
+            options,
+        )
+        .await
+        {
+            Ok(transcode) => transcode,
+            Err(error) => {
+                // Don't keep the tuner busy when nothing can watch it.
+                let _ = release_tuner(self.client(), &operation.key).await;
+                return Err(error);
+            }
+        };
+
+        Ok(LiveTvSession {
+            client: self.client().clone(),
+            operation,
+            transcode,
+        })
+    }
+}
@@ -0,0 +1,310 @@
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+use crate::{
+    endpoints::Endpoint,
+    media_container::{preferences::Setting, server::library::MetadataType, MediaContainerWrapper},
+    url::{SERVER_SUBSCRIPTION, SERVER_SUBSCRIPTIONS, SERVER_SUBSCRIPTION_MOVE},
+    Error, HttpClient, Result,
+};
+
+use super::Program;
+
+/// How a recording subscription should be recorded. Anything left unset uses
+/// the DVR's default.
+#[derive(Debug, Clone, Default)]
+pub struct RecordSettings {
+    /// Skip reruns of episodes that have aired before.
+    pub only_new_airings: Option<bool>,
+    /// Only record airings on this channel, identified by
+    /// [`Airing::channel_identifier`](super::Airing::channel_identifier).
+    pub channel: Option<String>,
+    /// Start recording this many minutes before the scheduled start.
+    pub start_offset_minutes: Option<u32>,
+    /// Keep recording for this many minutes after the scheduled end.
+    pub end_offset_minutes: Option<u32>,
+    /// Minimum video height to record, 0 to prefer HD but accept anything.
+    pub min_video_quality: Option<u32>,
+    /// Replace existing items in the library with better quality recordings.
+    pub replace_lower_quality: Option<bool>,
+    /// Remove commercials from the recording.
+    pub remove_commercials: Option<bool>,
+}
+
+fn bool_param(value: bool) -> String {
+    if value { "true" } else { "false" }.to_owned()
+}
+
+impl RecordSettings {
+    fn params(&self) -> Vec<(String, String)> {
+        let prefs = [
+            (
+                "onlyNewAirings",
+                self.only_new_airings.map(|v| (v as u8).to_string()),
+            ),
+            ("lineupChannel", self.channel.clone()),
+            (
+                "startOffsetMinutes",
+                self.start_offset_minutes.map(|v| v.to_string()),
+            ),
+            (
+                "endOffsetMinutes",
+                self.end_offset_minutes.map(|v| v.to_string()),
+            ),
+            (
+                "minVideoQuality",
+                self.min_video_quality.map(|v| v.to_string()),
+            ),
+            (
+                "replaceLowerQuality",
+                self.replace_lower_quality.map(bool_param),
+            ),
+            (
+                "comskipEnabled",
+                self.remove_commercials.map(|v| (v as u8).to_string()),
+            ),
+        ];
+
+        prefs
+            .into_iter()
+            .filter_map(|(name, value)| value.map(|value| (format!("prefs[{name}]"), value)))
+            .collect()
+    }
+}
+
+/// Describes a new recording subscription.
+#[derive(Debug, Clone)]
+pub struct SubscriptionOptions {
+    /// Record every episode of the program's show rather than just this
+    /// program.
+    pub series: bool,
+    /// The library section that recordings are added to.
+    pub library_section_id: u32,
+    /// Which of the library section's folders to save recordings in, the
+    /// first one if not set.
+    pub location_id: Option<u32>,
+    pub settings: RecordSettings,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+struct SubscriptionState {
+    key: String,
+    rating_key: Option<String>,
+    guid: Option<String>,
+    #[serde(rename = "type")]
+    metadata_type: Option<u8>,
+    title: String,
+    #[serde(default, with = "time::serde::timestamp::option")]
+    created_at: Option<OffsetDateTime>,
+    #[serde(rename = "targetLibrarySectionID")]
+    target_library_section_id: Option<u32>,
+    #[serde(rename = "targetSectionLocationID")]
+    target_section_location_id: Option<u32>,
+    airings_type: Option<String>,
+    library_section_title: Option<String>,
+    location_path: Option<String>,
+    #[serde(rename = "Setting", default)]
+    settings: Vec<Setting>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+struct SubscriptionsContainer {
+    #[serde(rename = "size")]
+    _size: Option<u32>,
+    #[serde(rename = "MediaSubscription", default)]
+    subscriptions: Vec<SubscriptionState>,
+}
+
+/// A rule telling the DVR what to record, either a single program or every
+/// episode of a show (a series pass).
+///
+/// The server considers subscriptions in order, earlier ones take priority
+/// when there aren't enough tuners to record everything.
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    client: HttpClient,
+    state: SubscriptionState,
+}
+
+fn first_subscription(
+    client: &HttpClient,
+    wrapper: MediaContainerWrapper<SubscriptionsContainer>,
+) -> Result<Subscription> {
+    wrapper
+        .media_container
+        .subscriptions
+        .into_iter()
+        .next()
+        .map(|state| Subscription {
+            client: client.clone(),
+            state,
+        })
+        .ok_or(Error::ItemNotFound)
+}
+
+impl Subscription {
+    pub(crate) async fn all(client: HttpClient) -> Result<Vec<Self>> {
+        let wrapper: MediaContainerWrapper<SubscriptionsContainer> =
+            client.get(SERVER_SUBSCRIPTIONS).json().await?;
+
+        Ok(wrapper
+            .media_container
+            .subscriptions
+            .into_iter()
+            .map(|state| Self {
+                client: client.clone(),
+                state,
+            })
+            .collect())
+    }
+
+    pub(crate) async fn create(
+        client: HttpClient,
+        program: &Program,
+        options: &SubscriptionOptions,
+    ) -> Result<Self> {
+        // Series passes are attached to the show rather than the episode.
+        let (metadata_type, guid, rating_key, title) =
+            match (options.series, &program.grandparent_rating_key) {
+                (true, Some(rating_key)) => (
+                    2,
+                    program.grandparent_guid.as_ref(),
+                    rating_key,
+                    program.grandparent_title.as_ref().unwrap_or(&program.title),
+                ),
+                (true, None) => return Err(Error::NotAnEpisode),
+                (false, _) => (
+                    match program.metadata_type {
+                        Some(MetadataType::Movie) => 1,
+                        _ => 4,
+                    },
+                    program.guid.as_ref(),
+                    &program.rating_key,
+                    &program.title,
+                ),
+            };
+
+        let mut params = vec![
+            ("type".to_owned(), metadata_type.to_string()),
+            (
+                "targetLibrarySectionID".to_owned(),
+                options.library_section_id.to_string(),
+            ),
+            ("hints[type]".to_owned(), metadata_type.to_string()),
+            ("hints[ratingKey]".to_owned(), rating_key.clone()),
+            ("hints[title]".to_owned(), title.clone()),
+            ("prefs[oneShot]".to_owned(), bool_param(!options.series)),
+        ];
+        if let Some(location_id) = options.location_id {
+            params.push((
+                "targetSectionLocationID".to_owned(),
+                location_id.to_string(),
+            ));
+        }
+        if let Some(guid) = guid {
+            params.push(("hints[guid]".to_owned(), guid.clone()));
+        }
+        params.extend(options.settings.params());
+
+        let uri = format!(
+            "{}?{}",
+            SERVER_SUBSCRIPTIONS,
+            serde_urlencoded::to_string(params)?
+        );
+        let wrapper = client.post(uri).json().await?;
+        first_subscription(&client, wrapper)
+    }
+
+    pub fn key(&self) -> &str {
+        &self.state.key
+    }
+
+    pub fn title(&self) -> &str {
+        &self.state.title
+    }
+
+    pub fn guid(&self) -> Option<&str> {
+        self.state.guid.as_deref()
+    }
+
+    pub fn rating_key(&self) -> Option<&str> {
+        self.state.rating_key.as_deref()
+    }
+
+    /// Whether this subscription records every episode of a show.
+    pub fn is_series(&self) -> bool {
+        self.state.metadata_type == Some(2)
+    }
+
+    pub fn created_at(&self) -> Option<OffsetDateTime> {
+        self.state.created_at
+    }
+
+    /// A description of which airings get recorded, e.g. `New Airings Only`.
+    pub fn airings_type(&self) -> Option<&str> {
+        self.state.airings_type.as_deref()
+    }
+
+    /// The library section that recordings are added to.
+    pub fn library_section_id(&self) -> Option<u32> {
+        self.state.target_library_section_id
+    }
+
+    /// The folder of the library section that recordings are saved in.
+    pub fn location_id(&self) -> Option<u32> {
+        self.state.target_section_location_id
+    }
+
+    pub fn library_section_title(&self) -> Option<&str> {
+        self.state.library_section_title.as_deref()
+    }
+
+    /// Where on the server the recordings are saved.
+    pub fn location_path(&self) -> Option<&str> {
+        self.state.location_path.as_deref()
+    }
+
+    /// The subscription's current record settings.
+    pub fn settings(&self) -> &[Setting] {
+        &self.state.settings
+    }
+
+    /// Changes how this subscription is recorded.
+    #[tracing::instrument(level = "debug", skip(self, settings), fields(key = self.state.key))]
+    pub async fn update(&mut self, settings: &RecordSettings) -> Result {
+        let uri = format!(
+            "{}?{}",
+            Endpoint::new(SERVER_SUBSCRIPTION).segment("subscriptionId", &self.state.key),
+            serde_urlencoded::to_string(settings.params())?
+        );
+        let wrapper = self.client.put(uri).json().await?;
+        *self = first_subscription(&self.client, wrapper)?;
+        Ok(())
+    }
+
+    /// Changes this subscription's priority to be immediately below another
+    /// subscription, or the highest priority if `after` is `None`.
+    #[tracing::instrument(level = "debug", skip(self, after), fields(key = self.state.key))]
+    pub async fn move_after(&self, after: Option<&Subscription>) -> Result {
+        let mut endpoint =
+            Endpoint::new(SERVER_SUBSCRIPTION_MOVE).segment("subscriptionId", &self.state.key);
+        if let Some(after) = after {
+            endpoint = endpoint.query("after", after.key());
+        }
+
+        self.client.put(endpoint).consume().await
+    }
+
+    /// Deletes this subscription. Recordings already made are kept.
+    #[tracing::instrument(level = "debug", skip(self), fields(key = self.state.key))]
+    pub async fn delete(self) -> Result {
+        self.client
+            .delete(Endpoint::new(SERVER_SUBSCRIPTION).segment("subscriptionId", &self.state.key))
+            .consume()
+            .await
+    }
+}
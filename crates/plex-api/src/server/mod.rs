@@ -1,39 +1,93 @@
+pub mod butler;
+mod capabilities;
+pub mod content_rating;
+mod item_settings;
 pub mod library;
+pub mod livetv;
+pub mod optimize;
 pub(crate) mod prefs;
+mod preview_thumbnails;
+mod remote_access;
 pub mod transcode;
+mod transcoder_settings;
 
 use self::{
-    library::{metadata_items, FromMetadata, Item, Library, MediaItem, MetadataItem},
+    butler::{Butler, DatabaseBackup},
+    library::{metadata_items, Collection, FromMetadata, Item, Library, MediaItem, MetadataItem},
+    livetv::{Dvr, Guide, Program, Subscription, SubscriptionOptions},
+    optimize::Optimizer,
     prefs::Preferences,
     transcode::{
+        artwork_query,
         session::{transcode_session_stats, TranscodeSession, TranscodeSessionsMediaContainer},
-        transcode_artwork, ArtTranscodeOptions,
+        session_id, transcode_artwork, ArtTranscodeOptions,
     },
 };
+pub use self::{
+    capabilities::{ServerCapabilities, ServerVersion},
+    item_settings::{DeleteWatched, EpisodeSort, ItemSettings, KeepUnwatched, SeasonsDisplay},
+    preview_thumbnails::PreviewThumbnails,
+    remote_access::{MappingState, RemoteAccess},
+    transcoder_settings::{TranscoderSettings, X264Preset},
+};
 #[cfg(not(feature = "tests_deny_unknown_fields"))]
 use crate::media_container::server::library::LibraryType;
 use crate::{
+    endpoints::Endpoint,
     http_client::HttpClient,
     isahc_compat::StatusCodeExt,
     media_container::{
-        server::{library::ContentDirectory, MediaProviderFeature, Server as ServerMediaContainer},
+        server::{
+            accounts::{AccountsMediaContainer, ServerAccount},
+            activities::{ActivitiesMediaContainer, Activity},
+            agents::{
+                Agent, AgentContributor, AgentContributorsMediaContainer, AgentsMediaContainer,
+                Scanner, ScannersMediaContainer,
+            },
+            library::{ContentDirectory, SearchType},
+            Identity, MediaProvider, MediaProviderFeature, Server as ServerMediaContainer,
+        },
         MediaContainerWrapper,
     },
     myplex::MyPlex,
     transcode::download_queue::DownloadQueue,
     url::{
+        SERVER_ACCOUNT, SERVER_ACCOUNTS, SERVER_ACTIVITIES, SERVER_IDENTITY, SERVER_LIBRARY_ALL,
         SERVER_MEDIA_PROVIDERS, SERVER_MYPLEX_ACCOUNT, SERVER_MYPLEX_CLAIM, SERVER_SCROBBLE,
-        SERVER_TIMELINE, SERVER_TRANSCODE_SESSIONS, SERVER_UNSCROBBLE,
+        SERVER_SECURITY_TOKEN, SERVER_SYSTEM_AGENTS, SERVER_SYSTEM_AGENT_CONFIG,
+        SERVER_SYSTEM_SCANNERS, SERVER_TIMELINE, SERVER_TRANSCODE_ART, SERVER_TRANSCODE_SESSIONS,
+        SERVER_UNSCROBBLE,
     },
     Error, HttpClientBuilder, Result,
 };
-use futures::AsyncWrite;
+use futures::{
+    future::{select_ok, try_join_all},
+    AsyncWrite, FutureExt,
+};
 use http::{StatusCode, Uri};
 use isahc::AsyncReadResponseExt;
+use serde::Deserialize;
 use std::{
     collections::HashMap,
     fmt::{self, Debug},
+    time::{Duration, Instant},
 };
+use tracing::{debug, trace};
+
+const LIBRARY_PROVIDER: &str = "com.plexapp.plugins.library";
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn agent_config_path(agent: &str, media_type: SearchType) -> Endpoint {
+    Endpoint::new(SERVER_SYSTEM_AGENT_CONFIG)
+        .segment("agentId", agent)
+        .segment("mediaType", media_type as u16)
+}
+
+/// The response to [`Server::transient_token`].
+#[derive(Deserialize)]
+struct TransientToken {
+    token: String,
+}
 
 struct Query {
     params: HashMap<String, String>,
@@ -69,11 +123,69 @@ impl fmt::Display for Query {
     }
 }
 
+/// Builds a full URL to the path on the server, including the authentication
+/// token so the URL can be handed to other software.
+fn authenticated_url(client: &HttpClient, path: &str, mut query: Query) -> Result<Uri> {
+    if client.is_authenticated() {
+        query = query.param("X-Plex-Token", client.x_plex_token());
+    }
+    let path_and_query = if query.params.is_empty() {
+        path.to_owned()
+    } else {
+        format!("{path}?{query}")
+    };
+
+    let mut uri_parts = client.base_url().into_parts();
+    uri_parts.path_and_query = Some(
+        path_and_query
+            .try_into()
+            .map_err(Into::<http::Error>::into)?,
+    );
+    Ok(Uri::from_parts(uri_parts).map_err(Into::<http::Error>::into)?)
+}
+
+/// Optional parts of the server's API that not every server offers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerFeature {
+    /// The server's media libraries.
+    Library,
+    /// Queueing items to be transcoded for download.
+    DownloadQueue,
+    /// Tuner devices and DVRs.
+    Dvr,
+    /// A program guide for watching and recording Live TV.
+    LiveTv,
+}
+
+impl fmt::Display for ServerFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::Library => "media libraries",
+            Self::DownloadQueue => "the download queue",
+            Self::Dvr => "DVRs",
+            Self::LiveTv => "Live TV",
+        })
+    }
+}
+
+/// The result of [`Server::health`].
+#[derive(Debug, Clone)]
+pub struct Health {
+    /// How long the server took to answer.
+    pub latency: Duration,
+    pub machine_identifier: String,
+    pub version: ServerVersion,
+}
+
 #[derive(Debug, Clone)]
 pub struct Server {
     client: HttpClient,
     pub myplex_api_url: Uri,
     pub media_container: ServerMediaContainer,
+    capabilities: ServerCapabilities,
+    /// The addresses the server can be reached at, in the order they're
+    /// tried when the current one fails.
+    connections: Vec<Uri>,
 }
 
 impl Server {
@@ -82,7 +194,9 @@ impl Server {
             client.get(SERVER_MEDIA_PROVIDERS).json().await?;
 
         Ok(Self {
+            capabilities: ServerCapabilities::new(&media_container_wrapper.media_container),
             media_container: media_container_wrapper.media_container,
+            connections: vec![client.base_url()],
             client,
             myplex_api_url,
         })
@@ -103,13 +217,55 @@ impl Server {
         .await
     }
 
-    fn content(&self) -> Option<&Vec<ContentDirectory>> {
-        if let Some(provider) = self
-            .media_container
-            .media_providers
+    /// Sets all of the addresses the server is known to be reachable at, in
+    /// the order requests should fail over to them.
+    pub(crate) fn with_connections(mut self, connections: Vec<Uri>) -> Self {
+        self.client = self.client.with_failover(connections.clone());
+        self.connections = connections;
+        self
+    }
+
+    /// The media providers offered by the server, as listed by
+    /// `/media/providers`. These describe where the server's content lives
+    /// and what can be done with it.
+    pub fn media_providers(&self) -> &[MediaProvider] {
+        &self.media_container.media_providers
+    }
+
+    /// Finds a media provider by its identifier, e.g.
+    /// `com.plexapp.plugins.library`.
+    pub fn media_provider(&self, identifier: &str) -> Option<&MediaProvider> {
+        self.media_providers()
             .iter()
-            .find(|p| p.identifier == "com.plexapp.plugins.library")
-        {
+            .find(|p| p.identifier == identifier)
+    }
+
+    /// What the server is able to do.
+    pub fn capabilities(&self) -> &ServerCapabilities {
+        &self.capabilities
+    }
+
+    /// Checks whether the server offers a feature.
+    pub fn has_feature(&self, feature: ServerFeature) -> bool {
+        let capabilities = &self.capabilities;
+        match feature {
+            ServerFeature::Library => capabilities.supports_library(),
+            ServerFeature::DownloadQueue => capabilities.supports_download_queue(),
+            ServerFeature::Dvr => capabilities.supports_tuners(),
+            ServerFeature::LiveTv => capabilities.supports_live_tv(),
+        }
+    }
+
+    fn require(&self, feature: ServerFeature) -> Result {
+        if self.has_feature(feature) {
+            Ok(())
+        } else {
+            Err(Error::ServerFeatureNotAvailable(feature))
+        }
+    }
+
+    fn content(&self) -> Option<&Vec<ContentDirectory>> {
+        if let Some(provider) = self.media_provider(LIBRARY_PROVIDER) {
             for feature in &provider.features {
                 if let MediaProviderFeature::Content {
                     key: _,
@@ -165,7 +321,29 @@ impl Server {
         transcode_artwork(&self.client, art, width, height, options, writer).await
     }
 
+    /// Builds a URL that returns a scaled version of the given artwork, the
+    /// same image that [`Server::transcode_artwork`] would download. The URL
+    /// includes the authentication token so it can be handed to anything that
+    /// loads images (a web view or an image cache for example).
+    pub fn photo_transcode_url(
+        &self,
+        art: &str,
+        width: u32,
+        height: u32,
+        options: ArtTranscodeOptions,
+    ) -> Result<Uri> {
+        authenticated_url(
+            &self.client,
+            SERVER_TRANSCODE_ART,
+            artwork_query(art, width, height, options),
+        )
+    }
+
     /// Retrieves a list of the current transcode sessions.
+    ///
+    /// The returned sessions are full handles, not just statistics, so a
+    /// client that lost track of its sessions (after a crash for example) can
+    /// use them to download the transcoded data or cancel the transcode.
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn transcode_sessions(&self) -> Result<Vec<TranscodeSession>> {
         let wrapper: MediaContainerWrapper<TranscodeSessionsMediaContainer> =
@@ -179,7 +357,8 @@ impl Server {
             .collect())
     }
 
-    /// Retrieves the transcode session with the passed ID.
+    /// Retrieves the transcode session with the passed ID. Use this to attach
+    /// to a session started earlier, see [`TranscodeSession::session_id`].
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn transcode_session(&self, session_id: &str) -> Result<TranscodeSession> {
         let stats = transcode_session_stats(&self.client, session_id).await?;
@@ -190,7 +369,7 @@ impl Server {
     /// their rating key.
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn item_by_id(&self, rating_key: &str) -> Result<Item> {
-        let path = format!("/library/metadata/{rating_key}?includeConcerts=1&includeExtras=1&includePopularLeaves=1&includePreferences=1&includeReviews=1&includeOnDeck=1&includeChapters=1&includeStations=1&includeExternalMedia=1&asyncAugmentMetadata=1&asyncCheckFiles=1&asyncRefreshAnalysis=1&asyncRefreshLocalMediaAgent=1&includeMarkers=1");
+        let path = format!("/library/metadata/{rating_key}?includeConcerts=1&includeExtras=1&includeGuids=1&includePopularLeaves=1&includePreferences=1&includeReviews=1&includeOnDeck=1&includeChapters=1&includeStations=1&includeExternalMedia=1&asyncAugmentMetadata=1&asyncCheckFiles=1&asyncRefreshAnalysis=1&asyncRefreshLocalMediaAgent=1&includeMarkers=1");
 
         match metadata_items(&self.client, &path).await {
             Ok(items) => items.into_iter().next().ok_or(Error::ItemNotFound),
@@ -212,6 +391,19 @@ impl Server {
         }
     }
 
+    /// Finds the items in any of the server's libraries with the given guid,
+    /// either the item's own (e.g. `plex://movie/...`) or an external one
+    /// like `imdb://tt0165832`, see [`ExternalId`](crate::media_container::server::library::ExternalId).
+    /// External guids only match items using the newer agents.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn items_by_guid(&self, guid: &str) -> Result<Vec<Item>> {
+        metadata_items(
+            &self.client,
+            &format!("{SERVER_LIBRARY_ALL}?{}", Query::new().param("guid", guid)),
+        )
+        .await
+    }
+
     /// Marks a media item as fully watched increasing its view count by one.
     pub async fn mark_watched<M: MediaItem + FromMetadata>(&self, item: &M) -> Result<M> {
         let rating_key = item.rating_key();
@@ -271,14 +463,129 @@ impl Server {
 
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn refresh(self) -> Result<Self> {
-        Self::build(self.client, self.myplex_api_url).await
+        let server = Self::build(self.client, self.myplex_api_url).await?;
+        Ok(server.with_connections(self.connections))
     }
 
     pub fn myplex(&self) -> Result<MyPlex> {
         self.myplex_with_api_url(self.myplex_api_url.clone())
     }
 
+    /// Fetches the server's machine identifier, version and whether it has
+    /// been claimed.
+    pub async fn identity(&self) -> Result<Identity> {
+        let wrapper: MediaContainerWrapper<Identity> =
+            self.client.get(SERVER_IDENTITY).json().await?;
+        Ok(wrapper.media_container)
+    }
+
+    /// The accounts that have used or can use the server, with their
+    /// default audio and subtitle preferences.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn accounts(&self) -> Result<Vec<ServerAccount>> {
+        let wrapper: MediaContainerWrapper<AccountsMediaContainer> =
+            self.client.get(SERVER_ACCOUNTS).json().await?;
+        Ok(wrapper.media_container.accounts)
+    }
+
+    /// The account with the passed ID, as found in e.g. the watch history.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn account(&self, id: u32) -> Result<ServerAccount> {
+        let wrapper: MediaContainerWrapper<AccountsMediaContainer> = self
+            .client
+            .get(Endpoint::new(SERVER_ACCOUNT).segment("accountId", id))
+            .json()
+            .await?;
+        wrapper
+            .media_container
+            .accounts
+            .into_iter()
+            .find(|account| account.id == id)
+            .ok_or(Error::ItemNotFound)
+    }
+
+    /// Maps the server's account IDs to the names of the accounts, for
+    /// showing who watched something without asking plex.tv. The system
+    /// account without a name is left out.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn account_names(&self) -> Result<HashMap<u32, String>> {
+        Ok(self
+            .accounts()
+            .await?
+            .into_iter()
+            .filter(|account| !account.name.is_empty())
+            .map(|account| (account.id, account.name))
+            .collect())
+    }
+
+    /// Checks that the server responds, giving up after a few seconds.
     #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn health(&self) -> Result<Health> {
+        let started = Instant::now();
+        let wrapper: MediaContainerWrapper<Identity> = self
+            .client
+            .get(SERVER_IDENTITY)
+            .timeout(Some(HEALTH_CHECK_TIMEOUT))
+            .json()
+            .await?;
+
+        Ok(Health {
+            latency: started.elapsed(),
+            machine_identifier: wrapper.media_container.machine_identifier,
+            version: wrapper.media_container.version,
+        })
+    }
+
+    /// Measures the round-trip time to the server, see [`Server::health`].
+    pub async fn ping(&self) -> Result<Duration> {
+        Ok(self.health().await?.latency)
+    }
+
+    /// Returns the server as is when it still responds at its current
+    /// address, otherwise connects to whichever of its other addresses
+    /// responds first. Servers connected through
+    /// [`Device::connect`](crate::device::Device::connect) know all of the
+    /// addresses plex.tv lists for them, others only the one they were
+    /// created with.
+    ///
+    /// Requests already move to the next address on their own when the
+    /// current one can't be reached, this also covers a server that is
+    /// reachable but not responding.
+    #[tracing::instrument(level = "debug", skip(self), fields(base_url = %self.client.base_url()))]
+    pub async fn reconnect(self) -> Result<Self> {
+        let error = match self.ping().await {
+            Ok(_) => return Ok(self),
+            Err(error) => error,
+        };
+
+        let base_url = self.client.base_url();
+        let futures = self
+            .connections
+            .iter()
+            .filter(|uri| **uri != base_url)
+            .map(|uri| {
+                trace!("Trying {uri}");
+                let client = HttpClientBuilder::from(self.client.clone())
+                    .set_api_url(uri.clone())
+                    .build();
+                let myplex_api_url = self.myplex_api_url.clone();
+                async move { Self::build(client?, myplex_api_url).await }.boxed()
+            })
+            .collect::<Vec<_>>();
+        if futures.is_empty() {
+            return Err(error);
+        }
+
+        let (server, _) = select_ok(futures).await?;
+        debug!("Reconnected via {address}", address = server.client.api_url);
+        // Items loaded from the server follow it to the new address.
+        let client = self.client.moved_to(server.client.api_url);
+        Ok(Self { client, ..server }.with_connections(self.connections))
+    }
+
+    /// Links the server to the Plex account that generated the claim token,
+    /// see [`MyPlex::claim_token`].
+    #[tracing::instrument(level = "debug", skip_all)]
     pub async fn claim(self, claim_token: &str) -> Result<Self> {
         let url = format!(
             "{}?{}",
@@ -295,6 +602,32 @@ impl Server {
         }
     }
 
+    /// Exchanges the client's token for a short-lived one that only works
+    /// with this server, e.g. to hand to a player or another application
+    /// without giving it the account's token.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn transient_token(&self) -> Result<String> {
+        let wrapper: MediaContainerWrapper<TransientToken> = self
+            .client
+            .get(
+                Endpoint::new(SERVER_SECURITY_TOKEN)
+                    .query("type", "delegation")
+                    .query("scope", "all"),
+            )
+            .json()
+            .await?;
+        Ok(wrapper.media_container.token)
+    }
+
+    /// Fetches a claim token from MyPlex and uses it to link the server to
+    /// that account.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn claim_with(self, myplex: &MyPlex) -> Result<Self> {
+        let claim_token = myplex.claim_token().await?;
+        self.claim(&claim_token.to_string()).await
+    }
+
+    /// Unlinks the server from its owner's Plex account.
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn unclaim(self) -> Result<Self> {
         let mut response = self.client.delete(SERVER_MYPLEX_ACCOUNT).send().await?;
@@ -323,15 +656,279 @@ impl Server {
         &self.client
     }
 
+    /// The client for talking to the server directly, e.g. for endpoints the
+    /// crate doesn't support yet. Its requests go to the server's current
+    /// address and carry the same authentication as the crate's own.
+    ///
+    /// ```no_run
+    /// # async fn example(server: plex_api::Server) -> plex_api::Result {
+    /// use plex_api::{endpoints::Endpoint, media_container::MediaContainer};
+    ///
+    /// let container: MediaContainer = server
+    ///     .raw()
+    ///     .get(Endpoint::new("/statistics/bandwidth").query("timespan", "6"))
+    ///     .media_container()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn raw(&self) -> &HttpClient {
+        &self.client
+    }
+
+    /// Returns a copy of the server whose requests belong to a new playback,
+    /// so the server groups the transcode sessions and timeline updates of
+    /// one playback together, e.g. on its dashboard. Items must be retrieved
+    /// through the copy to be part of the playback.
+    ///
+    /// Transcode sessions get a playback of their own when they're not
+    /// created as part of one.
+    pub fn with_playback_session(&self) -> Self {
+        Self {
+            client: self.client.with_playback_session(session_id()),
+            ..self.clone()
+        }
+    }
+
+    /// The playback this server's requests belong to, see
+    /// [`Server::with_playback_session`].
+    pub fn playback_session_id(&self) -> Option<&str> {
+        Some(self.client.x_plex_playback_session_id.as_str()).filter(|id| !id.is_empty())
+    }
+
+    /// Returns a copy of the server whose requests use a different time
+    /// limit. Items retrieved through the copy keep using that limit.
+    pub fn with_timeout(&self, timeout: Option<Duration>) -> Self {
+        Self {
+            client: self.client.with_timeout(timeout),
+            ..self.clone()
+        }
+    }
+
     pub async fn preferences<'a>(&self) -> Result<Preferences<'a>> {
         Preferences::new(&self.client).await
     }
 
+    /// The server's remote access settings and whether plex.tv can reach it
+    /// from outside its network.
+    pub async fn remote_access(&self) -> Result<RemoteAccess> {
+        RemoteAccess::new(self.client.clone()).await
+    }
+
+    /// What the server is currently busy with, e.g. library scans and
+    /// running [butler](Server::butler) tasks, along with their progress.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn activities(&self) -> Result<Vec<Activity>> {
+        let wrapper: MediaContainerWrapper<ActivitiesMediaContainer> =
+            self.client.get(SERVER_ACTIVITIES).json().await?;
+        Ok(wrapper.media_container.activities)
+    }
+
+    /// Runs and inspects the server's scheduled maintenance tasks.
+    pub fn butler(&self) -> Butler {
+        Butler::new(self.client.clone())
+    }
+
+    /// Backs up the server's database and waits for the backup to be
+    /// written, see [`Butler::backup_database`].
+    pub async fn backup_database(&self, timeout: Option<Duration>) -> Result<DatabaseBackup> {
+        self.butler().backup_database(timeout).await
+    }
+
+    /// Lists the server's database backups, see [`Butler::database_backups`].
+    pub async fn database_backups(&self) -> Result<Vec<DatabaseBackup>> {
+        self.butler().database_backups().await
+    }
+
+    /// Creates a smart collection in a movie or TV library. It holds the
+    /// library's items that match the filter, which takes the form of the
+    /// query of a library listing, e.g. `genre=5&year>>=2000`.
+    #[tracing::instrument(level = "debug", skip(self, library), fields(library.id = library.id()))]
+    pub async fn create_smart_collection(
+        &self,
+        library: &Library,
+        title: &str,
+        filter: &str,
+    ) -> Result<Item> {
+        library
+            .create_smart_collection(self.machine_identifier(), title, filter)
+            .await
+    }
+
+    /// Replaces the filter of a smart collection, see
+    /// [`Server::create_smart_collection`].
+    #[tracing::instrument(level = "debug", skip(self, collection), fields(collection.rating_key = collection.rating_key()))]
+    pub async fn set_smart_collection_filter<M>(
+        &self,
+        collection: &Collection<M>,
+        filter: &str,
+    ) -> Result {
+        collection
+            .set_filter(self.machine_identifier(), filter)
+            .await
+    }
+
+    /// Manages the versions of items that the server transcodes ahead of
+    /// time for particular kinds of devices.
+    pub fn optimizer(&self) -> Optimizer {
+        Optimizer::new(self.client.clone(), self.machine_identifier().to_owned())
+    }
+
+    /// The settings controlling how the server transcodes.
+    pub async fn transcoder_settings(&self) -> Result<TranscoderSettings> {
+        TranscoderSettings::load(&self.client).await
+    }
+
+    /// Validates and stores the transcoder settings.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn set_transcoder_settings(&self, settings: &TranscoderSettings) -> Result {
+        settings.save(&self.client).await
+    }
+
+    /// The metadata agents that can match the given type of items, e.g.
+    /// [`SearchType::Movie`] for the agents offered for movie libraries.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn agents(&self, media_type: SearchType) -> Result<Vec<Agent>> {
+        let wrapper: MediaContainerWrapper<AgentsMediaContainer> = self
+            .client
+            .get(format!(
+                "{SERVER_SYSTEM_AGENTS}?{}",
+                Query::new().param("mediaType", (media_type as u16).to_string())
+            ))
+            .json()
+            .await?;
+        Ok(wrapper.media_container.agents)
+    }
+
+    /// The scanners that can find the given type of items in a library's
+    /// folders.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn scanners(&self, media_type: SearchType) -> Result<Vec<Scanner>> {
+        let wrapper: MediaContainerWrapper<ScannersMediaContainer> = self
+            .client
+            .get(Endpoint::new(SERVER_SYSTEM_SCANNERS).segment("mediaType", media_type as u16))
+            .json()
+            .await?;
+        Ok(wrapper.media_container.scanners)
+    }
+
+    /// The agents adding to the metadata found by one of the legacy primary
+    /// agents, in the order they're used.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn agent_contributors(
+        &self,
+        agent: &str,
+        media_type: SearchType,
+    ) -> Result<Vec<AgentContributor>> {
+        let wrapper: MediaContainerWrapper<AgentContributorsMediaContainer> = self
+            .client
+            .get(agent_config_path(agent, media_type))
+            .json()
+            .await?;
+        Ok(wrapper.media_container.contributors)
+    }
+
+    /// Changes the order the contributing agents of a legacy primary agent
+    /// are used in. The order applies to every library using the agent.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn set_agent_contributors_order(
+        &self,
+        agent: &str,
+        media_type: SearchType,
+        order: &[&str],
+    ) -> Result {
+        self.client
+            .put(format!(
+                "{}?{}",
+                agent_config_path(agent, media_type),
+                Query::new().param("order", order.join(","))
+            ))
+            .consume()
+            .await
+    }
+
     pub fn machine_identifier(&self) -> &str {
         &self.media_container.machine_identifier
     }
 
+    /// Lists the DVRs set up for Live TV on this server.
+    pub async fn dvrs(&self) -> Result<Vec<Dvr>> {
+        self.require(ServerFeature::Dvr)?;
+        Dvr::all(self.client.clone()).await
+    }
+
+    /// The program guides provided by the server for Live TV.
+    pub fn guides(&self) -> Vec<Guide> {
+        self.media_container
+            .media_providers
+            .iter()
+            .filter_map(|provider| Guide::from_provider(&self.client, provider))
+            .collect()
+    }
+
+    /// The program guide used by a DVR.
+    pub fn guide(&self, dvr: &Dvr) -> Option<Guide> {
+        let identifier = dvr.epg_identifier()?;
+        self.guides()
+            .into_iter()
+            .find(|guide| guide.identifier() == identifier)
+    }
+
+    /// Lists the DVR's recording subscriptions in priority order.
+    pub async fn subscriptions(&self) -> Result<Vec<Subscription>> {
+        self.require(ServerFeature::LiveTv)?;
+        Subscription::all(self.client.clone()).await
+    }
+
+    /// Schedules a program from the [guide](Server::guide) to be recorded,
+    /// or with [`SubscriptionOptions::series`] every episode of its show.
+    #[tracing::instrument(level = "debug", skip(self, program), fields(title = program.title))]
+    pub async fn subscribe(
+        &self,
+        program: &Program,
+        options: &SubscriptionOptions,
+    ) -> Result<Subscription> {
+        self.require(ServerFeature::LiveTv)?;
+        Subscription::create(self.client.clone(), program, options).await
+    }
+
     pub async fn download_queue(&self) -> Result<DownloadQueue> {
+        self.require(ServerFeature::DownloadQueue)?;
         DownloadQueue::get_or_create(self.client.clone()).await
     }
+
+    /// Retrieves the download queue belonging to another of the account's
+    /// devices, identified by its client identifier.
+    ///
+    /// As with [`Server::download_queue`] the server creates the queue if
+    /// the device doesn't have one yet.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn device_download_queue(&self, client_identifier: &str) -> Result<DownloadQueue> {
+        self.require(ServerFeature::DownloadQueue)?;
+        let client = HttpClientBuilder::from(self.client.clone())
+            .set_x_plex_client_identifier(client_identifier)
+            .build()?;
+        DownloadQueue::get_or_create(client).await
+    }
+
+    /// Retrieves the download queues of all of the account's devices other
+    /// than servers, allowing queues left behind by other clients to be
+    /// inspected and cleaned up. The devices are listed using MyPlex.
+    ///
+    /// The server has no way to only list the queues that exist, so as with
+    /// [`Server::device_download_queue`] a queue is created for every device
+    /// that doesn't have one yet.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_or_create_device_download_queues(&self) -> Result<Vec<DownloadQueue>> {
+        let device_manager = self.myplex()?.device_manager()?;
+        let devices = device_manager.devices().await?;
+
+        try_join_all(
+            devices
+                .iter()
+                .filter(|device| !device.is_server())
+                .map(|device| self.device_download_queue(device.identifier())),
+        )
+        .await
+    }
 }
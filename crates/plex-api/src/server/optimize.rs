@@ -0,0 +1,252 @@
+//! Media optimization, where the server transcodes items ahead of time into
+//! additional versions that suit a kind of device.
+//!
+//! Each optimization request is kept by the server as a generator covering
+//! the requested content, which may be a single movie or a whole show. The
+//! optimized versions show up as extra media of the items.
+
+use super::{
+    library::{metadata_items, Item, MetadataItem},
+    Query, LIBRARY_PROVIDER,
+};
+use crate::{
+    endpoints::Endpoint,
+    isahc_compat::StatusCodeExt,
+    media_container::{server::library::MetadataMediaContainer, MediaContainerWrapper},
+    sync::{SyncScope, SyncState},
+    url::{SERVER_OPTIMIZED_ITEM, SERVER_OPTIMIZED_ITEMS, SERVER_PLAYLISTS, SERVER_PLAYLIST_ITEMS},
+    Error, HttpClient, Result,
+};
+use http::StatusCode;
+use isahc::AsyncReadResponseExt;
+use serde::Deserialize;
+
+/// The playlist type the server keeps optimization requests under.
+const OPTIMIZED_TYPE: &str = "42";
+
+/// The built-in optimization profiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptimizeTarget {
+    Mobile,
+    Tv,
+    /// Keeps the original quality but produces a version that most devices
+    /// can play directly.
+    OriginalQuality,
+}
+
+impl OptimizeTarget {
+    fn tag_id(self) -> u32 {
+        match self {
+            Self::Mobile => 1,
+            Self::Tv => 2,
+            Self::OriginalQuality => 3,
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::Mobile => "Optimized for Mobile",
+            Self::Tv => "Optimized for TV",
+            Self::OriginalQuality => "Original Quality",
+        }
+    }
+
+    fn from_tag_id(id: u32) -> Option<Self> {
+        match id {
+            1 => Some(Self::Mobile),
+            2 => Some(Self::Tv),
+            3 => Some(Self::OriginalQuality),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizePolicy {
+    pub scope: SyncScope,
+    /// The number of items to optimize when the scope is
+    /// [`SyncScope::Count`].
+    #[serde(default)]
+    pub value: u32,
+    /// Whether to only optimize items that haven't been watched yet.
+    #[serde(default)]
+    pub unwatched: bool,
+}
+
+/// The progress of an optimization request.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizeStatus {
+    pub state: SyncState,
+    pub failure: Option<String>,
+    /// The number of media items covered by the request.
+    #[serde(default)]
+    pub items_count: u32,
+    #[serde(default)]
+    pub items_complete_count: u32,
+    #[serde(default)]
+    pub items_successful_count: u32,
+    /// The total size of the optimized versions in bytes.
+    #[serde(default)]
+    pub total_size: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+pub struct OptimizeLocation {
+    pub uri: String,
+}
+
+/// A request to optimize some content.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizedItem {
+    pub id: u32,
+    pub title: String,
+    pub target: Option<String>,
+    #[serde(rename = "targetTagID")]
+    pub target_tag_id: Option<u32>,
+    #[serde(rename = "type")]
+    pub item_type: Option<u32>,
+    #[serde(rename = "Location")]
+    pub location: OptimizeLocation,
+    #[serde(rename = "Policy")]
+    pub policy: Option<OptimizePolicy>,
+    #[serde(rename = "Status")]
+    pub status: Option<OptimizeStatus>,
+}
+
+impl OptimizedItem {
+    /// The built-in profile used, `None` for custom profiles.
+    pub fn target(&self) -> Option<OptimizeTarget> {
+        self.target_tag_id.and_then(OptimizeTarget::from_tag_id)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OptimizedItemsContainer {
+    #[serde(rename = "Item", default)]
+    items: Vec<OptimizedItem>,
+}
+
+/// Manages a server's optimized versions, see [`crate::Server::optimizer`].
+#[derive(Debug, Clone)]
+pub struct Optimizer {
+    client: HttpClient,
+    machine_identifier: String,
+}
+
+impl Optimizer {
+    pub(crate) fn new(client: HttpClient, machine_identifier: String) -> Self {
+        Self {
+            client,
+            machine_identifier,
+        }
+    }
+
+    /// Lists the optimization requests along with their progress.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn items(&self) -> Result<Vec<OptimizedItem>> {
+        let wrapper: MediaContainerWrapper<OptimizedItemsContainer> = self
+            .client
+            .get(format!(
+                "{SERVER_OPTIMIZED_ITEMS}?{}",
+                Query::new().param("type", OPTIMIZED_TYPE)
+            ))
+            .json()
+            .await?;
+        Ok(wrapper.media_container.items)
+    }
+
+    /// Fetches the current state of an optimization request, e.g. to
+    /// monitor its progress.
+    pub async fn item(&self, id: u32) -> Result<OptimizedItem> {
+        self.items()
+            .await?
+            .into_iter()
+            .find(|item| item.id == id)
+            .ok_or(Error::ItemNotFound)
+    }
+
+    /// The library items covered by an optimization request.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn contents(&self, id: u32) -> Result<Vec<Item>> {
+        metadata_items(
+            &self.client,
+            &format!(
+                "{}/items",
+                Endpoint::new(SERVER_OPTIMIZED_ITEM).segment("generatorId", id)
+            ),
+        )
+        .await
+    }
+
+    /// Asks the server to create an optimized version of an item. For shows
+    /// and seasons all of their episodes are optimized.
+    #[tracing::instrument(level = "debug", skip(self, item), fields(item.rating_key = item.rating_key()))]
+    pub async fn queue<M: MetadataItem>(&self, item: &M, target: OptimizeTarget) -> Result {
+        let wrapper: MediaContainerWrapper<MetadataMediaContainer> = self
+            .client
+            .get(format!(
+                "{SERVER_PLAYLISTS}?{}",
+                Query::new().param("type", OPTIMIZED_TYPE)
+            ))
+            .json()
+            .await?;
+        let playlist = wrapper
+            .media_container
+            .metadata
+            .into_iter()
+            .next()
+            .ok_or(Error::ItemNotFound)?;
+
+        let params = Query::new()
+            .param("Item[type]", OPTIMIZED_TYPE)
+            .param("Item[title]", target.title())
+            .param("Item[target]", "")
+            .param("Item[targetTagID]", target.tag_id().to_string())
+            .param("Item[locationID]", "-1")
+            .param(
+                "Item[Location][uri]",
+                format!(
+                    "server://{}/{LIBRARY_PROVIDER}{}",
+                    self.machine_identifier,
+                    item.metadata().key
+                ),
+            )
+            .param("Item[Policy][scope]", SyncScope::All.to_string())
+            .param("Item[Policy][value]", "")
+            .param("Item[Policy][unwatched]", "0");
+
+        self.client
+            .put(format!(
+                "{}?{params}",
+                Endpoint::new(SERVER_PLAYLIST_ITEMS).segment("playlistId", &playlist.rating_key)
+            ))
+            .consume()
+            .await
+    }
+
+    /// Removes an optimization request along with the optimized versions it
+    /// produced.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn delete(&self, id: u32) -> Result {
+        let mut response = self
+            .client
+            .delete(Endpoint::new(SERVER_OPTIMIZED_ITEM).segment("generatorId", id))
+            .send()
+            .await?;
+
+        match response.status().as_http_status() {
+            StatusCode::OK | StatusCode::NO_CONTENT => {
+                response.consume().await?;
+                Ok(())
+            }
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+}
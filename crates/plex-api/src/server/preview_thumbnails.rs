@@ -0,0 +1,100 @@
+//! Parsing of the BIF files the server generates for video scrubbing.
+//!
+//! A BIF file is a 64 byte header followed by an index of timestamps and
+//! offsets and then the JPEG images themselves. All numbers are little-endian.
+
+use std::{ops::Range, time::Duration};
+
+use crate::{Error, Result};
+
+const MAGIC: [u8; 8] = [0x89, b'B', b'I', b'F', 0x0d, 0x0a, 0x1a, 0x0a];
+const HEADER_LENGTH: usize = 64;
+const INDEX_ENTRY_LENGTH: usize = 8;
+const END_OF_INDEX: u32 = u32::MAX;
+
+/// The preview thumbnails of a video file, see
+/// [`Part::preview_thumbnails`](crate::library::Part::preview_thumbnails).
+#[derive(Debug, Clone)]
+pub struct PreviewThumbnails {
+    data: Vec<u8>,
+    frames: Vec<(Duration, Range<usize>)>,
+}
+
+impl PreviewThumbnails {
+    pub(crate) fn parse(data: Vec<u8>) -> Result<Self> {
+        if data.len() < HEADER_LENGTH || data[..MAGIC.len()] != MAGIC {
+            return Err(Error::InvalidPreviewThumbnails(
+                "missing BIF header".to_owned(),
+            ));
+        }
+
+        let count = read_u32(&data, 12)? as usize;
+        // A multiplier of 0 means timestamps are in seconds.
+        let multiplier = match read_u32(&data, 16)? {
+            0 => 1000,
+            multiplier => multiplier,
+        };
+
+        let entry = |index: usize| -> Result<(u32, usize)> {
+            let position = HEADER_LENGTH + index * INDEX_ENTRY_LENGTH;
+            Ok((
+                read_u32(&data, position)?,
+                read_u32(&data, position + 4)? as usize,
+            ))
+        };
+
+        let mut frames = Vec::with_capacity(count);
+        for index in 0..count {
+            let (timestamp, start) = entry(index)?;
+            // The entry after the last image only marks where its data ends.
+            let (next_timestamp, end) = entry(index + 1)?;
+            if start > end
+                || end > data.len()
+                || (index + 1 == count && next_timestamp != END_OF_INDEX)
+            {
+                return Err(Error::InvalidPreviewThumbnails(format!(
+                    "bad index entry {index}"
+                )));
+            }
+
+            frames.push((
+                Duration::from_millis(u64::from(timestamp) * u64::from(multiplier)),
+                start..end,
+            ));
+        }
+
+        Ok(Self { data, frames })
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The JPEG image to show for a position in the video, which is the last
+    /// one at or before it.
+    pub fn at(&self, position: Duration) -> Option<&[u8]> {
+        let index = self
+            .frames
+            .partition_point(|(timestamp, _)| *timestamp <= position);
+        let (_, range) = self.frames.get(index.checked_sub(1)?)?;
+        Some(&self.data[range.clone()])
+    }
+
+    /// All images along with the position in the video they're taken from.
+    pub fn iter(&self) -> impl Iterator<Item = (Duration, &[u8])> {
+        self.frames
+            .iter()
+            .map(|(timestamp, range)| (*timestamp, &self.data[range.clone()]))
+    }
+}
+
+fn read_u32(data: &[u8], position: usize) -> Result<u32> {
+    data.get(position..position + 4)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u32::from_le_bytes)
+        .ok_or_else(|| Error::InvalidPreviewThumbnails("truncated data".to_owned()))
+}
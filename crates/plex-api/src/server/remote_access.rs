@@ -0,0 +1,203 @@
+use secrecy::SecretString;
+use serde::Deserialize;
+use serde_aux::prelude::deserialize_option_number_from_string;
+
+use crate::{
+    media_container::{helpers::optional_boolish, preferences::Value},
+    url::{SERVER_MYPLEX_ACCOUNT, SERVER_PREFS},
+    HttpClient, Preferences, Result,
+};
+
+const PUBLISH_SERVER: &str = "PublishServerOnPlexOnlineKey";
+const MANUAL_PORT_MAPPING_MODE: &str = "ManualPortMappingMode";
+const MANUAL_PORT_MAPPING_PORT: &str = "ManualPortMappingPort";
+
+/// Whether plex.tv managed to reach the server on its public address.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MappingState {
+    Mapped,
+    /// The server is waiting for plex.tv to check the mapping.
+    Waiting,
+    /// plex.tv couldn't reach the server, see
+    /// [`RemoteAccess::mapping_error`].
+    Failed,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+struct AccountState {
+    #[serde(rename = "authToken")]
+    _auth_token: Option<SecretString>,
+    username: Option<String>,
+    mapping_state: MappingState,
+    mapping_error: Option<String>,
+    mapping_error_message: Option<String>,
+    sign_in_state: Option<String>,
+    public_address: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    public_port: Option<u16>,
+    private_address: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    private_port: Option<u16>,
+    #[serde(rename = "subscriptionFeatures")]
+    _subscription_features: Option<String>,
+    #[serde(
+        rename = "subscriptionActive",
+        default,
+        deserialize_with = "optional_boolish"
+    )]
+    _subscription_active: Option<bool>,
+    #[serde(rename = "subscriptionState")]
+    _subscription_state: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AccountWrapper {
+    #[serde(rename = "MyPlex")]
+    account: AccountState,
+}
+
+/// The server's remote access settings and the last known state of its port
+/// mapping.
+#[derive(Debug, Clone)]
+pub struct RemoteAccess {
+    client: HttpClient,
+    state: AccountState,
+    enabled: bool,
+    manual_port: Option<u16>,
+}
+
+impl RemoteAccess {
+    pub(crate) async fn new(client: HttpClient) -> Result<Self> {
+        let (state, enabled, manual_port) = Self::load(&client).await?;
+        Ok(Self {
+            client,
+            state,
+            enabled,
+            manual_port,
+        })
+    }
+
+    async fn load(client: &HttpClient) -> Result<(AccountState, bool, Option<u16>)> {
+        let wrapper: AccountWrapper = client.get(SERVER_MYPLEX_ACCOUNT).json().await?;
+        let prefs = Preferences::new(client).await?;
+
+        let enabled = matches!(
+            prefs.get(PUBLISH_SERVER).map(|s| &s.value),
+            Some(Value::Bool(true))
+        );
+        let manual_port = match (
+            prefs.get(MANUAL_PORT_MAPPING_MODE).map(|s| &s.value),
+            prefs.get(MANUAL_PORT_MAPPING_PORT).map(|s| &s.value),
+        ) {
+            (Some(Value::Bool(true)), Some(Value::Int(port))) => u16::try_from(*port).ok(),
+            _ => None,
+        };
+
+        Ok((wrapper.account, enabled, manual_port))
+    }
+
+    /// Fetches the current state from the server.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn refresh(&mut self) -> Result {
+        (self.state, self.enabled, self.manual_port) = Self::load(&self.client).await?;
+        Ok(())
+    }
+
+    /// Whether the server is published on plex.tv, i.e. remote access is
+    /// turned on.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// The public port that has been forwarded to the server by hand, `None`
+    /// when the port is mapped automatically.
+    pub fn manual_port(&self) -> Option<u16> {
+        self.manual_port
+    }
+
+    pub fn mapping_state(&self) -> MappingState {
+        self.state.mapping_state
+    }
+
+    /// Why plex.tv couldn't reach the server the last time it tried.
+    pub fn mapping_error(&self) -> Option<&str> {
+        self.state
+            .mapping_error_message
+            .as_deref()
+            .or(self.state.mapping_error.as_deref())
+            .filter(|error| !error.is_empty())
+    }
+
+    /// Whether the server is signed in to its owner's Plex account.
+    pub fn is_signed_in(&self) -> bool {
+        self.state.sign_in_state.as_deref() == Some("ok")
+    }
+
+    /// The owner's Plex username.
+    pub fn username(&self) -> Option<&str> {
+        self.state.username.as_deref()
+    }
+
+    pub fn public_address(&self) -> Option<&str> {
+        self.state.public_address.as_deref()
+    }
+
+    pub fn public_port(&self) -> Option<u16> {
+        self.state.public_port
+    }
+
+    pub fn private_address(&self) -> Option<&str> {
+        self.state.private_address.as_deref()
+    }
+
+    pub fn private_port(&self) -> Option<u16> {
+        self.state.private_port
+    }
+
+    async fn set_prefs(&mut self, prefs: &[(&str, Value)]) -> Result {
+        let params = prefs
+            .iter()
+            .map(|(id, value)| (*id, value.to_string()))
+            .collect::<Vec<_>>();
+        let uri = format!("{}?{}", SERVER_PREFS, serde_urlencoded::to_string(params)?);
+        self.client.put(uri).consume().await?;
+        self.refresh().await
+    }
+
+    /// Publishes the server on plex.tv so that it can be reached from outside
+    /// its network.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn enable(&mut self) -> Result {
+        self.set_prefs(&[(PUBLISH_SERVER, Value::Bool(true))]).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn disable(&mut self) -> Result {
+        self.set_prefs(&[(PUBLISH_SERVER, Value::Bool(false))])
+            .await
+    }
+
+    /// Tells the server which public port has been forwarded to it, or to go
+    /// back to mapping the port automatically when `None`.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn set_manual_port(&mut self, port: Option<u16>) -> Result {
+        match port {
+            Some(port) => {
+                self.set_prefs(&[
+                    (MANUAL_PORT_MAPPING_MODE, Value::Bool(true)),
+                    (MANUAL_PORT_MAPPING_PORT, Value::Int(port.into())),
+                ])
+                .await
+            }
+            None => {
+                self.set_prefs(&[(MANUAL_PORT_MAPPING_MODE, Value::Bool(false))])
+                    .await
+            }
+        }
+    }
+}
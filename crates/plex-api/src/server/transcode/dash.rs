@@ -0,0 +1,352 @@
+//! Support for the MPEG-DASH manifests returned by streaming transcodes.
+//!
+//! Only the subset of the format produced by the Plex transcoder is
+//! modelled: periods containing adaptation sets whose segments are described
+//! by segment templates, either with a fixed segment duration or an explicit
+//! segment timeline.
+
+use std::{str::FromStr, time::Duration};
+
+use serde::{Deserialize, Deserializer};
+
+use crate::{Error, Result};
+
+/// Parses an ISO 8601 duration as used by DASH, e.g. `PT1H43M34.293S`.
+/// Negative, infinite or otherwise unrepresentable values are rejected.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.strip_prefix('P')?;
+    let (days, time) = match value.split_once('T') {
+        Some((days, time)) => (days, time),
+        None => (value, ""),
+    };
+
+    let mut seconds = 0.0;
+    if let Some(days) = days.strip_suffix('D') {
+        seconds += parse_part(days)? * 86400.0;
+    } else if !days.is_empty() {
+        return None;
+    }
+
+    let mut number = String::new();
+    for c in time.chars() {
+        let multiplier = match c {
+            'H' => 3600.0,
+            'M' => 60.0,
+            'S' => 1.0,
+            _ => {
+                number.push(c);
+                continue;
+            }
+        };
+        seconds += parse_part(&number)? * multiplier;
+        number.clear();
+    }
+
+    if !number.is_empty() {
+        return None;
+    }
+
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+fn parse_part(number: &str) -> Option<f64> {
+    number
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite() && number.is_sign_positive())
+}
+
+fn deserialize_duration<'de, D>(deserializer: D) -> std::result::Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(value) => parse_duration(&value)
+            .map(Some)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid duration: {value}"))),
+        None => Ok(None),
+    }
+}
+
+/// The root of a DASH manifest (the `MPD` element).
+#[derive(Debug, Clone, Deserialize)]
+pub struct Manifest {
+    /// Either `static` for on-demand content or `dynamic` for live streams.
+    #[serde(rename = "@type")]
+    pub presentation_type: Option<String>,
+    #[serde(
+        rename = "@mediaPresentationDuration",
+        default,
+        deserialize_with = "deserialize_duration"
+    )]
+    pub media_presentation_duration: Option<Duration>,
+    #[serde(
+        rename = "@maxSegmentDuration",
+        default,
+        deserialize_with = "deserialize_duration"
+    )]
+    pub max_segment_duration: Option<Duration>,
+    #[serde(
+        rename = "@minBufferTime",
+        default,
+        deserialize_with = "deserialize_duration"
+    )]
+    pub min_buffer_time: Option<Duration>,
+    #[serde(rename = "Period", default)]
+    pub periods: Vec<Period>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Period {
+    #[serde(rename = "@id")]
+    pub id: Option<String>,
+    #[serde(rename = "@start", default, deserialize_with = "deserialize_duration")]
+    pub start: Option<Duration>,
+    #[serde(
+        rename = "@duration",
+        default,
+        deserialize_with = "deserialize_duration"
+    )]
+    pub duration: Option<Duration>,
+    #[serde(rename = "AdaptationSet", default)]
+    pub adaptation_sets: Vec<AdaptationSet>,
+}
+
+/// A group of interchangeable representations of one component of the
+/// media, e.g. the video or an audio track.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdaptationSet {
+    #[serde(rename = "@id")]
+    pub id: Option<String>,
+    #[serde(rename = "@lang")]
+    pub lang: Option<String>,
+    #[serde(rename = "@mimeType")]
+    pub mime_type: Option<String>,
+    #[serde(rename = "@contentType")]
+    pub content_type: Option<String>,
+    #[serde(rename = "SegmentTemplate")]
+    pub segment_template: Option<SegmentTemplate>,
+    #[serde(rename = "Representation", default)]
+    pub representations: Vec<Representation>,
+}
+
+/// A single encoding of a media component.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Representation {
+    #[serde(rename = "@id")]
+    pub id: String,
+    #[serde(rename = "@mimeType")]
+    pub mime_type: Option<String>,
+    #[serde(rename = "@codecs")]
+    pub codecs: Option<String>,
+    /// Bitrate of the representation in bits per second.
+    #[serde(rename = "@bandwidth")]
+    pub bandwidth: Option<u64>,
+    #[serde(rename = "@width")]
+    pub width: Option<u32>,
+    #[serde(rename = "@height")]
+    pub height: Option<u32>,
+    #[serde(rename = "@audioSamplingRate")]
+    pub audio_sampling_rate: Option<u32>,
+    #[serde(rename = "SegmentTemplate")]
+    pub segment_template: Option<SegmentTemplate>,
+}
+
+/// Describes how to build the URLs of a representation's segments.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SegmentTemplate {
+    #[serde(rename = "@timescale")]
+    pub timescale: Option<u64>,
+    /// Duration of each segment in `timescale` units, when the segments are
+    /// all the same length.
+    #[serde(rename = "@duration")]
+    pub duration: Option<u64>,
+    /// Template for the initialization segment. When retrieved through
+    /// [`TranscodeSession`](super::TranscodeSession) this is a path on the
+    /// server.
+    #[serde(rename = "@initialization")]
+    pub initialization: Option<String>,
+    /// Template for the media segments. When retrieved through
+    /// [`TranscodeSession`](super::TranscodeSession) this is a path on the
+    /// server.
+    #[serde(rename = "@media")]
+    pub media: Option<String>,
+    #[serde(rename = "@startNumber")]
+    pub start_number: Option<u64>,
+    #[serde(rename = "SegmentTimeline")]
+    pub segment_timeline: Option<SegmentTimeline>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SegmentTimeline {
+    #[serde(rename = "S", default)]
+    pub segments: Vec<TimelineSegment>,
+}
+
+/// An entry in a segment timeline, describing one or more consecutive
+/// segments of the same duration.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TimelineSegment {
+    /// Start time in `timescale` units. Continues from the previous entry if
+    /// not set.
+    #[serde(rename = "@t")]
+    pub time: Option<u64>,
+    #[serde(rename = "@d")]
+    pub duration: u64,
+    /// How many more segments follow with the same duration. A negative
+    /// value repeats until the end of the period.
+    #[serde(rename = "@r")]
+    pub repeat: Option<i64>,
+}
+
+/// The locations of all of the segments of a representation, in playback
+/// order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentList {
+    pub initialization: Option<String>,
+    pub media: Vec<String>,
+}
+
+impl FromStr for Manifest {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        quick_xml::de::from_str(text).map_err(|e| Error::InvalidPlaylist(e.to_string()))
+    }
+}
+
+/// Expands the `$Identifier$` placeholders of a segment template.
+fn expand(template: &str, representation: &Representation, number: u64, time: u64) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut parts = template.split('$');
+
+    // Text before the first `$` is never a placeholder, after that the parts
+    // alternate between placeholders and plain text.
+    result.push_str(parts.next().unwrap_or_default());
+    let mut placeholder = true;
+    for part in parts {
+        if !placeholder {
+            result.push_str(part);
+        } else if part.is_empty() {
+            result.push('$');
+        } else {
+            let (name, format) = match part.split_once('%') {
+                Some((name, format)) => (name, Some(format)),
+                None => (part, None),
+            };
+            let value = match name {
+                "RepresentationID" => Some(representation.id.clone()),
+                "Bandwidth" => representation.bandwidth.map(|b| b.to_string()),
+                "Number" => Some(number.to_string()),
+                "Time" => Some(time.to_string()),
+                _ => None,
+            };
+
+            match (value, format) {
+                (Some(value), Some(format)) => {
+                    // Only `%0<width>d` is valid for these identifiers.
+                    let width = format
+                        .trim_start_matches('0')
+                        .trim_end_matches('d')
+                        .parse::<usize>()
+                        .unwrap_or(0);
+                    result.push_str(&format!("{value:0>width$}"));
+                }
+                (Some(value), None) => result.push_str(&value),
+                (None, _) => {
+                    result.push('$');
+                    result.push_str(part);
+                    result.push('$');
+                }
+            }
+        }
+        placeholder = !placeholder;
+    }
+
+    result
+}
+
+impl Period {
+    /// Lists the segments making up one of the representations in this
+    /// period. Returns `None` if the representation isn't described by a
+    /// segment template or if the number of segments can't be determined.
+    ///
+    /// `fallback_duration` is used when the period doesn't specify its own
+    /// duration, normally [`Manifest::media_presentation_duration`].
+    pub fn segments(
+        &self,
+        adaptation_set: &AdaptationSet,
+        representation: &Representation,
+        fallback_duration: Option<Duration>,
+    ) -> Option<SegmentList> {
+        let template = representation
+            .segment_template
+            .as_ref()
+            .or(adaptation_set.segment_template.as_ref())?;
+        let media = template.media.as_deref()?;
+        let timescale = template.timescale.unwrap_or(1).max(1);
+        let start_number = template.start_number.unwrap_or(1);
+        let period_end = self
+            .duration
+            .or(fallback_duration)
+            .map(|d| (d.as_secs_f64() * timescale as f64).round() as u64);
+
+        // Pairs of segment number and start time.
+        let mut entries = Vec::new();
+        if let Some(timeline) = &template.segment_timeline {
+            let mut time = 0;
+            for segment in &timeline.segments {
+                time = segment.time.unwrap_or(time);
+                let repeat = match segment.repeat {
+                    Some(r) if r < 0 => {
+                        let end = period_end?;
+                        end.saturating_sub(time)
+                            .div_ceil(segment.duration.max(1))
+                            .max(1)
+                            - 1
+                    }
+                    Some(r) => r as u64,
+                    None => 0,
+                };
+                for _ in 0..=repeat {
+                    entries.push((start_number + entries.len() as u64, time));
+                    time += segment.duration;
+                }
+            }
+        } else {
+            let duration = template.duration?.max(1);
+            let count = period_end?.div_ceil(duration);
+            for index in 0..count {
+                entries.push((start_number + index, index * duration));
+            }
+        }
+
+        Some(SegmentList {
+            initialization: template
+                .initialization
+                .as_deref()
+                .map(|init| expand(init, representation, start_number, 0)),
+            media: entries
+                .into_iter()
+                .map(|(number, time)| expand(media, representation, number, time))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations() {
+        assert_eq!(
+            parse_duration("PT1H43M34.293S"),
+            Some(Duration::from_millis(6_214_293))
+        );
+        assert_eq!(parse_duration("P1DT1S"), Some(Duration::from_secs(86_401)));
+        for invalid in ["PT-5S", "PTNaNS", "PT1e400S", "PTinfS", "P-1D", "PT5", "1S"] {
+            assert_eq!(parse_duration(invalid), None, "{invalid}");
+        }
+    }
+}
@@ -1,16 +1,27 @@
-use std::{fmt, ops::RangeBounds, str::FromStr};
+use std::{
+    fmt,
+    ops::RangeBounds,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use content_disposition::parse_content_disposition;
-use futures::AsyncWrite;
-use http::StatusCode;
-use isahc::{
-    http::header::CONTENT_DISPOSITION, http::header::CONTENT_LENGTH, AsyncReadResponseExt,
+use futures::{
+    future::{join_all, try_join_all},
+    stream, AsyncSeek, AsyncWrite, Stream as FuturesStream,
 };
+use futures_timer::Delay;
+use http::StatusCode;
+use isahc::{http::header::CONTENT_DISPOSITION, http::header::CONTENT_LENGTH};
 use serde::Deserialize;
 use serde_json::Value;
 
 use crate::{
+    download::{byte_range, Download},
+    endpoints::Endpoint,
     isahc_compat::StatusCodeExt,
+    library::{MetadataItem, Transcodable},
     media_container::{
         server::library::{ContainerFormat, Metadata, Protocol},
         MediaContainerWrapper,
@@ -23,9 +34,24 @@ use crate::{
         DOWNLOAD_QUEUE_ADD, DOWNLOAD_QUEUE_CREATE, DOWNLOAD_QUEUE_DOWNLOAD, DOWNLOAD_QUEUE_ITEM,
         DOWNLOAD_QUEUE_LIST,
     },
-    Error, HttpClient, Result,
+    DownloadOptions, Error, HttpClient, ProgressObserver, Result, SparseFile,
 };
 
+/// The outcome of [`DownloadQueue::clear`] and
+/// [`DownloadQueue::purge_expired`].
+#[derive(Debug, Default)]
+pub struct DeletedItems {
+    /// The IDs of the items that were deleted.
+    pub deleted: Vec<u32>,
+    /// The items that are still in the queue, by ID, and why.
+    pub failed: Vec<(u32, Error)>,
+}
+
+/// The first delay between checks when waiting for an item to change state.
+const WAIT_INITIAL_DELAY: Duration = Duration::from_millis(250);
+/// Checks don't slow down any further than this.
+const WAIT_MAX_DELAY: Duration = Duration::from_secs(5);
+
 #[derive(Deserialize)]
 #[serde(rename_all = "lowercase")]
 enum QueueStatus {
@@ -88,7 +114,8 @@ struct QueueAddedContainer {
 ///
 /// Each server maintains one download queue per user per device.
 pub struct DownloadQueue {
-    client: HttpClient,
+    // Shared with every item of the queue rather than copied into each.
+    client: Arc<HttpClient>,
     id: u32,
 }
 
@@ -106,7 +133,7 @@ impl DownloadQueue {
 
         if let Some(queue) = wrapper.media_container.queues.first() {
             Ok(Self {
-                client,
+                client: Arc::new(client),
                 id: queue.id,
             })
         } else {
@@ -114,11 +141,16 @@ impl DownloadQueue {
         }
     }
 
+    /// The client identifier of the device that this queue belongs to.
+    pub fn client_identifier(&self) -> &str {
+        &self.client.x_plex_client_identifier
+    }
+
     /// Lists the items in this download queue.
     pub async fn items(&self) -> Result<Vec<QueueItem>> {
         Ok(self
             .client
-            .get(DOWNLOAD_QUEUE_LIST.replace("{queueId}", &self.id.to_string()))
+            .get(Endpoint::new(DOWNLOAD_QUEUE_LIST).segment("queueId", self.id))
             .json::<MediaContainerWrapper<QueueItemContainer>>()
             .await?
             .media_container
@@ -131,6 +163,45 @@ impl DownloadQueue {
             .collect())
     }
 
+    /// Deletes every item in this download queue.
+    pub async fn clear(&self) -> Result<DeletedItems> {
+        self.delete_where(|_| true).await
+    }
+
+    /// Deletes the items that have expired or failed to transcode.
+    pub async fn purge_expired(&self) -> Result<DeletedItems> {
+        self.delete_where(|status| {
+            matches!(status, QueueItemStatus::Expired | QueueItemStatus::Error)
+        })
+        .await
+    }
+
+    /// Deletes the matching items, all at once. An item that can't be deleted
+    /// doesn't stop the others from being deleted.
+    async fn delete_where<F>(&self, matches: F) -> Result<DeletedItems>
+    where
+        F: Fn(&QueueItemStatus) -> bool,
+    {
+        let items = self
+            .items()
+            .await?
+            .into_iter()
+            .filter(|item| matches(&item.state.status))
+            .map(|item| async move {
+                let id = item.state.id;
+                (id, item.delete().await)
+            });
+
+        let mut deleted = DeletedItems::default();
+        for (id, result) in join_all(items).await {
+            match result {
+                Ok(()) => deleted.deleted.push(id),
+                Err(error) => deleted.failed.push((id, error)),
+            }
+        }
+        Ok(deleted)
+    }
+
     /// Gets a specific item in this download queue by its ID.
     pub async fn item(&self, id: u32) -> Result<QueueItem> {
         let state = QueueItemState::fetch(&self.client, self.id, id).await?;
@@ -153,8 +224,44 @@ impl DownloadQueue {
         part_index: Option<usize>,
         options: O,
     ) -> Result<QueueItem> {
+        self.add(&[metadata], media_index, part_index, options)
+            .await?
+            .pop()
+            .ok_or(Error::ItemNotFound)
+    }
+
+    /// Adds a number of media items (for example all the episodes of a season)
+    /// to this download queue in a single request, all using the same
+    /// transcode options. The queue items are returned in the same order as
+    /// the media items.
+    ///
+    /// As with [`Transcodable::queue_download`] items already in the queue
+    /// with the same options are returned rather than added again.
+    pub async fn add_items<M>(&self, items: &[M], options: M::Options) -> Result<Vec<QueueItem>>
+    where
+        M: Transcodable + MetadataItem,
+    {
+        let metadata: Vec<&Metadata> = items.iter().map(|item| item.metadata()).collect();
+        self.add(&metadata, None, None, options).await
+    }
+
+    async fn add<O: TranscodeOptions>(
+        &self,
+        metadata: &[&Metadata],
+        media_index: Option<usize>,
+        part_index: Option<usize>,
+        options: O,
+    ) -> Result<Vec<QueueItem>> {
+        let Some(first) = metadata.first() else {
+            return Ok(Vec::new());
+        };
+
         let id = session_id();
-        let key = &metadata.key;
+        let keys = metadata
+            .iter()
+            .map(|m| m.key.as_str())
+            .collect::<Vec<_>>()
+            .join(",");
 
         let params = get_transcode_params(
             &id,
@@ -164,28 +271,32 @@ impl DownloadQueue {
             part_index,
             options,
         )?
-        .param("keys", &metadata.key)
-        .param("path", &metadata.key);
+        .param("keys", keys)
+        .param("path", &first.key);
 
         let wrapper: MediaContainerWrapper<QueueAddedContainer> = self
             .client
             .post(format!(
                 "{}?{params}",
-                DOWNLOAD_QUEUE_ADD.replace("{queueId}", &self.id.to_string())
+                Endpoint::new(DOWNLOAD_QUEUE_ADD).segment("queueId", self.id)
             ))
             .json()
             .await?;
+        let added = wrapper.media_container.items;
 
-        if let Some(item) = wrapper.media_container.items.iter().find(|i| &i.key == key) {
-            let item = QueueItemState::fetch(&self.client, self.id, item.id).await?;
+        try_join_all(metadata.iter().map(|m| async {
+            let item = added
+                .iter()
+                .find(|i| i.key == m.key)
+                .ok_or(Error::ItemNotFound)?;
+            let state = QueueItemState::fetch(&self.client, self.id, item.id).await?;
 
-            Ok(QueueItem {
+            Ok::<_, Error>(QueueItem {
                 client: self.client.clone(),
-                state: item,
+                state,
             })
-        } else {
-            Err(Error::ItemNotFound)
-        }
+        }))
+        .await
     }
 }
 
@@ -212,9 +323,9 @@ impl QueueItemState {
     async fn fetch(client: &HttpClient, queue_id: u32, id: u32) -> Result<Self> {
         let items = client
             .get(
-                DOWNLOAD_QUEUE_ITEM
-                    .replace("{queueId}", &queue_id.to_string())
-                    .replace("{itemId}", &id.to_string()),
+                Endpoint::new(DOWNLOAD_QUEUE_ITEM)
+                    .segment("queueId", queue_id)
+                    .segment("itemId", id),
             )
             .json::<MediaContainerWrapper<QueueItemContainer>>()
             .await?
@@ -233,7 +344,7 @@ struct QueueItemContainer {
 
 /// An item in a download queue.
 pub struct QueueItem {
-    client: HttpClient,
+    client: Arc<HttpClient>,
     state: QueueItemState,
 }
 
@@ -263,6 +374,12 @@ impl QueueItem {
         self.state.status.clone()
     }
 
+    /// How the server decided to deliver this item, e.g. why it needs to be
+    /// transcoded.
+    pub fn decision(&self) -> &DecisionResult {
+        &self.state.decision_result
+    }
+
     /// If this item is currently being transcoded this will return the current
     /// transcode stats.
     pub fn stats(&self) -> Option<TranscodeSessionStats> {
@@ -291,9 +408,9 @@ impl QueueItem {
         // header of the download endpoint does include the filename complete
         // with correct extension for the container so we can use that.
 
-        let path = DOWNLOAD_QUEUE_DOWNLOAD
-            .replace("{queueId}", &self.state.queue_id.to_string())
-            .replace("{itemId}", &self.state.id.to_string());
+        let path = Endpoint::new(DOWNLOAD_QUEUE_DOWNLOAD)
+            .segment("queueId", self.state.queue_id)
+            .segment("itemId", self.state.id);
 
         let response = self.client.head(path).send().await?;
         match response.status().as_http_status() {
@@ -324,9 +441,9 @@ impl QueueItem {
     ///
     /// This will fail if the item is not available.
     pub async fn content_length(&self) -> Result<Option<u64>> {
-        let path = DOWNLOAD_QUEUE_DOWNLOAD
-            .replace("{queueId}", &self.state.queue_id.to_string())
-            .replace("{itemId}", &self.state.id.to_string());
+        let path = Endpoint::new(DOWNLOAD_QUEUE_DOWNLOAD)
+            .segment("queueId", self.state.queue_id)
+            .segment("itemId", self.state.id);
 
         let response = self.client.head(path).send().await?;
         match response.status().as_http_status() {
@@ -353,6 +470,111 @@ impl QueueItem {
         Ok(())
     }
 
+    /// Waits until the server has decided how to process this item, i.e. its
+    /// status is no longer [`QueueItemStatus::Deciding`].
+    ///
+    /// The item's status is polled with an increasing delay between checks.
+    /// Fails with [`Error::Timeout`] if `timeout` passes first and with
+    /// [`Error::TranscodeError`] or [`Error::QueueItemExpired`] if the item
+    /// fails or expires. Dropping the returned future stops waiting.
+    pub async fn wait_for_decision(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.wait_while(|status| *status == QueueItemStatus::Deciding, timeout)
+            .await
+    }
+
+    /// Streams the status of this item, polling the server every
+    /// `poll_interval`. The current status comes first. The stream ends once
+    /// the item is available, has failed or has expired, or after a request
+    /// fails.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use futures::TryStreamExt;
+    /// # async fn example(item: plex_api::transcode::QueueItem) -> plex_api::Result<()> {
+    /// let mut statuses = std::pin::pin!(item.status_stream(Duration::from_secs(1)));
+    /// while let Some(status) = statuses.try_next().await? {
+    ///     println!("{status:?}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn status_stream(
+        &self,
+        poll_interval: Duration,
+    ) -> impl FuturesStream<Item = Result<QueueItemStatus>> + Send + 'static {
+        let client = self.client.clone();
+        let (queue_id, id) = (self.state.queue_id, self.state.id);
+
+        // The status to report without asking the server, if any, until the
+        // stream ends.
+        let initial = Some(Some(self.state.status.clone()));
+        stream::try_unfold(initial, move |next| {
+            let client = client.clone();
+            async move {
+                let status = match next {
+                    None => return Ok(None),
+                    Some(Some(status)) => status,
+                    Some(None) => {
+                        Delay::new(poll_interval).await;
+                        QueueItemState::fetch(&client, queue_id, id).await?.status
+                    }
+                };
+
+                let finished = matches!(
+                    status,
+                    QueueItemStatus::Available | QueueItemStatus::Error | QueueItemStatus::Expired
+                );
+                Ok(Some((status, (!finished).then_some(None))))
+            }
+        })
+    }
+
+    /// Waits until this item is available for download, with the same
+    /// behaviour as [`QueueItem::wait_for_decision`].
+    pub async fn wait_until_available(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.wait_while(|status| *status != QueueItemStatus::Available, timeout)
+            .await
+    }
+
+    async fn wait_while<F>(&mut self, waiting: F, timeout: Option<Duration>) -> Result<()>
+    where
+        F: Fn(&QueueItemStatus) -> bool,
+    {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut delay = WAIT_INITIAL_DELAY;
+
+        loop {
+            match self.state.status {
+                QueueItemStatus::Error => {
+                    return Err(Error::TranscodeError(
+                        self.state
+                            .error
+                            .clone()
+                            .unwrap_or_else(|| "unknown error".to_string()),
+                    ))
+                }
+                QueueItemStatus::Expired => return Err(Error::QueueItemExpired),
+                ref status if !waiting(status) => return Ok(()),
+                _ => (),
+            }
+
+            let sleep = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Err(Error::Timeout);
+                    }
+                    delay.min(remaining)
+                }
+                None => delay,
+            };
+
+            Delay::new(sleep).await;
+            delay = (delay * 2).min(WAIT_MAX_DELAY);
+            self.update().await?;
+        }
+    }
+
     /// Downloads the item to the provided writer.
     ///
     /// This will fail if the item is not available.
@@ -361,51 +583,92 @@ impl QueueItem {
         W: AsyncWrite + Unpin,
         R: RangeBounds<u64>,
     {
-        let path = DOWNLOAD_QUEUE_DOWNLOAD
-            .replace("{queueId}", &self.state.queue_id.to_string())
-            .replace("{itemId}", &self.state.id.to_string());
-
-        let start = match range.start_bound() {
-            std::ops::Bound::Included(v) => *v,
-            std::ops::Bound::Excluded(v) => v + 1,
-            std::ops::Bound::Unbounded => 0,
-        };
+        self.download_with(writer, range, DownloadOptions::default(), ())
+            .await
+    }
 
-        let end = match range.end_bound() {
-            std::ops::Bound::Included(v) => Some(*v),
-            std::ops::Bound::Excluded(v) => Some(v - 1),
-            std::ops::Bound::Unbounded => None,
+    /// Same as [`QueueItem::download`] but allows resuming, parallel
+    /// downloads and progress reporting to be configured. See
+    /// [`DownloadOptions`].
+    pub async fn download_with<W, R, O>(
+        &self,
+        writer: W,
+        range: R,
+        options: DownloadOptions,
+        observer: O,
+    ) -> Result
+    where
+        W: AsyncWrite + Unpin,
+        R: RangeBounds<u64>,
+        O: ProgressObserver,
+    {
+        let path = Endpoint::new(DOWNLOAD_QUEUE_DOWNLOAD)
+            .segment("queueId", self.state.queue_id)
+            .segment("itemId", self.state.id)
+            .to_string();
+
+        let (start, end) = byte_range(range);
+
+        // Splitting the download into chunks needs to know where it ends.
+        let size = if options.connections > 1 && end.is_none() {
+            self.content_length().await?
+        } else {
+            None
         };
 
-        let mut builder = self.client.get(path).timeout(None);
-        if start != 0 || end.is_some() {
-            // We're requesting part of the file.
-            let end = end.map(|v| v.to_string()).unwrap_or_default();
-            builder = builder.header("Range", format!("bytes={start}-{end}"))
+        Download {
+            client: &self.client,
+            path: &path,
+            start,
+            end,
+            size,
+            timeout: None,
+            transcoding: true,
         }
+        .run(writer, options, observer)
+        .await
+    }
 
-        let mut response = builder.send().await?;
-        match response.status().as_http_status() {
-            StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
-                response.copy_to(writer).await?;
-                Ok(())
-            }
-            StatusCode::SERVICE_UNAVAILABLE => Err(Error::TranscodeIncomplete),
-            _ => Err(crate::Error::from_response(response).await),
+    /// Downloads the item into a [`SparseFile`], writing each part of the
+    /// file at its offset. If this fails the file keeps what was downloaded
+    /// and calling this again continues from there.
+    pub async fn download_sparse<F, O>(
+        &self,
+        file: &mut SparseFile<F>,
+        options: DownloadOptions,
+        observer: O,
+    ) -> Result
+    where
+        F: AsyncWrite + AsyncSeek + Unpin,
+        O: ProgressObserver,
+    {
+        let path = Endpoint::new(DOWNLOAD_QUEUE_DOWNLOAD)
+            .segment("queueId", self.state.queue_id)
+            .segment("itemId", self.state.id)
+            .to_string();
+
+        Download {
+            client: &self.client,
+            path: &path,
+            start: 0,
+            end: None,
+            size: None,
+            timeout: None,
+            transcoding: true,
         }
+        .run_sparse(file, options, observer)
+        .await
     }
 
     /// Deletes this item from the download queue.
     pub async fn delete(self) -> Result<()> {
         self.client
             .delete(
-                DOWNLOAD_QUEUE_ITEM
-                    .replace("{queueId}", &self.state.queue_id.to_string())
-                    .replace("{itemId}", &self.state.id.to_string()),
+                Endpoint::new(DOWNLOAD_QUEUE_ITEM)
+                    .segment("queueId", self.state.queue_id)
+                    .segment("itemId", self.state.id),
             )
-            .send()
-            .await?;
-
-        Ok(())
+            .consume()
+            .await
     }
 }
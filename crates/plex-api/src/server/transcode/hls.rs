@@ -0,0 +1,277 @@
+//! Support for the HTTP Live Streaming playlists returned by streaming
+//! transcodes.
+//!
+//! Only the parts of the format that the Plex transcoder produces are
+//! supported: a master playlist listing one or more variant streams and media
+//! playlists listing the segments of each variant.
+
+use std::str::FromStr;
+
+use http::Uri;
+
+use crate::{Error, Result};
+
+/// One of the variant streams listed in a master playlist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariantStream {
+    /// The location of the variant's media playlist. When retrieved through
+    /// [`TranscodeSession`](super::TranscodeSession) this is a path on the
+    /// server, playlists pointing elsewhere are rejected.
+    pub uri: String,
+    /// Peak bitrate of the stream in bits per second.
+    pub bandwidth: u64,
+    pub average_bandwidth: Option<u64>,
+    pub codecs: Option<String>,
+    /// Width and height of the video.
+    pub resolution: Option<(u32, u32)>,
+    pub frame_rate: Option<f32>,
+}
+
+/// The top level playlist of an HLS stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MasterPlaylist {
+    pub variants: Vec<VariantStream>,
+}
+
+/// A single piece of media listed in a media playlist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    /// The location of the segment. When retrieved through
+    /// [`TranscodeSession`](super::TranscodeSession) this is a path on the
+    /// server, playlists pointing elsewhere are rejected.
+    pub uri: String,
+    /// Duration of the segment in seconds.
+    pub duration: f32,
+    pub title: Option<String>,
+}
+
+/// The list of segments making up a variant stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaPlaylist {
+    /// The maximum duration of any segment in seconds.
+    pub target_duration: u64,
+    /// The sequence number of the first segment.
+    pub media_sequence: u64,
+    pub segments: Vec<Segment>,
+    /// Whether the playlist is complete. If not more segments will be added
+    /// as the transcode progresses.
+    pub ended: bool,
+}
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::InvalidPlaylist(message.into())
+}
+
+/// Returns the playlist's lines, verifying that it's actually a playlist.
+fn lines(text: &str) -> Result<impl Iterator<Item = &str>> {
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    match lines.next() {
+        Some("#EXTM3U") => Ok(lines),
+        _ => Err(invalid("missing #EXTM3U header")),
+    }
+}
+
+/// Splits an attribute list (`NAME=value,NAME="quoted, value"`) into its
+/// parts.
+fn attributes(list: &str) -> Vec<(&str, &str)> {
+    let mut result = Vec::new();
+    let mut rest = list;
+
+    while let Some(eq) = rest.find('=') {
+        let name = rest[..eq].trim();
+        let value_start = &rest[eq + 1..];
+
+        let (value, remainder) = if let Some(quoted) = value_start.strip_prefix('"') {
+            match quoted.find('"') {
+                Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                None => (quoted, ""),
+            }
+        } else {
+            match value_start.find(',') {
+                Some(end) => (&value_start[..end], &value_start[end..]),
+                None => (value_start, ""),
+            }
+        };
+
+        result.push((name, value));
+        rest = remainder.trim_start_matches(',');
+    }
+
+    result
+}
+
+fn parse_number<T: FromStr>(tag: &str, value: &str) -> Result<T> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| invalid(format!("invalid value for {tag}: {value}")))
+}
+
+impl FromStr for MasterPlaylist {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let mut variants = Vec::new();
+        let mut pending: Option<VariantStream> = None;
+
+        for line in lines(text)? {
+            if let Some(list) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+                let mut variant = VariantStream {
+                    uri: String::new(),
+                    bandwidth: 0,
+                    average_bandwidth: None,
+                    codecs: None,
+                    resolution: None,
+                    frame_rate: None,
+                };
+
+                for (name, value) in attributes(list) {
+                    match name {
+                        "BANDWIDTH" => variant.bandwidth = parse_number(name, value)?,
+                        "AVERAGE-BANDWIDTH" => {
+                            variant.average_bandwidth = Some(parse_number(name, value)?)
+                        }
+                        "CODECS" => variant.codecs = Some(value.to_owned()),
+                        "RESOLUTION" => {
+                            let (width, height) = value
+                                .split_once('x')
+                                .ok_or_else(|| invalid(format!("invalid resolution: {value}")))?;
+                            variant.resolution =
+                                Some((parse_number(name, width)?, parse_number(name, height)?));
+                        }
+                        "FRAME-RATE" => variant.frame_rate = Some(parse_number(name, value)?),
+                        _ => (),
+                    }
+                }
+
+                pending = Some(variant);
+            } else if !line.starts_with('#') {
+                if let Some(mut variant) = pending.take() {
+                    variant.uri = line.to_owned();
+                    variants.push(variant);
+                }
+            }
+        }
+
+        if variants.is_empty() {
+            return Err(invalid("no variant streams found"));
+        }
+
+        Ok(Self { variants })
+    }
+}
+
+impl FromStr for MediaPlaylist {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let mut playlist = MediaPlaylist {
+            target_duration: 0,
+            media_sequence: 0,
+            segments: Vec::new(),
+            ended: false,
+        };
+        let mut pending: Option<(f32, Option<String>)> = None;
+
+        for line in lines(text)? {
+            if let Some(value) = line.strip_prefix("#EXT-X-TARGETDURATION:") {
+                playlist.target_duration = parse_number("#EXT-X-TARGETDURATION", value)?;
+            } else if let Some(value) = line.strip_prefix("#EXT-X-MEDIA-SEQUENCE:") {
+                playlist.media_sequence = parse_number("#EXT-X-MEDIA-SEQUENCE", value)?;
+            } else if let Some(value) = line.strip_prefix("#EXTINF:") {
+                let (duration, title) = match value.split_once(',') {
+                    Some((duration, title)) if !title.is_empty() => {
+                        (duration, Some(title.to_owned()))
+                    }
+                    Some((duration, _)) => (duration, None),
+                    None => (value, None),
+                };
+                pending = Some((parse_number("#EXTINF", duration)?, title));
+            } else if line == "#EXT-X-ENDLIST" {
+                playlist.ended = true;
+            } else if !line.starts_with('#') {
+                let (duration, title) = pending
+                    .take()
+                    .ok_or_else(|| invalid(format!("segment {line} has no duration")))?;
+                playlist.segments.push(Segment {
+                    uri: line.to_owned(),
+                    duration,
+                    title,
+                });
+            }
+        }
+
+        Ok(playlist)
+    }
+}
+
+/// Resolves a URI from a playlist against the path of the playlist itself.
+///
+/// Absolute URIs are turned into paths when they point at the server the
+/// playlist came from. Requests carry the server's token, so pointing to
+/// anywhere else is an error rather than something to follow.
+pub(crate) fn resolve(server: &Uri, playlist_path: &str, uri: &str) -> Result<String> {
+    if uri.contains("://") || uri.starts_with("//") {
+        let absolute = match uri.strip_prefix("//") {
+            Some(rest) => format!("{}://{rest}", server.scheme_str().unwrap_or("http")),
+            None => uri.to_owned(),
+        };
+        let parsed: Uri = absolute
+            .parse()
+            .map_err(|_| invalid(format!("invalid URI: {uri}")))?;
+        if parsed.scheme() != server.scheme() || parsed.authority() != server.authority() {
+            return Err(invalid(format!("{uri} isn't on the server")));
+        }
+        return Ok(parsed
+            .path_and_query()
+            .map_or("/", |path| path.as_str())
+            .to_owned());
+    }
+
+    if uri.starts_with('/') {
+        return Ok(uri.to_owned());
+    }
+
+    let path = playlist_path.split('?').next().unwrap_or_default();
+    Ok(match path.rfind('/') {
+        Some(idx) => format!("{}{uri}", &path[..=idx]),
+        None => uri.to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_uris() {
+        let server: Uri = "http://192.168.0.2:32400".parse().unwrap();
+        let playlist = "/video/:/transcode/universal/start.m3u8?session=abc";
+
+        let resolved = |uri| resolve(&server, playlist, uri);
+        assert_eq!(
+            resolved("session/abc/base/index.m3u8").unwrap(),
+            "/video/:/transcode/universal/session/abc/base/index.m3u8"
+        );
+        assert_eq!(resolved("/segment/0.ts").unwrap(), "/segment/0.ts");
+        assert_eq!(
+            resolved("http://192.168.0.2:32400/segment/0.ts?x=1").unwrap(),
+            "/segment/0.ts?x=1"
+        );
+        assert_eq!(
+            resolved("//192.168.0.2:32400/segment/0.ts").unwrap(),
+            "/segment/0.ts"
+        );
+        for elsewhere in [
+            "http://example.com/segment/0.ts",
+            "https://192.168.0.2:32400/segment/0.ts",
+            "http://192.168.0.2:32401/segment/0.ts",
+            "//example.com/segment/0.ts",
+        ] {
+            assert!(
+                matches!(resolved(elsewhere), Err(Error::InvalidPlaylist(_))),
+                "{elsewhere}"
+            );
+        }
+    }
+}
@@ -16,7 +16,10 @@
 //! This feature should be considered quite experimental, lots of the API calls
 //! are derived from inspection and guesswork.
 
+pub mod dash;
 pub(crate) mod download_queue;
+pub mod hls;
+mod profile;
 pub(crate) mod session;
 
 use std::{collections::HashMap, fmt::Display};
@@ -40,7 +43,8 @@ use crate::{
 
 use super::Query;
 
-pub use download_queue::{DownloadQueue, QueueItem, QueueItemStatus};
+pub use download_queue::{DeletedItems, DownloadQueue, QueueItem, QueueItemStatus};
+pub use profile::ClientProfile;
 pub use session::{TranscodeSession, TranscodeStatus};
 
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
@@ -55,24 +59,33 @@ pub enum Context {
 
 derive_display_from_serialize!(Context);
 
+/// The server's explanation of how it decided to deliver an item.
+///
+/// Codes in the 1000s mean playback is possible (1000 for direct play, 1001
+/// for a transcode), 2000s and 3000s explain what ruled an option out. The
+/// texts are meant to be shown to people.
 #[derive(Debug, Clone, Deserialize)]
-#[allow(dead_code)]
 #[serde(rename_all = "camelCase")]
 #[cfg_attr(feature = "tests_deny_unknown_fields", serde(deny_unknown_fields))]
-struct DecisionResult {
-    available_bandwidth: Option<u32>,
+pub struct DecisionResult {
+    /// The bandwidth in kbps the server assumed was available.
+    pub available_bandwidth: Option<u32>,
 
-    mde_decision_code: Option<u32>,
-    mde_decision_text: Option<String>,
+    /// The media decision engine's own verdict.
+    pub mde_decision_code: Option<u32>,
+    pub mde_decision_text: Option<String>,
 
-    general_decision_code: Option<u32>,
-    general_decision_text: Option<String>,
+    /// The overall outcome.
+    pub general_decision_code: Option<u32>,
+    pub general_decision_text: Option<String>,
 
-    direct_play_decision_code: Option<u32>,
-    direct_play_decision_text: Option<String>,
+    /// Why the original file could or couldn't be played as is.
+    pub direct_play_decision_code: Option<u32>,
+    pub direct_play_decision_text: Option<String>,
 
-    transcode_decision_code: Option<u32>,
-    transcode_decision_text: Option<String>,
+    /// Why the streams had to be transcoded, or why transcoding failed.
+    pub transcode_decision_code: Option<u32>,
+    pub transcode_decision_text: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -155,6 +168,8 @@ pub enum VideoSetting {
     Profile,
     /// Framerate.
     FrameRate,
+    /// Colour transfer characteristics, e.g. `smpte2084` for HDR10.
+    ColorTransfer,
 }
 
 impl Display for VideoSetting {
@@ -169,6 +184,7 @@ impl Display for VideoSetting {
                 VideoSetting::Level => "video.level",
                 VideoSetting::Profile => "video.profile",
                 VideoSetting::FrameRate => "video.frameRate",
+                VideoSetting::ColorTransfer => "video.colorTrc",
             }
         )
     }
@@ -289,6 +305,187 @@ pub trait TranscodeOptions {
     ) -> HashMap<String, String>;
 }
 
+/// How subtitles are delivered in a video transcode.
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleMode {
+    /// Let the server decide based on the subtitle codecs the client
+    /// supports.
+    #[default]
+    Auto,
+    /// Render the selected subtitles into the video.
+    Burn,
+    /// Deliver the selected subtitles as a separate file.
+    Sidecar,
+    /// Leave subtitles out entirely.
+    None,
+}
+
+derive_display_from_serialize!(SubtitleMode);
+
+/// The best dynamic range a client can display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DynamicRange {
+    /// Standard dynamic range only. HDR sources are tone mapped down, which
+    /// needs [`TranscoderSettings::tone_mapping`](crate::TranscoderSettings::tone_mapping)
+    /// to be enabled, otherwise they look dim and washed out.
+    Sdr,
+    /// HDR10 and HLG video can be passed through or transcoded as 10-bit HDR.
+    Hdr,
+}
+
+impl DynamicRange {
+    fn limitations(&self) -> Vec<Limitation<VideoCodec, VideoSetting>> {
+        match self {
+            DynamicRange::Sdr => vec![
+                (VideoSetting::BitDepth, Constraint::Max("8".to_string())).into(),
+                (
+                    VideoSetting::ColorTransfer,
+                    Constraint::NotMatch("smpte2084".to_string()),
+                )
+                    .into(),
+                (
+                    VideoSetting::ColorTransfer,
+                    Constraint::NotMatch("arib-std-b67".to_string()),
+                )
+                    .into(),
+            ],
+            DynamicRange::Hdr => {
+                vec![(VideoSetting::BitDepth, Constraint::Max("10".to_string())).into()]
+            }
+        }
+    }
+}
+
+/// Allows the server to vary the quality of a streaming transcode to match
+/// the bandwidth available to the client.
+///
+/// The configured [`VideoTranscodeOptions::bitrate`] becomes the starting
+/// point. Use [`TranscodeSession::set_bitrate`] to move it as the client's
+/// connection changes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AdaptiveQuality {
+    /// The highest bitrate in kbps that the stream may briefly burst to.
+    pub peak_bitrate: Option<u32>,
+    /// Size of the client's playback buffer in kilobytes. A larger buffer
+    /// lets the server hold on to a higher quality for longer when the
+    /// connection slows down.
+    pub media_buffer_size: Option<u32>,
+}
+
+impl AdaptiveQuality {
+    fn params(&self) -> Query {
+        let mut query = Query::new()
+            .param("autoAdjustQuality", bs(true))
+            .param("hasMDE", bs(true));
+
+        if let Some(peak) = self.peak_bitrate {
+            query = query.param("peakBitrate", peak.to_string());
+        }
+        if let Some(size) = self.media_buffer_size {
+            query = query.param("mediaBufferSize", size.to_string());
+        }
+
+        query
+    }
+}
+
+/// How the server schedules a transcode against the others it's running.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TranscodePriority {
+    #[default]
+    Normal,
+    /// For bulk offline transcodes that shouldn't slow down playback on
+    /// other clients. The server uses its background transcoder preset
+    /// for these, see [`TranscoderSettings`](crate::TranscoderSettings).
+    Background,
+}
+
+/// Settings for the transcode session itself rather than its output.
+#[derive(Debug, Clone, Default)]
+pub struct SessionOptions {
+    pub priority: TranscodePriority,
+    /// Whether the server pauses the transcode once it's far enough ahead
+    /// of the client. `None` leaves it to the server, which throttles
+    /// streaming transcodes.
+    pub throttle: Option<bool>,
+    /// Describes what the client can decode in the
+    /// `X-Plex-Client-Capabilities` format, e.g.
+    /// `protocols=http-live-streaming;videoDecoders=h264{profile:high&resolution:1080&level:51};audioDecoders=aac`.
+    pub client_capabilities: Option<String>,
+}
+
+impl SessionOptions {
+    fn params(&self) -> Query {
+        let mut query = Query::new();
+
+        if self.priority == TranscodePriority::Background {
+            query = query.param("priority", "background");
+        }
+        if let Some(throttle) = self.throttle {
+            query = query.param("throttle", bs(throttle));
+        }
+        if let Some(capabilities) = &self.client_capabilities {
+            query = query.param("X-Plex-Client-Capabilities", capabilities);
+        }
+
+        query
+    }
+}
+
+/// The standard video qualities that Plex players offer, see
+/// [`VideoTranscodeOptions::with_quality`].
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quality {
+    /// Limits high enough that the server keeps the original video where
+    /// the codecs allow it.
+    Original,
+    P1080_20Mbps,
+    P1080_12Mbps,
+    P1080_10Mbps,
+    P1080_8Mbps,
+    P720_4Mbps,
+    P720_3Mbps,
+    P720_2Mbps,
+    P480_1_5Mbps,
+    P328_0_7Mbps,
+    P240_0_3Mbps,
+}
+
+impl Quality {
+    /// The maximum bitrate in kbps.
+    pub fn bitrate(self) -> u32 {
+        match self {
+            Self::Original => 200000,
+            Self::P1080_20Mbps => 20000,
+            Self::P1080_12Mbps => 12000,
+            Self::P1080_10Mbps => 10000,
+            Self::P1080_8Mbps => 8000,
+            Self::P720_4Mbps => 4000,
+            Self::P720_3Mbps => 3000,
+            Self::P720_2Mbps => 2000,
+            Self::P480_1_5Mbps => 1500,
+            Self::P328_0_7Mbps => 720,
+            Self::P240_0_3Mbps => 320,
+        }
+    }
+
+    /// The maximum width and height.
+    pub fn resolution(self) -> (u32, u32) {
+        match self {
+            Self::Original => (3840, 2160),
+            Self::P1080_20Mbps | Self::P1080_12Mbps | Self::P1080_10Mbps | Self::P1080_8Mbps => {
+                (1920, 1080)
+            }
+            Self::P720_4Mbps | Self::P720_3Mbps | Self::P720_2Mbps => (1280, 720),
+            Self::P480_1_5Mbps => (720, 480),
+            Self::P328_0_7Mbps => (576, 328),
+            Self::P240_0_3Mbps => (420, 240),
+        }
+    }
+}
+
 /// Defines the media formats suitable for transcoding video. The server uses
 /// these settings to choose a format to transcode to.
 ///
@@ -318,8 +515,16 @@ pub struct VideoTranscodeOptions {
     pub video_quality: Option<u32>,
     /// Audio gain from 0 to 100.
     pub audio_boost: Option<u8>,
+    /// How to deliver subtitles.
+    pub subtitles: SubtitleMode,
     /// Whether to burn the subtitles into the video. If false the server will decide.
+    #[deprecated(note = "use `subtitles: SubtitleMode::Burn` instead")]
     pub burn_subtitles: bool,
+    /// The best dynamic range the client can display. Adds the limitations
+    /// that make the server tone map or preserve HDR video.
+    pub dynamic_range: Option<DynamicRange>,
+    /// Maximum video framerate.
+    pub max_frame_rate: Option<u32>,
     /// Supported media container formats. Ignored for streaming transcodes.
     pub containers: Vec<ContainerFormat>,
     /// Supported video codecs.
@@ -332,9 +537,14 @@ pub struct VideoTranscodeOptions {
     pub audio_limitations: Vec<Limitation<AudioCodec, AudioSetting>>,
     /// Supported subtitle codecs.
     pub subtitle_codecs: Vec<SubtitleCodec>,
+    /// Lets the server adjust the quality of streaming transcodes. Ignored
+    /// for offline transcodes.
+    pub adaptive_quality: Option<AdaptiveQuality>,
+    pub session: SessionOptions,
 }
 
 impl Default for VideoTranscodeOptions {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             bitrate: 4000,
@@ -342,13 +552,48 @@ impl Default for VideoTranscodeOptions {
             height: 720,
             video_quality: None,
             audio_boost: None,
+            subtitles: SubtitleMode::Auto,
             burn_subtitles: false,
+            dynamic_range: None,
+            max_frame_rate: None,
             containers: vec![ContainerFormat::Mp4, ContainerFormat::Mkv],
             video_codecs: vec![VideoCodec::H264],
             video_limitations: Default::default(),
             audio_codecs: vec![AudioCodec::Aac, AudioCodec::Mp3],
             audio_limitations: Default::default(),
             subtitle_codecs: Default::default(),
+            adaptive_quality: None,
+            session: Default::default(),
+        }
+    }
+}
+
+impl VideoTranscodeOptions {
+    /// Replaces the bitrate and dimensions with those of the preset.
+    pub fn with_quality(self, quality: Quality) -> Self {
+        let (width, height) = quality.resolution();
+        Self {
+            bitrate: quality.bitrate(),
+            width,
+            height,
+            ..self
+        }
+    }
+}
+
+impl From<Quality> for VideoTranscodeOptions {
+    fn from(quality: Quality) -> Self {
+        Self::default().with_quality(quality)
+    }
+}
+
+impl VideoTranscodeOptions {
+    #[allow(deprecated)]
+    fn subtitle_mode(&self) -> SubtitleMode {
+        if self.burn_subtitles {
+            SubtitleMode::Burn
+        } else {
+            self.subtitles
         }
     }
 }
@@ -366,15 +611,9 @@ impl TranscodeOptions for VideoTranscodeOptions {
             .param("videoResolution", format!("{}x{}", self.width, self.height))
             .param("transcodeType", "video");
 
-        if self.burn_subtitles {
-            query = query
-                .param("subtitles", "burn")
-                .param("subtitleSize", "100");
-        } else {
-            query = query
-                .param("subtitles", "auto")
-                .param("subtitleSize", "100");
-        }
+        query = query
+            .param("subtitles", self.subtitle_mode().to_string())
+            .param("subtitleSize", "100");
 
         if let Some(boost) = self.audio_boost {
             query = query.param("audioBoost", boost.to_string());
@@ -384,6 +623,15 @@ impl TranscodeOptions for VideoTranscodeOptions {
             query = query.param("videoQuality", q.clamp(0, 99).to_string());
         }
 
+        if let Some(adaptive) = self
+            .adaptive_quality
+            .filter(|_| context == Context::Streaming)
+        {
+            query = query.append(adaptive.params().into());
+        }
+
+        query = query.append(self.session.params().into());
+
         let video_codecs = self
             .video_codecs
             .iter()
@@ -447,8 +695,17 @@ impl TranscodeOptions for VideoTranscodeOptions {
             );
         }
 
+        let mut video_limitations = self.video_limitations.clone();
+        if let Some(range) = self.dynamic_range {
+            video_limitations.extend(range.limitations());
+        }
+        if let Some(rate) = self.max_frame_rate {
+            video_limitations
+                .push((VideoSetting::FrameRate, Constraint::Max(rate.to_string())).into());
+        }
+
         profile.extend(
-            self.video_limitations
+            video_limitations
                 .iter()
                 .map(|l| l.build("videoCodec").to_string()),
         );
@@ -488,6 +745,7 @@ pub struct MusicTranscodeOptions {
     pub codecs: Vec<AudioCodec>,
     /// Limitations to constraint audio transcoding options.
     pub limitations: Vec<Limitation<AudioCodec, AudioSetting>>,
+    pub session: SessionOptions,
 }
 
 impl Default for MusicTranscodeOptions {
@@ -497,6 +755,7 @@ impl Default for MusicTranscodeOptions {
             containers: vec![ContainerFormat::Mp3],
             codecs: vec![AudioCodec::Mp3],
             limitations: Default::default(),
+            session: Default::default(),
         }
     }
 }
@@ -507,10 +766,23 @@ impl TranscodeOptions for MusicTranscodeOptions {
         context: Context,
         protocol: Protocol,
         container: Option<ContainerFormat>,
+    ) -> HashMap<String, String> {
+        self.music_parameters(context, protocol, container, true)
+    }
+}
+
+impl MusicTranscodeOptions {
+    fn music_parameters(
+        &self,
+        context: Context,
+        protocol: Protocol,
+        container: Option<ContainerFormat>,
+        allow_direct_play: bool,
     ) -> HashMap<String, String> {
         let query = Query::new()
             .param("musicBitrate", self.bitrate.to_string())
-            .param("transcodeType", "music");
+            .param("transcodeType", "music")
+            .append(self.session.params().into());
 
         let audio_codecs = self
             .codecs
@@ -546,7 +818,7 @@ impl TranscodeOptions for MusicTranscodeOptions {
         }
 
         // Allow potentially direct playing for offline transcodes.
-        if context == Context::Static {
+        if allow_direct_play && context == Context::Static {
             profile.push(
                 ProfileSetting::new("add-direct-play-profile")
                     .param("type", "musicProfile")
@@ -568,12 +840,32 @@ impl TranscodeOptions for MusicTranscodeOptions {
     }
 }
 
+/// Requests just the audio of a video item, see
+/// [`AudioTranscodable`](crate::library::AudioTranscodable).
+pub(crate) struct AudioOnly(pub(crate) MusicTranscodeOptions);
+
+impl TranscodeOptions for AudioOnly {
+    fn transcode_parameters(
+        &self,
+        context: Context,
+        protocol: Protocol,
+        container: Option<ContainerFormat>,
+    ) -> HashMap<String, String> {
+        // Playing the original file or copying its video stream would bring
+        // the video along, only the audio stream may be used as is.
+        let mut params = self.0.music_parameters(context, protocol, container, false);
+        params.insert("directPlay".to_owned(), bs(false));
+        params.insert("directStream".to_owned(), bs(false));
+        params
+    }
+}
+
 /// Generates a unique session id. This appears to just be any random string.
-fn session_id() -> String {
+pub(crate) fn session_id() -> String {
     Uuid::new_v4().as_simple().to_string()
 }
 
-fn bs(val: bool) -> String {
+pub(crate) fn bs(val: bool) -> String {
     if val {
         "1".to_string()
     } else {
@@ -629,6 +921,15 @@ fn get_transcode_params<O: TranscodeOptions>(
     Ok(query.append(options.transcode_parameters(context, protocol, container)))
 }
 
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+}
+
+derive_display_from_serialize!(ImageFormat);
+
 #[derive(Debug, Clone, Copy)]
 pub struct ArtTranscodeOptions {
     /// If true and the source image is smaller than that requested it will be
@@ -637,6 +938,13 @@ pub struct ArtTranscodeOptions {
     /// Sets whether the requested size is the minimum size desired or the
     /// maximum.
     pub min_size: bool,
+    /// Applies a gaussian blur with the given radius, useful for backgrounds.
+    pub blur: Option<u32>,
+    /// Opacity of the resulting image in percent (0-100). Only makes sense
+    /// together with [`ImageFormat::Png`].
+    pub opacity: Option<u8>,
+    /// The image format to return, the server defaults to JPEG.
+    pub format: Option<ImageFormat>,
 }
 
 impl Default for ArtTranscodeOptions {
@@ -644,10 +952,39 @@ impl Default for ArtTranscodeOptions {
         Self {
             upscale: true,
             min_size: true,
+            blur: None,
+            opacity: None,
+            format: None,
         }
     }
 }
 
+pub(super) fn artwork_query(
+    art: &str,
+    width: u32,
+    height: u32,
+    options: ArtTranscodeOptions,
+) -> Query {
+    let mut query = Query::new()
+        .param("url", art)
+        .param("upscale", bs(options.upscale))
+        .param("minSize", bs(options.min_size))
+        .param("width", width.to_string())
+        .param("height", height.to_string());
+
+    if let Some(blur) = options.blur {
+        query = query.param("blur", blur.to_string());
+    }
+    if let Some(opacity) = options.opacity {
+        query = query.param("opacity", opacity.min(100).to_string());
+    }
+    if let Some(format) = options.format {
+        query = query.param("format", format.to_string());
+    }
+
+    query
+}
+
 pub(crate) async fn transcode_artwork<W>(
     client: &HttpClient,
     art: &str,
@@ -659,12 +996,7 @@ pub(crate) async fn transcode_artwork<W>(
 where
     W: AsyncWrite + Unpin,
 {
-    let query = Query::new()
-        .param("url", art)
-        .param("upscale", bs(options.upscale))
-        .param("minSize", bs(options.min_size))
-        .param("width", width.to_string())
-        .param("height", height.to_string());
+    let query = artwork_query(art, width, height, options);
 
     let mut response = client
         .get(format!("{SERVER_TRANSCODE_ART}?{query}"))
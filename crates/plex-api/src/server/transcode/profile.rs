@@ -0,0 +1,161 @@
+use crate::media_container::server::library::{
+    AudioCodec, ContainerFormat, SubtitleCodec, VideoCodec,
+};
+
+use super::{
+    AudioSetting, Constraint, Limitation, MusicTranscodeOptions, VideoSetting,
+    VideoTranscodeOptions,
+};
+
+/// Predefined transcode settings for common kinds of playback device.
+///
+/// Each profile can be used in two ways. Passing it to
+/// [`HttpClientBuilder::set_client_profile`](crate::HttpClientBuilder::set_client_profile)
+/// identifies the client as that platform so the server applies its own
+/// built-in profile for the device. Alternatively
+/// [`ClientProfile::video_options`] and [`ClientProfile::music_options`]
+/// produce transcode options describing the device's capabilities which are
+/// sent along with an individual transcode request, these can be adjusted
+/// further before use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientProfile {
+    /// Third generation Chromecast, 1080p.
+    Chromecast,
+    /// Roku streaming players, 1080p.
+    Roku,
+    /// iPhone and iPad.
+    Ios,
+    /// Android TV devices, up to 4K.
+    AndroidTv,
+    /// A web browser with no special codec support.
+    GenericWeb,
+}
+
+fn max<C, S>(setting: S, value: u32) -> Limitation<C, S> {
+    (setting, Constraint::Max(value.to_string())).into()
+}
+
+impl ClientProfile {
+    /// The `X-Plex-Platform` the server recognises for this kind of device.
+    pub fn platform(&self) -> &'static str {
+        match self {
+            ClientProfile::Chromecast => "Chromecast",
+            ClientProfile::Roku => "Roku",
+            ClientProfile::Ios => "iOS",
+            ClientProfile::AndroidTv => "Android",
+            ClientProfile::GenericWeb => "Generic",
+        }
+    }
+
+    /// Video transcode options matching what the device can play.
+    pub fn video_options(&self) -> VideoTranscodeOptions {
+        match self {
+            ClientProfile::Chromecast => VideoTranscodeOptions {
+                bitrate: 10000,
+                width: 1920,
+                height: 1080,
+                containers: vec![ContainerFormat::Mp4, ContainerFormat::Mkv],
+                video_codecs: vec![VideoCodec::H264, VideoCodec::Vp9],
+                video_limitations: vec![(
+                    VideoCodec::H264,
+                    VideoSetting::Level,
+                    Constraint::Max("42".to_string()),
+                )
+                    .into()],
+                audio_codecs: vec![AudioCodec::Aac, AudioCodec::Mp3, AudioCodec::Opus],
+                audio_limitations: vec![max(AudioSetting::Channels, 2)],
+                subtitle_codecs: vec![SubtitleCodec::Vtt],
+                ..Default::default()
+            },
+            ClientProfile::Roku => VideoTranscodeOptions {
+                bitrate: 20000,
+                width: 1920,
+                height: 1080,
+                containers: vec![ContainerFormat::Mp4, ContainerFormat::Mkv],
+                video_codecs: vec![VideoCodec::H264, VideoCodec::Hevc],
+                video_limitations: vec![(
+                    VideoCodec::H264,
+                    VideoSetting::Level,
+                    Constraint::Max("41".to_string()),
+                )
+                    .into()],
+                audio_codecs: vec![
+                    AudioCodec::Aac,
+                    AudioCodec::Mp3,
+                    AudioCodec::Ac3,
+                    AudioCodec::Eac3,
+                ],
+                audio_limitations: vec![max(AudioSetting::Channels, 6)],
+                subtitle_codecs: vec![SubtitleCodec::Srt],
+                ..Default::default()
+            },
+            ClientProfile::Ios => VideoTranscodeOptions {
+                bitrate: 8000,
+                width: 1920,
+                height: 1080,
+                containers: vec![ContainerFormat::Mp4],
+                video_codecs: vec![VideoCodec::H264, VideoCodec::Hevc],
+                audio_codecs: vec![AudioCodec::Aac, AudioCodec::Ac3, AudioCodec::Eac3],
+                audio_limitations: vec![max(AudioSetting::Channels, 6)],
+                subtitle_codecs: vec![SubtitleCodec::MovText, SubtitleCodec::Vtt],
+                ..Default::default()
+            },
+            ClientProfile::AndroidTv => VideoTranscodeOptions {
+                bitrate: 40000,
+                width: 3840,
+                height: 2160,
+                containers: vec![ContainerFormat::Mp4, ContainerFormat::Mkv],
+                video_codecs: vec![VideoCodec::H264, VideoCodec::Hevc, VideoCodec::Vp9],
+                audio_codecs: vec![
+                    AudioCodec::Aac,
+                    AudioCodec::Mp3,
+                    AudioCodec::Ac3,
+                    AudioCodec::Eac3,
+                    AudioCodec::Opus,
+                ],
+                audio_limitations: vec![max(AudioSetting::Channels, 8)],
+                subtitle_codecs: vec![SubtitleCodec::Srt, SubtitleCodec::Ass, SubtitleCodec::Pgs],
+                ..Default::default()
+            },
+            ClientProfile::GenericWeb => VideoTranscodeOptions {
+                containers: vec![ContainerFormat::Mp4],
+                video_codecs: vec![VideoCodec::H264],
+                audio_codecs: vec![AudioCodec::Aac, AudioCodec::Mp3],
+                audio_limitations: vec![max(AudioSetting::Channels, 2)],
+                subtitle_codecs: vec![SubtitleCodec::Vtt],
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Music transcode options matching what the device can play.
+    pub fn music_options(&self) -> MusicTranscodeOptions {
+        match self {
+            ClientProfile::Chromecast | ClientProfile::AndroidTv => MusicTranscodeOptions {
+                bitrate: 320,
+                containers: vec![ContainerFormat::Mp3, ContainerFormat::Ogg],
+                codecs: vec![AudioCodec::Mp3, AudioCodec::Opus, AudioCodec::Flac],
+                ..Default::default()
+            },
+            ClientProfile::Roku | ClientProfile::Ios => MusicTranscodeOptions {
+                bitrate: 320,
+                containers: vec![ContainerFormat::Mp4, ContainerFormat::Mp3],
+                codecs: vec![AudioCodec::Aac, AudioCodec::Mp3],
+                ..Default::default()
+            },
+            ClientProfile::GenericWeb => MusicTranscodeOptions::default(),
+        }
+    }
+}
+
+impl From<ClientProfile> for VideoTranscodeOptions {
+    fn from(profile: ClientProfile) -> Self {
+        profile.video_options()
+    }
+}
+
+impl From<ClientProfile> for MusicTranscodeOptions {
+    fn from(profile: ClientProfile) -> Self {
+        profile.music_options()
+    }
+}
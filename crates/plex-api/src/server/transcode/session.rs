@@ -1,9 +1,14 @@
-use futures::AsyncWrite;
+use std::time::Duration;
+
+use futures::{stream, AsyncSeek, AsyncWrite, Stream as FuturesStream, StreamExt};
+use futures_timer::Delay;
 use http::StatusCode;
 use isahc::AsyncReadResponseExt;
 use serde::Deserialize;
 
 use crate::{
+    download::Download,
+    endpoints::Endpoint,
     isahc_compat::StatusCodeExt,
     media_container::{
         server::{
@@ -15,16 +20,20 @@ use crate::{
         },
         MediaContainer, MediaContainerWrapper,
     },
+    redact::redact,
     server::Query,
     transcode::{
-        bs, get_transcode_params, session_id, Context, DecisionResult, TranscodeOptions,
-        TranscodeSessionStats,
+        bs,
+        dash::{Manifest, SegmentList},
+        get_transcode_params,
+        hls::{self, MasterPlaylist, MediaPlaylist, VariantStream},
+        session_id, Context, DecisionResult, TranscodeOptions, TranscodeSessionStats,
     },
     url::{
-        SERVER_TRANSCODE_DECISION, SERVER_TRANSCODE_DOWNLOAD, SERVER_TRANSCODE_SESSIONS,
-        SERVER_TRANSCODE_STOP,
+        SERVER_TRANSCODE_DECISION, SERVER_TRANSCODE_DOWNLOAD, SERVER_TRANSCODE_PING,
+        SERVER_TRANSCODE_SESSIONS, SERVER_TRANSCODE_STOP,
     },
-    Error, HttpClient, Result,
+    DownloadOptions, Error, HttpClient, ProgressObserver, Result, SparseFile,
 };
 
 #[derive(Debug, Clone, Deserialize)]
@@ -59,7 +68,10 @@ struct TranscodeDecisionMediaContainer {
     metadata: Vec<Metadata>,
 }
 
-async fn transcode_decision(client: &HttpClient, params: &Query) -> Result<MediaMetadata> {
+async fn transcode_decision(
+    client: &HttpClient,
+    params: &Query,
+) -> Result<(MediaMetadata, DecisionResult)> {
     let path = format!("{SERVER_TRANSCODE_DECISION}?{params}");
 
     let mut response = client
@@ -99,7 +111,8 @@ async fn transcode_decision(client: &HttpClient, params: &Query) -> Result<Media
         return Err(Error::TranscodeRefused);
     }
 
-    wrapper
+    let decision = wrapper.media_container.decision_result.clone();
+    let media = wrapper
         .media_container
         .metadata
         .into_iter()
@@ -116,15 +129,17 @@ async fn transcode_decision(client: &HttpClient, params: &Query) -> Result<Media
             } else {
                 Error::UnexpectedApiResponse {
                     status_code: response.status().as_u16(),
-                    content: text,
+                    content: redact(&text).into_owned(),
                 }
             }
-        })
+        })?;
+
+    Ok((media, decision))
 }
 
 pub(crate) async fn create_transcode_session<O: TranscodeOptions>(
     client: &HttpClient,
-    item_metadata: &Metadata,
+    path: &str,
     context: Context,
     target_protocol: Protocol,
     media_index: Option<usize>,
@@ -132,6 +147,13 @@ pub(crate) async fn create_transcode_session<O: TranscodeOptions>(
     options: O,
 ) -> Result<TranscodeSession> {
     let id = session_id();
+    // Everything the session requests is part of the playback the client
+    // belongs to, or of a new one.
+    let client = if client.x_plex_playback_session_id.is_empty() {
+        client.with_playback_session(session_id())
+    } else {
+        client.clone()
+    };
 
     let mut params = get_transcode_params(
         &id,
@@ -141,13 +163,13 @@ pub(crate) async fn create_transcode_session<O: TranscodeOptions>(
         part_index,
         options,
     )?
-    .param("path", item_metadata.key.clone());
+    .param("path", path);
 
     if context == Context::Static {
         params = params.param("offlineTranscode", bs(true));
     }
 
-    let media_data = transcode_decision(client, &params).await?;
+    let (media_data, decision) = transcode_decision(&client, &params).await?;
 
     if target_protocol != media_data.protocol.unwrap_or(Protocol::Http) {
         return Err(Error::TranscodeError(
@@ -157,8 +179,9 @@ pub(crate) async fn create_transcode_session<O: TranscodeOptions>(
 
     TranscodeSession::from_metadata(
         id,
-        client.clone(),
+        client,
         media_data,
+        decision,
         context == Context::Static,
         params,
     )
@@ -210,6 +233,7 @@ pub struct TranscodeSession {
     container: ContainerFormat,
     video_transcode: Option<(Decision, VideoCodec)>,
     audio_transcode: Option<(Decision, AudioCodec)>,
+    decision: Option<DecisionResult>,
     params: Query,
 }
 
@@ -225,6 +249,7 @@ impl TranscodeSession {
             protocol: stats.protocol,
             video_transcode: stats.video_decision.zip(stats.video_codec),
             audio_transcode: stats.audio_decision.zip(stats.audio_codec),
+            decision: None,
             id: stats.key,
         }
     }
@@ -233,6 +258,7 @@ impl TranscodeSession {
         id: String,
         client: HttpClient,
         media_data: MediaMetadata,
+        decision: DecisionResult,
         offline: bool,
         params: Query,
     ) -> Result<Self> {
@@ -285,6 +311,7 @@ impl TranscodeSession {
             protocol: media_data.protocol.unwrap_or(Protocol::Http),
             video_transcode,
             audio_transcode,
+            decision: Some(decision),
         })
     }
 
@@ -293,6 +320,13 @@ impl TranscodeSession {
         &self.id
     }
 
+    /// The playback the session's requests are grouped into, `None` for
+    /// sessions retrieved with
+    /// [`Server::transcode_session`](crate::Server::transcode_session).
+    pub fn playback_session_id(&self) -> Option<&str> {
+        Some(self.client.x_plex_playback_session_id.as_str()).filter(|id| !id.is_empty())
+    }
+
     pub fn is_offline(&self) -> bool {
         self.offline
     }
@@ -317,12 +351,34 @@ impl TranscodeSession {
         self.audio_transcode
     }
 
+    /// Why the server chose to transcode the way it did. Only known for
+    /// sessions started by this client, the server doesn't report it for
+    /// existing sessions.
+    pub fn decision(&self) -> Option<&DecisionResult> {
+        self.decision.as_ref()
+    }
+
+    fn download_path(&self) -> String {
+        // Strictly speaking it doesn't appear that the requested extension
+        // matters but we'll attempt to match other clients anyway.
+        let ext = match (self.protocol, self.container) {
+            (Protocol::Dash, _) => "mpd".to_string(),
+            (Protocol::Hls, _) => "m3u8".to_string(),
+            (_, container) => container.to_string(),
+        };
+
+        format!(
+            "{}?{}",
+            Endpoint::new(SERVER_TRANSCODE_DOWNLOAD).segment("extension", ext),
+            self.params
+        )
+    }
+
     /// Downloads the transcoded data to the provided writer.
     ///
-    /// For streaming transcodes (MPEG-DASH or HLS) this will return the
-    /// playlist data. This crate doesn't contain any support for processing
-    /// these streaming formats and figuring out how to use them is currently
-    /// left as an exercise for the caller.
+    /// For streaming transcodes (MPEG-DASH or HLS) this will return the raw
+    /// playlist data. [`TranscodeSession::hls_master_playlist`] and
+    /// [`TranscodeSession::dash_manifest`] parse it instead.
     ///
     /// For offline transcodes it is possible to start downloading before the
     /// transcode is complete. In this case any data already transcoded is
@@ -332,45 +388,212 @@ impl TranscodeSession {
     /// more data to be transcoded and so the normal timeouts are disabled for
     /// offline transcode downloads.
     ///
-    /// Unfortunately there does not appear to be any way to restart downloads
-    /// from a specific point in the file. So if the download fails for
-    /// any reason you have to start downloading all over again. It may make
-    /// more sense to wait until the transcode is complete or nearly complete
-    /// before attempting download.
+    /// If the connection drops the download is resumed using the default
+    /// [`DownloadOptions`], see [`TranscodeSession::download_with`].
     #[tracing::instrument(level = "debug", skip_all)]
     pub async fn download<W>(&self, writer: W) -> Result<()>
     where
         W: AsyncWrite + Unpin,
     {
-        // Strictly speaking it doesn't appear that the requested extension
-        // matters but we'll attempt to match other clients anyway.
-        let ext = match (self.protocol, self.container) {
-            (Protocol::Dash, _) => "mpd".to_string(),
-            (Protocol::Hls, _) => "m3u8".to_string(),
-            (_, container) => container.to_string(),
-        };
+        self.download_with(writer, DownloadOptions::default(), ())
+            .await
+    }
 
-        let path = format!(
-            "{}?{}",
-            SERVER_TRANSCODE_DOWNLOAD.replace("{extension}", &ext),
-            self.params
-        );
+    /// Same as [`TranscodeSession::download`] but allows configuring how
+    /// failed downloads are resumed and reports progress to the observer.
+    ///
+    /// Resuming asks the server for the remaining bytes only. The server
+    /// doesn't always support that while the transcode is still running, in
+    /// which case the data is requested from the start again and the bytes
+    /// that were already written are skipped. Parallel downloads aren't
+    /// supported as the final size of a transcode isn't known up front.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn download_with<W, O>(
+        &self,
+        writer: W,
+        options: DownloadOptions,
+        observer: O,
+    ) -> Result<()>
+    where
+        W: AsyncWrite + Unpin,
+        O: ProgressObserver,
+    {
+        let path = self.download_path();
+
+        Download {
+            client: &self.client,
+            path: &path,
+            start: 0,
+            end: None,
+            size: None,
+            timeout: if self.offline {
+                None
+            } else {
+                self.client.timeout()
+            },
+            transcoding: false,
+        }
+        .run(
+            writer,
+            DownloadOptions {
+                connections: 1,
+                ..options
+            },
+            observer,
+        )
+        .await
+    }
+
+    /// Downloads an offline transcode into a [`SparseFile`], writing each
+    /// part of the file at its offset. If this fails the file keeps what was
+    /// downloaded and calling this again continues from there, so a
+    /// transcode can be fetched over several runs of an application.
+    ///
+    /// While the transcode is running the server may not support resuming,
+    /// in which case the file is written from the start again.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn download_sparse<F, O>(
+        &self,
+        file: &mut SparseFile<F>,
+        options: DownloadOptions,
+        observer: O,
+    ) -> Result<()>
+    where
+        F: AsyncWrite + AsyncSeek + Unpin,
+        O: ProgressObserver,
+    {
+        let path = self.download_path();
+
+        Download {
+            client: &self.client,
+            path: &path,
+            start: 0,
+            end: None,
+            size: None,
+            timeout: if self.offline {
+                None
+            } else {
+                self.client.timeout()
+            },
+            transcoding: false,
+        }
+        .run_sparse(file, options, observer)
+        .await
+    }
 
-        let mut builder = self.client.get(path);
-        if self.offline {
-            builder = builder.timeout(None)
+    async fn fetch_playlist(&self, protocol: Protocol, path: &str) -> Result<String> {
+        if self.protocol != protocol {
+            return Err(Error::InvalidPlaylist(format!(
+                "{} transcodes don't have {protocol} playlists",
+                self.protocol
+            )));
         }
-        let mut response = builder.send().await?;
 
+        let mut response = self.client.get(path).send().await?;
         match response.status().as_http_status() {
-            StatusCode::OK => {
-                response.copy_to(writer).await?;
-                Ok(())
-            }
+            StatusCode::OK => Ok(response.text().await?),
             _ => Err(crate::Error::from_response(response).await),
         }
     }
 
+    /// Retrieves the master playlist of an HLS transcode. The URIs of the
+    /// variant streams are resolved to paths on the server.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn hls_master_playlist(&self) -> Result<MasterPlaylist> {
+        let path = self.download_path();
+        let mut playlist: MasterPlaylist =
+            self.fetch_playlist(Protocol::Hls, &path).await?.parse()?;
+        let server = self.client.base_url();
+        for variant in playlist.variants.iter_mut() {
+            variant.uri = hls::resolve(&server, &path, &variant.uri)?;
+        }
+        Ok(playlist)
+    }
+
+    /// Retrieves the media playlist for one of the variants of an HLS
+    /// transcode. The URIs of the segments are resolved to paths on the
+    /// server.
+    #[tracing::instrument(level = "debug", skip_all, fields(variant.uri = variant.uri))]
+    pub async fn hls_media_playlist(&self, variant: &VariantStream) -> Result<MediaPlaylist> {
+        let mut playlist: MediaPlaylist = self
+            .fetch_playlist(Protocol::Hls, &variant.uri)
+            .await?
+            .parse()?;
+        let server = self.client.base_url();
+        for segment in playlist.segments.iter_mut() {
+            segment.uri = hls::resolve(&server, &variant.uri, &segment.uri)?;
+        }
+        Ok(playlist)
+    }
+
+    /// Downloads the segments of a media playlist one after another. The
+    /// contents of each segment are returned in playlist order.
+    pub fn hls_segments<'a>(
+        &'a self,
+        playlist: &'a MediaPlaylist,
+    ) -> impl FuturesStream<Item = Result<Vec<u8>>> + 'a {
+        self.fetch_segments(playlist.segments.iter().map(|segment| segment.uri.as_str()))
+    }
+
+    /// Retrieves the manifest of an MPEG-DASH transcode. The segment
+    /// templates are resolved to paths on the server.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn dash_manifest(&self) -> Result<Manifest> {
+        let path = self.download_path();
+        let mut manifest: Manifest = self.fetch_playlist(Protocol::Dash, &path).await?.parse()?;
+        let server = self.client.base_url();
+
+        let templates = manifest
+            .periods
+            .iter_mut()
+            .flat_map(|period| period.adaptation_sets.iter_mut())
+            .flat_map(|set| {
+                set.representations
+                    .iter_mut()
+                    .filter_map(|r| r.segment_template.as_mut())
+                    .chain(set.segment_template.as_mut())
+            });
+        for template in templates {
+            for uri in [&mut template.initialization, &mut template.media]
+                .into_iter()
+                .flatten()
+            {
+                *uri = hls::resolve(&server, &path, uri)?;
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    /// Downloads the segments of a DASH representation one after another,
+    /// starting with the initialization segment if there is one. Use
+    /// [`Period::segments`](super::dash::Period::segments) to list them.
+    pub fn dash_segments<'a>(
+        &'a self,
+        segments: &'a SegmentList,
+    ) -> impl FuturesStream<Item = Result<Vec<u8>>> + 'a {
+        self.fetch_segments(
+            segments
+                .initialization
+                .iter()
+                .chain(segments.media.iter())
+                .map(String::as_str),
+        )
+    }
+
+    fn fetch_segments<'a>(
+        &'a self,
+        paths: impl Iterator<Item = &'a str> + 'a,
+    ) -> impl FuturesStream<Item = Result<Vec<u8>>> + 'a {
+        stream::iter(paths).then(move |path| async move {
+            let mut response = self.client.get(path).send().await?;
+            match response.status().as_http_status() {
+                StatusCode::OK => Ok(response.bytes().await?),
+                _ => Err(crate::Error::from_response(response).await),
+            }
+        })
+    }
+
     #[tracing::instrument(level = "debug", skip_all)]
     pub async fn status(&self) -> Result<TranscodeStatus> {
         let stats = self.stats().await?;
@@ -393,6 +616,88 @@ impl TranscodeSession {
         transcode_session_stats(&self.client, &self.id).await
     }
 
+    /// Tells the server that the session is still in use. Streaming transcodes
+    /// are stopped after a while if the client doesn't request any data.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn ping(&self) -> Result<()> {
+        let mut response = self
+            .client
+            .get(format!("{SERVER_TRANSCODE_PING}?session={}", self.id))
+            .send()
+            .await?;
+
+        match response.status().as_http_status() {
+            StatusCode::OK => Ok(response.consume().await?),
+            _ => Err(crate::Error::from_response(response).await),
+        }
+    }
+
+    /// Pings the server every `interval` to hold the session open. The
+    /// returned future only completes if a ping fails so it should be run
+    /// alongside whatever is consuming the stream, for example with
+    /// `futures::select!` or by spawning it on a runtime.
+    pub async fn keepalive(&self, interval: Duration) -> Result<()> {
+        loop {
+            self.ping().await?;
+            Delay::new(interval).await;
+        }
+    }
+
+    /// Changes the bitrate (in kbps) of a streaming transcode. The server
+    /// restarts the transcode at the new quality so any playlists or
+    /// manifests should be retrieved again.
+    ///
+    /// Only possible for sessions started by this client, not those
+    /// retrieved with [`Server::transcode_session`](crate::Server::transcode_session).
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn set_bitrate(&mut self, bitrate: u32) -> Result<()> {
+        self.restart(|params| {
+            params
+                .param("maxVideoBitrate", bitrate.to_string())
+                .param("videoBitrate", bitrate.to_string())
+        })
+        .await
+    }
+
+    /// Restarts a streaming transcode at the given position in the media,
+    /// keeping the session and its settings. As with
+    /// [`TranscodeSession::set_bitrate`] the playlists or manifests should be
+    /// retrieved again afterwards.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn seek(&mut self, position: Duration) -> Result<()> {
+        self.restart(|params| params.param("offset", format!("{:.3}", position.as_secs_f64())))
+            .await
+    }
+
+    /// Runs the transcode decision again with updated parameters.
+    async fn restart<F>(&mut self, update: F) -> Result<()>
+    where
+        F: FnOnce(Query) -> Query,
+    {
+        if self.offline || !self.params.params.contains_key("path") {
+            return Err(Error::InvalidTranscodeSettings);
+        }
+
+        let params = update(std::mem::replace(&mut self.params, Query::new()));
+        let result = transcode_decision(&self.client, &params).await;
+        self.params = params;
+
+        let (media_data, decision) = result?;
+        let updated = Self::from_metadata(
+            self.id.clone(),
+            self.client.clone(),
+            media_data,
+            decision,
+            self.offline,
+            Query::new(),
+        )?;
+        self.video_transcode = updated.video_transcode;
+        self.audio_transcode = updated.audio_transcode;
+        self.decision = updated.decision;
+
+        Ok(())
+    }
+
     /// Cancels the transcode and removes any transcoded data from the server.
     ///
     /// NB! Be careful with cancelling sessions too often! Cancelling a few transcoding
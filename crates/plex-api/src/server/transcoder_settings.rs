@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+use serde_plain::{derive_display_from_serialize, derive_fromstr_from_deserialize};
+use std::time::Duration;
+
+use crate::{
+    media_container::preferences::Value, url::SERVER_PREFS, Error, HttpClient, Preferences, Result,
+};
+
+const HARDWARE_ACCELERATION: &str = "HardwareAcceleratedCodecs";
+const HARDWARE_ENCODING: &str = "HardwareAcceleratedEncoders";
+const TEMP_DIRECTORY: &str = "TranscoderTempDirectory";
+const THROTTLE_BUFFER: &str = "TranscoderThrottleBuffer";
+const BACKGROUND_PRESET: &str = "TranscoderH264BackgroundPreset";
+const TONE_MAPPING: &str = "TranscoderToneMapping";
+
+/// The x264 presets, from the fastest to the one producing the best quality
+/// for the size.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum X264Preset {
+    UltraFast,
+    SuperFast,
+    VeryFast,
+    Faster,
+    Fast,
+    Medium,
+    Slow,
+    Slower,
+    VerySlow,
+}
+
+derive_display_from_serialize!(X264Preset);
+derive_fromstr_from_deserialize!(X264Preset);
+
+/// The server settings that control how it transcodes, read from and written
+/// to its preferences.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscoderSettings {
+    /// Use the GPU for decoding video when available.
+    pub hardware_acceleration: bool,
+    /// Also use the GPU for encoding when hardware acceleration is on.
+    pub hardware_encoding: bool,
+    /// Where the transcoder writes its temporary files, on the server's file
+    /// system. The server picks a location itself when `None`.
+    pub temp_directory: Option<String>,
+    /// How far ahead of the player the transcoder works before pausing.
+    /// Stored in whole seconds.
+    pub throttle_buffer: Duration,
+    /// The preset used for Sync and the Media Optimizer.
+    pub background_preset: X264Preset,
+    /// Tone map HDR video when transcoding it to SDR, see
+    /// [`DynamicRange::Sdr`](crate::transcode::DynamicRange::Sdr).
+    pub tone_mapping: bool,
+}
+
+impl TranscoderSettings {
+    pub(crate) async fn load(client: &HttpClient) -> Result<Self> {
+        let prefs = Preferences::new(client).await?;
+        let value = |id: &str| {
+            prefs
+                .get(id)
+                .map(|setting| &setting.value)
+                .ok_or_else(|| Error::RequestedSettingNotFound(id.to_owned()))
+        };
+        let bool_value = |id: &str| match value(id)? {
+            Value::Bool(value) => Ok(*value),
+            _ => Err(Error::IncompatibleSettingValues),
+        };
+        let text_value = |id: &str| match value(id)? {
+            Value::Text(value) => Ok(value.clone()),
+            _ => Err(Error::IncompatibleSettingValues),
+        };
+
+        let throttle_buffer = match value(THROTTLE_BUFFER)? {
+            Value::Int(seconds) => Duration::from_secs(
+                u64::try_from(*seconds)
+                    .map_err(|_| Error::InvalidSettingValue(THROTTLE_BUFFER.to_owned()))?,
+            ),
+            _ => return Err(Error::IncompatibleSettingValues),
+        };
+
+        Ok(Self {
+            hardware_acceleration: bool_value(HARDWARE_ACCELERATION)?,
+            hardware_encoding: bool_value(HARDWARE_ENCODING)?,
+            temp_directory: Some(text_value(TEMP_DIRECTORY)?).filter(|path| !path.is_empty()),
+            throttle_buffer,
+            background_preset: text_value(BACKGROUND_PRESET)?
+                .parse()
+                .map_err(|_| Error::InvalidSettingValue(BACKGROUND_PRESET.to_owned()))?,
+            tone_mapping: bool_value(TONE_MAPPING)?,
+        })
+    }
+
+    /// Checks that the server would accept the settings.
+    pub fn validate(&self) -> Result {
+        if self.throttle_buffer.as_secs() == 0 {
+            return Err(Error::InvalidSettingValue(THROTTLE_BUFFER.to_owned()));
+        }
+
+        if let Some(path) = &self.temp_directory {
+            if !is_absolute(path) {
+                return Err(Error::InvalidSettingValue(TEMP_DIRECTORY.to_owned()));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) async fn save(&self, client: &HttpClient) -> Result {
+        self.validate()?;
+
+        let throttle_buffer = i64::try_from(self.throttle_buffer.as_secs())
+            .map_err(|_| Error::InvalidSettingValue(THROTTLE_BUFFER.to_owned()))?;
+        let params = [
+            (
+                HARDWARE_ACCELERATION,
+                Value::Bool(self.hardware_acceleration),
+            ),
+            (HARDWARE_ENCODING, Value::Bool(self.hardware_encoding)),
+            (
+                TEMP_DIRECTORY,
+                Value::Text(self.temp_directory.clone().unwrap_or_default()),
+            ),
+            (THROTTLE_BUFFER, Value::Int(throttle_buffer)),
+            (
+                BACKGROUND_PRESET,
+                Value::Text(self.background_preset.to_string()),
+            ),
+            (TONE_MAPPING, Value::Bool(self.tone_mapping)),
+        ]
+        .into_iter()
+        .map(|(id, value)| (id, value.to_string()))
+        .collect::<Vec<_>>();
+
+        let uri = format!("{}?{}", SERVER_PREFS, serde_urlencoded::to_string(params)?);
+        client.put(uri).consume().await
+    }
+}
+
+/// The server may run on a different OS than the client, so both Unix and
+/// Windows paths are accepted.
+fn is_absolute(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    path.starts_with('/')
+        || path.starts_with("\\\\")
+        || (bytes.len() >= 3
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && matches!(bytes[2], b'\\' | b'/'))
+}
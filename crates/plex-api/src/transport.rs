@@ -0,0 +1,35 @@
+use crate::Result;
+use futures::future::BoxFuture;
+use isahc::{AsyncBody, Request as HttpRequest, Response as HttpResponse};
+use std::{fmt, sync::Arc};
+
+/// Sends the requests of an [`HttpClient`](crate::HttpClient).
+///
+/// Requests are sent using the client's isahc client unless a transport is
+/// set with [`HttpClientBuilder::set_transport`](crate::HttpClientBuilder::set_transport),
+/// so another HTTP stack can be plugged in at runtime, or a test double that
+/// answers requests without touching the network.
+pub trait HttpTransport: Send + Sync {
+    fn send(
+        &self,
+        request: HttpRequest<AsyncBody>,
+    ) -> BoxFuture<'_, Result<HttpResponse<AsyncBody>>>;
+}
+
+impl HttpTransport for isahc::HttpClient {
+    fn send(
+        &self,
+        request: HttpRequest<AsyncBody>,
+    ) -> BoxFuture<'_, Result<HttpResponse<AsyncBody>>> {
+        Box::pin(async move { Ok(self.send_async(request).await?) })
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Transport(pub(crate) Arc<dyn HttpTransport>);
+
+impl fmt::Debug for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("HttpTransport")
+    }
+}
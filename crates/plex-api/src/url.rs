@@ -7,13 +7,19 @@ pub const MYPLEX_CLAIM_TOKEN_PATH: &str = "/api/claim/token.json";
 pub const MYPLEX_PRIVACY_PATH: &str = "/api/v2/user/privacy";
 pub const MYPLEX_WEBHOOKS_PATH: &str = "/api/v2/user/webhooks";
 pub const MYPLEX_DEVICES: &str = "/devices.xml";
-pub const MYPLEX_RESOURCES: &str = "/api/resources"; // TODO: migrate to /api/v2/resources.json
+pub const MYPLEX_DEVICE: &str = "/devices/{deviceId}.xml";
+pub const MYPLEX_RESOURCES: &str = "/api/resources";
+pub const MYPLEX_RESOURCES_V2: &str = "/api/v2/resources";
 pub const MYPLEX_FEATURES: &str = "/api/v2/features";
 pub const MYPLEX_COMPANIONS: &str = "/api/v2/companions";
 pub const MYPLEX_PROVIDERS: &str = "/media/providers";
+pub const MYPLEX_SYNC_ITEMS: &str = "/devices/{clientId}/sync_items";
+pub const MYPLEX_SYNC_ITEM: &str = "/devices/{clientId}/sync_items/{syncId}";
 
 pub const MYPLEX_ANNOUNCEMENTS: &str = "/api/announcements";
 
+pub const MYPLEX_GEOIP: &str = "/api/v2/geoip";
+pub const MYPLEX_PUBLIC_IP: &str = "/:/ip";
 pub const MYPLEX_SERVERS: &str = "/api/v2/servers";
 
 pub const MYPLEX_INVITES: &str = "/api/invites";
@@ -28,21 +34,70 @@ pub const MYPLEX_PINS: &str = "/api/v2/pins";
 pub const MYPLEX_PINS_LINK: &str = "/api/v2/pins/link.json";
 
 pub const MYPLEX_USERS: &str = "/api/home/users";
+pub const MYPLEX_USER: &str = "/api/home/users/{userId}";
+pub const MYPLEX_USERS_V2: &str = "/api/v2/home/users";
 pub const MYPLEX_USER_SWITCH: &str = "/api/v2/home/users/{uuid}/switch"; // ?includeSubscriptions=1&includeProviders=1&includeSettings=1&includeSharedSettings=1&pin=0373
 
 pub const SERVER_MEDIA_PROVIDERS: &str = "/media/providers";
+pub const SERVER_IDENTITY: &str = "/identity";
 pub const SERVER_MYPLEX_ACCOUNT: &str = "/myplex/account";
 pub const SERVER_MYPLEX_CLAIM: &str = "/myplex/claim";
+pub const SERVER_SECURITY_TOKEN: &str = "/security/token";
+pub const SERVER_ACCOUNTS: &str = "/accounts";
+pub const SERVER_ACCOUNT: &str = "/accounts/{accountId}";
+pub const SERVER_ACTIVITIES: &str = "/activities";
+pub const SERVER_BUTLER: &str = "/butler";
+pub const SERVER_BUTLER_TASK: &str = "/butler/{taskName}";
+pub const SERVER_BROWSE: &str = "/services/browse/{path}";
 pub const SERVER_PREFS: &str = "/:/prefs";
 pub const SERVER_TRANSCODE_SESSIONS: &str = "/transcode/sessions";
 pub const SERVER_TRANSCODE_DECISION: &str = "/video/:/transcode/universal/decision";
 pub const SERVER_TRANSCODE_DOWNLOAD: &str = "/video/:/transcode/universal/start.{extension}";
 pub const SERVER_TRANSCODE_STOP: &str = "/video/:/transcode/universal/stop";
+pub const SERVER_TRANSCODE_PING: &str = "/video/:/transcode/universal/ping";
 pub const SERVER_TRANSCODE_ART: &str = "/photo/:/transcode";
 pub const SERVER_SYSTEM_PROXY: &str = "/system/proxy";
+pub const SERVER_SYSTEM_AGENTS: &str = "/system/agents";
+pub const SERVER_SYSTEM_AGENT_CONFIG: &str = "/system/agents/{agentId}/config/{mediaType}";
+pub const SERVER_SYSTEM_SCANNERS: &str = "/system/scanners/{mediaType}";
 pub const SERVER_SCROBBLE: &str = "/:/scrobble";
 pub const SERVER_UNSCROBBLE: &str = "/:/unscrobble";
 pub const SERVER_TIMELINE: &str = "/:/timeline";
+pub const SERVER_LIBRARY_ALL: &str = "/library/all";
+pub const SERVER_LIBRARY_PREFS: &str = "/library/metadata/{ratingKey}/prefs";
+pub const SERVER_LIBRARY_SUBTITLES: &str = "/library/metadata/{ratingKey}/subtitles";
+pub const SERVER_LIBRARY_PART_INDEX: &str = "/library/parts/{partId}/indexes/{index}";
+pub const SERVER_LIBRARY_STREAM: &str = "/library/streams/{streamId}";
+pub const SERVER_LIBRARY_STREAM_LEVELS: &str = "/library/streams/{streamId}/levels";
+pub const SERVER_LIBRARY_SIMILAR: &str = "/library/metadata/{ratingKey}/similar";
+pub const SERVER_LIBRARY_NEAREST: &str = "/library/metadata/{ratingKey}/nearest";
+pub const SERVER_LIBRARY_STATION: &str = "/library/metadata/{ratingKey}/station/{stationId}";
+pub const SERVER_LIBRARY_UPLOAD: &str = "/library/sections/{sectionId}/upload";
+pub const SERVER_LIBRARY_SECTION: &str = "/library/sections/{sectionId}";
+pub const SERVER_LIBRARY_SECTION_PREFS: &str = "/library/sections/{sectionId}/prefs";
+pub const SERVER_LIBRARY_SECTION_ANALYZE: &str = "/library/sections/{sectionId}/analyze";
+pub const SERVER_LIBRARY_SECTION_ALL: &str = "/library/sections/{sectionId}/all";
+pub const SERVER_LIBRARY_SECTION_RECENTLY_ADDED: &str =
+    "/library/sections/{sectionId}/recentlyAdded";
+pub const SERVER_LIBRARY_SECTION_NEWEST: &str = "/library/sections/{sectionId}/newest";
+pub const SERVER_LIBRARY_SECTION_FOLDER: &str = "/library/sections/{sectionId}/folder";
+pub const SERVER_LIBRARY_SECTION_TAGS: &str = "/library/sections/{sectionId}/{field}";
+pub const SERVER_COLLECTIONS: &str = "/library/collections";
+pub const SERVER_COLLECTION_ITEMS: &str = "/library/collections/{collectionId}/items";
+pub const SERVER_PLAYLISTS: &str = "/playlists";
+pub const SERVER_PLAYLIST_ITEMS: &str = "/playlists/{playlistId}/items";
+pub const SERVER_OPTIMIZED_ITEMS: &str = "/playlists/generators";
+pub const SERVER_OPTIMIZED_ITEM: &str = "/playlists/generators/{generatorId}";
+pub const SERVER_LIVETV_DVRS: &str = "/livetv/dvrs";
+pub const SERVER_LIVETV_DVR: &str = "/livetv/dvrs/{dvrId}";
+pub const SERVER_LIVETV_DVR_PREFS: &str = "/livetv/dvrs/{dvrId}/prefs";
+pub const SERVER_LIVETV_LINEUP_CHANNELS: &str = "/livetv/epg/lineupchannels";
+pub const SERVER_LIVETV_DEVICE_CHANNELS: &str = "/media/grabbers/devices/{deviceId}/channels";
+pub const SERVER_LIVETV_TUNE: &str = "/livetv/dvrs/{dvrId}/channels/{channel}/tune";
+pub const SERVER_GRAB_OPERATION: &str = "/media/grabbers/operations/{operationId}";
+pub const SERVER_SUBSCRIPTIONS: &str = "/media/subscriptions";
+pub const SERVER_SUBSCRIPTION: &str = "/media/subscriptions/{subscriptionId}";
+pub const SERVER_SUBSCRIPTION_MOVE: &str = "/media/subscriptions/{subscriptionId}/move";
 
 pub const CLIENT_RESOURCES: &str = "/resources";
 
@@ -53,3 +108,8 @@ pub const DOWNLOAD_QUEUE_LIST: &str = "/downloadQueue/{queueId}/items";
 pub const DOWNLOAD_QUEUE_ITEM: &str = "/downloadQueue/{queueId}/items/{itemId}";
 pub const DOWNLOAD_QUEUE_ADD: &str = "/downloadQueue/{queueId}/add";
 pub const DOWNLOAD_QUEUE_DOWNLOAD: &str = "/downloadQueue/{queueId}/item/{itemId}/media";
+
+/// Whether the host belongs to plex.tv rather than to a server.
+pub(crate) fn is_myplex_host(host: &str) -> bool {
+    host == "plex.tv" || host.ends_with(".plex.tv")
+}
@@ -0,0 +1,372 @@
+//! Reads the XML responses of the server into the shape of its JSON
+//! responses, so the same types can deserialize both. Plex builds its JSON
+//! from the XML: attributes become fields and child elements become arrays
+//! named after their tag.
+//!
+//! Library items are the exception: JSON lists them all as `Metadata`
+//! while XML names them after their kind, e.g. `Video` or `Directory`. A few
+//! elements only ever appear once and are objects in JSON.
+//!
+//! XML doesn't say which attributes are numbers though. Values that look
+//! like numbers become numbers, except for the identifiers and codes below
+//! which the server sends as strings in JSON, and `0` or `1` become booleans
+//! for the attributes that are booleans in JSON. The values of settings are
+//! typed by their `type` attribute instead. Free text like the titles of
+//! library items can look like a number as well, e.g. a library called
+//! `2024`, so the types accept both for those fields.
+
+use quick_xml::{
+    de::DeError,
+    events::{BytesStart, Event},
+    Reader,
+};
+use serde_json::{Map, Number, Value};
+
+use crate::Result;
+
+/// Attributes with values that look like numbers but are strings in JSON,
+/// and that aren't read as free text by the types.
+const STRING_ATTRIBUTES: &[&str] = &[
+    "albumGain",
+    "albumPeak",
+    "albumRange",
+    "allowSync",
+    "burn",
+    "channelIdentifier",
+    "channelVcn",
+    "contentRating",
+    "createdAtTZOffset",
+    "deepAnalysisVersion",
+    "deviceID",
+    "deviceIdentifier",
+    "enabled",
+    "gain",
+    "hasPremiumPrimaryExtra",
+    "hasThumbnail",
+    "identifier",
+    "ignore",
+    "iso",
+    "key",
+    "lineupIdentifier",
+    "loudness",
+    "loudnessAnalysisVersion",
+    "lra",
+    "maxYear",
+    "minLines",
+    "minYear",
+    "musicAnalysisVersion",
+    "name",
+    "parentRatingKey",
+    "peak",
+    "postalCode",
+    "privatePort",
+    "product",
+    "publicPort",
+    "ratingKey",
+    "scrobbleTypes",
+    "state",
+    "streamIdentifier",
+    "summary",
+    "tag",
+    "timed",
+    "title",
+    "totalSamples",
+    "tuners",
+    "version",
+    "videoResolution",
+];
+
+/// Attributes that are booleans in JSON.
+const BOOL_ATTRIBUTES: &[&str] = &[
+    "accepted",
+    "active",
+    "advanced",
+    "allLibraries",
+    "allowCameraUpload",
+    "allowChannelAccess",
+    "allowChannels",
+    "allowMediaDeletion",
+    "allowSharing",
+    "allowSubtitleAdmin",
+    "anamorphic",
+    "autoSelectAudio",
+    "backgroundProcessing",
+    "certificate",
+    "claimed",
+    "companionProxy",
+    "complete",
+    "confirmed",
+    "dnsRebindingProtection",
+    "downloaded",
+    "error",
+    "eventStream",
+    "experimentalFeatures",
+    "forced",
+    "guest",
+    "has64bitOffsets",
+    "hasAttribution",
+    "hasPassword",
+    "hasPrefs",
+    "hasScalingMatrix",
+    "hearingImpaired",
+    "hidden",
+    "home",
+    "homeAdmin",
+    "httpsRequired",
+    "local",
+    "myPlex",
+    "myPlexSubscription",
+    "natLoopbackSupported",
+    "nocache",
+    "onAir",
+    "owned",
+    "perfectMatch",
+    "photoAutoTag",
+    "pluginHost",
+    "premiere",
+    "premium",
+    "presence",
+    "primary",
+    "protected",
+    "public",
+    "publicAddressMatches",
+    "pushNotifications",
+    "readOnlyLibraries",
+    "refreshing",
+    "relay",
+    "restricted",
+    "selected",
+    "smart",
+    "sync",
+    "synced",
+    "throttled",
+    "transcodeHwRequested",
+    "transcoderAudio",
+    "transcoderLyrics",
+    "transcoderSubtitles",
+    "transcoderVideo",
+    "trusted",
+    "updater",
+    "voiceSearch",
+];
+
+/// Elements that are library items when they have a `ratingKey`.
+const METADATA_ELEMENTS: &[&str] = &["Directory", "Photo", "Playlist", "Track", "Video"];
+
+/// Elements that JSON has as an object rather than an array.
+const SINGLE_ELEMENTS: &[&str] = &["Attributes", "Context", "Extras", "Preferences"];
+
+/// Converts an XML document to the JSON the server would have sent, e.g.
+/// `<MediaContainer size="1"><Video title="Up"/></MediaContainer>` to
+/// `{"MediaContainer": {"size": 1, "Video": [{"title": "Up"}]}}`.
+pub(crate) fn to_json(xml: &[u8]) -> Result<Value> {
+    let xml = String::from_utf8_lossy(xml);
+    let mut reader = Reader::from_str(&xml);
+    reader.config_mut().trim_text(true);
+
+    // The open elements with their tag names.
+    let mut stack: Vec<(String, Map<String, Value>)> = Vec::new();
+
+    loop {
+        match reader.read_event().map_err(DeError::from)? {
+            Event::Start(element) => {
+                stack.push(read_element(&reader, &element)?);
+            }
+            Event::Empty(element) => {
+                let (name, object) = read_element(&reader, &element)?;
+                match stack.last_mut() {
+                    Some((_, parent)) => add_child(parent, name, object),
+                    None => return Ok(root(name, object)),
+                }
+            }
+            Event::End(_) => {
+                let Some((name, object)) = stack.pop() else {
+                    break;
+                };
+                match stack.last_mut() {
+                    Some((_, parent)) => add_child(parent, name, object),
+                    None => return Ok(root(name, object)),
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Err(DeError::Custom("XML document has no root element".to_owned()).into())
+}
+
+fn read_element(
+    reader: &Reader<&[u8]>,
+    element: &BytesStart<'_>,
+) -> Result<(String, Map<String, Value>)> {
+    let mut name = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+
+    let mut attributes = Vec::new();
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(|error| DeError::from(quick_xml::Error::from(error)))?;
+        let key = String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned();
+        let value = attribute
+            .decode_and_unescape_value(reader.decoder())
+            .map_err(DeError::from)?;
+        attributes.push((key, value.into_owned()));
+    }
+
+    let setting_type = (name == "Setting")
+        .then(|| attributes.iter().find(|(key, _)| key == "type"))
+        .flatten()
+        .map(|(_, value)| value.clone());
+    let mut object = Map::new();
+    for (key, value) in attributes {
+        let value = match (&setting_type, key.as_str()) {
+            (Some(setting_type), "value" | "default") => match setting_type.as_str() {
+                "bool" => bool_value(&value).unwrap_or(Value::String(value)),
+                _ => Value::String(value),
+            },
+            (_, "default") if name == "Stream" => {
+                bool_value(&value).unwrap_or(Value::String(value))
+            }
+            _ => attribute_value(&key, value),
+        };
+        object.insert(key, value);
+    }
+
+    if METADATA_ELEMENTS.contains(&name.as_str()) && object.contains_key("ratingKey") {
+        name = "Metadata".to_owned();
+    }
+
+    Ok((name, object))
+}
+
+fn attribute_value(key: &str, value: String) -> Value {
+    if BOOL_ATTRIBUTES.contains(&key) {
+        if let Some(value) = bool_value(&value) {
+            return value;
+        }
+    }
+
+    if !STRING_ATTRIBUTES.contains(&key) {
+        if let Some(number) = number_value(&value) {
+            return Value::Number(number);
+        }
+    }
+
+    Value::String(value)
+}
+
+fn bool_value(value: &str) -> Option<Value> {
+    match value {
+        "1" | "true" => Some(Value::Bool(true)),
+        "0" | "false" => Some(Value::Bool(false)),
+        _ => None,
+    }
+}
+
+/// Only values that read back the same, e.g. `2046` or `25.0` but not `007`
+/// or `1e5`, so that text fields accepting numbers get the original text.
+fn number_value(value: &str) -> Option<Number> {
+    let number = match value.parse::<i64>() {
+        Ok(number) => number.into(),
+        Err(_) => Number::from_f64(value.parse().ok()?)?,
+    };
+    (number.to_string() == value).then_some(number)
+}
+
+fn add_child(parent: &mut Map<String, Value>, name: String, child: Map<String, Value>) {
+    if SINGLE_ELEMENTS.contains(&name.as_str()) {
+        parent.insert(name, Value::Object(child));
+        return;
+    }
+
+    // Dropped when an attribute has the same name, which JSON can't
+    // represent either.
+    if let Value::Array(children) = parent
+        .entry(name)
+        .or_insert_with(|| Value::Array(Vec::new()))
+    {
+        children.push(Value::Object(child));
+    }
+}
+
+fn root(name: String, object: Map<String, Value>) -> Value {
+    let mut root = Map::new();
+    root.insert(name, Value::Object(object));
+    Value::Object(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn converts_to_json() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<MediaContainer size="2" allowSync="1" identifier="com.plexapp.plugins.library">
+  <Video ratingKey="182" title="1917" year="2019" rating="8.3" selected="1" summary="A &amp; B">
+    <Media id="1" videoResolution="1080" />
+    <Media id="2" />
+    <Extras size="0" />
+  </Video>
+  <Directory ratingKey="183" title="Up" duration="5760000" />
+  <Directory key="all" title="All" />
+</MediaContainer>"#;
+
+        assert_eq!(
+            to_json(xml.as_bytes()).unwrap(),
+            json!({
+                "MediaContainer": {
+                    "size": 2,
+                    "allowSync": "1",
+                    "identifier": "com.plexapp.plugins.library",
+                    "Metadata": [
+                        {
+                            "ratingKey": "182",
+                            "title": "1917",
+                            "year": 2019,
+                            "rating": 8.3,
+                            "selected": true,
+                            "summary": "A & B",
+                            "Media": [
+                                {"id": 1, "videoResolution": "1080"},
+                                {"id": 2}
+                            ],
+                            "Extras": {"size": 0}
+                        },
+                        {"ratingKey": "183", "title": "Up", "duration": 5760000}
+                    ],
+                    "Directory": [{"key": "all", "title": "All"}]
+                }
+            })
+        );
+
+        let xml = r#"<Stream id="1" default="1"><Setting id="a" type="bool" value="0" default="1" /><Setting id="b" type="int" value="32400" default="0" /></Stream>"#;
+        assert_eq!(
+            to_json(xml.as_bytes()).unwrap(),
+            json!({
+                "Stream": {
+                    "id": 1,
+                    "default": true,
+                    "Setting": [
+                        {"id": "a", "type": "bool", "value": false, "default": true},
+                        {"id": "b", "type": "int", "value": "32400", "default": "0"}
+                    ]
+                }
+            })
+        );
+
+        assert!(to_json(b"not xml").is_err());
+    }
+
+    #[test]
+    fn numbers_read_back_the_same() {
+        assert_eq!(number_value("2046"), Some(2046.into()));
+        assert_eq!(number_value("-3"), Some((-3).into()));
+        assert_eq!(number_value("25.0"), Number::from_f64(25.0));
+        assert_eq!(number_value("007"), None);
+        assert_eq!(number_value("1e5"), None);
+        assert_eq!(number_value("+1"), None);
+        assert_eq!(number_value("NaN"), None);
+        assert_eq!(number_value("1080p"), None);
+    }
+}
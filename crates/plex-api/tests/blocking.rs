@@ -0,0 +1,62 @@
+mod offline {
+    use httpmock::{Method::GET, MockServer};
+    use plex_api::{
+        blocking::{Server, Wait},
+        library::{Library, MetadataItem, Movie},
+        url::SERVER_MEDIA_PROVIDERS,
+        HttpClientBuilder,
+    };
+
+    #[test]
+    fn server() {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_MEDIA_PROVIDERS);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("../plex-api-mock-server/mocks/server/media/providers_free.json");
+        });
+        mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/sections/1/all")
+                .query_param("type", "1");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("../plex-api-mock-server/mocks/server/media/movie_library.json");
+        });
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/108");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/metadata_108.json");
+        });
+
+        let client = HttpClientBuilder::new(mock_server.base_url())
+            .build()
+            .unwrap();
+        let server = Server::new(mock_server.base_url(), client).unwrap();
+
+        let libraries = server.libraries();
+        assert!(matches!(*libraries[0], Library::Movie(_)));
+        let items = libraries[0].items().unwrap();
+        assert_eq!(
+            items.iter().map(|item| item.title()).collect::<Vec<_>>(),
+            vec![
+                "Big Buck Bunny",
+                "Elephants Dream",
+                "Interstate 60",
+                "Sintel"
+            ]
+        );
+
+        let movie: Movie = server.item_by_id("108").unwrap().try_into().unwrap();
+        assert_eq!(movie.title(), "Interstate 60");
+
+        // Methods that aren't wrapped can be waited for.
+        let library = libraries[0].clone().into_async();
+        let Library::Movie(library) = library else {
+            unreachable!()
+        };
+        assert_eq!(library.movies().wait().unwrap().len(), 4);
+    }
+}
@@ -2,10 +2,25 @@ mod fixtures;
 
 mod offline {
     use super::fixtures::offline::mock_server;
-    use httpmock::{Method::GET, MockServer};
+    use futures::future::BoxFuture;
+    use httpmock::{
+        Method::{GET, POST},
+        MockServer,
+    };
     use isahc::HttpClient;
-    use plex_api::HttpClientBuilder;
-    use std::time::Duration;
+    use plex_api::{
+        media_container::server::library::VideoCodec,
+        transcode::{ClientProfile, VideoTranscodeOptions},
+        ClientIdentity, HttpClientBuilder, HttpTransport, Proxy, RateLimit, RequestEvent,
+        RequestMetrics, ResponseCache, ResponseInspector, RetryPolicy,
+    };
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        time::{Duration, Instant},
+    };
 
     #[plex_api_test_helper::offline_test]
     async fn default_client(mock_server: MockServer) {
@@ -84,6 +99,29 @@ mod offline {
         get_result.expect("failed to perform first http request");
     }
 
+    #[plex_api_test_helper::offline_test]
+    async fn client_profile(mock_server: MockServer) {
+        let client = HttpClientBuilder::new(mock_server.base_url())
+            .set_client_profile(ClientProfile::Roku)
+            .build()
+            .expect("failed to build client");
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/").header("X-Plex-Platform", "Roku");
+            then.status(200).body("");
+        });
+
+        let get_result = client.get("/").send().await;
+
+        m.assert();
+
+        get_result.expect("failed to perform first http request");
+
+        let options = VideoTranscodeOptions::from(ClientProfile::AndroidTv);
+        assert_eq!((options.width, options.height), (3840, 2160));
+        assert!(options.video_codecs.contains(&VideoCodec::Hevc));
+    }
+
     #[plex_api_test_helper::offline_test]
     async fn auth_token_updated_after_build(mock_server: MockServer) {
         let client = HttpClientBuilder::new(mock_server.base_url())
@@ -163,4 +201,557 @@ mod offline {
 
         get_result.expect("failed to perform first http request");
     }
+
+    #[plex_api_test_helper::offline_test]
+    async fn retry_policy(mock_server: MockServer) {
+        let client = HttpClientBuilder::new(mock_server.base_url())
+            .set_retry_policy(
+                RetryPolicy::default()
+                    .max_attempts(3)
+                    .initial_backoff(Duration::from_millis(1)),
+            )
+            .build()
+            .expect("failed to build client");
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/busy");
+            then.status(503);
+        });
+        let response = client.get("/busy").send().await.unwrap();
+        assert_eq!(response.status().as_u16(), 503);
+        m.assert_calls(3);
+        m.delete();
+
+        // Non-idempotent requests might have been processed already.
+        let mut m = mock_server.mock(|when, then| {
+            when.method(POST).path("/busy");
+            then.status(503);
+        });
+        client.post("/busy").send().await.unwrap();
+        m.assert_calls(1);
+        m.delete();
+
+        // ...unless the server says it didn't.
+        let mut m = mock_server.mock(|when, then| {
+            when.method(POST).path("/limited");
+            then.status(429).header("Retry-After", "0");
+        });
+        let error = client.post("/limited").consume().await.unwrap_err();
+        assert!(matches!(
+            error,
+            plex_api::Error::RateLimited {
+                retry_after: Some(retry_after)
+            } if retry_after.is_zero()
+        ));
+        m.assert_calls(3);
+        m.delete();
+
+        // Bodies can't be sent again.
+        let m = mock_server.mock(|when, then| {
+            when.method(POST).path("/limited");
+            then.status(429);
+        });
+        client
+            .post("/limited")
+            .form(&[("key", "value")])
+            .unwrap()
+            .send()
+            .await
+            .unwrap();
+        m.assert_calls(1);
+
+        let client = HttpClientBuilder::new(mock_server.base_url())
+            .build()
+            .expect("failed to build client");
+        assert!(client.retry_policy().is_none());
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/unavailable");
+            then.status(503);
+        });
+        client.get("/unavailable").send().await.unwrap();
+        m.assert_calls(1);
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn rate_limit(mock_server: MockServer) {
+        let client = HttpClientBuilder::new(mock_server.base_url())
+            .set_server_rate_limit(RateLimit::per_second(20.0).burst(2))
+            .build()
+            .expect("failed to build client");
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/");
+            then.status(200);
+        });
+
+        // The first two requests fit in the burst, the next two have to wait
+        // for the bucket to refill. Clones share the limit.
+        let started = Instant::now();
+        client.get("/").consume().await.unwrap();
+        client.get("/").consume().await.unwrap();
+        let clone = client.clone();
+        clone.get("/").consume().await.unwrap();
+        clone.get("/").consume().await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        m.assert_calls(4);
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn rate_limit_invalid_rates(mock_server: MockServer) {
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/");
+            then.status(200);
+        });
+
+        // Rates that can't be waited for disable the limit.
+        for rate in [f64::NAN, f64::INFINITY, -1.0] {
+            let client = HttpClientBuilder::new(mock_server.base_url())
+                .set_server_rate_limit(RateLimit::per_second(rate).burst(1))
+                .build()
+                .expect("failed to build client");
+            client.get("/").consume().await.unwrap();
+            client.get("/").consume().await.unwrap();
+        }
+        m.assert_calls(6);
+
+        // A tiny rate waits for practically ever instead of panicking.
+        let client = HttpClientBuilder::new(mock_server.base_url())
+            .set_server_rate_limit(RateLimit::per_second(f64::MIN_POSITIVE))
+            .build()
+            .expect("failed to build client");
+        client.get("/").consume().await.unwrap();
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), client.get("/").consume())
+                .await
+                .is_err()
+        );
+        m.assert_calls(7);
+    }
+
+    /// Counts the responses and answers requests for `/replayed` itself.
+    struct Replay {
+        seen: Arc<AtomicUsize>,
+    }
+
+    impl ResponseInspector for Replay {
+        fn inspect<'a>(
+            &'a self,
+            _method: &'a isahc::http::Method,
+            uri: &'a isahc::http::Uri,
+            response: isahc::Response<isahc::AsyncBody>,
+        ) -> futures::future::BoxFuture<'a, plex_api::Result<isahc::Response<isahc::AsyncBody>>>
+        {
+            Box::pin(async move {
+                self.seen.fetch_add(1, Ordering::SeqCst);
+                if uri.path() == "/replayed" {
+                    Ok(isahc::Response::builder()
+                        .status(200)
+                        .body(isahc::AsyncBody::from("{\"replayed\": true}"))
+                        .unwrap())
+                } else {
+                    Ok(response)
+                }
+            })
+        }
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn middleware(mock_server: MockServer) {
+        let seen = Arc::new(AtomicUsize::new(0));
+        let client = HttpClientBuilder::new(mock_server.base_url())
+            .add_request_interceptor(|mut request: isahc::Request<isahc::AsyncBody>| {
+                request
+                    .headers_mut()
+                    .insert("X-Custom", "intercepted".parse().unwrap());
+                request
+            })
+            .add_response_inspector(Replay { seen: seen.clone() })
+            .build()
+            .expect("failed to build client");
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/").header("X-Custom", "intercepted");
+            then.status(200);
+        });
+        client.get("/").consume().await.unwrap();
+        m.assert();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/replayed");
+            then.status(404);
+        });
+        let response: serde_json::Value = client.get("/replayed").json().await.unwrap();
+        assert_eq!(response["replayed"], true);
+        m.assert();
+
+        assert_eq!(seen.load(Ordering::SeqCst), 2);
+    }
+
+    type Event = (String, String, Option<u16>, bool);
+
+    #[derive(Default, Clone)]
+    struct Recorder {
+        events: Arc<Mutex<Vec<Event>>>,
+    }
+
+    impl RequestMetrics for Recorder {
+        fn record(&self, event: &RequestEvent<'_>) {
+            self.events.lock().unwrap().push((
+                event.method.to_string(),
+                event.path.to_owned(),
+                event.status,
+                event.is_error(),
+            ));
+        }
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn metrics(mock_server: MockServer) {
+        let recorder = Recorder::default();
+        let client = HttpClientBuilder::new(mock_server.base_url())
+            .set_metrics(recorder.clone())
+            .build()
+            .expect("failed to build client");
+
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/identity");
+            then.status(200);
+        });
+        mock_server.mock(|when, then| {
+            when.method(POST).path("/missing");
+            then.status(404);
+        });
+
+        client
+            .get("/identity?X-Plex-Token=secret")
+            .consume()
+            .await
+            .unwrap();
+        client.post("/missing").consume().await.unwrap_err();
+
+        assert_eq!(
+            *recorder.events.lock().unwrap(),
+            vec![
+                ("GET".to_owned(), "/identity".to_owned(), Some(200), false),
+                ("POST".to_owned(), "/missing".to_owned(), Some(404), true),
+            ]
+        );
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn compression(mock_server: MockServer) {
+        let client = HttpClientBuilder::new(mock_server.base_url())
+            .build()
+            .expect("failed to build client");
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/identity")
+                .header_includes("Accept-Encoding", "gzip");
+            then.status(200)
+                .header("content-type", "application/json")
+                .header("content-encoding", "gzip")
+                .body_from_file("tests/mocks/server/identity.json.gz");
+        });
+        let identity: serde_json::Value = client.get("/identity").json().await.unwrap();
+        assert_eq!(
+            identity["MediaContainer"]["machineIdentifier"],
+            "machine_id3"
+        );
+        m.assert();
+
+        let client = HttpClientBuilder::new(mock_server.base_url())
+            .set_compression(false)
+            .build()
+            .expect("failed to build client");
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/uncompressed")
+                .header_missing("Accept-Encoding");
+            then.status(200);
+        });
+        client.get("/uncompressed").consume().await.unwrap();
+        m.assert();
+    }
+
+    struct Canned;
+
+    impl HttpTransport for Canned {
+        fn send(
+            &self,
+            request: isahc::Request<isahc::AsyncBody>,
+        ) -> BoxFuture<'_, plex_api::Result<isahc::Response<isahc::AsyncBody>>> {
+            Box::pin(async move {
+                let body = format!(r#"{{"path": "{}"}}"#, request.uri().path());
+                Ok(isahc::Response::builder()
+                    .status(200)
+                    .header("content-type", "application/json")
+                    .body(isahc::AsyncBody::from(body))?)
+            })
+        }
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn custom_transport() {
+        let client = HttpClientBuilder::new("http://plex.invalid:32400")
+            .set_transport(Canned)
+            .build()
+            .expect("failed to build client");
+
+        let response: serde_json::Value = client.get("/identity").json().await.unwrap();
+        assert_eq!(response["path"], "/identity");
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn proxy(mock_server: MockServer) {
+        let client = HttpClientBuilder::new("http://plex.invalid:32400")
+            .set_server_proxy(Proxy::new(mock_server.base_url()).unwrap())
+            .build()
+            .expect("failed to build client");
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/identity")
+                .header("Host", "plex.invalid:32400");
+            then.status(200);
+        });
+        client.get("/identity").consume().await.unwrap();
+        m.assert();
+
+        // Hosts excluded from the proxy are requested directly.
+        let client = HttpClientBuilder::new(mock_server.base_url())
+            .set_proxy(
+                Proxy::new("http://proxy.invalid:3128")
+                    .unwrap()
+                    .no_proxy(["127.0.0.1", "localhost"]),
+            )
+            .build()
+            .expect("failed to build client");
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/direct");
+            then.status(200);
+        });
+        client.get("/direct").consume().await.unwrap();
+        m.assert();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn timeouts(mock_server: MockServer) {
+        let client = HttpClientBuilder::new(mock_server.base_url())
+            .build()
+            .expect("failed to build client");
+        assert_eq!(client.timeout(), Some(Duration::from_secs(30)));
+
+        let client = HttpClientBuilder::new(mock_server.base_url())
+            .set_timeout(Some(Duration::from_millis(200)))
+            .set_connect_timeout(Duration::from_secs(1))
+            .build()
+            .expect("failed to build client");
+
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/slow");
+            then.status(200).delay(Duration::from_millis(600));
+        });
+
+        let error = client.get("/slow").consume().await.unwrap_err();
+        assert!(
+            matches!(&error, plex_api::Error::IsahcError { source } if source.is_timeout()),
+            "unexpected error: {error:?}"
+        );
+
+        client
+            .with_timeout(Some(Duration::from_secs(5)))
+            .get("/slow")
+            .consume()
+            .await
+            .unwrap();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn client_identity(mock_server: MockServer) {
+        let path = std::env::temp_dir()
+            .join(format!("plex-api-identity-{}", std::process::id()))
+            .join("identity.json");
+        let _ = std::fs::remove_file(&path);
+
+        let first = HttpClientBuilder::new(mock_server.base_url())
+            .set_x_plex_product("product")
+            .load_identity(&path)
+            .build()
+            .expect("failed to build client");
+        assert!(path.exists());
+
+        let second = HttpClientBuilder::new(mock_server.base_url())
+            .load_identity(&path)
+            .build()
+            .expect("failed to build client");
+        assert_eq!(second.identity(), first.identity());
+        assert_ne!(
+            HttpClientBuilder::default().build().unwrap().identity(),
+            first.identity()
+        );
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/")
+                .header("X-Plex-Product", "product")
+                .header(
+                    "X-Plex-Client-Identifier",
+                    first.x_plex_client_identifier.clone(),
+                );
+            then.status(200).body("");
+        });
+        second.get("/").send().await.unwrap();
+        m.assert();
+
+        let identity = ClientIdentity::load_or_create(&path).unwrap();
+        assert_eq!(identity.client_identifier, first.x_plex_client_identifier);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn language(mock_server: MockServer) {
+        let client = HttpClientBuilder::new(mock_server.base_url())
+            .set_x_plex_language("de")
+            .set_x_plex_country_code("DE")
+            .build()
+            .expect("failed to build client");
+
+        let german = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/")
+                .header("X-Plex-Language", "de")
+                .header("X-Plex-Country-Code", "DE");
+            then.status(200).body("");
+        });
+        client.get("/").send().await.unwrap();
+        german.assert();
+
+        let french = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/fr")
+                .header("X-Plex-Language", "fr")
+                .header_not("X-Plex-Language", "de");
+            then.status(200).body("");
+        });
+        client.get("/fr").language("fr").send().await.unwrap();
+        french.assert();
+
+        let default = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/default")
+                .header_missing("X-Plex-Language")
+                .header_missing("X-Plex-Country-Code");
+            then.status(200).body("");
+        });
+        HttpClientBuilder::new(mock_server.base_url())
+            .build()
+            .unwrap()
+            .get("/default")
+            .send()
+            .await
+            .unwrap();
+        default.assert();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn device_headers(mock_server: MockServer) {
+        let client = HttpClientBuilder::new(mock_server.base_url())
+            .set_x_plex_device_screen_resolution("1440x789,1440x900")
+            .set_x_plex_device_vendor("Apple")
+            .set_x_plex_session_identifier("session")
+            .build()
+            .expect("failed to build client");
+
+        let defaults = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/")
+                .header("X-Plex-Device-Screen-Resolution", "1440x789,1440x900")
+                .header("X-Plex-Device-Vendor", "Apple")
+                .header("X-Plex-Session-Identifier", "session");
+            then.status(200).body("");
+        });
+        client.get("/").send().await.unwrap();
+        defaults.assert();
+
+        let overridden = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/overridden")
+                .header("X-Plex-Device-Screen-Resolution", "1920x1080")
+                .header_not("X-Plex-Device-Screen-Resolution", "1440x789,1440x900")
+                .header("X-Plex-Device-Vendor", "Samsung")
+                .header("X-Plex-Session-Identifier", "other");
+            then.status(200).body("");
+        });
+        client
+            .get("/overridden")
+            .screen_resolution("1920x1080")
+            .device_vendor("Samsung")
+            .session_identifier("other")
+            .send()
+            .await
+            .unwrap();
+        overridden.assert();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn response_cache(mock_server: MockServer) {
+        let client = HttpClientBuilder::new(mock_server.base_url())
+            .set_response_cache(ResponseCache::new(10))
+            .build()
+            .expect("failed to build client");
+
+        let fresh = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/sections")
+                .header("Accept", "application/json")
+                .header_missing("If-None-Match");
+            then.status(200)
+                .header("ETag", "\"v1\"")
+                .header("content-type", "application/json")
+                .body(r#"{"title": "Movies"}"#);
+        });
+        let not_modified = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/sections")
+                .header("If-None-Match", "\"v1\"");
+            then.status(304);
+        });
+
+        for _ in 0..3 {
+            let response: serde_json::Value = client.get("/library/sections").json().await.unwrap();
+            assert_eq!(response["title"], "Movies");
+        }
+        fresh.assert_calls(1);
+        not_modified.assert_calls(2);
+
+        // Responses are cached separately for each content type.
+        let mut xml = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/sections")
+                .header("Accept", "application/xml")
+                .header_missing("If-None-Match");
+            then.status(200)
+                .header("ETag", "\"v1\"")
+                .header("content-type", "application/xml")
+                .body("<MediaContainer/>");
+        });
+        client
+            .get("/library/sections")
+            .header("Accept", "application/xml")
+            .send()
+            .await
+            .unwrap();
+        xml.assert();
+        xml.delete();
+
+        let cache = client.response_cache().unwrap();
+        assert_eq!(cache.len(), 2);
+        cache.clear();
+        client.get("/library/sections").consume().await.unwrap();
+        fresh.assert_calls(2);
+    }
 }
@@ -1,14 +1,20 @@
 mod fixtures;
 
 mod offline {
-    use std::collections::HashMap;
+    use std::{collections::HashMap, time::Duration};
+
+    use futures::TryStreamExt;
 
     use super::fixtures::offline::{server::*, Mocked};
-    use httpmock::{prelude::HttpMockRequest, Method::GET};
+    use httpmock::{
+        prelude::HttpMockRequest,
+        Method::{DELETE, GET, HEAD},
+    };
     use plex_api::{
-        library::{Movie, Transcodable},
+        library::{Movie, Season, Transcodable},
         media_container::server::library::{AudioCodec, VideoCodec},
-        Server,
+        transcode::QueueItemStatus,
+        DownloadOptions, DownloadVerificationError, Error, Server, SparseFile,
     };
 
     // Expands a profile query parameter into the list of settings.
@@ -155,6 +161,600 @@ mod offline {
         m.delete();
     }
 
+    #[plex_api_test_helper::offline_test]
+    async fn get_or_create_device_download_queues(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let mut devices = mock_server.mock(|when, then| {
+            when.method(GET).path("/devices.xml");
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body_from_file("tests/mocks/myplex/devices.xml");
+        });
+
+        let mut queues = mock_server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/downloadQueue");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/queue_created.json");
+        });
+
+        let result = server.get_or_create_device_download_queues().await.unwrap();
+        devices.assert();
+        devices.delete();
+        // One of the devices is a server which doesn't get a queue.
+        queues.assert_calls(25);
+        queues.delete();
+
+        assert_eq!(result.len(), 25);
+        assert_eq!(result[0].client_identifier(), "1");
+        assert!(result.iter().all(|queue| queue.client_identifier() != "3"));
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(httpmock::Method::POST)
+                .path("/downloadQueue")
+                .header("X-Plex-Client-Identifier", "9");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/queue_created.json");
+        });
+
+        let queue = server.device_download_queue("9").await.unwrap();
+        m.assert();
+        m.delete();
+
+        assert_eq!(queue.client_identifier(), "9");
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn clear_queue(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/downloadQueue");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/queue_created.json");
+        });
+
+        let queue = server.download_queue().await.unwrap();
+        m.assert();
+        m.delete();
+
+        let mut items = mock_server.mock(|when, then| {
+            when.method(GET).path("/downloadQueue/1/items");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/items_mixed.json");
+        });
+        let deletes = [123, 124, 125].map(|id| {
+            mock_server.mock(|when, then| {
+                when.method(DELETE)
+                    .path(format!("/downloadQueue/1/items/{id}"));
+                then.status(200);
+            })
+        });
+
+        let mut purged = queue.purge_expired().await.unwrap();
+        items.assert();
+        deletes[0].assert_calls(0);
+        deletes[1].assert();
+        deletes[2].assert();
+        purged.deleted.sort();
+        assert_eq!(purged.deleted, [124, 125]);
+        assert!(purged.failed.is_empty());
+
+        let mut cleared = queue.clear().await.unwrap();
+        items.assert_calls(2);
+        deletes[0].assert();
+        deletes[1].assert_calls(2);
+        deletes[2].assert_calls(2);
+        cleared.deleted.sort();
+        assert_eq!(cleared.deleted, [123, 124, 125]);
+
+        // An item that can't be deleted doesn't stop the others.
+        let [mut first, second, third] = deletes;
+        first.delete();
+        let failing = mock_server.mock(|when, then| {
+            when.method(DELETE).path("/downloadQueue/1/items/123");
+            then.status(500);
+        });
+
+        let mut cleared = queue.clear().await.unwrap();
+        items.assert_calls(3);
+        failing.assert();
+        second.assert_calls(3);
+        third.assert_calls(3);
+        cleared.deleted.sort();
+        assert_eq!(cleared.deleted, [124, 125]);
+        assert_eq!(cleared.failed.len(), 1);
+        let (id, error) = &cleared.failed[0];
+        assert_eq!(*id, 123);
+        assert!(
+            matches!(
+                error,
+                Error::UnexpectedApiResponse {
+                    status_code: 500,
+                    ..
+                }
+            ),
+            "{error:?}"
+        );
+
+        items.delete();
+        for mut m in [failing, second, third] {
+            m.delete();
+        }
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn wait_for_item(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/downloadQueue");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/queue_created.json");
+        });
+
+        let queue = server.download_queue().await.unwrap();
+        m.assert();
+        m.delete();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/downloadQueue/1/items/123");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/item_deciding.json");
+        });
+
+        let mut item = queue.item(123).await.unwrap();
+
+        // Still deciding after the timeout.
+        let error = item
+            .wait_for_decision(Some(Duration::from_millis(600)))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, plex_api::Error::Timeout));
+        m.delete();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/downloadQueue/1/items/123");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/item_processing.json");
+        });
+
+        item.wait_for_decision(None).await.unwrap();
+        assert_eq!(item.status(), QueueItemStatus::Processing);
+        m.delete();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/downloadQueue/1/items/123");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/item_available.json");
+        });
+
+        item.wait_until_available(Some(Duration::from_secs(5)))
+            .await
+            .unwrap();
+        assert_eq!(item.status(), QueueItemStatus::Available);
+        m.delete();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/downloadQueue/1/items/123");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/item_deciding.json");
+        });
+        item.update().await.unwrap();
+        m.delete();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/downloadQueue/1/items/123");
+            then.status(200).header("content-type", "text/json").body(
+                r#"{
+                    "MediaContainer": {
+                        "size": 1,
+                        "DownloadQueueItem": [{
+                            "id": 123,
+                            "queueId": 1,
+                            "key": "/library/metadata/159637",
+                            "status": "expired",
+                            "error": null,
+                            "transcode": null,
+                            "DecisionResult": {}
+                        }]
+                    }
+                }"#,
+            );
+        });
+
+        let error = item.wait_until_available(None).await.unwrap_err();
+        assert!(matches!(error, plex_api::Error::QueueItemExpired));
+        m.delete();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn status_stream(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/downloadQueue");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/queue_created.json");
+        });
+
+        let queue = server.download_queue().await.unwrap();
+        m.assert();
+        m.delete();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/downloadQueue/1/items/123");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/item_deciding.json");
+        });
+
+        let item = queue.item(123).await.unwrap();
+        m.assert();
+
+        let mut statuses = std::pin::pin!(item.status_stream(Duration::from_millis(10)));
+        assert_eq!(
+            statuses.try_next().await.unwrap(),
+            Some(QueueItemStatus::Deciding)
+        );
+        // Nothing was requested for the current status.
+        m.assert_calls(1);
+        m.delete();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/downloadQueue/1/items/123");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/item_processing.json");
+        });
+        assert_eq!(
+            statuses.try_next().await.unwrap(),
+            Some(QueueItemStatus::Processing)
+        );
+        m.assert();
+        m.delete();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/downloadQueue/1/items/123");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/item_available.json");
+        });
+        assert_eq!(
+            statuses.try_next().await.unwrap(),
+            Some(QueueItemStatus::Available)
+        );
+        assert_eq!(statuses.try_next().await.unwrap(), None);
+        m.assert();
+        m.delete();
+
+        // A failed request ends the stream.
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/downloadQueue/1/items/123");
+            then.status(500);
+        });
+        let mut statuses = std::pin::pin!(item.status_stream(Duration::ZERO));
+        assert_eq!(
+            statuses.try_next().await.unwrap(),
+            Some(QueueItemStatus::Deciding)
+        );
+        assert!(statuses.try_next().await.is_err());
+        assert!(statuses.try_next().await.unwrap().is_none());
+        m.assert();
+        m.delete();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn sparse_download(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/downloadQueue");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/queue_created.json");
+        });
+
+        let queue = server.download_queue().await.unwrap();
+        m.assert();
+        m.delete();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/downloadQueue/1/items/123");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/item_available.json");
+        });
+
+        let item = queue.item(123).await.unwrap();
+        m.assert();
+        m.delete();
+
+        // The connection drops half way through.
+        let mut m1 = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/downloadQueue/1/item/123/media")
+                .header("range", "bytes=0-");
+            then.status(206)
+                .header("content-range", "bytes 0-19/20")
+                .body("0123456789");
+        });
+
+        let options = DownloadOptions {
+            max_retries: 0,
+            retry_delay: Duration::ZERO,
+            ..Default::default()
+        };
+        let mut file = SparseFile::new(futures::io::Cursor::new(Vec::new()));
+        item.download_sparse(&mut file, options, ())
+            .await
+            .unwrap_err();
+        m1.assert();
+        m1.delete();
+
+        assert_eq!(file.downloaded(), 10);
+        assert_eq!(file.size(), Some(20));
+        assert!(!file.is_complete());
+
+        let mut m2 = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/downloadQueue/1/item/123/media")
+                .header("range", "bytes=10-");
+            then.status(206)
+                .header("content-range", "bytes 10-19/20")
+                .body("abcdefghij");
+        });
+
+        item.download_sparse(&mut file, options, ()).await.unwrap();
+        m2.assert();
+        m2.delete();
+
+        assert!(file.is_complete());
+        assert_eq!(
+            file.into_inner().into_inner(),
+            b"0123456789abcdefghij".to_vec()
+        );
+
+        // A server that skips ahead would leave a hole in the file.
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/downloadQueue/1/item/123/media")
+                .header("range", "bytes=5-");
+            then.status(206)
+                .header("content-range", "bytes 10-19/20")
+                .body("abcdefghij");
+        });
+
+        let mut file = SparseFile::resume(futures::io::Cursor::new(Vec::new()), 5);
+        let error = item
+            .download_sparse(&mut file, options, ())
+            .await
+            .unwrap_err();
+        m.assert();
+        m.delete();
+
+        assert!(matches!(
+            error,
+            Error::UnexpectedDownloadOffset {
+                requested: 5,
+                received: 10
+            }
+        ));
+
+        // The server explains why the transcode isn't ready yet.
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/downloadQueue/1/item/123/media");
+            then.status(503)
+                .header("content-type", "application/json")
+                .body(r#"{"error": "Transcode in progress"}"#);
+        });
+
+        let mut file = SparseFile::new(futures::io::Cursor::new(Vec::new()));
+        let error = item
+            .download_sparse(&mut file, options, ())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::TranscodeIncomplete), "{error:?}");
+        let error = item.download(Vec::new(), ..).await.unwrap_err();
+        assert!(matches!(error, Error::TranscodeIncomplete), "{error:?}");
+        m.assert_calls(2);
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn verified_download(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/downloadQueue");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/queue_created.json");
+        });
+
+        let queue = server.download_queue().await.unwrap();
+        m.assert();
+        m.delete();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/downloadQueue/1/items/123");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/item_available.json");
+        });
+
+        let item = queue.item(123).await.unwrap();
+        m.assert();
+        m.delete();
+
+        let options = DownloadOptions {
+            max_retries: 0,
+            verify: true,
+            ..Default::default()
+        };
+
+        // The size comes from the Content-Length of the response.
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/downloadQueue/1/item/123/media");
+            then.status(200).body("0123456789");
+        });
+
+        let mut buf = Vec::<u8>::new();
+        item.download_with(&mut buf, .., options, ()).await.unwrap();
+        m.assert();
+        m.delete();
+
+        assert_eq!(buf, b"0123456789");
+
+        // More data than the server said the file has.
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/downloadQueue/1/item/123/media")
+                .header("range", "bytes=0-");
+            then.status(206)
+                .header("content-range", "bytes 0-9/10")
+                .body("0123456789abc");
+        });
+
+        let mut file = SparseFile::new(futures::io::Cursor::new(Vec::new()));
+        let error = item
+            .download_sparse(&mut file, options, ())
+            .await
+            .unwrap_err();
+        m.assert();
+        m.delete();
+
+        assert!(matches!(
+            error,
+            Error::DownloadVerification(DownloadVerificationError::SizeMismatch {
+                expected: 10,
+                received: 13
+            })
+        ));
+
+        // Without a size there's nothing to check against.
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/downloadQueue/1/item/123/media")
+                .header("range", "bytes=0-");
+            then.status(206).body("0123456789");
+        });
+
+        let mut file = SparseFile::new(futures::io::Cursor::new(Vec::new()));
+        let error = item
+            .download_sparse(&mut file, options, ())
+            .await
+            .unwrap_err();
+        m.assert();
+        m.delete();
+
+        assert!(matches!(
+            error,
+            Error::DownloadVerification(DownloadVerificationError::UnknownSize)
+        ));
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn queue_season(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/89");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/tv_seasons.json");
+        });
+
+        let season: Season = server.item_by_id("89").await.unwrap().try_into().unwrap();
+        m.assert();
+        m.delete();
+
+        let mut queue_mock = mock_server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/downloadQueue");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/queue_created.json");
+        });
+
+        let mut episodes_mock = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/89/children");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/tv_episodes.json");
+        });
+
+        let mut add_mock = mock_server.mock(|when, then| {
+            when.method(httpmock::Method::POST)
+                .path("/downloadQueue/1/add")
+                .query_param(
+                    "keys",
+                    "/library/metadata/90,/library/metadata/91,/library/metadata/92",
+                )
+                .query_param("mediaIndex", "-1")
+                .query_param("partIndex", "-1")
+                .query_param("context", "static");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/download_queue/add_items_response.json");
+        });
+
+        let item_mocks = [(124, 90), (125, 91), (126, 92)].map(|(id, key)| {
+            mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path(format!("/downloadQueue/1/items/{id}"));
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body(format!(
+                        r#"{{
+                            "MediaContainer": {{
+                                "size": 1,
+                                "DownloadQueueItem": [{{
+                                    "id": {id},
+                                    "queueId": 1,
+                                    "key": "/library/metadata/{key}",
+                                    "status": "deciding",
+                                    "error": null,
+                                    "transcode": null,
+                                    "DecisionResult": {{}}
+                                }}]
+                            }}
+                        }}"#
+                    ));
+            })
+        });
+
+        let items = season
+            .queue_download_all(Default::default(), None)
+            .await
+            .unwrap();
+        queue_mock.assert();
+        queue_mock.delete();
+        episodes_mock.assert();
+        episodes_mock.delete();
+        add_mock.assert();
+        add_mock.delete();
+        for mut m in item_mocks {
+            m.assert();
+            m.delete();
+        }
+
+        assert_eq!(
+            items.iter().map(|item| item.id()).collect::<Vec<_>>(),
+            vec![124, 125, 126]
+        );
+        assert_eq!(items[2].key(), "/library/metadata/92");
+        assert_eq!(items[0].status(), QueueItemStatus::Deciding);
+    }
+
     #[plex_api_test_helper::offline_test]
     async fn queue_item(#[future] server_authenticated: Mocked<Server>) {
         let (server, mock_server) = server_authenticated.split();
@@ -163,7 +763,7 @@ mod offline {
             when.method(GET).path("/library/metadata/159637");
             then.status(200)
                 .header("content-type", "text/json")
-                .body_from_file("tests/mocks/transcode/metadata_159637.json");
+                .body_from_file("../plex-api-mock-server/mocks/transcode/metadata_159637.json");
         });
 
         let item: Movie = server
@@ -327,6 +927,12 @@ mod offline {
         let stats = queue_item.stats().unwrap();
         assert_eq!(stats.progress, 25.5_f32);
         assert_eq!(stats.speed, Some(2.5_f32));
+        let decision = queue_item.decision();
+        assert_eq!(decision.direct_play_decision_code, Some(2000));
+        assert_eq!(
+            decision.direct_play_decision_text.as_deref(),
+            Some("transcode needed")
+        );
 
         // Update to Available state
         let mut m = mock_server.mock(|when, then| {
@@ -344,6 +950,43 @@ mod offline {
             queue_item.status(),
             plex_api::transcode::QueueItemStatus::Available
         ));
+
+        // Download using several connections
+        let mut head = mock_server.mock(|when, then| {
+            when.method(HEAD).path("/downloadQueue/1/item/123/media");
+            then.status(200).header("content-length", "10");
+        });
+        let chunks = [("0-3", "0123"), ("4-7", "4567"), ("8-9", "89")].map(|(range, body)| {
+            mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/downloadQueue/1/item/123/media")
+                    .header("range", format!("bytes={range}"));
+                then.status(206).body(body);
+            })
+        });
+
+        let mut buf = Vec::<u8>::new();
+        queue_item
+            .download_with(
+                &mut buf,
+                ..,
+                DownloadOptions {
+                    connections: 2,
+                    chunk_size: 4,
+                    ..Default::default()
+                },
+                (),
+            )
+            .await
+            .unwrap();
+        head.assert();
+        head.delete();
+        for mut m in chunks {
+            m.assert();
+            m.delete();
+        }
+
+        assert_eq!(buf, b"0123456789");
     }
 }
 
@@ -380,19 +1023,9 @@ mod online {
 
     /// Waits for an item to become available.
     async fn wait_for_available(item: &mut QueueItem) {
-        let mut count = 0;
-        loop {
-            if matches!(item.status(), QueueItemStatus::Available) {
-                break;
-            }
-            sleep(Duration::from_millis(250)).await;
-            item.update().await.unwrap();
-            count += 1;
-
-            if count > 480 {
-                panic!("Waited too long for item to become available");
-            }
-        }
+        item.wait_until_available(Some(Duration::from_secs(120)))
+            .await
+            .expect("Waited too long for item to become available");
     }
 
     /// Checks the item was correct.
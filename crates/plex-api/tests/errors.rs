@@ -1,6 +1,8 @@
 mod fixtures;
 
 mod offline {
+    use std::time::Duration;
+
     use super::fixtures::offline::{myplex::myplex, Mocked};
     use httpmock::Method::GET;
     use plex_api::{url::MYPLEX_USER_INFO_PATH, MyPlex};
@@ -37,6 +39,27 @@ mod offline {
         ));
     }
 
+    #[plex_api_test_helper::offline_test]
+    async fn error_response_hides_tokens(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+        let _mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_USER_INFO_PATH);
+            then.status(500)
+                .header("content-type", "text/xml")
+                .body(r#"<user id="1" authToken="secret-token" />"#);
+        });
+        assert!(!format!("{myplex:?}").contains("fixture_auth_token"));
+
+        let error = myplex.refresh().await.err().unwrap();
+        assert!(matches!(
+            error,
+            plex_api::Error::UnexpectedApiResponse { .. }
+        ));
+        let message = format!("{error} {error:?}");
+        assert!(!message.contains("secret-token"));
+        assert!(message.contains("REDACTED"));
+    }
+
     #[plex_api_test_helper::offline_test]
     async fn correct_api_error(#[future] myplex: Mocked<MyPlex>) {
         let (myplex, mock_server) = myplex.split();
@@ -47,9 +70,110 @@ mod offline {
                 .body(r#"{"errors": [{"code": 1111, "message": "test", "status": 400}]}"#);
         });
         let r = myplex.refresh().await;
+        let error = r.err().unwrap();
+        assert!(matches!(error, plex_api::Error::MyPlexErrorResponse { .. }));
+        assert_eq!(
+            error.api_error(),
+            Some(&plex_api::PlexApiError {
+                code: 1111,
+                message: "test".to_owned(),
+                status: 400,
+            })
+        );
+    }
+
+    #[plex_api_test_helper::offline_test]
+    #[case::application_xml("application/xml")]
+    #[case::text_xml("text/xml;charset=utf-8")]
+    async fn xml_api_error(#[future] myplex: Mocked<MyPlex>, #[case] content_type: &str) {
+        let (myplex, mock_server) = myplex.split();
+        let _mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_USER_INFO_PATH);
+            then.status(400)
+                .header("content-type", content_type)
+                .body(r#"<Response code="400" status="Bad Request"/>"#);
+        });
+        let r = myplex.refresh().await;
+        match r.err().unwrap() {
+            plex_api::Error::PlexApiError(error) => {
+                assert_eq!(error.code, 400);
+                assert_eq!(error.message, "Bad Request");
+            }
+            error => panic!("Unexpected error: {error:?}"),
+        }
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn message_api_error(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+        let _mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_USER_INFO_PATH);
+            then.status(401)
+                .header("content-type", "application/json")
+                .body(r#"{"error": "Invalid authentication token."}"#);
+        });
+        let r = myplex.refresh().await;
+        let error = r.err().unwrap();
+        assert_eq!(
+            error.api_error().map(|e| (e.status, e.message.as_str())),
+            Some((401, "Invalid authentication token."))
+        );
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn well_known_errors(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_USER_INFO_PATH);
+            then.status(429).header("Retry-After", "30");
+        });
+        let r = myplex.clone().refresh().await;
+        assert!(matches!(
+            r.err().unwrap(),
+            plex_api::Error::RateLimited {
+                retry_after: Some(retry_after)
+            } if retry_after == Duration::from_secs(30)
+        ));
+        m.delete();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_USER_INFO_PATH);
+            then.status(403)
+                .header("content-type", "text/html")
+                .body("<html><head><title>Forbidden</title></head></html>");
+        });
+        let r = myplex.clone().refresh().await;
+        assert!(matches!(
+            r.err().unwrap(),
+            plex_api::Error::NotAuthorized(None)
+        ));
+        m.delete();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_USER_INFO_PATH);
+            then.status(403)
+                .header("content-type", "text/xml")
+                .body(r#"<Response code="1001" status="Access denied" />"#);
+        });
+        let r = myplex.clone().refresh().await;
+        let error = r.err().unwrap();
+        assert!(matches!(error, plex_api::Error::NotAuthorized(Some(_))));
+        assert_eq!(
+            error
+                .api_error()
+                .map(|e| (e.code, e.status, e.message.as_str())),
+            Some((1001, 403, "Access denied"))
+        );
+        m.delete();
+
+        let _mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_USER_INFO_PATH);
+            then.status(423);
+        });
+        let r = myplex.refresh().await;
         assert!(matches!(
             r.err().unwrap(),
-            plex_api::Error::MyPlexErrorResponse { .. }
+            plex_api::Error::ItemLocked(None)
         ));
     }
 }
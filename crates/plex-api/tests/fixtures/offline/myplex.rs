@@ -1,18 +1,16 @@
 use super::{client::client_authenticated, Mocked};
-use plex_api::{url::MYPLEX_USER_INFO_PATH, HttpClient, MyPlex, MyPlexBuilder};
+use plex_api::{HttpClient, MyPlex, MyPlexBuilder};
+use plex_api_mock_server::{Account, MockPlexServer};
 use rstest::fixture;
 
-#[fixture]
-pub async fn myplex(client_authenticated: Mocked<HttpClient>) -> Mocked<MyPlex> {
-    let (client_authenticated, mock_server) = client_authenticated.split();
-    let mut mock = mock_server.mock(|when, then| {
-        when.path(MYPLEX_USER_INFO_PATH);
-        then.status(200)
-            .body_from_file("tests/mocks/myplex/api/v2/user/user_info_free.json");
-    });
+async fn sign_in(client: Mocked<HttpClient>, account: Account) -> Mocked<MyPlex> {
+    let (client, mock_server) = client.split();
+    let mock_server = MockPlexServer::from(mock_server);
+
+    let mut mock = mock_server.account(account);
 
     let ret = MyPlexBuilder::default()
-        .set_client(client_authenticated)
+        .set_client(client)
         .build()
         .await
         .expect("failed to login");
@@ -20,26 +18,15 @@ pub async fn myplex(client_authenticated: Mocked<HttpClient>) -> Mocked<MyPlex>
     mock.assert();
     mock.delete();
 
-    Mocked::new(ret, mock_server)
+    Mocked::new(ret, mock_server.into_inner())
 }
 
 #[fixture]
-pub async fn myplex_plexpass(client_authenticated: Mocked<HttpClient>) -> Mocked<MyPlex> {
-    let (client_authenticated, mock_server) = client_authenticated.split();
-    let mut mock = mock_server.mock(|when, then| {
-        when.path(MYPLEX_USER_INFO_PATH);
-        then.status(200)
-            .body_from_file("tests/mocks/myplex/api/v2/user/user_info_plexpass.json");
-    });
-
-    let ret = MyPlexBuilder::default()
-        .set_client(client_authenticated)
-        .build()
-        .await
-        .expect("failed to login");
-
-    mock.assert();
-    mock.delete();
+pub async fn myplex(client_authenticated: Mocked<HttpClient>) -> Mocked<MyPlex> {
+    sign_in(client_authenticated, Account::Free).await
+}
 
-    Mocked::new(ret, mock_server)
+#[fixture]
+pub async fn myplex_plexpass(client_authenticated: Mocked<HttpClient>) -> Mocked<MyPlex> {
+    sign_in(client_authenticated, Account::PlexPass).await
 }
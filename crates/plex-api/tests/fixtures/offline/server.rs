@@ -2,46 +2,36 @@ use super::{
     client::{client_anonymous, client_authenticated},
     Mocked,
 };
-use httpmock::Method::GET;
-use plex_api::{url::SERVER_MEDIA_PROVIDERS, HttpClient, Server};
+use plex_api::{HttpClient, Server};
+use plex_api_mock_server::{MockPlexServer, ServerKind};
 use rstest::fixture;
 
-#[fixture]
-pub async fn server_anonymous(client_anonymous: Mocked<HttpClient>) -> Mocked<Server> {
-    let (client_anonymous, mock_server) = client_anonymous.split();
+async fn load_server(client: Mocked<HttpClient>, kind: ServerKind) -> Mocked<Server> {
+    let (client, mock_server) = client.split();
+    let mock_server = MockPlexServer::from(mock_server);
 
-    let mut m = mock_server.mock(|when, then| {
-        when.method(GET).path(SERVER_MEDIA_PROVIDERS);
-        then.status(200)
-            .header("content-type", "text/json")
-            .body_from_file("tests/mocks/server/media/providers_free.json");
-    });
+    let mut m = mock_server.media_providers(kind);
 
-    let ret = Server::new(mock_server.base_url(), client_anonymous)
+    let ret = Server::new(mock_server.base_url(), client)
         .await
         .expect("failed to get server");
 
     m.delete();
 
-    Mocked::new(ret, mock_server)
+    Mocked::new(ret, mock_server.into_inner())
 }
 
 #[fixture]
-pub async fn server_authenticated(client_authenticated: Mocked<HttpClient>) -> Mocked<Server> {
-    let (client_authenticated, mock_server) = client_authenticated.split();
-
-    let mut m = mock_server.mock(|when, then| {
-        when.method(GET).path(SERVER_MEDIA_PROVIDERS);
-        then.status(200)
-            .header("content-type", "text/json")
-            .body_from_file("tests/mocks/server/media/providers_unclaimed.json");
-    });
-
-    let ret = Server::new(mock_server.base_url(), client_authenticated)
-        .await
-        .expect("failed to get server");
+pub async fn server_anonymous(client_anonymous: Mocked<HttpClient>) -> Mocked<Server> {
+    load_server(client_anonymous, ServerKind::Free).await
+}
 
-    m.delete();
+#[fixture]
+pub async fn server_authenticated(client_authenticated: Mocked<HttpClient>) -> Mocked<Server> {
+    load_server(client_authenticated, ServerKind::Unclaimed).await
+}
 
-    Mocked::new(ret, mock_server)
+#[fixture]
+pub async fn server_plexpass(client_authenticated: Mocked<HttpClient>) -> Mocked<Server> {
+    load_server(client_authenticated, ServerKind::PlexPass).await
 }
@@ -1,4 +1,6 @@
 pub mod client;
+#[cfg(feature = "tests_record_mocks")]
+pub mod recorder;
 pub mod server;
 
 use client::client_authenticated;
@@ -24,6 +26,11 @@ pub fn client_builder() -> HttpClientBuilder {
         builder = builder.set_x_plex_client_identifier(client_id);
     }
 
+    #[cfg(feature = "tests_record_mocks")]
+    {
+        builder = builder.add_response_inspector(recorder::Recorder);
+    }
+
     builder
 }
 
@@ -0,0 +1,229 @@
+//! Saves the responses received by online tests as mocks, so that offline
+//! tests for new endpoints don't need hand-written ones. Enabled with the
+//! `tests_record_mocks` feature, e.g. `cargo xtask test --online --record-mocks`.
+//!
+//! Every test gets its own folder under `tests/mocks/recorded`, named after
+//! its module and function, with one file per requested path. Tokens are
+//! replaced by `auth_token`, like in the hand-written mocks. The folder is
+//! kept out of git on purpose: recordings are reviewed and trimmed before
+//! they're moved next to the hand-written mocks.
+
+use futures::{future::BoxFuture, AsyncReadExt};
+use isahc::{
+    http::{header::CONTENT_TYPE, Method, Uri},
+    AsyncBody, Response,
+};
+use plex_api::ResponseInspector;
+use quick_xml::{
+    events::{attributes::Attribute, BytesStart, Event},
+    Reader, Writer,
+};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+const PLACEHOLDER_TOKEN: &str = "auth_token";
+
+struct ActiveRecording {
+    directory: PathBuf,
+    recorded: HashMap<String, usize>,
+}
+
+static ACTIVE: Mutex<Option<ActiveRecording>> = Mutex::new(None);
+
+/// Records the responses until it's dropped. Online tests run one at a time,
+/// so there's only ever one recording.
+// Started by the online test macros, which not every test file uses.
+#[allow(dead_code)]
+pub struct Recording;
+
+#[allow(dead_code)]
+impl Recording {
+    pub fn start(module_path: &str, test_name: &str) -> Self {
+        let mut directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/mocks/recorded");
+        directory.extend(module_path.split("::"));
+        directory.push(test_name);
+
+        *ACTIVE.lock().unwrap() = Some(ActiveRecording {
+            directory,
+            recorded: HashMap::new(),
+        });
+        Self
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        *ACTIVE.lock().unwrap() = None;
+    }
+}
+
+/// Writes the successful JSON and XML responses of the active recording.
+pub struct Recorder;
+
+impl ResponseInspector for Recorder {
+    fn inspect<'a>(
+        &'a self,
+        _method: &'a Method,
+        uri: &'a Uri,
+        response: Response<AsyncBody>,
+    ) -> BoxFuture<'a, plex_api::Result<Response<AsyncBody>>> {
+        Box::pin(async move {
+            let extension = match response
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+            {
+                Some(content_type) if content_type.contains("json") => "json",
+                Some(content_type) if content_type.contains("xml") => "xml",
+                _ => return Ok(response),
+            };
+            if !response.status().is_success() || ACTIVE.lock().unwrap().is_none() {
+                return Ok(response);
+            }
+
+            let (parts, mut body) = response.into_parts();
+            let mut bytes = Vec::new();
+            body.read_to_end(&mut bytes).await?;
+            save(uri, extension, &bytes);
+
+            Ok(Response::from_parts(parts, AsyncBody::from(bytes)))
+        })
+    }
+}
+
+fn save(uri: &Uri, extension: &str, body: &[u8]) {
+    let mut active = ACTIVE.lock().unwrap();
+    let Some(recording) = active.as_mut() else {
+        return;
+    };
+
+    let mut name = uri
+        .path()
+        .trim_matches('/')
+        .split('/')
+        .map(|segment| segment.replace(|c: char| !c.is_ascii_alphanumeric(), "_"))
+        .collect::<Vec<_>>()
+        .join("/");
+    if name.is_empty() {
+        name = "root".to_owned();
+    }
+    // Later responses for the same path, e.g. with different parameters,
+    // are numbered.
+    let count = recording.recorded.entry(name.clone()).or_default();
+    *count += 1;
+    if *count > 1 {
+        name = format!("{name}_{count}");
+    }
+
+    let body = scrub_tokens(&String::from_utf8_lossy(body));
+    let body = match extension {
+        "json" => match serde_json::from_str::<Value>(&body) {
+            Ok(mut value) => {
+                scrub_token_fields(&mut value);
+                serde_json::to_string_pretty(&value).unwrap()
+            }
+            Err(_) => body,
+        },
+        _ => scrub_token_attributes(&body).unwrap_or(body),
+    };
+
+    let path = recording.directory.join(format!("{name}.{extension}"));
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, body).unwrap();
+}
+
+fn scrub_tokens(text: &str) -> String {
+    match std::env::var("PLEX_API_AUTH_TOKEN") {
+        Ok(token) if !token.is_empty() => text.replace(&token, PLACEHOLDER_TOKEN),
+        _ => text.to_owned(),
+    }
+}
+
+/// Hides tokens that weren't issued for the test, e.g. the access tokens of
+/// shared servers.
+fn scrub_token_fields(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (name, value) in fields {
+                if name.to_lowercase().ends_with("token") && value.is_string() {
+                    *value = Value::String(PLACEHOLDER_TOKEN.to_owned());
+                } else {
+                    scrub_token_fields(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(scrub_token_fields),
+        _ => {}
+    }
+}
+
+/// Same as [`scrub_token_fields`] for XML responses, where the tokens are
+/// attributes, e.g. `accessToken` on a resource. Returns `None` when the
+/// body isn't valid XML.
+fn scrub_token_attributes(xml: &str) -> Option<String> {
+    let mut reader = Reader::from_str(xml);
+    let mut writer = Writer::new(Vec::new());
+
+    loop {
+        let event = match reader.read_event().ok()? {
+            Event::Eof => break,
+            Event::Start(element) => Event::Start(scrub_element(&element)?),
+            Event::Empty(element) => Event::Empty(scrub_element(&element)?),
+            event => event,
+        };
+        writer.write_event(event).ok()?;
+    }
+
+    String::from_utf8(writer.into_inner()).ok()
+}
+
+fn scrub_element(element: &BytesStart<'_>) -> Option<BytesStart<'static>> {
+    let mut scrubbed = element.to_owned();
+    scrubbed.clear_attributes();
+    for attribute in element.attributes() {
+        let attribute = attribute.ok()?;
+        let name = String::from_utf8_lossy(attribute.key.as_ref()).to_lowercase();
+        if name.ends_with("token") {
+            scrubbed.push_attribute(Attribute {
+                key: attribute.key,
+                value: PLACEHOLDER_TOKEN.as_bytes().into(),
+            });
+        } else {
+            scrubbed.push_attribute(attribute);
+        }
+    }
+    Some(scrubbed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::scrub_token_attributes;
+
+    #[test]
+    fn scrubs_xml_token_attributes() {
+        let devices = r#"<?xml version="1.0" encoding="UTF-8"?>
+<MediaContainer publicAddress="1.0.0.1" size="2">
+  <Device name="Safari" clientIdentifier="1" token="secret1" createdAt="1628211599">
+  </Device>
+  <Device name="Box" provides="server" clientIdentifier="3" accessToken="secret2" AUTHTOKEN="secret3">
+    <Connection uri="http://172.1.0.1:32400" tokenType="url"/>
+  </Device>
+</MediaContainer>"#;
+
+        let scrubbed = scrub_token_attributes(devices).unwrap();
+
+        assert!(!scrubbed.contains("secret"));
+        assert!(scrubbed.contains(r#"token="auth_token""#));
+        assert!(scrubbed.contains(r#"accessToken="auth_token""#));
+        assert!(scrubbed.contains(r#"AUTHTOKEN="auth_token""#));
+        assert!(scrubbed.contains(r#"tokenType="url""#));
+        assert!(scrubbed.contains(r#"createdAt="1628211599""#));
+        assert!(scrubbed.contains(r#"<Connection uri="http://172.1.0.1:32400" tokenType="url"/>"#));
+        assert!(scrubbed.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+    }
+}
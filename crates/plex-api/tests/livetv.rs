@@ -0,0 +1,439 @@
+mod fixtures;
+
+mod offline {
+    use super::fixtures::offline::{server::*, Mocked};
+    use httpmock::Method::{DELETE, GET, POST, PUT};
+    use plex_api::{
+        livetv::{RecordSettings, SubscriptionOptions},
+        media_container::{
+            preferences::Value,
+            server::library::{MetadataType, Protocol},
+        },
+        transcode::VideoTranscodeOptions,
+        url::{
+            SERVER_GRAB_OPERATION, SERVER_LIVETV_DEVICE_CHANNELS, SERVER_LIVETV_DVR,
+            SERVER_LIVETV_DVRS, SERVER_LIVETV_DVR_PREFS, SERVER_LIVETV_LINEUP_CHANNELS,
+            SERVER_LIVETV_TUNE, SERVER_SUBSCRIPTION, SERVER_SUBSCRIPTIONS,
+            SERVER_SUBSCRIPTION_MOVE,
+        },
+        Error, Server,
+    };
+    use time::OffsetDateTime;
+
+    #[plex_api_test_helper::offline_test]
+    async fn dvrs(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_LIVETV_DVRS);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/livetv/dvrs.json");
+        });
+
+        let dvrs = server.dvrs().await.unwrap();
+        m.assert();
+
+        assert_eq!(dvrs.len(), 1);
+        let dvr = &dvrs[0];
+        assert_eq!(dvr.key(), "9");
+        assert_eq!(dvr.lineup_title(), Some("Antenna, 94103"));
+        assert_eq!(dvr.epg_identifier(), Some("tv.plex.providers.epg.cloud:30"));
+        assert_eq!(dvr.settings().len(), 3);
+
+        let device = &dvr.devices()[0];
+        assert_eq!(device.key, "8");
+        assert_eq!(device.tuners, 2);
+        assert_eq!(device.source.as_deref(), Some("Antenna"));
+        assert_eq!(device.channel_mappings.len(), 2);
+        assert!(device.channel_mappings[0].enabled);
+        assert!(!device.channel_mappings[1].enabled);
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path(SERVER_LIVETV_LINEUP_CHANNELS)
+                .query_param("lineup", dvr.lineup());
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/livetv/lineup_channels.json");
+        });
+
+        let channels = dvr.lineup_channels().await.unwrap();
+        m.assert();
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0].call_sign.as_deref(), Some("KTVU"));
+        assert_eq!(channels[0].key, device.channel_mappings[0].channel_key);
+        assert!(channels[0].hd);
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path(SERVER_LIVETV_DEVICE_CHANNELS.replace("{deviceId}", "8"));
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/livetv/device_channels.json");
+        });
+
+        let channels = dvr.tuner_channels(device).await.unwrap();
+        m.assert();
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[1].identifier, "4.1");
+        assert_eq!(channels[1].signal_strength, Some(70));
+        assert!(!channels[1].hd);
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn dvr_settings(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_LIVETV_DVRS);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/livetv/dvrs.json");
+        });
+
+        let mut dvr = server.dvrs().await.unwrap().pop().unwrap();
+        m.assert();
+
+        let prefs_mock = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path(SERVER_LIVETV_DVR_PREFS.replace("{dvrId}", "9"))
+                .query_param("startOffsetMinutes", "2");
+            then.status(200);
+        });
+
+        let dvr_mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path(SERVER_LIVETV_DVR.replace("{dvrId}", "9"));
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/livetv/dvr_updated.json");
+        });
+
+        dvr.set_setting("startOffsetMinutes", Value::Int(2))
+            .await
+            .unwrap();
+        prefs_mock.assert();
+        dvr_mock.assert();
+
+        let setting = dvr
+            .settings()
+            .iter()
+            .find(|s| s.id == "startOffsetMinutes")
+            .unwrap();
+        assert!(matches!(setting.value, Value::Int(2)));
+
+        let result = dvr.set_setting("comskipEnabled", Value::Int(1)).await;
+        assert!(matches!(result, Err(Error::IncompatibleSettingValues)));
+
+        let result = dvr.set_setting("missing", Value::Bool(true)).await;
+        assert!(matches!(result, Err(Error::RequestedSettingNotFound(_))));
+
+        prefs_mock.assert_calls(1);
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn guide_grid(#[future] server_plexpass: Mocked<Server>) {
+        let (server, mock_server) = server_plexpass.split();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_LIVETV_DVRS);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/livetv/dvrs.json");
+        });
+
+        let dvr = server.dvrs().await.unwrap().pop().unwrap();
+        m.assert();
+
+        let guide = server.guide(&dvr).unwrap();
+        assert_eq!(guide.identifier(), "tv.plex.providers.epg.cloud:30");
+        assert_eq!(guide.channel_filters().len(), 12);
+        assert_eq!(server.guides().len(), 1);
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/tv.plex.providers.epg.cloud:30/grid")
+                .query_param("type", "1,4")
+                .query_param("endsAt>", "1700000000")
+                .query_param("beginsAt<", "1700003600")
+                .query_param_missing("channelIdentifier");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/livetv/grid.json");
+        });
+
+        let programs = guide
+            .grid(
+                OffsetDateTime::from_unix_timestamp(1700000000).unwrap(),
+                OffsetDateTime::from_unix_timestamp(1700003600).unwrap(),
+                None,
+            )
+            .await
+            .unwrap();
+        m.assert();
+
+        assert_eq!(programs.len(), 2);
+        let news = &programs[0];
+        assert!(matches!(news.metadata_type, Some(MetadataType::Episode)));
+        assert_eq!(news.grandparent_title.as_deref(), Some("KTVU News"));
+        let airing = &news.airings[0];
+        assert_eq!(airing.begins_at.unix_timestamp(), 1700000000);
+        assert_eq!(airing.on_air, Some(true));
+
+        // The first channel is enabled on the tuner, the second isn't.
+        let (device, mapping) = dvr.channel_for(airing).unwrap();
+        assert_eq!(device.key, "8");
+        assert_eq!(mapping.device_identifier, "2.1");
+        assert!(dvr.channel_for(&programs[1].airings[0]).is_none());
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/tv.plex.providers.epg.cloud:30/grid")
+                .query_param("channelIdentifier", &airing.channel_identifier);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/livetv/grid.json");
+        });
+
+        guide
+            .grid(
+                airing.begins_at,
+                airing.ends_at,
+                Some(&airing.channel_identifier),
+            )
+            .await
+            .unwrap();
+        m.assert();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn subscriptions(#[future] server_plexpass: Mocked<Server>) {
+        let (server, mock_server) = server_plexpass.split();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_SUBSCRIPTIONS);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/livetv/subscriptions.json");
+        });
+
+        let mut subscriptions = server.subscriptions().await.unwrap();
+        m.assert();
+
+        assert_eq!(subscriptions.len(), 2);
+        let cooking = subscriptions.pop().unwrap();
+        let mut news = subscriptions.pop().unwrap();
+        assert_eq!(news.key(), "12");
+        assert!(news.is_series());
+        assert_eq!(news.airings_type(), Some("New Airings Only"));
+        assert_eq!(news.library_section_id(), Some(3));
+        assert_eq!(news.location_path(), Some("/data/tv"));
+        assert_eq!(news.created_at().unwrap().unix_timestamp(), 1700001000);
+        assert_eq!(news.settings().len(), 3);
+
+        let m = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path(SERVER_SUBSCRIPTION.replace("{subscriptionId}", "12"))
+                .query_param("prefs[startOffsetMinutes]", "2")
+                .query_param("prefs[onlyNewAirings]", "1")
+                .query_param_missing("prefs[comskipEnabled]");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/livetv/subscription.json");
+        });
+
+        news.update(&RecordSettings {
+            only_new_airings: Some(true),
+            start_offset_minutes: Some(2),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        m.assert();
+
+        let setting = news
+            .settings()
+            .iter()
+            .find(|s| s.id == "startOffsetMinutes")
+            .unwrap();
+        assert!(matches!(setting.value, Value::Int(2)));
+
+        let m = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path(SERVER_SUBSCRIPTION_MOVE.replace("{subscriptionId}", "15"))
+                .query_param_missing("after");
+            then.status(200);
+        });
+
+        cooking.move_after(None).await.unwrap();
+        m.assert();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path(SERVER_SUBSCRIPTION_MOVE.replace("{subscriptionId}", "12"))
+                .query_param("after", "15");
+            then.status(200);
+        });
+
+        news.move_after(Some(&cooking)).await.unwrap();
+        m.assert();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(DELETE)
+                .path(SERVER_SUBSCRIPTION.replace("{subscriptionId}", "15"));
+            then.status(200);
+        });
+
+        cooking.delete().await.unwrap();
+        m.assert();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn subscribe(#[future] server_plexpass: Mocked<Server>) {
+        let (server, mock_server) = server_plexpass.split();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_LIVETV_DVRS);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/livetv/dvrs.json");
+        });
+
+        let dvr = server.dvrs().await.unwrap().pop().unwrap();
+        m.assert();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/tv.plex.providers.epg.cloud:30/grid");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/livetv/grid.json");
+        });
+
+        let programs = server
+            .guide(&dvr)
+            .unwrap()
+            .grid(
+                OffsetDateTime::from_unix_timestamp(1700000000).unwrap(),
+                OffsetDateTime::from_unix_timestamp(1700003600).unwrap(),
+                None,
+            )
+            .await
+            .unwrap();
+        m.assert();
+        let news = &programs[0];
+
+        let m = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path(SERVER_SUBSCRIPTIONS)
+                .query_param("type", "2")
+                .query_param("targetLibrarySectionID", "3")
+                .query_param("hints[ratingKey]", "5d9f4d3a3ab0e7001f84ba00")
+                .query_param("hints[guid]", "plex://show/5d9f4d3a3ab0e7001f84ba00")
+                .query_param("hints[title]", "KTVU News")
+                .query_param("prefs[oneShot]", "false")
+                .query_param("prefs[onlyNewAirings]", "1")
+                .query_param("prefs[lineupChannel]", &news.airings[0].channel_identifier)
+                .query_param_missing("targetSectionLocationID");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/livetv/subscription.json");
+        });
+
+        let options = SubscriptionOptions {
+            series: true,
+            library_section_id: 3,
+            location_id: None,
+            settings: RecordSettings {
+                only_new_airings: Some(true),
+                channel: Some(news.airings[0].channel_identifier.clone()),
+                ..Default::default()
+            },
+        };
+        let subscription = server.subscribe(news, &options).await.unwrap();
+        m.assert();
+        assert_eq!(subscription.key(), "12");
+        assert_eq!(subscription.title(), "KTVU News");
+
+        // Only episodes belong to a show that can be recorded as a series.
+        let result = server.subscribe(&programs[1], &options).await;
+        assert!(matches!(result, Err(Error::NotAnEpisode)));
+        m.assert_calls(1);
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn tune(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_LIVETV_DVRS);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/livetv/dvrs.json");
+        });
+
+        let dvr = server.dvrs().await.unwrap().pop().unwrap();
+        m.assert();
+        let channel = &dvr.devices()[0].channel_mappings[0];
+
+        let tune_mock = mock_server.mock(|when, then| {
+            when.method(POST).path(
+                SERVER_LIVETV_TUNE
+                    .replace("{dvrId}", "9")
+                    .replace("{channel}", &channel.channel_key),
+            );
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/livetv/tune.json");
+        });
+
+        let decision_mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/video/:/transcode/universal/decision")
+                .query_param(
+                    "path",
+                    "/livetv/sessions/2b3ebcf4-1f2e-4c7e-9a4a-1d0b6e1f7a3c",
+                )
+                .query_param("protocol", "dash")
+                .query_param("context", "streaming");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("../plex-api-mock-server/mocks/transcode/video_dash_h264_mp3.json");
+        });
+
+        let session = dvr
+            .tune(channel, Protocol::Dash, VideoTranscodeOptions::default())
+            .await
+            .unwrap();
+        tune_mock.assert();
+        decision_mock.assert();
+
+        assert_eq!(session.device_key(), Some("8"));
+        assert_eq!(session.status(), Some("inprogress"));
+        assert_eq!(session.program().title, "The Evening News");
+        assert_eq!(
+            session.program().airings[0].channel_identifier,
+            channel.channel_key
+        );
+        assert_eq!(session.transcode_session().protocol(), Protocol::Dash);
+
+        let stop_mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/video/:/transcode/universal/stop")
+                .query_param("session", session.transcode_session().session_id());
+            then.status(200);
+        });
+
+        let release_mock = mock_server.mock(|when, then| {
+            when.method(DELETE).path(
+                SERVER_GRAB_OPERATION.replace("{operationId}", "6a8bb5ddd9ea4e83b3e1f2c0a13c6d45"),
+            );
+            then.status(200);
+        });
+
+        session.release().await.unwrap();
+        stop_mock.assert();
+        release_mock.assert();
+    }
+}
@@ -0,0 +1,215 @@
+mod fixtures;
+
+mod offline {
+    use super::fixtures::offline::{server::*, Mocked};
+
+    use httpmock::Method::{GET, PUT};
+    use plex_api::{
+        library::{Artist, MetadataItem, SonicSimilarityOptions, Track},
+        media_container::server::library::LyricCodec,
+        Server,
+    };
+
+    #[plex_api_test_helper::offline_test]
+    async fn track_features(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/158");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/music_track_158.json");
+        });
+
+        let track: Track = server.item_by_id("158").await.unwrap().try_into().unwrap();
+        m.assert();
+        m.delete();
+
+        assert_eq!(track.moods(), vec!["Energetic", "Aggressive"]);
+        assert_eq!(track.styles(), vec!["Dubstep"]);
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/metadata/158/nearest")
+                .query_param("limit", "10")
+                .query_param("maxDistance", "0.25");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/music_nearest_158.json");
+        });
+
+        let similar = track
+            .sonically_similar(SonicSimilarityOptions {
+                limit: Some(10),
+                max_distance: Some(0.25),
+            })
+            .await
+            .unwrap();
+        m.assert();
+        m.delete();
+
+        assert_eq!(
+            similar.iter().map(|t| t.rating_key()).collect::<Vec<_>>(),
+            vec!["159", "160"]
+        );
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path_matches(r"^/library/metadata/158/station/[0-9a-f-]{36}$")
+                .query_param("type", "10");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/music_tracks.json");
+        });
+
+        let radio = track.radio().await.unwrap();
+        m.assert();
+        m.delete();
+
+        assert_eq!(radio.len(), 3);
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn lyrics(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/158");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/music_track_158.json");
+        });
+
+        let track: Track = server.item_by_id("158").await.unwrap().try_into().unwrap();
+        m.assert();
+        m.delete();
+
+        assert!(track.has_lyrics());
+        let streams = track.lyric_streams();
+        assert_eq!(streams.len(), 1);
+        assert_eq!(streams[0].codec, LyricCodec::Lrc);
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/streams/413");
+            then.status(200)
+                .header("content-type", "text/plain")
+                .body_from_file("tests/mocks/server/media/music_lyrics_413.lrc");
+        });
+
+        let lyrics = track.lyrics(streams[0]).await.unwrap();
+        m.assert();
+        m.delete();
+
+        assert!(lyrics.starts_with("[00:00.50]Try it out"));
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn loudness(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/157786");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/transcode/metadata_157786.json");
+        });
+
+        let track: Track = server
+            .item_by_id("157786")
+            .await
+            .unwrap()
+            .try_into()
+            .unwrap();
+        m.assert();
+        m.delete();
+
+        assert!(track.has_sonic_analysis());
+        let loudness = track.loudness().unwrap();
+        assert_eq!(loudness.gain, -6.89);
+        assert_eq!(loudness.peak, 1.0);
+        assert_eq!(loudness.loudness, Some(-12.23));
+        assert_eq!(loudness.range, Some(6.73));
+        assert_eq!(loudness.album_range, Some(8.604516));
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/streams/560564/levels")
+                .query_param("subsample", "8");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/music/levels_560564.json");
+        });
+
+        let levels = track.loudness_levels(8).await.unwrap();
+        m.assert();
+        m.delete();
+
+        assert_eq!(levels.len(), 8);
+        assert_eq!(levels[0], -31.2);
+        assert_eq!(levels[5], -9.8);
+
+        let m = mock_server.mock(|when, then| {
+            when.method(PUT).path("/library/sections/5/analyze");
+            then.status(200);
+        });
+
+        server.libraries()[2].analyze().await.unwrap();
+        m.assert();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn artist_features(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/sections/5/all")
+                .query_param("type", "8");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/music_library.json");
+        });
+
+        let artists: Vec<Artist> = match &server.libraries()[2] {
+            plex_api::library::Library::Music(lib) => lib.artists().await.unwrap(),
+            lib => panic!("Unexpected library: {lib:?}"),
+        };
+        m.assert();
+        m.delete();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/156/similar");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/music_library.json");
+        });
+
+        let similar = artists[0].similar().await.unwrap();
+        m.assert();
+        m.delete();
+
+        assert_eq!(
+            similar.iter().map(|a| a.title()).collect::<Vec<_>>(),
+            vec!["Skrillex", "System of a Down"]
+        );
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path_matches(r"^/library/metadata/156/station/[0-9a-f-]{36}$")
+                .query_param("type", "10")
+                .query_param("maxDegreesOfSeparation", "-1");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/music_tracks.json");
+        });
+
+        let radio = artists[0].radio().await.unwrap();
+        m.assert();
+        m.delete();
+
+        assert_eq!(
+            radio.iter().map(|t| t.rating_key()).collect::<Vec<_>>(),
+            vec!["158", "159", "160"]
+        );
+    }
+}
@@ -2,11 +2,209 @@ mod fixtures;
 
 mod offline {
     use super::fixtures::offline::{myplex::*, Mocked};
-    use httpmock::Method::{GET, PUT};
+    use httpmock::Method::{DELETE, GET, POST, PUT};
     use plex_api::{
-        url::{MYPLEX_CLAIM_TOKEN_PATH, MYPLEX_PRIVACY_PATH},
-        Error, MyPlex,
+        account::SubscriptionStatus,
+        media_container::server::Feature,
+        url::{
+            MYPLEX_CLAIM_TOKEN_PATH, MYPLEX_INVITES_FRIENDS, MYPLEX_PRIVACY_PATH, MYPLEX_USERS,
+            MYPLEX_USERS_V2,
+        },
+        Error, MetricStatus, MyPlex, RestrictionProfile,
     };
+    use secrecy::ExposeSecret;
+
+    #[plex_api_test_helper::offline_test]
+    async fn account_details(
+        #[future] myplex: Mocked<MyPlex>,
+        #[future] myplex_plexpass: Mocked<MyPlex>,
+    ) {
+        let (myplex, _) = myplex.split();
+        let account = myplex.account().unwrap();
+
+        assert!(!myplex.has_plex_pass());
+        assert!(matches!(
+            account.subscription.status,
+            SubscriptionStatus::Inactive
+        ));
+        assert_eq!(account.plan(), None);
+        assert!(!account.has_entitlement("android"));
+        assert!(!account.has_role("plexpass"));
+        assert!(!account.is_managed());
+        assert_eq!(account.token().expose_secret(), "auth_token");
+        assert_eq!(
+            account.remember_expires_at().unwrap().unix_timestamp(),
+            1642415212
+        );
+
+        let (myplex, _) = myplex_plexpass.split();
+        let account = myplex.account().unwrap();
+
+        assert!(myplex.has_plex_pass());
+        assert_eq!(account.plan(), Some("lifetime"));
+        assert!(account.has_entitlement("android"));
+        assert!(account.has_role("plexpass"));
+        assert!(account.has_feature(&Feature::Webhooks));
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn home_users(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+        let home = myplex.home().unwrap();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_USERS_V2);
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/myplex/api/v2/home/users.json");
+        });
+
+        let users = home.users().await.unwrap();
+        m.assert();
+        m.delete();
+
+        assert_eq!(users.len(), 2);
+        assert_eq!(users[0].friendly_name.as_deref(), Some("Admin"));
+        assert!(users[0].admin && users[0].protected && !users[0].restricted);
+        assert!(users[1].restricted);
+        assert_eq!(
+            users[1].restriction_profile,
+            Some(RestrictionProfile::LittleKid)
+        );
+
+        // Without the v2 endpoint the XML one is used.
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_USERS);
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body_from_file("tests/mocks/myplex/api/home/users.xml");
+        });
+
+        let xml_users = home.users().await.unwrap();
+        m.assert();
+        m.delete();
+
+        assert_eq!(xml_users.len(), 2);
+        assert_eq!(xml_users[0].friendly_name, None);
+        for (xml_user, user) in xml_users.iter().zip(&users) {
+            assert_eq!(xml_user.uuid, user.uuid);
+            assert_eq!(xml_user.restricted, user.restricted);
+            assert_eq!(xml_user.protected, user.protected);
+            assert_eq!(xml_user.thumb, user.thumb);
+        }
+
+        // A v2 endpoint answering with something other than JSON, e.g. a
+        // maintenance page, isn't available either.
+        let mut v2 = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_USERS_V2);
+            then.status(200)
+                .header("content-type", "text/html")
+                .body("<html><body>Down for maintenance</body></html>");
+        });
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_USERS);
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body_from_file("tests/mocks/myplex/api/home/users.xml");
+        });
+
+        assert_eq!(home.users().await.unwrap().len(), 2);
+        v2.assert();
+        v2.delete();
+        m.assert();
+        m.delete();
+
+        // JSON the types can't read is an error rather than a reason to
+        // fall back.
+        let v2 = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_USERS_V2);
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"users": "none"}"#);
+        });
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_USERS);
+            then.status(200);
+        });
+
+        let error = home.users().await.unwrap_err();
+        assert!(
+            matches!(error, Error::JsonDeserealiseError { .. }),
+            "{error:?}"
+        );
+        v2.assert();
+        m.assert_calls(0);
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn home_invitations(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+        let home = myplex.home().unwrap();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path(MYPLEX_USERS)
+                .query_param("invitedEmail", "guest@example.com");
+            then.status(200);
+        });
+        home.invite("guest@example.com").await.unwrap();
+        m.assert();
+
+        let sent = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path(MYPLEX_INVITES_FRIENDS)
+                .query_param("status", "pending_sent");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/myplex/api/v2/friends_empty.json");
+        });
+        let received = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path(MYPLEX_INVITES_FRIENDS)
+                .query_param("status", "pending_received");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file(
+                    "tests/mocks/myplex/api/v2/friends_pending_received_one_external.json",
+                );
+        });
+        let invitations = home.invitations().await.unwrap();
+        sent.assert();
+        received.assert();
+        assert_eq!(invitations.len(), 1);
+        assert_eq!(invitations[0].title, "Guest");
+
+        let m = mock_server.mock(|when, then| {
+            when.method(DELETE)
+                .path(format!("{MYPLEX_INVITES_FRIENDS}/5"));
+            then.status(204);
+        });
+        invitations
+            .into_iter()
+            .next()
+            .unwrap()
+            .delete()
+            .await
+            .unwrap();
+        m.assert();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_USERS_V2);
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/myplex/api/v2/home/users.json");
+        });
+        let users = home.users().await.unwrap();
+        m.assert();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(DELETE)
+                .path(format!("{MYPLEX_USERS}/{}", users[1].id));
+            then.status(200);
+        });
+        home.remove_user(&users[1]).await.unwrap();
+        m.assert();
+    }
 
     #[plex_api_test_helper::offline_test]
     async fn privacy(#[future] myplex: Mocked<MyPlex>) {
@@ -32,6 +230,15 @@ mod offline {
             privacy.opt_out_library_stats,
             "Unexpected opt_out_library_stats value"
         );
+        let itemstart = privacy
+            .metrics
+            .iter()
+            .find(|metric| metric.event == "playback:itemstart")
+            .unwrap();
+        assert_eq!(itemstart.status, MetricStatus::Off);
+        assert!(privacy
+            .identifiable_events()
+            .any(|event| event == "client:signin"));
 
         let m = mock_server.mock(|when, then| {
             when.method(PUT)
@@ -59,6 +266,17 @@ mod offline {
             !privacy.opt_out_library_stats,
             "Unexpected opt_out_library_stats value"
         );
+
+        let m = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path(MYPLEX_PRIVACY_PATH)
+                .form_urlencoded_tuple("optOutPlayback", "1")
+                .form_urlencoded_tuple("optOutLibraryStats", "0");
+            then.status(204);
+        });
+        privacy.set_opt_out_playback(true).await.unwrap();
+        m.assert();
+        assert!(privacy.opt_out_playback && !privacy.opt_out_library_stats);
     }
 
     #[plex_api_test_helper::offline_test]
@@ -3,7 +3,7 @@ mod fixtures;
 mod offline {
     use super::fixtures::offline::{client::*, Mocked};
     use httpmock::Method::{GET, PUT};
-    use plex_api::{url::MYPLEX_ANNOUNCEMENTS, Error, HttpClient, MyPlex};
+    use plex_api::{url::MYPLEX_ANNOUNCEMENTS, Error, HttpClient, MyPlex, PlexApiError};
 
     #[plex_api_test_helper::offline_test]
     async fn list_announcements(client_anonymous: Mocked<HttpClient>) {
@@ -67,6 +67,7 @@ mod offline {
         m.delete();
 
         tmp.unwrap();
+        assert!(first_announcement.read);
 
         let m = mock_server.mock(|when, then| {
             when.method(PUT)
@@ -82,7 +83,58 @@ mod offline {
 
         let err = tmp.unwrap_err();
 
-        assert!(matches!(err, Error::MyPlexApiError { code: 400, .. }));
+        assert_eq!(
+            err.api_error(),
+            Some(&PlexApiError {
+                code: 400,
+                message: "Some error".to_owned(),
+                status: 400,
+            })
+        );
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn dismiss_announcement(client_anonymous: Mocked<HttpClient>) {
+        let (client_anonymous, mock_server) = client_anonymous.split();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_ANNOUNCEMENTS);
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body_from_file("tests/mocks/myplex/api/announcements.xml");
+        });
+
+        let plex = MyPlex::new(client_anonymous);
+        let mut announcements_manager = plex.announcements().await.unwrap();
+        m.assert();
+
+        let unread = announcements_manager
+            .unread_announcements()
+            .map(|a| a.id)
+            .collect::<Vec<_>>();
+        assert_eq!(unread, vec![78]);
+
+        let m = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path(format!("{MYPLEX_ANNOUNCEMENTS}/78"))
+                .query_param("read", "1");
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body(r#"<Response code="200" status="Updated announcement status"/>"#);
+        });
+
+        announcements_manager.dismiss(78).await.unwrap();
+        m.assert();
+
+        assert_eq!(announcements_manager.announcements().len(), 3);
+        assert_eq!(announcements_manager.unread_announcements().count(), 0);
+
+        let err = announcements_manager.dismiss(78).await.unwrap_err();
+        assert!(matches!(err, Error::ItemNotFound));
+
+        // Everything left has already been read.
+        announcements_manager.mark_all_read().await.unwrap();
+        m.assert_calls(1);
     }
 }
 
@@ -20,7 +20,9 @@ mod offline {
                 .form_urlencoded_tuple("rememberMe", "true");
             then.status(200)
                 .header("content-type", "text/json")
-                .body_from_file("tests/mocks/myplex/api/v2/user/user_info_free.json");
+                .body_from_file(
+                    "../plex-api-mock-server/mocks/myplex/api/v2/user/user_info_free.json",
+                );
         });
 
         let plex_result = MyPlexBuilder::default()
@@ -51,7 +53,9 @@ mod offline {
                 .form_urlencoded_tuple("rememberMe", "true");
             then.status(200)
                 .header("content-type", "text/json")
-                .body_from_file("tests/mocks/myplex/api/v2/user/user_info_plexpass.json");
+                .body_from_file(
+                    "../plex-api-mock-server/mocks/myplex/api/v2/user/user_info_plexpass.json",
+                );
         });
 
         let plex_result = MyPlexBuilder::default()
@@ -110,7 +114,9 @@ mod offline {
                 .form_urlencoded_tuple("verificationCode", "123456");
             then.status(200)
                 .header("content-type", "text/json")
-                .body_from_file("tests/mocks/myplex/api/v2/user/user_info_free.json");
+                .body_from_file(
+                    "../plex-api-mock-server/mocks/myplex/api/v2/user/user_info_free.json",
+                );
         });
 
         let plex_result = MyPlexBuilder::default()
@@ -131,9 +137,11 @@ mod offline {
     }
 
     #[plex_api_test_helper::offline_test]
-    #[case::free_user("tests/mocks/myplex/api/v2/user/user_info_free.json")]
+    #[case::free_user("../plex-api-mock-server/mocks/myplex/api/v2/user/user_info_free.json")]
     #[case::free_user_managed_guest("tests/mocks/myplex/api/v2/user/user_info_free_guest.json")]
-    #[case::plexpass_user("tests/mocks/myplex/api/v2/user/user_info_plexpass.json")]
+    #[case::plexpass_user(
+        "../plex-api-mock-server/mocks/myplex/api/v2/user/user_info_plexpass.json"
+    )]
     #[case::plexpass_user_managed_guest(
         "tests/mocks/myplex/api/v2/user/user_info_plexpass_guest.json"
     )]
@@ -1,13 +1,28 @@
 mod fixtures;
 
 mod offline {
-    use super::fixtures::offline::{myplex::*, Mocked};
-    use httpmock::Method::GET;
+    use super::fixtures::offline::{client::*, myplex::*, Mocked};
+    use httpmock::{
+        Method::{DELETE, GET, PUT},
+        MockServer,
+    };
     use plex_api::{
-        device::DeviceConnection,
-        url::{MYPLEX_DEVICES, MYPLEX_RESOURCES, SERVER_MEDIA_PROVIDERS},
-        MyPlex,
+        device::{DeviceConnection, DeviceFilter},
+        media_container::devices::Feature,
+        url::{
+            MYPLEX_DEVICE, MYPLEX_DEVICES, MYPLEX_RESOURCES, MYPLEX_RESOURCES_V2,
+            MYPLEX_USER_INFO_PATH, SERVER_IDENTITY, SERVER_MEDIA_PROVIDERS,
+        },
+        Error, FailoverEvent, HttpClient, HttpClientBuilder, MyPlex, MyPlexBuilder,
+    };
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread,
+        time::Duration,
     };
+    use tokio::time::{sleep, timeout};
 
     #[plex_api_test_helper::offline_test]
     async fn load_devices(#[future] myplex: Mocked<MyPlex>) {
@@ -31,6 +46,195 @@ mod offline {
             .unwrap();
     }
 
+    #[plex_api_test_helper::offline_test]
+    async fn access_token(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path(MYPLEX_RESOURCES_V2)
+                .query_param("includeHttps", "1");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/myplex/api/v2/resources.json");
+        });
+
+        let device_manager = myplex.device_manager().unwrap();
+        assert_eq!(
+            device_manager.access_token("client_id2").await.unwrap(),
+            "auth_token"
+        );
+        assert!(matches!(
+            device_manager.access_token("unknown").await,
+            Err(Error::ItemNotFound)
+        ));
+        m.assert_calls(2);
+        m.delete();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn filter_devices(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+
+        let mut devices_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_DEVICES);
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body_from_file("tests/mocks/myplex/devices.xml");
+        });
+
+        let device_manager = myplex.device_manager().unwrap();
+
+        let servers = device_manager
+            .devices_matching(DeviceFilter::servers())
+            .await
+            .unwrap();
+        assert!(!servers.is_empty());
+        assert!(servers.iter().all(|d| d.is_server()));
+
+        let players = device_manager
+            .devices_matching(DeviceFilter::players())
+            .await
+            .unwrap();
+        assert!(!players.is_empty());
+        assert!(players.iter().all(|d| d.is_player()));
+
+        let sync_targets = device_manager
+            .devices_matching(DeviceFilter::providing(Feature::SyncTarget))
+            .await
+            .unwrap();
+        let iphone = sync_targets
+            .iter()
+            .find(|d| d.name() == "iPhone - 1")
+            .unwrap();
+        assert_eq!(iphone.product(), "Plex for iOS");
+        assert_eq!(iphone.platform(), "iOS");
+        assert_eq!(iphone.connections().len(), 1);
+
+        // Everything listed in devices.xml belongs to the account.
+        let shared = device_manager
+            .devices_matching(DeviceFilter::all().shared())
+            .await
+            .unwrap();
+        assert!(shared.is_empty());
+
+        let device = device_manager.device("3").await.unwrap();
+        assert_eq!(device.name(), "Box");
+        assert!(device.is_owned());
+
+        devices_mock.assert_calls(5);
+        devices_mock.delete();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn manage_device(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+
+        let devices_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_DEVICES);
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body_from_file("tests/mocks/myplex/devices.xml");
+        });
+
+        let device_manager = myplex.device_manager().unwrap();
+        let mut device = device_manager.device("4").await.unwrap();
+        devices_mock.assert();
+        let device_path = MYPLEX_DEVICE.replace("{deviceId}", "123456");
+
+        let rename_mock = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path(&device_path)
+                .query_param("name", "Kitchen Assistant");
+            then.status(200);
+        });
+
+        device.rename("Kitchen Assistant").await.unwrap();
+        rename_mock.assert();
+        assert_eq!(device.name(), "Kitchen Assistant");
+
+        let delete_mock = mock_server.mock(|when, then| {
+            when.method(DELETE).path(&device_path);
+            then.status(200);
+        });
+
+        device.delete().await.unwrap();
+        delete_mock.assert();
+
+        device_manager.revoke("9").await.unwrap();
+        delete_mock.assert_calls(2);
+        assert!(matches!(
+            device_manager.revoke("unknown").await,
+            Err(Error::ItemNotFound)
+        ));
+        delete_mock.assert_calls(2);
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn revoke_all_except_current(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+        let current = &myplex.client().x_plex_client_identifier;
+
+        let devices_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_DEVICES);
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body(
+                    include_str!("mocks/myplex/devices_revoke.xml")
+                        .replace("current_client", current),
+                );
+        });
+        let delete_mocks = ["103", "105"].map(|id| {
+            mock_server.mock(|when, then| {
+                when.method(DELETE)
+                    .path(MYPLEX_DEVICE.replace("{deviceId}", id));
+                then.status(200);
+            })
+        });
+        // The tablet can't be signed out, which doesn't stop the others.
+        let failing_mock = mock_server.mock(|when, then| {
+            when.method(DELETE)
+                .path(MYPLEX_DEVICE.replace("{deviceId}", "104"));
+            then.status(500);
+        });
+        // Neither this client nor the server are signed out.
+        let kept_mocks = ["101", "102"].map(|id| {
+            mock_server.mock(|when, then| {
+                when.method(DELETE)
+                    .path(MYPLEX_DEVICE.replace("{deviceId}", id));
+                then.status(200);
+            })
+        });
+
+        let device_manager = myplex.device_manager().unwrap();
+        let result = device_manager.revoke_all_except_current().await.unwrap();
+        devices_mock.assert();
+        for mock in &delete_mocks {
+            mock.assert();
+        }
+        failing_mock.assert();
+        for mock in &kept_mocks {
+            mock.assert_calls(0);
+        }
+
+        let mut revoked = result.revoked;
+        revoked.sort();
+        assert_eq!(revoked, ["browser", "phone"]);
+        assert_eq!(result.failed.len(), 1);
+        let (identifier, error) = &result.failed[0];
+        assert_eq!(identifier, "tablet");
+        assert!(
+            matches!(
+                error,
+                Error::UnexpectedApiResponse {
+                    status_code: 500,
+                    ..
+                }
+            ),
+            "{error:?}"
+        );
+    }
+
     #[plex_api_test_helper::offline_test]
     async fn load_resources(#[future] myplex: Mocked<MyPlex>) {
         let (myplex, mock_server) = myplex.split();
@@ -49,6 +253,86 @@ mod offline {
         resources.unwrap();
     }
 
+    #[plex_api_test_helper::offline_test]
+    async fn load_resources_v2(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+
+        let resources_mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path(MYPLEX_RESOURCES_V2)
+                .query_param("includeHttps", "1")
+                .query_param("includeRelay", "1");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/myplex/api/v2/resources.json");
+        });
+        let xml_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_RESOURCES);
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body_from_file("tests/mocks/myplex/api/resources.xml");
+        });
+
+        let device_manager = myplex.device_manager().unwrap();
+        let resources = device_manager.resources().await.unwrap();
+        resources_mock.assert();
+        xml_mock.assert_calls(0);
+
+        let server = &resources[0];
+        assert_eq!(server.name(), "Box");
+        assert_eq!(server.identifier(), "client_id2");
+        assert!(server.is_server());
+        assert!(server.is_owned());
+        assert_eq!(server.access_token(), Some("auth_token"));
+        assert_eq!(server.is_present(), Some(true));
+        assert_eq!(server.created_at().unix_timestamp(), 1609503144);
+        assert_eq!(
+            server.connections()[0].uri,
+            "https://172-18-0-3.client-id2.plex.direct:32400"
+        );
+        assert_eq!(server.connections()[0].ipv6, Some(false));
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn wait_until_online(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+        let device_manager = myplex.device_manager().unwrap();
+
+        let mut online_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_RESOURCES);
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body_from_file("tests/mocks/myplex/api/resources.xml");
+        });
+
+        let resource = device_manager
+            .wait_until_online("3", Duration::from_secs(1))
+            .await
+            .unwrap();
+        online_mock.assert();
+        online_mock.delete();
+        assert_eq!(resource.is_present(), Some(true));
+        assert_eq!(resource.public_address_matches(), Some(false));
+        assert_eq!(resource.https_required(), Some(false));
+        assert_eq!(resource.last_seen_at().unix_timestamp(), 1628211599);
+
+        let offline_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_RESOURCES);
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body(
+                    include_str!("mocks/myplex/api/resources.xml")
+                        .replace(r#"presence="1""#, r#"presence="0""#),
+                );
+        });
+
+        let result = device_manager
+            .wait_until_online("3", Duration::from_millis(100))
+            .await;
+        assert!(matches!(result, Err(Error::Timeout)));
+        offline_mock.assert_calls(2);
+    }
+
     #[plex_api_test_helper::offline_test]
     async fn connection_from_device(#[future] myplex: Mocked<MyPlex>) {
         let (myplex, mock_server) = myplex.split();
@@ -66,7 +350,7 @@ mod offline {
             when.method(GET).path(SERVER_MEDIA_PROVIDERS);
             then.status(200)
                 .header("content-type", "application/json")
-                .body_from_file("tests/mocks/server/media/providers_free.json");
+                .body_from_file("../plex-api-mock-server/mocks/server/media/providers_free.json");
         });
 
         let device_manager = myplex.device_manager().unwrap();
@@ -88,6 +372,250 @@ mod offline {
             panic!("No devices found");
         }
     }
+
+    #[plex_api_test_helper::offline_test]
+    async fn reconnect_to_other_address(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+        let fallback_server = MockServer::start_async().await;
+        let body = include_str!("mocks/myplex/api/resources.xml")
+            .replace("http://1.0.0.2:443", &mock_server.base_url())
+            .replace("https://example.com:443", &fallback_server.base_url());
+
+        let mut resources_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_RESOURCES);
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body(body);
+        });
+
+        let mut providers_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_MEDIA_PROVIDERS);
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../plex-api-mock-server/mocks/server/media/providers_free.json");
+        });
+
+        let connect_mock = fallback_server.mock(|when, then| {
+            when.method(GET).path(SERVER_MEDIA_PROVIDERS);
+            then.status(404);
+        });
+
+        let device_manager = myplex.device_manager().unwrap();
+        let resources = device_manager.resources().await.unwrap();
+        resources_mock.assert();
+        resources_mock.delete();
+
+        let DeviceConnection::Server(server) = resources[0].connect().await.unwrap() else {
+            panic!("Connected to a strange device");
+        };
+        providers_mock.assert();
+        providers_mock.delete();
+        assert_eq!(server.client().api_url, mock_server.base_url().as_str());
+
+        // Connecting doesn't wait for the other addresses to answer, so the
+        // request it sent to the fallback may still be on its way.
+        let _ = timeout(Duration::from_secs(1), async {
+            while connect_mock.calls_async().await == 0 {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await;
+        connect_mock.delete_async().await;
+
+        // The original address no longer answers.
+        let identity_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_IDENTITY);
+            then.status(503);
+        });
+        let fallback_providers_mock = fallback_server.mock(|when, then| {
+            when.method(GET).path(SERVER_MEDIA_PROVIDERS);
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../plex-api-mock-server/mocks/server/media/providers_free.json");
+        });
+
+        let loaded_client = server.client().clone();
+        let server = server.reconnect().await.unwrap();
+        identity_mock.assert();
+        fallback_providers_mock.assert();
+        assert_eq!(server.client().api_url, fallback_server.base_url().as_str());
+        // Anything loaded before reconnecting follows the server.
+        assert_eq!(
+            loaded_client.base_url().to_string(),
+            format!("{}/", fallback_server.base_url())
+        );
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn cached_resources(client_authenticated: Mocked<HttpClient>) {
+        let (client, mock_server) = client_authenticated.split();
+
+        let user_mock = mock_server.mock(|when, then| {
+            when.path(MYPLEX_USER_INFO_PATH);
+            then.status(200).body_from_file(
+                "../plex-api-mock-server/mocks/myplex/api/v2/user/user_info_free.json",
+            );
+        });
+        let resources_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_RESOURCES);
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body_from_file("tests/mocks/myplex/api/resources.xml");
+        });
+
+        let myplex = MyPlexBuilder::default()
+            .set_client(client)
+            .set_cache_ttl(Duration::from_secs(60))
+            .build()
+            .await
+            .unwrap();
+        let myplex = myplex.refresh().await.unwrap();
+        user_mock.assert_calls(1);
+
+        let device_manager = myplex.device_manager().unwrap();
+        assert_eq!(device_manager.resources().await.unwrap().len(), 1);
+        assert_eq!(device_manager.resources().await.unwrap().len(), 1);
+        let device_manager = myplex.device_manager().unwrap();
+        assert_eq!(device_manager.resources().await.unwrap().len(), 1);
+        resources_mock.assert_calls(1);
+
+        myplex.invalidate();
+        assert_eq!(device_manager.resources().await.unwrap().len(), 1);
+        resources_mock.assert_calls(2);
+        myplex.refresh().await.unwrap();
+        user_mock.assert_calls(2);
+
+        // Waiting for a resource always asks plex.tv and refreshes the cache.
+        device_manager
+            .wait_until_online("3", Duration::from_secs(1))
+            .await
+            .unwrap();
+        resources_mock.assert_calls(3);
+        device_manager.resources().await.unwrap();
+        resources_mock.assert_calls(3);
+
+        // Renaming or removing a device makes the resources stale.
+        mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_DEVICES);
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body_from_file("tests/mocks/myplex/devices.xml");
+        });
+        let device_path = MYPLEX_DEVICE.replace("{deviceId}", "123456");
+        mock_server.mock(|when, then| {
+            when.method(PUT).path(&device_path);
+            then.status(200);
+        });
+        mock_server.mock(|when, then| {
+            when.method(DELETE).path(&device_path);
+            then.status(200);
+        });
+
+        let mut device = device_manager.device("4").await.unwrap();
+        device.rename("Kitchen Assistant").await.unwrap();
+        device_manager.resources().await.unwrap();
+        resources_mock.assert_calls(4);
+        device.delete().await.unwrap();
+        device_manager.resources().await.unwrap();
+        resources_mock.assert_calls(5);
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn failover_to_next_address(client_authenticated: Mocked<HttpClient>) {
+        let (client, mock_server) = client_authenticated.split();
+        let failovers = Arc::new(Mutex::new(Vec::new()));
+        let observer_failovers = failovers.clone();
+        let client = HttpClientBuilder::from(client)
+            .set_failover_observer(move |event: &FailoverEvent<'_>| {
+                observer_failovers
+                    .lock()
+                    .unwrap()
+                    .push((event.from.to_string(), event.to.to_string()));
+            })
+            .build()
+            .unwrap();
+
+        let mut user_mock = mock_server.mock(|when, then| {
+            when.path(MYPLEX_USER_INFO_PATH);
+            then.status(200).body_from_file(
+                "../plex-api-mock-server/mocks/myplex/api/v2/user/user_info_free.json",
+            );
+        });
+        let myplex = MyPlexBuilder::default()
+            .set_client(client)
+            .build()
+            .await
+            .unwrap();
+        user_mock.assert();
+        user_mock.delete();
+
+        // The local address answers the first request and then goes away.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let local_url = format!("http://{}", listener.local_addr().unwrap());
+        let local_server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = stream.read(&mut buffer).unwrap();
+                request.extend_from_slice(&buffer[..read]);
+            }
+            let body =
+                include_str!("../../plex-api-mock-server/mocks/server/media/providers_free.json");
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        });
+
+        let remote_server = MockServer::start_async().await;
+        let body = include_str!("mocks/myplex/api/resources.xml")
+            .replace("http://172.18.0.12:32400", &local_url)
+            .replace("https://example.com:443", &remote_server.base_url());
+        let mut resources_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_RESOURCES);
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body(body);
+        });
+
+        let device_manager = myplex.device_manager().unwrap();
+        let resources = device_manager.resources().await.unwrap();
+        resources_mock.assert();
+        resources_mock.delete();
+
+        let DeviceConnection::Server(server) = resources[0].connect().await.unwrap() else {
+            panic!("Connected to a strange device");
+        };
+        local_server.join().unwrap();
+        assert_eq!(
+            server.client().base_url().to_string(),
+            format!("{local_url}/")
+        );
+
+        let identity_mock = remote_server.mock(|when, then| {
+            when.method(GET).path(SERVER_IDENTITY);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("../plex-api-mock-server/mocks/server/identity.json");
+        });
+
+        server.identity().await.unwrap();
+        identity_mock.assert();
+        assert_eq!(
+            server.client().base_url().to_string(),
+            format!("{}/", remote_server.base_url())
+        );
+        assert_eq!(
+            *failovers.lock().unwrap(),
+            [(
+                format!("{local_url}/identity"),
+                format!("{}/identity", remote_server.base_url())
+            )]
+        );
+    }
 }
 
 mod online {
@@ -0,0 +1,108 @@
+mod fixtures;
+
+mod offline {
+    use std::time::Duration;
+
+    use super::fixtures::offline::{myplex::*, Mocked};
+    use httpmock::Method::GET;
+    use plex_api::{
+        device::DeviceFilter,
+        diagnostics::{ConnectionKind, NatStatus},
+        url::{MYPLEX_GEOIP, MYPLEX_PUBLIC_IP, MYPLEX_RESOURCES, SERVER_IDENTITY},
+        MyPlex,
+    };
+
+    #[plex_api_test_helper::offline_test]
+    async fn run_diagnostics(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+        let body = include_str!("mocks/myplex/api/resources.xml")
+            .replace("http://1.0.0.2:443", &mock_server.base_url())
+            .replace("https://example.com:443", "http://127.0.0.1:1");
+
+        let ip_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_PUBLIC_IP);
+            then.status(200)
+                .header("content-type", "text/plain")
+                .body("1.0.0.1\n");
+        });
+
+        let geoip_mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path(MYPLEX_GEOIP)
+                .query_param("ip_address", "1.0.0.1");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/myplex/api/v2/geoip.json");
+        });
+
+        let mut resources_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_RESOURCES);
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body(body.clone());
+        });
+
+        let identity_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_IDENTITY);
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../plex-api-mock-server/mocks/server/identity.json");
+        });
+
+        let diagnostics = myplex
+            .diagnostics()
+            .unwrap()
+            .timeout(Duration::from_secs(1));
+        let report = diagnostics.run().await.unwrap();
+        ip_mock.assert();
+        geoip_mock.assert();
+        resources_mock.assert();
+        identity_mock.assert();
+
+        assert_eq!(report.public_address.to_string(), "1.0.0.1");
+        assert_eq!(report.location.unwrap().code, "NL");
+
+        assert_eq!(report.servers.len(), 1);
+        let server = &report.servers[0];
+        assert_eq!(server.identifier, "3");
+        assert_eq!(server.same_network, Some(false));
+        assert_eq!(
+            server
+                .connections
+                .iter()
+                .map(|c| (c.kind, c.is_reachable()))
+                .collect::<Vec<_>>(),
+            vec![
+                (ConnectionKind::Local, false),
+                (ConnectionKind::Remote, false),
+                (ConnectionKind::Remote, true),
+            ]
+        );
+        assert!(server.connections[0].error.is_some());
+        assert!(server.remote_access_works());
+        assert_eq!(
+            server.fastest().unwrap().uri.to_string(),
+            format!("{}/", mock_server.base_url())
+        );
+        assert_eq!(server.nat, NatStatus::Open);
+
+        // The server's router sits behind a carrier-grade NAT.
+        resources_mock.delete();
+        let resources_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_RESOURCES);
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body(body.replace("publicAddress=\"1.0.0.2\"", "publicAddress=\"100.64.0.2\""));
+        });
+
+        let device_manager = myplex.device_manager().unwrap();
+        let servers = device_manager
+            .resources_matching(DeviceFilter::servers())
+            .await
+            .unwrap();
+        resources_mock.assert();
+
+        let server = diagnostics.check_server(&servers[0]).await.unwrap();
+        assert_eq!(server.nat, NatStatus::DoubleNat);
+    }
+}
@@ -3,7 +3,7 @@ mod fixtures;
 mod offline {
     use super::fixtures::offline::{myplex::*, Mocked};
     use httpmock::{
-        Method::{DELETE, GET, POST},
+        Method::{DELETE, GET, POST, PUT},
         Mock, MockServer,
     };
     use plex_api::{
@@ -254,6 +254,71 @@ mod offline {
         assert_eq!("deadbeef1", share.invited.unwrap().uuid);
     }
 
+    #[plex_api_test_helper::offline_test]
+    async fn update_share(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+        let sharing = myplex.sharing().unwrap();
+
+        let mut m = prepare_friends_mock(
+            &mock_server,
+            "accepted",
+            "tests/mocks/myplex/api/v2/friends_accepted_one_restricted.json",
+        );
+        let mut friends = sharing.friends(InviteStatus::Accepted).await.unwrap();
+        m.assert();
+        m.delete();
+
+        let share = friends.pop().unwrap().shared_servers.pop().unwrap();
+        assert_eq!(share.library_ids(), vec!["1", "2"]);
+
+        let server_info_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(format!("{MYPLEX_SERVERS}/deadb33f"));
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/myplex/api/v2/servers/machine_id.json");
+        });
+
+        let share_update_mock = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path(format!("{MYPLEX_INVITES_SHARED_SERVERS}/1"))
+                .json_body(serde_json::json!({
+                    "librarySectionIds": [1001],
+                    "settings": {
+                        "allowChannels": false,
+                        "allowSubtitleAdmin": false,
+                        "allowSync": true,
+                        "allowCameraUpload": false,
+                        "allowTuners": 2,
+                        "filterMovies": "label=Kids",
+                        "filterMusic": "",
+                        "filterTelevision": "contentRating!=TV-MA",
+                        "filterPhotos": null,
+                        "filterAll": null
+                    }
+                }));
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/myplex/api/v2/shared_server_create.json");
+        });
+
+        let mut filters = share.filters();
+        filters.movies.label = vec!["Kids".to_owned()];
+        filters.television.exclude_content_rating = vec!["TV-MA".to_owned()];
+
+        sharing
+            .update_share(
+                &share,
+                &[ShareableLibrary::LibraryId("1")],
+                share.permissions(),
+                filters,
+            )
+            .await
+            .unwrap();
+
+        server_info_mock.assert();
+        share_update_mock.assert();
+    }
+
     #[plex_api_test_helper::offline_test]
     async fn accept_not_pending_forbidden(#[future] myplex: Mocked<MyPlex>) {
         let (myplex, mock_server) = myplex.split();
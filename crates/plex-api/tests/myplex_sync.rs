@@ -0,0 +1,152 @@
+mod fixtures;
+
+mod offline {
+    use super::fixtures::offline::{myplex::*, Mocked};
+    use httpmock::Method::{DELETE, GET, POST};
+    use plex_api::{
+        sync::{MediaSettings, NewSyncItem, SyncPolicy, SyncScope, SyncState},
+        url::{MYPLEX_DEVICES, MYPLEX_SYNC_ITEM, MYPLEX_SYNC_ITEMS},
+        MyPlex,
+    };
+
+    #[plex_api_test_helper::offline_test]
+    async fn list_items(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path(MYPLEX_SYNC_ITEMS.replace("{clientId}", "9"));
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body_from_file("tests/mocks/myplex/sync/items.xml");
+        });
+
+        let items = myplex.sync().unwrap().items("9").await.unwrap();
+        m.assert();
+
+        assert_eq!(items.len(), 2);
+        let item = &items[0];
+        assert_eq!(item.id, 1234);
+        assert_eq!(item.title, "Big Buck Bunny");
+        assert_eq!(item.client_identifier, "9");
+        assert_eq!(item.status.state, SyncState::Complete);
+        assert_eq!(item.status.total_size, 412318720);
+        assert_eq!(item.media_settings.max_video_bitrate, Some(4000));
+        assert_eq!(item.policy.scope, SyncScope::All);
+
+        let item = &items[1];
+        assert_eq!(item.status.state, SyncState::Processing);
+        assert_eq!(item.media_settings.music_bitrate, None);
+        assert_eq!(
+            item.policy,
+            SyncPolicy {
+                scope: SyncScope::Count,
+                value: 5,
+                unwatched: true,
+            }
+        );
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn list_all_items(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+
+        let devices_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_DEVICES);
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body_from_file("tests/mocks/myplex/devices.xml");
+        });
+
+        let items_mock = mock_server.mock(|when, then| {
+            when.method(GET).path_suffix("/sync_items");
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body_from_file("tests/mocks/myplex/sync/items.xml");
+        });
+
+        let items = myplex.sync().unwrap().all_items().await.unwrap();
+        devices_mock.assert();
+        // Only the iOS devices can be synced to.
+        items_mock.assert_calls(4);
+
+        assert_eq!(items.len(), 8);
+        let mut devices = items
+            .iter()
+            .map(|item| item.client_identifier.as_str())
+            .collect::<Vec<_>>();
+        devices.dedup();
+        assert_eq!(devices, vec!["9", "14", "15", "22"]);
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn create_item(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path(MYPLEX_SYNC_ITEMS.replace("{clientId}", "9"))
+                .query_param("SyncItem[title]", "Sintel")
+                .query_param("SyncItem[machineIdentifier]", "3")
+                .query_param(
+                    "SyncItem[Location][uri]",
+                    "library://7c2cd1b8/item/%2Flibrary%2Fmetadata%2F61",
+                )
+                .query_param("SyncItem[Policy][scope]", "all")
+                .query_param("SyncItem[Policy][unwatched]", "0")
+                .query_param("SyncItem[metadataType]", "movie")
+                .query_param("SyncItem[MediaSettings][maxVideoBitrate]", "4000")
+                .query_param("SyncItem[MediaSettings][videoResolution]", "1280x720")
+                .query_param_missing("SyncItem[MediaSettings][musicBitrate]");
+            then.status(200)
+                .header("content-type", "application/xml")
+                .body_from_file("tests/mocks/myplex/sync/create.xml");
+        });
+
+        let item = myplex
+            .sync()
+            .unwrap()
+            .create(
+                "9",
+                &NewSyncItem {
+                    title: "Sintel".to_owned(),
+                    root_title: Some("Movies".to_owned()),
+                    metadata_type: Some("movie".to_owned()),
+                    content_type: Some("video".to_owned()),
+                    machine_identifier: "3".to_owned(),
+                    uri: "library://7c2cd1b8/item/%2Flibrary%2Fmetadata%2F61".to_owned(),
+                    policy: SyncPolicy::default(),
+                    media_settings: MediaSettings {
+                        max_video_bitrate: Some(4000),
+                        video_quality: Some(60),
+                        video_resolution: Some("1280x720".to_owned()),
+                        ..Default::default()
+                    },
+                },
+            )
+            .await
+            .unwrap();
+        m.assert();
+
+        assert_eq!(item.id, 1240);
+        assert_eq!(item.client_identifier, "9");
+        assert_eq!(item.status.state, SyncState::Pending);
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn delete_item(#[future] myplex: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex.split();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(DELETE).path(
+                MYPLEX_SYNC_ITEM
+                    .replace("{clientId}", "9")
+                    .replace("{syncId}", "1234"),
+            );
+            then.status(200);
+        });
+
+        myplex.sync().unwrap().delete("9", 1234).await.unwrap();
+        m.assert();
+    }
+}
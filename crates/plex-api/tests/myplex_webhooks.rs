@@ -3,7 +3,7 @@ mod fixtures;
 mod offline {
     use super::fixtures::offline::{myplex::*, Mocked};
     use httpmock::Method::{GET, POST};
-    use plex_api::{url::MYPLEX_WEBHOOKS_PATH, Error, MyPlex};
+    use plex_api::{url::MYPLEX_WEBHOOKS_PATH, Error, MyPlex, WebhookManager};
 
     #[plex_api_test_helper::offline_test]
     async fn webhook_free_user(#[future] myplex: Mocked<MyPlex>) {
@@ -174,6 +174,36 @@ mod offline {
         assert_eq!(webhook_manager.webhooks().len(), 2, "Expected two webhooks");
     }
 
+    #[plex_api_test_helper::offline_test]
+    async fn webhook_add_existing(#[future] myplex_plexpass: Mocked<MyPlex>) {
+        let (myplex, mock_server) = myplex_plexpass.split();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_WEBHOOKS_PATH);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/myplex/api/v2/user/webhooks_two.json");
+        });
+
+        let mut webhook_manager: WebhookManager = myplex.webhook_manager().await.unwrap();
+        m.assert();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(POST).path(MYPLEX_WEBHOOKS_PATH);
+            then.status(201)
+                .header("content-type", "text/json")
+                .body("");
+        });
+
+        webhook_manager
+            .add("https://example.com/webhook1")
+            .await
+            .expect("failed to add webhook");
+        m.assert_calls(0);
+
+        assert_eq!(webhook_manager.webhooks().len(), 2, "Expected two webhooks");
+    }
+
     #[plex_api_test_helper::offline_test]
     async fn webhook_erase(#[future] myplex_plexpass: Mocked<MyPlex>) {
         let (myplex, mock_server) = myplex_plexpass.split();
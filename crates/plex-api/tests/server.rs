@@ -11,18 +11,42 @@ mod offline {
     use crate::map;
 
     use super::fixtures::offline::{client::*, server::*, Mocked};
-    use httpmock::Method::GET;
+    use futures::{future::join, TryStreamExt};
+    use httpmock::Method::{DELETE, GET, POST, PUT};
     use plex_api::{
-        library::{Collection, Item, Library, MetadataItem, Movie, Playlist, Video},
-        media_container::server::library::SearchType,
-        url::{MYPLEX_USER_INFO_PATH, SERVER_MEDIA_PROVIDERS},
-        HttpClient, Server,
+        content_rating,
+        endpoints::Endpoint,
+        library::{
+            Collection, Episode, Item, Library, MediaItem, MetadataItem, Movie, Playlist, Show,
+            TagField, Video,
+        },
+        media_container::{
+            preferences::Value,
+            server::{
+                library::{ContainerFormat, ExternalId, SearchType},
+                Feature,
+            },
+        },
+        optimize::OptimizeTarget,
+        url::{
+            MYPLEX_CLAIM_TOKEN_PATH, MYPLEX_USER_INFO_PATH, SERVER_IDENTITY,
+            SERVER_MEDIA_PROVIDERS, SERVER_MYPLEX_CLAIM,
+        },
+        DeleteWatched, DownloadOptions, EpisodeSort, Error, HttpClient, ItemSettings,
+        KeepUnwatched, Progress, RestrictionProfile, SeasonsDisplay, Server, ServerFeature,
     };
+    use serde::Deserialize;
+    use std::{
+        ops::Bound,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+    use tokio::time::sleep;
 
     #[plex_api_test_helper::offline_test]
-    #[case::free("tests/mocks/server/media/providers_free.json")]
-    #[case::plexpass("tests/mocks/server/media/providers_plexpass.json")]
-    #[case::unclaimed("tests/mocks/server/media/providers_unclaimed.json")]
+    #[case::free("../plex-api-mock-server/mocks/server/media/providers_free.json")]
+    #[case::plexpass("../plex-api-mock-server/mocks/server/media/providers_plexpass.json")]
+    #[case::unclaimed("../plex-api-mock-server/mocks/server/media/providers_unclaimed.json")]
     async fn load_server(client_authenticated: Mocked<HttpClient>, #[case] mock_file: &str) {
         let (client_authenticated, mock_server) = client_authenticated.split();
 
@@ -41,6 +65,455 @@ mod offline {
         server_result.expect("can't load server");
     }
 
+    #[plex_api_test_helper::offline_test]
+    async fn media_providers(
+        #[future] server_authenticated: Mocked<Server>,
+        #[future] server_plexpass: Mocked<Server>,
+    ) {
+        let (server, _) = server_authenticated.split();
+
+        assert_eq!(server.media_providers().len(), 1);
+        let library = server
+            .media_provider("com.plexapp.plugins.library")
+            .unwrap();
+        assert_eq!(library.title, "Library");
+        assert!(server.has_feature(ServerFeature::Library));
+        assert!(server.has_feature(ServerFeature::DownloadQueue));
+        assert!(server.has_feature(ServerFeature::Dvr));
+        assert!(!server.has_feature(ServerFeature::LiveTv));
+
+        // The server has no program guide so nothing can be recorded. This
+        // fails without making a request.
+        let result = server.subscriptions().await;
+        assert!(matches!(
+            result,
+            Err(Error::ServerFeatureNotAvailable(ServerFeature::LiveTv))
+        ));
+
+        let (server, _) = server_plexpass.split();
+        assert_eq!(server.media_providers().len(), 2);
+        assert!(server
+            .media_provider("tv.plex.providers.epg.cloud:30")
+            .is_some());
+        assert!(server.has_feature(ServerFeature::LiveTv));
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn capabilities(
+        #[future] server_anonymous: Mocked<Server>,
+        #[future] server_plexpass: Mocked<Server>,
+    ) {
+        let (server, _) = server_anonymous.split();
+        let capabilities = server.capabilities();
+
+        let version = capabilities.version().unwrap();
+        assert_eq!((version.major, version.minor, version.patch), (1, 25, 3));
+        assert_eq!(version.build, 5409);
+        assert_eq!(version.revision(), Some("f11334058"));
+        assert_eq!(version.to_string(), "1.25.3.5409-f11334058");
+        assert_eq!(*version, "1.25.3.5409-0a1b2c3d4".parse().unwrap());
+        assert_eq!(*version, "1.25.3.5409".parse().unwrap());
+        assert!(*version < "1.25.3.5410-0a1b2c3d4".parse().unwrap());
+
+        assert!(capabilities.version_at_least(1, 25, 3));
+        assert!(capabilities.version_at_least(1, 24, 10));
+        assert!(!capabilities.version_at_least(1, 25, 4));
+        assert!(!capabilities.version_at_least(1, 40, 0));
+
+        assert!(capabilities.supports_download_queue());
+        assert!(capabilities.supports_video_transcoding());
+        assert!(!capabilities.supports_intro_markers());
+        assert!(!capabilities.supports_live_tv());
+
+        let (server, _) = server_plexpass.split();
+        let capabilities = server.capabilities();
+        assert!(capabilities.supports_intro_markers());
+        assert!(capabilities.supports_live_tv());
+        assert!(capabilities.has_feature(&Feature::Webhooks));
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn transient_token(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/security/token")
+                .query_param("type", "delegation")
+                .query_param("scope", "all");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"MediaContainer":{"size":0,"token":"transient-4b1e5a2c"}}"#);
+        });
+
+        assert_eq!(
+            server.transient_token().await.unwrap(),
+            "transient-4b1e5a2c"
+        );
+        m.assert();
+        m.delete();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn accounts(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/accounts");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/server/accounts.json");
+        });
+
+        let accounts = server.accounts().await.unwrap();
+        assert_eq!(accounts.len(), 3);
+        assert_eq!(accounts[2].name, "friend");
+        assert_eq!(accounts[2].default_audio_language.as_deref(), Some("de"));
+        assert!(!accounts[2].auto_select_audio);
+
+        let names = server.account_names().await.unwrap();
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[&1], "owner");
+        assert_eq!(names[&21458923], "friend");
+        m.assert_calls(2);
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/accounts/21458923");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/server/accounts/21458923.json");
+        });
+        let account = server.account(21458923).await.unwrap();
+        m.assert();
+        assert_eq!(account.key, "/accounts/21458923");
+        assert_eq!(account.default_subtitle_language.as_deref(), Some("de"));
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn butler(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+        let butler = server.butler();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/butler");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/server/butler.json");
+        });
+        let tasks = butler.tasks().await.unwrap();
+        m.assert();
+        assert_eq!(tasks.len(), 3);
+        assert_eq!(tasks[0].name, "BackupDatabase");
+        assert_eq!(tasks[0].interval, 3);
+        assert!(tasks[1].schedule_randomized);
+        assert!(!tasks[1].enabled);
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/:/prefs");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/server/prefs.json");
+        });
+        let window = butler.window().await.unwrap();
+        m.assert();
+        assert_eq!(window.start_hour, 1);
+        assert_eq!(window.end_hour, 7);
+
+        let m = mock_server.mock(|when, then| {
+            when.method(POST).path("/butler/BackupDatabase");
+            then.status(200);
+        });
+        butler.start("BackupDatabase").await.unwrap();
+        m.assert();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(DELETE).path("/butler");
+            then.status(200);
+        });
+        butler.stop_all().await.unwrap();
+        m.assert();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/activities");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/server/activities.json");
+        });
+        let activities = server.activities().await.unwrap();
+        m.assert();
+        assert_eq!(activities.len(), 1);
+        assert_eq!(activities[0].activity_type, "library.update.section");
+        assert_eq!(activities[0].progress, 42);
+        assert_eq!(activities[0].context["librarySectionID"], "1");
+    }
+
+    const BACKUPS_PATH: &str = "/services/browse/L2NvbmZpZy9MaWJyYXJ5L0FwcGxpY2F0aW9uIFN1cHBvcnQvUGxleCBNZWRpYSBTZXJ2ZXIvUGx1Zy1pbiBTdXBwb3J0L0RhdGFiYXNlcw==";
+    const NO_ACTIVITIES: &str = r#"{"MediaContainer":{"size":0}}"#;
+    const BACKUP_ACTIVITY: &str = r#"{"MediaContainer":{"size":1,"Activity":[{"uuid":"9d2c41e6-7a0b-4f3e-8c55-1b6e0f2a7d94","type":"butler.backup","cancellable":false,"userID":1,"title":"Backing up database","progress":0}]}}"#;
+
+    #[plex_api_test_helper::offline_test]
+    async fn backup_database(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let prefs_mock = mock_server.mock(|when, then| {
+            when.method(GET).path("/:/prefs");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/server/prefs.json");
+        });
+        let browse_mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path(BACKUPS_PATH)
+                .query_param("includeFiles", "1");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/server/browse_backups.json");
+        });
+        let start_mock = mock_server.mock(|when, then| {
+            when.method(POST).path("/butler/BackupDatabase");
+            then.status(200);
+        });
+        let activities_mock = |body: &'static str| {
+            mock_server.mock(move |when, then| {
+                when.method(GET).path("/activities");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(body);
+            })
+        };
+
+        let backups = server.database_backups().await.unwrap();
+        assert_eq!(
+            backups
+                .iter()
+                .map(|backup| backup.date.to_string())
+                .collect::<Vec<_>>(),
+            vec!["2024-03-17", "2024-03-20"]
+        );
+
+        // The task hasn't started yet on the first poll, the backup is only
+        // done once its activity went away again.
+        let not_started_mock = activities_mock(NO_ACTIVITIES);
+        let server_activities = async {
+            while not_started_mock.calls_async().await == 0 {
+                sleep(Duration::from_millis(10)).await;
+            }
+            not_started_mock.delete_async().await;
+
+            let running_mock = activities_mock(BACKUP_ACTIVITY);
+            while running_mock.calls_async().await == 0 {
+                sleep(Duration::from_millis(10)).await;
+            }
+            running_mock.delete_async().await;
+
+            activities_mock(NO_ACTIVITIES)
+        };
+
+        let (backup, finished_mock) = join(server.backup_database(None), server_activities).await;
+        let backup = backup.unwrap();
+        start_mock.assert();
+        finished_mock.assert();
+        prefs_mock.assert_calls(4);
+        browse_mock.assert_calls(4);
+        assert_eq!(
+            backup.path,
+            "/config/Library/Application Support/Plex Media Server/Plug-in Support/Databases/com.plexapp.plugins.library.db-2024-03-20"
+        );
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn backup_database_missed_activity(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/:/prefs");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/server/prefs.json");
+        });
+        mock_server.mock(|when, then| {
+            when.method(GET)
+                .path(BACKUPS_PATH)
+                .query_param("includeFiles", "1");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/server/browse_backups.json");
+        });
+        let start_mock = mock_server.mock(|when, then| {
+            when.method(POST).path("/butler/BackupDatabase");
+            then.status(200);
+        });
+        let activities_mock = mock_server.mock(|when, then| {
+            when.method(GET).path("/activities");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(NO_ACTIVITIES);
+        });
+
+        // The backup replaced the one from the same day and finished before
+        // the first poll, so neither its activity nor a newer backup shows up.
+        let backup = server.backup_database(None).await.unwrap();
+        start_mock.assert();
+        assert!(activities_mock.calls() > 1);
+        assert_eq!(backup.date.to_string(), "2024-03-20");
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn backup_database_timeout(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/:/prefs");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/server/prefs.json");
+        });
+        mock_server.mock(|when, then| {
+            when.method(GET)
+                .path(BACKUPS_PATH)
+                .query_param("includeFiles", "1");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/server/browse_backups.json");
+        });
+        let start_mock = mock_server.mock(|when, then| {
+            when.method(POST).path("/butler/BackupDatabase");
+            then.status(200);
+        });
+        let activities_mock = mock_server.mock(|when, then| {
+            when.method(GET).path("/activities");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(NO_ACTIVITIES);
+        });
+
+        // The task never shows up and no newer backup is written.
+        let error = server
+            .backup_database(Some(Duration::from_millis(100)))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::Timeout), "{error:?}");
+        start_mock.assert();
+        activities_mock.assert_calls(2);
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn identity(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_IDENTITY);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("../plex-api-mock-server/mocks/server/identity.json");
+        });
+
+        let identity = server.identity().await.unwrap();
+        m.assert();
+
+        assert!(!identity.claimed);
+        assert_eq!(identity.machine_identifier, server.machine_identifier());
+        assert_eq!(identity.version.to_string(), "1.25.3.5409-f11334058");
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn health(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_IDENTITY);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("../plex-api-mock-server/mocks/server/identity.json");
+        });
+
+        let health = server.health().await.unwrap();
+        m.assert();
+        assert_eq!(health.machine_identifier, server.machine_identifier());
+        assert_eq!(health.version.to_string(), "1.25.3.5409-f11334058");
+        m.delete();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_IDENTITY);
+            then.status(503);
+        });
+
+        assert!(server.ping().await.is_err());
+        m.assert();
+
+        // The server has no other known addresses to fall back to.
+        assert!(server.reconnect().await.is_err());
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn raw(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/statistics/bandwidth")
+                .query_param("timespan", "6")
+                .header("X-Plex-Token", "fixture_auth_token");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body(r#"{"MediaContainer":{"size":2,"StatisticsBandwidth":[{},{}]}}"#);
+        });
+
+        #[derive(Deserialize)]
+        struct Bandwidth {
+            size: u32,
+            #[serde(rename = "StatisticsBandwidth")]
+            statistics: Vec<serde_json::Value>,
+        }
+
+        let container: Bandwidth = server
+            .raw()
+            .get(Endpoint::new("/statistics/bandwidth").query("timespan", "6"))
+            .media_container()
+            .await
+            .unwrap();
+        m.assert();
+        assert_eq!(container.size, 2);
+        assert_eq!(container.statistics.len(), 2);
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn claim(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+        let myplex = server.myplex().unwrap();
+
+        let token_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(MYPLEX_CLAIM_TOKEN_PATH);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/myplex/api/claim/token.json");
+        });
+
+        let claim_mock = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path(SERVER_MYPLEX_CLAIM)
+                .query_param("token", "claim-TOKEN");
+            then.status(200);
+        });
+
+        let providers_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_MEDIA_PROVIDERS);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("../plex-api-mock-server/mocks/server/media/providers_free.json");
+        });
+
+        let server = server.claim_with(&myplex).await.unwrap();
+        token_mock.assert();
+        claim_mock.assert();
+        providers_mock.assert();
+
+        assert_eq!(
+            server.media_container.my_plex_username.as_deref(),
+            Some("username")
+        );
+    }
+
     #[plex_api_test_helper::offline_test]
     async fn myplex_recover_from_server(#[future] server_authenticated: Mocked<Server>) {
         let (server, mock_server) = server_authenticated.split();
@@ -83,7 +556,7 @@ mod offline {
                 .query_param("type", "1");
             then.status(200)
                 .header("content-type", "text/json")
-                .body_from_file("tests/mocks/server/media/movie_library.json");
+                .body_from_file("../plex-api-mock-server/mocks/server/media/movie_library.json");
         });
 
         let movies = library.movies().await.unwrap();
@@ -104,6 +577,50 @@ mod offline {
             map(&movies, |e| e.rating_key().to_owned()),
             vec!["55", "56", "108", "57"]
         );
+
+        let allowed =
+            content_rating::rated_at_most(movies, RestrictionProfile::OlderKid.max_age().unwrap());
+        assert_eq!(
+            map(&allowed, |e| e.content_rating().unwrap().to_string()),
+            vec!["G", "PG"]
+        );
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn movie_library_stream(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let libraries = server.libraries();
+        let library = if let Library::Movie(lib) = &libraries[0] {
+            lib
+        } else {
+            panic!("Unexpected library: {:?}", libraries[0]);
+        };
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/sections/1/all")
+                .query_param("type", "1");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("../plex-api-mock-server/mocks/server/media/movie_library.json");
+        });
+
+        let movies: Vec<Movie> = library.movies_stream().try_collect().await.unwrap();
+        m.assert();
+
+        assert_eq!(
+            map(&movies, |e| e.title().to_owned()),
+            vec![
+                "Big Buck Bunny",
+                "Elephants Dream",
+                "Interstate 60",
+                "Sintel"
+            ]
+        );
+        assert!(movies
+            .iter()
+            .all(|movie| movie.metadata().library_section_id == Some(1)));
     }
 
     #[plex_api_test_helper::offline_test]
@@ -139,6 +656,42 @@ mod offline {
 
         assert_eq!(map(&shows, |e| e.rating_key().to_owned()), vec!["22", "68"]);
 
+        assert_eq!(
+            shows[0].clear_logo(),
+            Some("/library/metadata/22/clearLogo/1579514246")
+        );
+        assert_eq!(shows[1].clear_logo(), None);
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/metadata/22/theme/1579514246");
+            then.status(200)
+                .header("content-type", "audio/mpeg")
+                .body("theme");
+        });
+
+        let mut buf = Vec::<u8>::new();
+        shows[0].download_theme(&mut buf).await.unwrap();
+        m.assert();
+        m.delete();
+
+        assert_eq!(buf, b"theme");
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/metadata/22/clearLogo/1579514246");
+            then.status(200)
+                .header("content-type", "image/png")
+                .body("logo");
+        });
+
+        let mut buf = Vec::<u8>::new();
+        shows[0].download_clear_logo(&mut buf).await.unwrap();
+        m.assert();
+        m.delete();
+
+        assert_eq!(buf, b"logo");
+
         let mut m = mock_server.mock(|when, then| {
             when.method(GET).path("/library/metadata/22/children");
             then.status(200)
@@ -183,20 +736,297 @@ mod offline {
     }
 
     #[plex_api_test_helper::offline_test]
-    async fn photo_library(#[future] server_anonymous: Mocked<Server>) {
+    async fn library_totals(#[future] server_anonymous: Mocked<Server>) {
         let (server, mock_server) = server_anonymous.split();
+        let library = &server.libraries()[1];
+
+        let count_mocks = [("2", 3), ("3", 7), ("4", 52)].map(|(item_type, count)| {
+            mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/library/sections/2/all")
+                    .query_param("type", item_type)
+                    .query_param("X-Plex-Container-Size", "0");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body(format!(
+                        r#"{{"MediaContainer": {{"size": 0, "totalSize": {count}}}}}"#
+                    ));
+            })
+        });
 
-        let libraries = server.libraries();
-        let library = if let Library::Photo(lib) = &libraries[3] {
-            lib
-        } else {
-            panic!("Unexpected library: {:?}", libraries[0]);
-        };
-
-        assert_eq!(library.title(), "Photos");
-
-        let mut m = mock_server.mock(|when, then| {
-            when.method(GET).path("/library/sections/3/all");
+        let providers = include_str!(
+            "../../plex-api-mock-server/mocks/server/media/providers_free.json"
+        )
+        .replace(
+            r#""title": "TV Shows","#,
+            r#""title": "TV Shows", "durationTotal": 93600000, "storageTotal": 52000000000,"#,
+        );
+        let providers_mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path(SERVER_MEDIA_PROVIDERS)
+                .query_param("includeStorage", "1");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body(providers);
+        });
+
+        let totals = library.totals().await.unwrap();
+        for mock in &count_mocks {
+            mock.assert();
+        }
+        providers_mock.assert();
+
+        assert_eq!(totals.count(SearchType::Show), 3);
+        assert_eq!(totals.count(SearchType::Season), 7);
+        assert_eq!(totals.count(SearchType::Episode), 52);
+        assert_eq!(totals.count(SearchType::Movie), 0);
+        assert_eq!(totals.size, Some(52_000_000_000));
+        assert_eq!(totals.duration, Some(Duration::from_secs(93600)));
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn recently_added(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+        let library = &server.libraries()[1];
+
+        let added_mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/sections/2/recentlyAdded")
+                .query_param("X-Plex-Container-Start", "0")
+                .query_param("X-Plex-Container-Size", "3");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/tv_episodes.json");
+        });
+
+        let items = library.recently_added(3).await.unwrap();
+        added_mock.assert();
+        assert_eq!(items.len(), 3);
+        assert!(items.iter().all(|item| matches!(item, Item::Episode(_))));
+
+        let newest_mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/sections/2/newest")
+                .query_param("X-Plex-Container-Start", "3")
+                .query_param("X-Plex-Container-Size", "3");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/tv_episodes.json");
+        });
+
+        let items = library.recently_released_page(3, 3).await.unwrap();
+        newest_mock.assert();
+        assert_eq!(items[0].metadata().library_section_id, Some(2));
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn library_folders(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+        let library = &server.libraries()[0];
+
+        let root_mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/sections/1/folder")
+                .query_param_missing("parent");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body(
+                    r#"{"MediaContainer": {
+                        "size": 2,
+                        "librarySectionID": 1,
+                        "title1": "Movies",
+                        "Directory": [
+                            {"key": "/library/sections/1/folder?parent=3", "title": "/data/movies"},
+                            {"key": "/library/sections/1/folder?parent=4", "title": "/data/more movies"}
+                        ]
+                    }}"#,
+                );
+        });
+
+        let root = library.folders().await.unwrap();
+        root_mock.assert();
+        assert_eq!(
+            map(&root.folders, |folder| folder.title().to_owned()),
+            vec!["/data/movies", "/data/more movies"]
+        );
+        assert!(root.items.is_empty());
+
+        let folder_mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/sections/1/folder")
+                .query_param("parent", "3");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("../plex-api-mock-server/mocks/server/media/movie_library.json");
+        });
+
+        let contents = root.folders[0].contents().await.unwrap();
+        folder_mock.assert();
+        assert!(contents.folders.is_empty());
+        assert_eq!(
+            map(&contents.items, |item| item.rating_key().to_owned()),
+            vec!["55", "56", "108", "57"]
+        );
+        assert!(contents
+            .items
+            .iter()
+            .all(|item| matches!(item, Item::Movie(_))));
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn library_tags(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+        let library = &server.libraries()[0];
+        let movie_library = if let Library::Movie(lib) = library {
+            lib
+        } else {
+            panic!("Unexpected library: {library:?}");
+        };
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/sections/1/all")
+                .query_param("type", "1");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("../plex-api-mock-server/mocks/server/media/movie_library.json");
+        });
+        let movies = movie_library.movies().await.unwrap();
+        m.assert();
+
+        let add_mock = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path("/library/sections/1/all")
+                .query_param("type", "1")
+                .query_param("id", "55,56,108,57")
+                .query_param("label[0].tag.tag", "4K")
+                .query_param("label[1].tag.tag", "HDR")
+                .query_param("label.locked", "1");
+            then.status(200);
+        });
+
+        library
+            .add_tags(TagField::Label, &movies, &["4K", "HDR"])
+            .await
+            .unwrap();
+        add_mock.assert();
+
+        let remove_mock = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path("/library/sections/1/all")
+                .query_param("type", "1")
+                .query_param("id", "55,108")
+                .query_param("genre[].tag.tag-", "Comedy,Drama");
+            then.status(200);
+        });
+
+        library
+            .remove_tags(
+                TagField::Genre,
+                &[movies[0].clone(), movies[2].clone()],
+                &["Comedy", "Drama"],
+            )
+            .await
+            .unwrap();
+        remove_mock.assert();
+
+        let tags_mock = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/sections/1/label");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body(
+                    r#"{"MediaContainer": {
+                        "size": 2,
+                        "Directory": [
+                            {"fastKey": "/library/sections/1/all?label=301", "key": "301", "title": "4K"},
+                            {"fastKey": "/library/sections/1/all?label=302", "key": "302", "title": "HDR"}
+                        ]
+                    }}"#,
+                );
+        });
+
+        let tags = library.tags(TagField::Label).await.unwrap();
+        tags_mock.assert();
+        assert_eq!(map(&tags, |tag| tag.title.clone()), vec!["4K", "HDR"]);
+        assert_eq!(tags[0].key, "301");
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn external_ids(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+        let library = &server.libraries()[0];
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/sections/1/all")
+                .query_param("includeGuids", "1");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("../plex-api-mock-server/mocks/server/media/movie_library.json");
+        });
+
+        let items = library
+            .find_by_external_id(&ExternalId::Imdb("tt0165832".to_owned()))
+            .await
+            .unwrap();
+        m.assert();
+        assert_eq!(
+            map(&items, |item| item.rating_key().to_owned()),
+            vec!["108"]
+        );
+        assert_eq!(
+            items[0].external_ids(),
+            vec![ExternalId::Imdb("tt0165832".to_owned())]
+        );
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/301");
+            then.status(200).header("content-type", "text/json").body(
+                r#"{"MediaContainer": {
+                        "size": 1,
+                        "Metadata": [{
+                            "ratingKey": "301",
+                            "key": "/library/metadata/301",
+                            "guid": "plex://movie/5d776830880197001ec90f5d",
+                            "type": "movie",
+                            "title": "Interstate 60",
+                            "Guid": [
+                                {"id": "imdb://tt0165832"},
+                                {"id": "tmdb://16275"},
+                                {"id": "tvdb://1431"}
+                            ]
+                        }]
+                    }}"#,
+            );
+        });
+
+        let item = server.item_by_id("301").await.unwrap();
+        m.assert();
+        assert_eq!(
+            item.external_ids(),
+            vec![
+                ExternalId::Imdb("tt0165832".to_owned()),
+                ExternalId::Tmdb(16275),
+                ExternalId::Tvdb(1431)
+            ]
+        );
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn photo_library(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let libraries = server.libraries();
+        let library = if let Library::Photo(lib) = &libraries[3] {
+            lib
+        } else {
+            panic!("Unexpected library: {:?}", libraries[0]);
+        };
+
+        assert_eq!(library.title(), "Photos");
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/sections/3/all");
             then.status(200)
                 .header("content-type", "text/json")
                 .body_from_file("tests/mocks/server/media/photo_library.json");
@@ -228,6 +1058,243 @@ mod offline {
         assert_eq!(map(&items, |e| e.rating_key().to_owned()), vec!["64", "59"]);
     }
 
+    #[plex_api_test_helper::offline_test]
+    async fn photo_timeline(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let libraries = server.libraries();
+        let library = if let Library::Photo(lib) = &libraries[3] {
+            lib
+        } else {
+            panic!("Unexpected library: {:?}", libraries[3]);
+        };
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/sections/3/all")
+                .query_param("type", "13")
+                .query_param("sort", "originallyAvailableAt:desc");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/photo_timeline.json");
+        });
+
+        let photos = library.timeline().await.unwrap();
+        m.assert();
+        m.delete();
+
+        assert_eq!(
+            map(&photos, |e| e.title().to_owned()),
+            vec!["Picture2", "Picture1"]
+        );
+        assert_eq!(photos[0].tags(), vec!["Paris", "Cats"]);
+        assert_eq!(
+            photos[0].taken_at(),
+            Some(time::Date::from_calendar_date(2021, time::Month::June, 2).unwrap())
+        );
+
+        let media = &photos[0].metadata().media.as_ref().unwrap()[0];
+        assert_eq!(media.make.as_deref(), Some("Apple"));
+        assert_eq!(media.model.as_deref(), Some("iPhone 12"));
+        assert_eq!(media.iso, Some(100));
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/photo/:/transcode")
+                .query_param("url", "/library/parts/51/1579515736/file.jpg")
+                .query_param("width", "640")
+                .query_param("height", "480");
+            then.status(200)
+                .header("content-type", "image/jpeg")
+                .body("foo");
+        });
+
+        let mut buf = Vec::<u8>::new();
+        photos[0]
+            .transcode(640, 480, Default::default(), &mut buf)
+            .await
+            .unwrap();
+        m.assert();
+        m.delete();
+
+        assert_eq!(buf, b"foo");
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn upload(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let libraries = server.libraries();
+        let library = &libraries[3];
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/library/sections/3/upload")
+                .query_param("filename", "IMG_0001.jpg")
+                .header("content-length", "11")
+                .body("not-a-jpeg!");
+            then.status(200);
+        });
+
+        let updates = Arc::new(Mutex::new(Vec::<Progress>::new()));
+        let observer = {
+            let updates = updates.clone();
+            move |progress| updates.lock().unwrap().push(progress)
+        };
+
+        library
+            .upload(
+                "IMG_0001.jpg",
+                futures::io::Cursor::new(b"not-a-jpeg!".to_vec()),
+                11,
+                observer,
+            )
+            .await
+            .unwrap();
+        m.assert();
+        m.delete();
+
+        let updates = updates.lock().unwrap();
+        let last = updates.last().unwrap();
+        assert_eq!(last.transferred, 11);
+        assert_eq!(last.total, Some(11));
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn resumed_download(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/108");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/metadata_108.json");
+        });
+
+        let movie: Movie = server.item_by_id("108").await.unwrap().try_into().unwrap();
+        m.assert();
+        m.delete();
+
+        // The first response ends early as if the connection dropped.
+        let mut m1 = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/parts/96/1579478991/file.mkv")
+                .query_param("download", "1")
+                .header("range", "bytes=0-19");
+            then.status(206).body("01234567");
+        });
+        let mut m2 = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/parts/96/1579478991/file.mkv")
+                .query_param("download", "1")
+                .header("range", "bytes=8-19");
+            then.status(206).body("89abcdefghij");
+        });
+
+        let updates = Arc::new(Mutex::new(Vec::<Progress>::new()));
+        let observer = {
+            let updates = updates.clone();
+            move |progress| updates.lock().unwrap().push(progress)
+        };
+
+        let media = movie.media();
+        let parts = media[0].parts();
+        let mut buf = Vec::<u8>::new();
+        parts[0]
+            .download_with(
+                &mut buf,
+                0..20,
+                DownloadOptions {
+                    max_retries: 1,
+                    retry_delay: Duration::ZERO,
+                    ..Default::default()
+                },
+                observer,
+            )
+            .await
+            .unwrap();
+        m1.assert();
+        m2.assert();
+
+        assert_eq!(buf, b"0123456789abcdefghij");
+        let last = *updates.lock().unwrap().last().unwrap();
+        assert_eq!(last.transferred, 20);
+        assert_eq!(last.total, Some(20));
+        assert!(last.rate >= 0.0);
+
+        // Without retries the truncated response is an error.
+        let mut buf = Vec::<u8>::new();
+        let result = parts[0]
+            .download_with(
+                &mut buf,
+                0..20,
+                DownloadOptions {
+                    max_retries: 0,
+                    retry_delay: Duration::ZERO,
+                    ..Default::default()
+                },
+                (),
+            )
+            .await;
+        assert!(result.is_err());
+        m2.delete();
+
+        // A server that ignores the range when resuming sends the whole file
+        // again, the bytes already written are skipped.
+        let mut m2 = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/parts/96/1579478991/file.mkv")
+                .query_param("download", "1")
+                .header("range", "bytes=8-19");
+            then.status(200).body("0123456789abcdefghijklmnop");
+        });
+
+        let mut buf = Vec::<u8>::new();
+        parts[0]
+            .download_with(
+                &mut buf,
+                0..20,
+                DownloadOptions {
+                    max_retries: 1,
+                    retry_delay: Duration::ZERO,
+                    ..Default::default()
+                },
+                (),
+            )
+            .await
+            .unwrap();
+        m1.delete();
+        m2.assert();
+        m2.delete();
+
+        assert_eq!(buf, b"0123456789abcdefghij");
+
+        // Empty ranges don't request anything, in parallel neither.
+        let m = mock_server.mock(|when, then| {
+            when.path("/library/parts/96/1579478991/file.mkv");
+            then.status(416);
+        });
+        let parallel = DownloadOptions {
+            connections: 2,
+            ..Default::default()
+        };
+        let backwards = (Bound::Included(10), Bound::Excluded(5));
+        let mut buf = Vec::<u8>::new();
+        parts[0].download(&mut buf, backwards).await.unwrap();
+        parts[0].download(&mut buf, ..0).await.unwrap();
+        parts[0].download(&mut buf, 13400382..).await.unwrap();
+        parts[0]
+            .download_with(&mut buf, backwards, parallel, ())
+            .await
+            .unwrap();
+        parts[0]
+            .download_with(&mut buf, 20000000..30000000, parallel, ())
+            .await
+            .unwrap();
+        assert!(buf.is_empty());
+        m.assert_calls(0);
+    }
+
     #[plex_api_test_helper::offline_test]
     async fn music_library(#[future] server_anonymous: Mocked<Server>) {
         let (server, mock_server) = server_anonymous.split();
@@ -319,6 +1386,544 @@ mod offline {
             map(&tracks, |e| e.rating_key().to_owned()),
             vec!["158", "159", "160"]
         );
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/156");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/music_library.json");
+        });
+
+        let artist = tracks[0].artist().await.unwrap().unwrap();
+        m.assert();
+        m.delete();
+        assert_eq!(artist.title(), "Skrillex");
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/156/allLeaves");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/music_tracks.json");
+        });
+
+        let tracks = artist.tracks().await.unwrap();
+        m.assert();
+        m.delete();
+        assert_eq!(tracks.len(), 3);
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/sections/5/all")
+                .query_param("type", "10")
+                .query_param("artist.id", "156")
+                .query_param("album.subformat!", "Compilation,Live")
+                .query_param("group", "title")
+                .query_param("sort", "ratingCount:desc");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/music_tracks.json");
+        });
+
+        let popular = artist.popular_tracks().await.unwrap();
+        m.assert();
+        assert_eq!(
+            map(&popular, |e| e.rating_key().to_owned()),
+            vec!["158", "159", "160"]
+        );
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn part_details(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/108");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/metadata_108.json");
+        });
+
+        let movie: Movie = server.item_by_id("108").await.unwrap().try_into().unwrap();
+        m.assert();
+
+        assert_eq!(movie.files(), vec!["/data/Movies/Interstate 60 (2002).mkv"]);
+
+        let media = movie.media();
+        let parts = media[0].parts();
+        let part = &parts[0];
+        assert_eq!(part.file(), Some("/data/Movies/Interstate 60 (2002).mkv"));
+        assert_eq!(part.len(), Some(13400382));
+        assert_eq!(part.container(), Some(ContainerFormat::Mkv));
+        assert!(!part.has_indexes());
+        assert_eq!(part.is_accessible(), None);
+        assert_eq!(part.streams().len(), 2);
+        assert_eq!(part.streams()[0].bitrate(), Some(21178));
+    }
+
+    /// Builds a BIF file with an image every 10 seconds.
+    fn bif(images: &[&[u8]]) -> Vec<u8> {
+        let mut data = vec![0x89, b'B', b'I', b'F', 0x0d, 0x0a, 0x1a, 0x0a];
+        data.extend(0_u32.to_le_bytes());
+        data.extend((images.len() as u32).to_le_bytes());
+        data.extend(10000_u32.to_le_bytes());
+        data.resize(64, 0);
+
+        let mut offset = 64 + (images.len() as u32 + 1) * 8;
+        for (index, image) in images.iter().enumerate() {
+            data.extend((index as u32).to_le_bytes());
+            data.extend(offset.to_le_bytes());
+            offset += image.len() as u32;
+        }
+        data.extend(u32::MAX.to_le_bytes());
+        data.extend(offset.to_le_bytes());
+
+        for image in images {
+            data.extend(*image);
+        }
+        data
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn preview_thumbnails(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let metadata = std::fs::read_to_string("tests/mocks/server/media/metadata_108.json")
+            .unwrap()
+            .replacen(
+                r#""key": "/library/parts/96/"#,
+                r#""indexes": "sd", "key": "/library/parts/96/"#,
+                1,
+            );
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/108");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body(metadata);
+        });
+
+        let movie: Movie = server.item_by_id("108").await.unwrap().try_into().unwrap();
+        m.assert();
+
+        let media = movie.media();
+        let parts = media[0].parts();
+        let part = &parts[0];
+        assert!(part.has_indexes());
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/parts/96/indexes/sd");
+            then.status(200).body(bif(&[b"first", b"second", b"third"]));
+        });
+
+        let thumbnails = part.preview_thumbnails().await.unwrap();
+        m.assert();
+
+        assert_eq!(thumbnails.len(), 3);
+        assert_eq!(thumbnails.at(Duration::from_secs(0)), Some(&b"first"[..]));
+        assert_eq!(thumbnails.at(Duration::from_secs(15)), Some(&b"second"[..]));
+        assert_eq!(thumbnails.at(Duration::from_secs(600)), Some(&b"third"[..]));
+        assert_eq!(
+            thumbnails.iter().map(|(time, _)| time).collect::<Vec<_>>(),
+            vec![
+                Duration::from_secs(0),
+                Duration::from_secs(10),
+                Duration::from_secs(20)
+            ]
+        );
+
+        let library = &server.libraries()[0];
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/sections/1/prefs");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/library_prefs.json");
+        });
+        assert!(!library.preview_thumbnails_enabled().await.unwrap());
+        m.assert();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path("/library/sections/1")
+                .query_param_exists("agent")
+                .query_param("prefs[enableBIFGeneration]", "1");
+            then.status(200);
+        });
+        library.set_preview_thumbnails_enabled(true).await.unwrap();
+        m.assert();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn agents_and_scanners(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/system/agents")
+                .query_param("mediaType", "1");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/system/agents_movie.json");
+        });
+        let agents = server.agents(SearchType::Movie).await.unwrap();
+        m.assert();
+
+        assert_eq!(
+            agents
+                .iter()
+                .filter(|agent| agent.primary)
+                .map(|agent| agent.identifier.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "tv.plex.agents.movie",
+                "tv.plex.agents.none",
+                "com.plexapp.agents.imdb"
+            ]
+        );
+        assert_eq!(agents[0].media_types[0].media_type, SearchType::Movie);
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/system/scanners/1");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/system/scanners_movie.json");
+        });
+        let scanners = server.scanners(SearchType::Movie).await.unwrap();
+        m.assert();
+
+        let library = &server.libraries()[0];
+        assert_eq!(library.agent(), "com.plexapp.agents.imdb");
+        assert!(scanners
+            .iter()
+            .any(|scanner| scanner.name == library.scanner()));
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/system/agents/com.plexapp.agents.imdb/config/1");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/system/agent_config_imdb.json");
+        });
+        let contributors = server
+            .agent_contributors(library.agent(), SearchType::Movie)
+            .await
+            .unwrap();
+        m.assert();
+
+        assert_eq!(contributors.len(), 2);
+        assert!(contributors[0].enabled);
+        assert!(!contributors[1].enabled);
+
+        let m = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path("/system/agents/com.plexapp.agents.imdb/config/1")
+                .query_param(
+                    "order",
+                    "com.plexapp.agents.themoviedb,com.plexapp.agents.localmedia",
+                );
+            then.status(200);
+        });
+        server
+            .set_agent_contributors_order(
+                library.agent(),
+                SearchType::Movie,
+                &[
+                    "com.plexapp.agents.themoviedb",
+                    "com.plexapp.agents.localmedia",
+                ],
+            )
+            .await
+            .unwrap();
+        m.assert();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path("/library/sections/1")
+                .query_param("agent", "tv.plex.agents.movie");
+            then.status(200);
+        });
+        library.set_agent("tv.plex.agents.movie").await.unwrap();
+        m.assert();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/sections/1/prefs");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/library_prefs.json");
+        });
+        let settings = library.settings().await.unwrap();
+        m.assert();
+        assert_eq!(settings.len(), 3);
+
+        let m = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path("/library/sections/1")
+                .query_param("agent", "com.plexapp.agents.imdb")
+                .query_param("prefs[collectionMode]", "2")
+                .query_param("prefs[enableCinemaTrailers]", "0");
+            then.status(200);
+        });
+        library
+            .set_settings(&[
+                ("collectionMode", Value::Int(2)),
+                ("enableCinemaTrailers", Value::Bool(false)),
+            ])
+            .await
+            .unwrap();
+        m.assert();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn optimized_versions(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+        let optimizer = server.optimizer();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/108");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/metadata_108.json");
+        });
+        let movie: Movie = server.item_by_id("108").await.unwrap().try_into().unwrap();
+        m.assert();
+
+        let playlists_mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/playlists")
+                .query_param("type", "42");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/playlists_optimized.json");
+        });
+        let queue_mock = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path("/playlists/190/items")
+                .query_param("Item[type]", "42")
+                .query_param("Item[targetTagID]", "1")
+                .query_param(
+                    "Item[Location][uri]",
+                    format!(
+                        "server://{}/com.plexapp.plugins.library/library/metadata/108",
+                        server.machine_identifier()
+                    ),
+                )
+                .query_param("Item[Policy][scope]", "all");
+            then.status(200);
+        });
+
+        optimizer
+            .queue(&movie, OptimizeTarget::Mobile)
+            .await
+            .unwrap();
+        playlists_mock.assert();
+        queue_mock.assert();
+
+        let items_mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/playlists/generators")
+                .query_param("type", "42");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body(
+                    r#"{"MediaContainer": {
+                        "size": 1,
+                        "Item": [{
+                            "id": 3,
+                            "title": "Optimized for Mobile",
+                            "target": "",
+                            "targetTagID": 1,
+                            "type": 42,
+                            "Location": {"uri": "server://machine_id/com.plexapp.plugins.library/library/metadata/108"},
+                            "Policy": {"scope": "all", "unwatched": false},
+                            "Status": {"state": "processing", "itemsCount": 1, "itemsCompleteCount": 0, "itemsSuccessfulCount": 0, "totalSize": 0}
+                        }]
+                    }}"#,
+                );
+        });
+
+        let item = optimizer.item(3).await.unwrap();
+        items_mock.assert();
+        assert_eq!(item.target(), Some(OptimizeTarget::Mobile));
+        let status = item.status.unwrap();
+        assert_eq!(status.items_count, 1);
+        assert_eq!(status.items_complete_count, 0);
+        assert!(matches!(optimizer.item(4).await, Err(Error::ItemNotFound)));
+
+        let contents_mock = mock_server.mock(|when, then| {
+            when.method(GET).path("/playlists/generators/3/items");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/metadata_108.json");
+        });
+
+        let contents = optimizer.contents(3).await.unwrap();
+        contents_mock.assert();
+        assert_eq!(
+            map(&contents, |item| item.rating_key().to_owned()),
+            vec!["108"]
+        );
+
+        let delete_mock = mock_server.mock(|when, then| {
+            when.method(DELETE).path("/playlists/generators/3");
+            then.status(200);
+        });
+
+        optimizer.delete(3).await.unwrap();
+        delete_mock.assert();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn show_settings(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/22");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/metadata_22.json");
+        });
+
+        let show: Show = server.item_by_id("22").await.unwrap().try_into().unwrap();
+        let settings = show.settings().await.unwrap();
+        m.assert_calls(2);
+        m.delete();
+
+        assert_eq!(
+            settings,
+            ItemSettings {
+                episode_sort: Some(EpisodeSort::NewestFirst),
+                seasons_display: Some(SeasonsDisplay::LibraryDefault),
+                keep_unwatched: Some(KeepUnwatched::AddedInLastDays(7)),
+                delete_watched: Some(DeleteWatched::AfterAWeek),
+                audio_language: Some(String::new()),
+                subtitle_language: Some(String::new()),
+            }
+        );
+
+        let m = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path("/library/metadata/22/prefs")
+                .query_param("autoDeletionItemPolicyUnwatchedLibrary", "5")
+                .query_param("autoDeletionItemPolicyWatchedLibrary", "0")
+                .query_param("subtitleLanguage", "en")
+                .query_param_missing("episodeSort");
+            then.status(200);
+        });
+
+        show.set_settings(&ItemSettings {
+            keep_unwatched: Some(KeepUnwatched::Latest(5)),
+            delete_watched: Some(DeleteWatched::Never),
+            subtitle_language: Some("en".to_owned()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        m.assert();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn items_by_guid(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/all")
+                .query_param("guid", "imdb://tt0165832");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/metadata_108.json");
+        });
+
+        let items = server
+            .items_by_guid(&ExternalId::Imdb("tt0165832".to_owned()).to_string())
+            .await
+            .unwrap();
+        m.assert();
+        assert_eq!(
+            map(&items, |item| item.rating_key().to_owned()),
+            vec!["108"]
+        );
+
+        let m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/all")
+                .query_param("guid", "plex://movie/5d776830880197001ec90f5d");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body(r#"{"MediaContainer": {"size": 0}}"#);
+        });
+
+        let items = server
+            .items_by_guid("plex://movie/5d776830880197001ec90f5d")
+            .await
+            .unwrap();
+        m.assert();
+        assert!(items.is_empty());
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn show_traversal(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let show_mock = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/22");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/metadata_22.json");
+        });
+        let seasons_mock = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/22/children");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/tv_seasons.json");
+        });
+        let episodes_mock = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/89/children");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/tv_episodes.json");
+        });
+
+        let show: Show = server.item_by_id("22").await.unwrap().try_into().unwrap();
+
+        let season = show.season_by_number(2).await.unwrap().unwrap();
+        assert_eq!(season.rating_key(), "33");
+        assert!(show.season_by_number(5).await.unwrap().is_none());
+
+        let season = show.season_by_number(1).await.unwrap().unwrap();
+        let episode = season.episode_by_number(2).await.unwrap().unwrap();
+        episodes_mock.assert();
+        seasons_mock.assert_calls(3);
+        assert_eq!(episode.title(), "Earth Skills");
+
+        let parent_show = episode.show().await.unwrap().unwrap();
+        show_mock.assert_calls(2);
+        assert_eq!(parent_show.title(), "The 100");
+
+        let episodes = include_str!("mocks/server/media/tv_episodes.json").replacen(
+            r#""size": 9,"#,
+            r#""size": 3, "totalSize": 6,"#,
+            1,
+        );
+        let page_mocks = ["0", "3"].map(|start| {
+            mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/library/metadata/22/allLeaves")
+                    .query_param("X-Plex-Container-Start", start)
+                    .query_param("X-Plex-Container-Size", "100");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body(&episodes);
+            })
+        });
+
+        let episodes: Vec<Episode> = show.all_episodes().try_collect().await.unwrap();
+        for m in page_mocks {
+            m.assert();
+        }
+        assert_eq!(
+            map(&episodes, |e| e.rating_key().to_owned()),
+            vec!["90", "91", "92", "90", "91", "92"]
+        );
     }
 
     #[plex_api_test_helper::offline_test]
@@ -364,6 +1969,55 @@ mod offline {
         m.assert();
         m.delete();
     }
+
+    #[plex_api_test_helper::offline_test]
+    async fn smart_collection(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+        let filter_uri = |filter: &str| {
+            format!(
+                "server://{}/com.plexapp.plugins.library/library/sections/1/all?type=1&{filter}",
+                server.machine_identifier()
+            )
+        };
+
+        let m = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/library/collections")
+                .query_param("type", "1")
+                .query_param("title", "Family Night")
+                .query_param("smart", "1")
+                .query_param("sectionId", "1")
+                .query_param("uri", filter_uri("contentRating=G&year>>=2000"));
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("tests/mocks/server/media/smart_collection.json");
+        });
+        let library = &server.libraries()[0];
+        let item = server
+            .create_smart_collection(library, "Family Night", "contentRating=G&year>>=2000")
+            .await
+            .unwrap();
+        m.assert();
+
+        let collection: Collection<Movie> = item.try_into().unwrap();
+        assert!(collection.is_smart());
+        assert_eq!(
+            collection.filter().as_deref(),
+            Some("contentRating=G&year>>=2000")
+        );
+
+        let m = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path("/library/collections/172/items")
+                .query_param("uri", filter_uri("contentRating=PG"));
+            then.status(200);
+        });
+        server
+            .set_smart_collection_filter(&collection, "contentRating=PG")
+            .await
+            .unwrap();
+        m.assert();
+    }
 }
 
 mod online {
@@ -3,7 +3,11 @@ mod fixtures;
 mod offline {
     use super::fixtures::offline::{server::*, Mocked};
     use httpmock::Method::{GET, PUT};
-    use plex_api::{url::SERVER_PREFS, Server};
+    use plex_api::{
+        url::{SERVER_MYPLEX_ACCOUNT, SERVER_PREFS},
+        Error, MappingState, Server, TranscoderSettings, X264Preset,
+    };
+    use std::time::Duration;
 
     #[plex_api_test_helper::offline_test]
     async fn load_prefs(#[future] server_anonymous: Mocked<Server>) {
@@ -45,6 +49,144 @@ mod offline {
 
         m.assert();
     }
+
+    #[plex_api_test_helper::offline_test]
+    async fn transcoder_settings(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let prefs_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_PREFS);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/prefs.json");
+        });
+
+        let mut settings = server.transcoder_settings().await.unwrap();
+        prefs_mock.assert();
+        assert_eq!(
+            settings,
+            TranscoderSettings {
+                hardware_acceleration: false,
+                hardware_encoding: true,
+                temp_directory: Some("/transcode".to_owned()),
+                throttle_buffer: Duration::from_secs(60),
+                background_preset: X264Preset::Slow,
+                tone_mapping: true,
+            }
+        );
+
+        settings.hardware_acceleration = true;
+        settings.temp_directory = None;
+        settings.throttle_buffer = Duration::from_secs(120);
+        settings.background_preset = X264Preset::VerySlow;
+        settings.tone_mapping = false;
+
+        let save_mock = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path(SERVER_PREFS)
+                .query_param("HardwareAcceleratedCodecs", "1")
+                .query_param("HardwareAcceleratedEncoders", "1")
+                .query_param("TranscoderTempDirectory", "")
+                .query_param("TranscoderThrottleBuffer", "120")
+                .query_param("TranscoderH264BackgroundPreset", "veryslow")
+                .query_param("TranscoderToneMapping", "0");
+            then.status(200).header("content-type", "text/json");
+        });
+        server.set_transcoder_settings(&settings).await.unwrap();
+        save_mock.assert();
+
+        settings.temp_directory = Some("transcode".to_owned());
+        assert!(matches!(
+            server.set_transcoder_settings(&settings).await,
+            Err(Error::InvalidSettingValue(_))
+        ));
+        settings.temp_directory = Some(r"D:\Transcode".to_owned());
+        settings.validate().unwrap();
+        settings.throttle_buffer = Duration::ZERO;
+        assert!(settings.validate().is_err());
+        save_mock.assert_calls(1);
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn remote_access(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let account_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_MYPLEX_ACCOUNT);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/myplex_account.json");
+        });
+
+        let mut prefs_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_PREFS);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/prefs.json");
+        });
+
+        let mut remote_access = server.remote_access().await.unwrap();
+        account_mock.assert();
+        prefs_mock.assert();
+
+        assert!(remote_access.is_enabled());
+        assert_eq!(remote_access.manual_port(), Some(443));
+        assert_eq!(remote_access.mapping_state(), MappingState::Failed);
+        assert_eq!(
+            remote_access.mapping_error(),
+            Some("Not reachable from outside your network")
+        );
+        assert!(remote_access.is_signed_in());
+        assert_eq!(remote_access.public_address(), Some("1.0.0.2"));
+        assert_eq!(remote_access.public_port(), Some(443));
+        assert_eq!(remote_access.private_port(), Some(32400));
+
+        let port_mock = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path(SERVER_PREFS)
+                .query_param("ManualPortMappingMode", "1")
+                .query_param("ManualPortMappingPort", "32401");
+            then.status(200).header("content-type", "text/json");
+        });
+
+        remote_access.set_manual_port(Some(32401)).await.unwrap();
+        port_mock.assert();
+        account_mock.assert_calls(2);
+
+        prefs_mock.delete();
+        let prefs = include_str!("mocks/server/prefs.json").replace(
+            r#""id": "PublishServerOnPlexOnlineKey",
+                "label": "Publish server on Plex Online",
+                "summary": "Publishing a server makes it automatically available on your client devices without any configuration of your router.",
+                "type": "bool",
+                "default": false,
+                "value": true"#,
+            r#""id": "PublishServerOnPlexOnlineKey",
+                "label": "Publish server on Plex Online",
+                "summary": "Publishing a server makes it automatically available on your client devices without any configuration of your router.",
+                "type": "bool",
+                "default": false,
+                "value": false"#,
+        );
+        let prefs_mock = mock_server.mock(|when, then| {
+            when.method(GET).path(SERVER_PREFS);
+            then.status(200)
+                .header("content-type", "text/json")
+                .body(prefs);
+        });
+
+        let disable_mock = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path(SERVER_PREFS)
+                .query_param("PublishServerOnPlexOnlineKey", "0");
+            then.status(200).header("content-type", "text/json");
+        });
+
+        remote_access.disable().await.unwrap();
+        disable_mock.assert();
+        prefs_mock.assert();
+        assert!(!remote_access.is_enabled());
+    }
 }
 
 mod online {
@@ -0,0 +1,110 @@
+mod fixtures;
+
+mod offline {
+    use super::fixtures::offline::{server::*, Mocked};
+
+    use httpmock::Method::{DELETE, GET, PUT};
+    use plex_api::{
+        library::{MediaItem, Movie, SubtitleSearchOptions},
+        media_container::server::library::Stream,
+        Error, Server,
+    };
+
+    #[plex_api_test_helper::offline_test]
+    async fn search_and_download(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/108");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/server/media/metadata_108.json");
+        });
+
+        let movie: Movie = server.item_by_id("108").await.unwrap().try_into().unwrap();
+        m.assert();
+        m.delete();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/library/metadata/108/subtitles")
+                .query_param("language", "en")
+                .query_param("hearingImpaired", "0")
+                .query_param("forced", "0");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/subtitles/search_108.json");
+        });
+
+        let results = movie
+            .search_subtitles("en", SubtitleSearchOptions::default())
+            .await
+            .unwrap();
+        m.assert();
+        m.delete();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].key, "/library/streams/opensubtitles/1954729301");
+        assert_eq!(results[0].provider_title.as_deref(), Some("OpenSubtitles"));
+        assert_eq!(results[1].hearing_impaired, Some(true));
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(PUT)
+                .path("/library/metadata/108/subtitles")
+                .query_param("key", "/library/streams/opensubtitles/1954729301")
+                .query_param("codec", "srt")
+                .query_param("language", "eng")
+                .query_param("hearingImpaired", "0")
+                .query_param("forced", "0")
+                .query_param("providerTitle", "OpenSubtitles");
+            then.status(200);
+        });
+
+        movie.download_subtitle(&results[0]).await.unwrap();
+        m.assert();
+        m.delete();
+    }
+
+    #[plex_api_test_helper::offline_test]
+    async fn delete(#[future] server_anonymous: Mocked<Server>) {
+        let (server, mock_server) = server_anonymous.split();
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(GET).path("/library/metadata/108");
+            then.status(200)
+                .header("content-type", "text/json")
+                .body_from_file("tests/mocks/subtitles/metadata_108.json");
+        });
+
+        let movie: Movie = server.item_by_id("108").await.unwrap().try_into().unwrap();
+        m.assert();
+        m.delete();
+
+        let media = movie.media();
+        let parts = media[0].parts();
+        let subtitle = parts[0]
+            .metadata()
+            .streams
+            .iter()
+            .flatten()
+            .find_map(|s| match s {
+                Stream::Subtitle(s) => Some(s.clone()),
+                _ => None,
+            })
+            .expect("subtitle stream is missing");
+
+        let mut m = mock_server.mock(|when, then| {
+            when.method(DELETE).path("/library/streams/3921");
+            then.status(200);
+        });
+
+        movie.delete_subtitle(&subtitle).await.unwrap();
+        m.assert();
+        m.delete();
+
+        let mut embedded = subtitle.clone();
+        embedded.key = None;
+        let result = movie.delete_subtitle(&embedded).await;
+        assert!(matches!(result, Err(Error::SubtitleStreamNotExternal)));
+    }
+}
@@ -225,11 +225,17 @@ mod offline {
 
     mod movie {
         use super::*;
+        use futures::TryStreamExt;
         use plex_api::{
-            library::{MediaItem, Movie, Transcodable},
+            library::{AudioTranscodable, MediaItem, Movie, Playback, Transcodable},
             media_container::server::library::SubtitleCodec,
-            transcode::{AudioSetting, Constraint, VideoSetting, VideoTranscodeOptions},
+            transcode::{
+                AdaptiveQuality, AudioSetting, Constraint, DynamicRange, MusicTranscodeOptions,
+                Quality, SessionOptions, SubtitleMode, TranscodePriority, VideoSetting,
+                VideoTranscodeOptions,
+            },
         };
+        use std::time::Duration;
 
         #[plex_api_test_helper::offline_test]
         async fn transcode_profile_params(#[future] server_authenticated: Mocked<Server>) {
@@ -239,7 +245,7 @@ mod offline {
                 when.method(GET).path("/library/metadata/159637");
                 then.status(200)
                     .header("content-type", "text/json")
-                    .body_from_file("tests/mocks/transcode/metadata_159637.json");
+                    .body_from_file("../plex-api-mock-server/mocks/transcode/metadata_159637.json");
             });
 
             let item: Movie = server
@@ -301,7 +307,9 @@ mod offline {
                     });
                 then.status(200)
                     .header("content-type", "text/json")
-                    .body_from_file("tests/mocks/transcode/video_dash_h264_mp3.json");
+                    .body_from_file(
+                        "../plex-api-mock-server/mocks/transcode/video_dash_h264_mp3.json",
+                    );
             });
 
             part.create_streaming_session(
@@ -310,7 +318,7 @@ mod offline {
                     bitrate: 2000,
                     width: 1280,
                     height: 720,
-                    burn_subtitles: true,
+                    subtitles: SubtitleMode::Burn,
                     video_codecs: vec![VideoCodec::H264],
                     audio_codecs: vec![AudioCodec::Aac, AudioCodec::Mp3],
                     ..Default::default()
@@ -319,6 +327,23 @@ mod offline {
             .await
             .unwrap();
             m.assert();
+
+            #[allow(deprecated)]
+            part.create_streaming_session(
+                Protocol::Dash,
+                VideoTranscodeOptions {
+                    bitrate: 2000,
+                    width: 1280,
+                    height: 720,
+                    burn_subtitles: true,
+                    video_codecs: vec![VideoCodec::H264],
+                    audio_codecs: vec![AudioCodec::Aac, AudioCodec::Mp3],
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            m.assert_calls(2);
             m.delete();
 
             let media = &item.media()[1];
@@ -523,7 +548,7 @@ mod offline {
                 bitrate: 2000,
                 width: 1280,
                 height: 720,
-                burn_subtitles: true,
+                subtitles: SubtitleMode::Burn,
                 video_codecs: vec![VideoCodec::H264],
                 audio_codecs: vec![AudioCodec::Aac],
                 ..Default::default()
@@ -614,7 +639,7 @@ mod offline {
                     bitrate: 2000,
                     width: 1280,
                     height: 720,
-                    burn_subtitles: true,
+                    subtitles: SubtitleMode::Burn,
                     video_codecs: vec![VideoCodec::H264],
                     audio_codecs: vec![AudioCodec::Aac],
                     ..Default::default()
@@ -704,7 +729,7 @@ mod offline {
                 bitrate: 2000,
                 width: 1280,
                 height: 720,
-                burn_subtitles: true,
+                subtitles: SubtitleMode::Burn,
                 video_codecs: vec![VideoCodec::H264],
                 audio_codecs: vec![AudioCodec::Aac],
                 ..Default::default()
@@ -716,14 +741,865 @@ mod offline {
         }
 
         #[plex_api_test_helper::offline_test]
-        async fn transcode_decision(#[future] server_authenticated: Mocked<Server>) {
+        async fn transcode_decision(#[future] server_authenticated: Mocked<Server>) {
+            let (server, mock_server) = server_authenticated.split();
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET).path("/library/metadata/159637");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("../plex-api-mock-server/mocks/transcode/metadata_159637.json");
+            });
+
+            let item: Movie = server
+                .item_by_id("159637")
+                .await
+                .unwrap()
+                .try_into()
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            let media = &item.media()[0];
+            let part = &media.parts()[0];
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file(
+                        "../plex-api-mock-server/mocks/transcode/video_dash_h264_mp3.json",
+                    );
+            });
+
+            let session = part
+                .create_streaming_session(Protocol::Dash, VideoTranscodeOptions::default())
+                .await
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            assert!(!session.is_offline());
+            assert_eq!(session.container(), ContainerFormat::Mp4);
+            assert_eq!(session.protocol(), Protocol::Dash);
+            assert_eq!(
+                session.audio_transcode(),
+                Some((Decision::Transcode, AudioCodec::Mp3))
+            );
+            assert_eq!(
+                session.video_transcode(),
+                Some((Decision::Transcode, VideoCodec::H264))
+            );
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("tests/mocks/transcode/video_dash_h265_aac.json");
+            });
+
+            let session = part
+                .create_streaming_session(Protocol::Dash, VideoTranscodeOptions::default())
+                .await
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            assert!(!session.is_offline());
+            assert_eq!(session.container(), ContainerFormat::Mp4);
+            assert_eq!(session.protocol(), Protocol::Dash);
+            assert_eq!(
+                session.audio_transcode(),
+                Some((Decision::Transcode, AudioCodec::Aac))
+            );
+            assert_eq!(
+                session.video_transcode(),
+                Some((Decision::Copy, VideoCodec::Hevc))
+            );
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("tests/mocks/transcode/video_hls_vp9_pcm.json");
+            });
+
+            let session = part
+                .create_streaming_session(Protocol::Hls, VideoTranscodeOptions::default())
+                .await
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            assert!(!session.is_offline());
+            assert_eq!(session.container(), ContainerFormat::MpegTs);
+            assert_eq!(session.protocol(), Protocol::Hls);
+            assert_eq!(
+                session.audio_transcode(),
+                Some((Decision::Copy, AudioCodec::Pcm))
+            );
+            assert_eq!(
+                session.video_transcode(),
+                Some((Decision::Transcode, VideoCodec::Vp9))
+            );
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("tests/mocks/transcode/video_hls_vp9_pcm.json");
+            });
+
+            let error = part
+                .create_streaming_session(Protocol::Dash, VideoTranscodeOptions::default())
+                .await
+                .err()
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            if let plex_api::Error::TranscodeError(message) = error {
+                assert_eq!(message, "Server returned an invalid protocol.");
+            } else {
+                panic!("Unexpected error {error}");
+            }
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file(
+                        "../plex-api-mock-server/mocks/transcode/video_dash_h264_mp3.json",
+                    );
+            });
+
+            let error = part
+                .create_streaming_session(Protocol::Hls, VideoTranscodeOptions::default())
+                .await
+                .err()
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            if let plex_api::Error::TranscodeError(message) = error {
+                assert_eq!(message, "Server returned an invalid protocol.");
+            } else {
+                panic!("Unexpected error {error}");
+            }
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("tests/mocks/transcode/video_offline_h264_mp3.json");
+            });
+
+            let session = part
+                .create_download_session(VideoTranscodeOptions::default())
+                .await
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            assert!(session.is_offline());
+            assert_eq!(session.container(), ContainerFormat::Mp4);
+            assert_eq!(session.protocol(), Protocol::Http);
+            assert_eq!(
+                session.audio_transcode(),
+                Some((Decision::Transcode, AudioCodec::Mp3))
+            );
+            assert_eq!(
+                session.video_transcode(),
+                Some((Decision::Transcode, VideoCodec::H264))
+            );
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET).path("/library/metadata/1036");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("tests/mocks/transcode/metadata_1036.json");
+            });
+
+            let item: Movie = server.item_by_id("1036").await.unwrap().try_into().unwrap();
+            m.assert();
+            m.delete();
+
+            let media = &item.media()[0];
+            let part = &media.parts()[0];
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("tests/mocks/transcode/video_offline_refused.json");
+            });
+
+            let error = part
+                .create_download_session(VideoTranscodeOptions::default())
+                .await
+                .err()
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            assert!(matches!(error, plex_api::Error::TranscodeRefused));
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET).path("/library/metadata/13194");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("tests/mocks/transcode/metadata_13194.json");
+            });
+
+            let item: Movie = server
+                .item_by_id("13194")
+                .await
+                .unwrap()
+                .try_into()
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            let media = &item.media()[0];
+            let part = &media.parts()[0];
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("tests/mocks/transcode/decision_13194.json");
+            });
+
+            let error = part
+                .create_download_session(VideoTranscodeOptions::default())
+                .await
+                .err()
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            assert!(matches!(error, plex_api::Error::TranscodeRefused));
+        }
+
+        #[plex_api_test_helper::offline_test]
+        async fn stream_playback(#[future] server_authenticated: Mocked<Server>) {
+            let (server, mock_server) = server_authenticated.split();
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET).path("/library/metadata/1036");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("tests/mocks/transcode/metadata_1036.json");
+            });
+
+            let item: Movie = server.item_by_id("1036").await.unwrap().try_into().unwrap();
+            m.assert();
+            m.delete();
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision")
+                    .query_param("protocol", "dash");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("tests/mocks/transcode/video_offline_refused.json");
+            });
+
+            let playback = item
+                .stream(Protocol::Dash, VideoTranscodeOptions::default())
+                .await
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            let Playback::DirectPlay(url) = playback else {
+                panic!("Expected direct play");
+            };
+            assert_eq!(url.path(), "/library/parts/320566/1677272892/file.mp4");
+            assert_eq!(url.query(), Some("X-Plex-Token=fixture_auth_token"));
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET).path("/library/metadata/159637");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("../plex-api-mock-server/mocks/transcode/metadata_159637.json");
+            });
+
+            let item: Movie = server
+                .item_by_id("159637")
+                .await
+                .unwrap()
+                .try_into()
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file(
+                        "../plex-api-mock-server/mocks/transcode/video_dash_h264_mp3.json",
+                    );
+            });
+
+            let playback = item
+                .stream(Protocol::Dash, VideoTranscodeOptions::default())
+                .await
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            let Playback::Transcode(session) = playback else {
+                panic!("Expected a transcode");
+            };
+            assert!(!session.is_offline());
+            assert_eq!(session.protocol(), Protocol::Dash);
+        }
+
+        #[plex_api_test_helper::offline_test]
+        async fn hls_playlists(#[future] server_authenticated: Mocked<Server>) {
+            let (server, mock_server) = server_authenticated.split();
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET).path("/library/metadata/159637");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("../plex-api-mock-server/mocks/transcode/metadata_159637.json");
+            });
+
+            let item: Movie = server
+                .item_by_id("159637")
+                .await
+                .unwrap()
+                .try_into()
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            let media = &item.media()[0];
+            let part = &media.parts()[0];
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("tests/mocks/transcode/video_hls_vp9_pcm.json");
+            });
+
+            let session = part
+                .create_streaming_session(Protocol::Hls, VideoTranscodeOptions::default())
+                .await
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/start.m3u8");
+                then.status(200)
+                    .header("content-type", "application/vnd.apple.mpegurl")
+                    .body_from_file("tests/mocks/transcode/hls/start.m3u8");
+            });
+
+            let master = session.hls_master_playlist().await.unwrap();
+            m.assert();
+            m.delete();
+
+            assert_eq!(master.variants.len(), 1);
+            let variant = &master.variants[0];
+            assert_eq!(
+                variant.uri,
+                "/video/:/transcode/universal/session/a1b2c3d4/base/index.m3u8"
+            );
+            assert_eq!(variant.bandwidth, 2275000);
+            assert_eq!(variant.resolution, Some((1280, 720)));
+            assert_eq!(variant.codecs.as_deref(), Some("avc1.640028,mp4a.40.2"));
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/session/a1b2c3d4/base/index.m3u8");
+                then.status(200)
+                    .header("content-type", "application/vnd.apple.mpegurl")
+                    .body_from_file("tests/mocks/transcode/hls/index.m3u8");
+            });
+
+            let playlist = session.hls_media_playlist(variant).await.unwrap();
+            m.assert();
+            m.delete();
+
+            assert!(playlist.ended);
+            assert_eq!(playlist.target_duration, 5);
+            assert_eq!(playlist.segments.len(), 2);
+            assert_eq!(playlist.segments[1].duration, 2.502502);
+
+            let segments = ["00000", "00001"].map(|name| {
+                mock_server.mock(|when, then| {
+                    when.method(GET).path(format!(
+                        "/video/:/transcode/universal/session/a1b2c3d4/base/{name}.ts"
+                    ));
+                    then.status(200).body(name);
+                })
+            });
+
+            let data: Vec<Vec<u8>> = session.hls_segments(&playlist).try_collect().await.unwrap();
+            for mut m in segments {
+                m.assert();
+                m.delete();
+            }
+
+            assert_eq!(data, vec![b"00000".to_vec(), b"00001".to_vec()]);
+        }
+
+        #[plex_api_test_helper::offline_test]
+        async fn dash_manifest(#[future] server_authenticated: Mocked<Server>) {
+            let (server, mock_server) = server_authenticated.split();
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET).path("/library/metadata/159637");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("../plex-api-mock-server/mocks/transcode/metadata_159637.json");
+            });
+
+            let item: Movie = server
+                .item_by_id("159637")
+                .await
+                .unwrap()
+                .try_into()
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            let media = &item.media()[0];
+            let part = &media.parts()[0];
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file(
+                        "../plex-api-mock-server/mocks/transcode/video_dash_h264_mp3.json",
+                    );
+            });
+
+            let session = part
+                .create_streaming_session(Protocol::Dash, VideoTranscodeOptions::default())
+                .await
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/start.mpd");
+                then.status(200)
+                    .header("content-type", "application/dash+xml")
+                    .body_from_file("tests/mocks/transcode/dash/start.mpd");
+            });
+
+            let manifest = session.dash_manifest().await.unwrap();
+            m.assert();
+            m.delete();
+
+            assert_eq!(
+                manifest.media_presentation_duration,
+                Some(Duration::from_millis(11500))
+            );
+            assert_eq!(manifest.periods.len(), 1);
+            let period = &manifest.periods[0];
+            assert_eq!(period.adaptation_sets.len(), 2);
+
+            let video = &period.adaptation_sets[0];
+            let representation = &video.representations[0];
+            assert_eq!(representation.bandwidth, Some(2000000));
+            assert_eq!(representation.width, Some(1280));
+            let segments = period
+                .segments(video, representation, manifest.media_presentation_duration)
+                .unwrap();
+            assert_eq!(
+                segments.initialization.as_deref(),
+                Some("/video/:/transcode/universal/session/a1b2c3d4/0/header")
+            );
+            assert_eq!(
+                segments.media,
+                vec![
+                    "/video/:/transcode/universal/session/a1b2c3d4/0/0.m4s",
+                    "/video/:/transcode/universal/session/a1b2c3d4/0/1.m4s",
+                    "/video/:/transcode/universal/session/a1b2c3d4/0/2.m4s",
+                ]
+            );
+
+            let audio = &period.adaptation_sets[1];
+            assert_eq!(audio.lang.as_deref(), Some("eng"));
+            let representation = &audio.representations[0];
+            assert_eq!(representation.audio_sampling_rate, Some(48000));
+            let segments = period
+                .segments(audio, representation, manifest.media_presentation_duration)
+                .unwrap();
+            assert_eq!(
+                segments.media,
+                vec![
+                    "/video/:/transcode/universal/session/a1b2c3d4/1/00001.m4s",
+                    "/video/:/transcode/universal/session/a1b2c3d4/1/00002.m4s",
+                    "/video/:/transcode/universal/session/a1b2c3d4/1/00003.m4s",
+                ]
+            );
+
+            let mocks = ["header", "00001.m4s", "00002.m4s", "00003.m4s"].map(|name| {
+                mock_server.mock(|when, then| {
+                    when.method(GET).path(format!(
+                        "/video/:/transcode/universal/session/a1b2c3d4/1/{name}"
+                    ));
+                    then.status(200).body(name);
+                })
+            });
+
+            let data: Vec<Vec<u8>> = session
+                .dash_segments(&segments)
+                .try_collect()
+                .await
+                .unwrap();
+            for mut m in mocks {
+                m.assert();
+                m.delete();
+            }
+
+            assert_eq!(data[0], b"header");
+            assert_eq!(data[3], b"00003.m4s");
+        }
+
+        #[plex_api_test_helper::offline_test]
+        async fn advanced_video_options(#[future] server_authenticated: Mocked<Server>) {
+            let (server, mock_server) = server_authenticated.split();
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET).path("/library/metadata/159637");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("../plex-api-mock-server/mocks/transcode/metadata_159637.json");
+            });
+
+            let item: Movie = server
+                .item_by_id("159637")
+                .await
+                .unwrap()
+                .try_into()
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            let media = &item.media()[0];
+            let part = &media.parts()[0];
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision")
+                    .query_param("subtitles", "sidecar")
+                    .query_param("audioBoost", "50")
+                    .query_param_exists("X-Plex-Client-Profile-Extra")
+                    .is_true(|req| {
+                        let settings = expand_profile(req);
+
+                        assert_setting_count(&settings, "add-limitation", 4);
+
+                        assert_setting(
+                            &settings,
+                            "add-limitation",
+                            &[
+                                ("scope", "videoCodec"),
+                                ("scopeName", "*"),
+                                ("type", "upperBound"),
+                                ("name", "video.bitDepth"),
+                                ("value", "8"),
+                            ],
+                        );
+
+                        assert_setting(
+                            &settings,
+                            "add-limitation",
+                            &[
+                                ("scope", "videoCodec"),
+                                ("scopeName", "*"),
+                                ("type", "notMatch"),
+                                ("name", "video.colorTrc"),
+                                ("value", "smpte2084"),
+                            ],
+                        );
+
+                        assert_setting(
+                            &settings,
+                            "add-limitation",
+                            &[
+                                ("scope", "videoCodec"),
+                                ("scopeName", "*"),
+                                ("type", "notMatch"),
+                                ("name", "video.colorTrc"),
+                                ("value", "arib-std-b67"),
+                            ],
+                        );
+
+                        assert_setting(
+                            &settings,
+                            "add-limitation",
+                            &[
+                                ("scope", "videoCodec"),
+                                ("scopeName", "*"),
+                                ("type", "upperBound"),
+                                ("name", "video.frameRate"),
+                                ("value", "30"),
+                            ],
+                        );
+
+                        true
+                    });
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file(
+                        "../plex-api-mock-server/mocks/transcode/video_dash_h264_mp3.json",
+                    );
+            });
+
+            part.create_streaming_session(
+                Protocol::Dash,
+                VideoTranscodeOptions {
+                    audio_boost: Some(50),
+                    subtitles: SubtitleMode::Sidecar,
+                    dynamic_range: Some(DynamicRange::Sdr),
+                    max_frame_rate: Some(30),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            m.assert();
+            m.delete();
+        }
+
+        #[plex_api_test_helper::offline_test]
+        async fn adaptive_quality(#[future] server_authenticated: Mocked<Server>) {
+            let (server, mock_server) = server_authenticated.split();
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET).path("/library/metadata/159637");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("../plex-api-mock-server/mocks/transcode/metadata_159637.json");
+            });
+
+            let item: Movie = server
+                .item_by_id("159637")
+                .await
+                .unwrap()
+                .try_into()
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            let media = &item.media()[0];
+            let part = &media.parts()[0];
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision")
+                    .query_param("maxVideoBitrate", "4000")
+                    .query_param("autoAdjustQuality", "1")
+                    .query_param("hasMDE", "1")
+                    .query_param("peakBitrate", "8000")
+                    .query_param("mediaBufferSize", "102400");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file(
+                        "../plex-api-mock-server/mocks/transcode/video_dash_h264_mp3.json",
+                    );
+            });
+
+            let mut session = part
+                .create_streaming_session(
+                    Protocol::Dash,
+                    VideoTranscodeOptions {
+                        adaptive_quality: Some(AdaptiveQuality {
+                            peak_bitrate: Some(8000),
+                            media_buffer_size: Some(102400),
+                        }),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision")
+                    .query_param("session", session.session_id())
+                    .query_param("path", "/library/metadata/159637")
+                    .query_param("maxVideoBitrate", "1500")
+                    .query_param("videoBitrate", "1500")
+                    .query_param("autoAdjustQuality", "1");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file(
+                        "../plex-api-mock-server/mocks/transcode/video_dash_h264_mp3.json",
+                    );
+            });
+
+            session.set_bitrate(1500).await.unwrap();
+            m.assert();
+            m.delete();
+
+            // Later requests carry the new bitrate.
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/start.mpd")
+                    .query_param("maxVideoBitrate", "1500");
+                then.status(200)
+                    .header("content-type", "application/dash+xml")
+                    .body_from_file("tests/mocks/transcode/dash/start.mpd");
+            });
+
+            session.dash_manifest().await.unwrap();
+            m.assert();
+            m.delete();
+        }
+
+        #[plex_api_test_helper::offline_test]
+        async fn seek(#[future] server_authenticated: Mocked<Server>) {
+            let (server, mock_server) = server_authenticated.split();
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET).path("/library/metadata/159637");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("../plex-api-mock-server/mocks/transcode/metadata_159637.json");
+            });
+
+            let item: Movie = server
+                .item_by_id("159637")
+                .await
+                .unwrap()
+                .try_into()
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file(
+                        "../plex-api-mock-server/mocks/transcode/video_dash_h264_mp3.json",
+                    );
+            });
+
+            let mut session = item
+                .create_streaming_session(Protocol::Dash, VideoTranscodeOptions::default())
+                .await
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision")
+                    .query_param("session", session.session_id())
+                    .query_param("path", "/library/metadata/159637")
+                    .query_param("offset", "90.500");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file(
+                        "../plex-api-mock-server/mocks/transcode/video_dash_h264_mp3.json",
+                    );
+            });
+
+            session.seek(Duration::from_millis(90500)).await.unwrap();
+            m.assert();
+            m.delete();
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/start.mpd")
+                    .query_param("offset", "90.500");
+                then.status(200)
+                    .header("content-type", "application/dash+xml")
+                    .body_from_file("tests/mocks/transcode/dash/start.mpd");
+            });
+
+            session.dash_manifest().await.unwrap();
+            m.assert();
+            m.delete();
+        }
+
+        #[plex_api_test_helper::offline_test]
+        async fn quality_presets(#[future] server_authenticated: Mocked<Server>) {
+            let (server, mock_server) = server_authenticated.split();
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET).path("/library/metadata/159637");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file("../plex-api-mock-server/mocks/transcode/metadata_159637.json");
+            });
+
+            let item: Movie = server
+                .item_by_id("159637")
+                .await
+                .unwrap()
+                .try_into()
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/decision")
+                    .query_param("maxVideoBitrate", "10000")
+                    .query_param("videoResolution", "1920x1080");
+                then.status(200)
+                    .header("content-type", "text/json")
+                    .body_from_file(
+                        "../plex-api-mock-server/mocks/transcode/video_dash_h264_mp3.json",
+                    );
+            });
+
+            item.create_streaming_session(Protocol::Dash, Quality::P1080_10Mbps.into())
+                .await
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            let options = VideoTranscodeOptions {
+                video_codecs: vec![VideoCodec::H264, VideoCodec::Hevc],
+                ..Default::default()
+            }
+            .with_quality(Quality::P480_1_5Mbps);
+            assert_eq!(options.bitrate, 1500);
+            assert_eq!((options.width, options.height), (720, 480));
+            assert_eq!(options.video_codecs.len(), 2);
+        }
+
+        #[plex_api_test_helper::offline_test]
+        async fn session_options(#[future] server_authenticated: Mocked<Server>) {
             let (server, mock_server) = server_authenticated.split();
 
             let mut m = mock_server.mock(|when, then| {
                 when.method(GET).path("/library/metadata/159637");
                 then.status(200)
                     .header("content-type", "text/json")
-                    .body_from_file("tests/mocks/transcode/metadata_159637.json");
+                    .body_from_file("../plex-api-mock-server/mocks/transcode/metadata_159637.json");
             });
 
             let item: Movie = server
@@ -735,202 +1611,231 @@ mod offline {
             m.assert();
             m.delete();
 
-            let media = &item.media()[0];
-            let part = &media.parts()[0];
-
             let mut m = mock_server.mock(|when, then| {
                 when.method(GET)
-                    .path("/video/:/transcode/universal/decision");
+                    .path("/video/:/transcode/universal/decision")
+                    .query_param("offlineTranscode", "1")
+                    .query_param("priority", "background")
+                    .query_param("throttle", "1")
+                    .query_param(
+                        "X-Plex-Client-Capabilities",
+                        "protocols=http-video;videoDecoders=h264;audioDecoders=aac",
+                    );
                 then.status(200)
                     .header("content-type", "text/json")
-                    .body_from_file("tests/mocks/transcode/video_dash_h264_mp3.json");
+                    .body_from_file("tests/mocks/transcode/video_offline_h264_mp3.json");
             });
 
-            let session = part
-                .create_streaming_session(Protocol::Dash, VideoTranscodeOptions::default())
-                .await
-                .unwrap();
+            item.create_download_session(VideoTranscodeOptions {
+                session: SessionOptions {
+                    priority: TranscodePriority::Background,
+                    throttle: Some(true),
+                    client_capabilities: Some(
+                        "protocols=http-video;videoDecoders=h264;audioDecoders=aac".to_owned(),
+                    ),
+                },
+                ..Default::default()
+            })
+            .await
+            .unwrap();
             m.assert();
             m.delete();
 
-            assert!(!session.is_offline());
-            assert_eq!(session.container(), ContainerFormat::Mp4);
-            assert_eq!(session.protocol(), Protocol::Dash);
-            assert_eq!(
-                session.audio_transcode(),
-                Some((Decision::Transcode, AudioCodec::Mp3))
-            );
-            assert_eq!(
-                session.video_transcode(),
-                Some((Decision::Transcode, VideoCodec::H264))
-            );
-
-            let mut m = mock_server.mock(|when, then| {
+            let m = mock_server.mock(|when, then| {
                 when.method(GET)
-                    .path("/video/:/transcode/universal/decision");
+                    .path("/video/:/transcode/universal/decision")
+                    .query_param_missing("priority")
+                    .query_param_missing("throttle")
+                    .query_param_missing("X-Plex-Client-Capabilities");
                 then.status(200)
                     .header("content-type", "text/json")
-                    .body_from_file("tests/mocks/transcode/video_dash_h265_aac.json");
+                    .body_from_file(
+                        "../plex-api-mock-server/mocks/transcode/video_dash_h264_mp3.json",
+                    );
             });
 
-            let session = part
-                .create_streaming_session(Protocol::Dash, VideoTranscodeOptions::default())
+            item.create_streaming_session(Protocol::Dash, VideoTranscodeOptions::default())
                 .await
                 .unwrap();
             m.assert();
-            m.delete();
+        }
 
-            assert!(!session.is_offline());
-            assert_eq!(session.container(), ContainerFormat::Mp4);
-            assert_eq!(session.protocol(), Protocol::Dash);
-            assert_eq!(
-                session.audio_transcode(),
-                Some((Decision::Transcode, AudioCodec::Aac))
-            );
-            assert_eq!(
-                session.video_transcode(),
-                Some((Decision::Copy, VideoCodec::Hevc))
-            );
+        #[plex_api_test_helper::offline_test]
+        async fn playback_session(#[future] server_authenticated: Mocked<Server>) {
+            let (server, mock_server) = server_authenticated.split();
 
             let mut m = mock_server.mock(|when, then| {
-                when.method(GET)
-                    .path("/video/:/transcode/universal/decision");
+                when.method(GET).path("/library/metadata/159637");
                 then.status(200)
                     .header("content-type", "text/json")
-                    .body_from_file("tests/mocks/transcode/video_hls_vp9_pcm.json");
+                    .body_from_file("../plex-api-mock-server/mocks/transcode/metadata_159637.json");
             });
 
-            let session = part
-                .create_streaming_session(Protocol::Hls, VideoTranscodeOptions::default())
+            let item: Movie = server
+                .item_by_id("159637")
                 .await
+                .unwrap()
+                .try_into()
                 .unwrap();
             m.assert();
             m.delete();
 
-            assert!(!session.is_offline());
-            assert_eq!(session.container(), ContainerFormat::MpegTs);
-            assert_eq!(session.protocol(), Protocol::Hls);
-            assert_eq!(
-                session.audio_transcode(),
-                Some((Decision::Copy, AudioCodec::Pcm))
-            );
-            assert_eq!(
-                session.video_transcode(),
-                Some((Decision::Transcode, VideoCodec::Vp9))
-            );
+            // Sessions created outside of a playback start their own.
+            assert_eq!(server.playback_session_id(), None);
 
             let mut m = mock_server.mock(|when, then| {
                 when.method(GET)
-                    .path("/video/:/transcode/universal/decision");
+                    .path("/video/:/transcode/universal/decision")
+                    .header_exists("X-Plex-Playback-Session-Id");
                 then.status(200)
                     .header("content-type", "text/json")
-                    .body_from_file("tests/mocks/transcode/video_hls_vp9_pcm.json");
+                    .body_from_file(
+                        "../plex-api-mock-server/mocks/transcode/video_dash_h264_mp3.json",
+                    );
             });
 
-            let error = part
+            let session = item
                 .create_streaming_session(Protocol::Dash, VideoTranscodeOptions::default())
                 .await
-                .err()
                 .unwrap();
             m.assert();
             m.delete();
+            assert!(session.playback_session_id().is_some());
 
-            if let plex_api::Error::TranscodeError(message) = error {
-                assert_eq!(message, "Server returned an invalid protocol.");
-            } else {
-                panic!("Unexpected error {error}");
-            }
+            let playback = server.with_playback_session();
+            let playback_session_id = playback.playback_session_id().unwrap().to_owned();
+            assert_ne!(session.playback_session_id(), Some(&*playback_session_id));
 
             let mut m = mock_server.mock(|when, then| {
                 when.method(GET)
-                    .path("/video/:/transcode/universal/decision");
+                    .path("/library/metadata/159637")
+                    .header("X-Plex-Playback-Session-Id", &playback_session_id)
+                    .header("X-Plex-Session-Identifier", &playback_session_id);
                 then.status(200)
                     .header("content-type", "text/json")
-                    .body_from_file("tests/mocks/transcode/video_dash_h264_mp3.json");
+                    .body_from_file("../plex-api-mock-server/mocks/transcode/metadata_159637.json");
             });
 
-            let error = part
-                .create_streaming_session(Protocol::Hls, VideoTranscodeOptions::default())
+            let item: Movie = playback
+                .item_by_id("159637")
                 .await
-                .err()
+                .unwrap()
+                .try_into()
                 .unwrap();
             m.assert();
             m.delete();
 
-            if let plex_api::Error::TranscodeError(message) = error {
-                assert_eq!(message, "Server returned an invalid protocol.");
-            } else {
-                panic!("Unexpected error {error}");
-            }
-
-            let mut m = mock_server.mock(|when, then| {
+            let m = mock_server.mock(|when, then| {
                 when.method(GET)
-                    .path("/video/:/transcode/universal/decision");
+                    .path("/video/:/transcode/universal/decision")
+                    .header("X-Plex-Playback-Session-Id", &playback_session_id);
                 then.status(200)
                     .header("content-type", "text/json")
-                    .body_from_file("tests/mocks/transcode/video_offline_h264_mp3.json");
+                    .body_from_file(
+                        "../plex-api-mock-server/mocks/transcode/video_dash_h264_mp3.json",
+                    );
             });
 
-            let session = part
-                .create_download_session(VideoTranscodeOptions::default())
+            let session = item
+                .create_streaming_session(Protocol::Dash, VideoTranscodeOptions::default())
                 .await
                 .unwrap();
             m.assert();
-            m.delete();
+            assert_eq!(session.playback_session_id(), Some(&*playback_session_id));
+        }
 
-            assert!(session.is_offline());
-            assert_eq!(session.container(), ContainerFormat::Mp4);
-            assert_eq!(session.protocol(), Protocol::Http);
-            assert_eq!(
-                session.audio_transcode(),
-                Some((Decision::Transcode, AudioCodec::Mp3))
-            );
-            assert_eq!(
-                session.video_transcode(),
-                Some((Decision::Transcode, VideoCodec::H264))
-            );
+        #[plex_api_test_helper::offline_test]
+        async fn audio_only(#[future] server_authenticated: Mocked<Server>) {
+            let (server, mock_server) = server_authenticated.split();
 
             let mut m = mock_server.mock(|when, then| {
-                when.method(GET).path("/library/metadata/1036");
+                when.method(GET).path("/library/metadata/159637");
                 then.status(200)
                     .header("content-type", "text/json")
-                    .body_from_file("tests/mocks/transcode/metadata_1036.json");
+                    .body_from_file("../plex-api-mock-server/mocks/transcode/metadata_159637.json");
             });
 
-            let item: Movie = server.item_by_id("1036").await.unwrap().try_into().unwrap();
+            let item: Movie = server
+                .item_by_id("159637")
+                .await
+                .unwrap()
+                .try_into()
+                .unwrap();
             m.assert();
             m.delete();
 
-            let media = &item.media()[0];
-            let part = &media.parts()[0];
-
             let mut m = mock_server.mock(|when, then| {
                 when.method(GET)
-                    .path("/video/:/transcode/universal/decision");
+                    .path("/video/:/transcode/universal/decision")
+                    .query_param("transcodeType", "music")
+                    .query_param("path", "/library/metadata/159637")
+                    .query_param("directPlay", "0")
+                    .query_param("directStream", "0")
+                    .query_param("directStreamAudio", "1")
+                    .query_param("musicBitrate", "128")
+                    .query_param("protocol", "dash")
+                    .query_param_missing("videoResolution")
+                    .is_true(|req| {
+                        let settings = expand_profile(req);
+
+                        assert_setting_count(&settings, "add-transcode-target", 1);
+                        assert_setting_count(&settings, "add-direct-play-profile", 0);
+
+                        assert_setting(
+                            &settings,
+                            "add-transcode-target",
+                            &[
+                                ("type", "musicProfile"),
+                                ("context", "streaming"),
+                                ("protocol", "dash"),
+                                ("container", "mp4"),
+                                ("audioCodec", "aac"),
+                                ("replace", "true"),
+                            ],
+                        );
+
+                        true
+                    });
                 then.status(200)
                     .header("content-type", "text/json")
-                    .body_from_file("tests/mocks/transcode/video_offline_refused.json");
+                    .body_from_file("tests/mocks/transcode/music_mp3.json");
             });
 
-            let error = part
-                .create_download_session(VideoTranscodeOptions::default())
+            let session = item
+                .create_audio_streaming_session(
+                    Protocol::Dash,
+                    MusicTranscodeOptions {
+                        bitrate: 128,
+                        codecs: vec![AudioCodec::Aac],
+                        ..Default::default()
+                    },
+                )
                 .await
-                .err()
                 .unwrap();
             m.assert();
             m.delete();
 
-            assert!(matches!(error, plex_api::Error::TranscodeRefused));
+            assert!(session.video_transcode().is_none());
+            assert_eq!(session.audio_transcode().unwrap().1, AudioCodec::Mp3);
+
+            let decision = session.decision().unwrap();
+            assert_eq!(decision.general_decision_code, Some(1001));
+            assert_eq!(decision.direct_play_decision_code, Some(3000));
+        }
+
+        #[plex_api_test_helper::offline_test]
+        async fn keepalive(#[future] server_authenticated: Mocked<Server>) {
+            let (server, mock_server) = server_authenticated.split();
 
             let mut m = mock_server.mock(|when, then| {
-                when.method(GET).path("/library/metadata/13194");
+                when.method(GET).path("/library/metadata/159637");
                 then.status(200)
                     .header("content-type", "text/json")
-                    .body_from_file("tests/mocks/transcode/metadata_13194.json");
+                    .body_from_file("../plex-api-mock-server/mocks/transcode/metadata_159637.json");
             });
 
             let item: Movie = server
-                .item_by_id("13194")
+                .item_by_id("159637")
                 .await
                 .unwrap()
                 .try_into()
@@ -946,18 +1851,51 @@ mod offline {
                     .path("/video/:/transcode/universal/decision");
                 then.status(200)
                     .header("content-type", "text/json")
-                    .body_from_file("tests/mocks/transcode/decision_13194.json");
+                    .body_from_file(
+                        "../plex-api-mock-server/mocks/transcode/video_dash_h264_mp3.json",
+                    );
             });
 
-            let error = part
-                .create_download_session(VideoTranscodeOptions::default())
+            let session = part
+                .create_streaming_session(Protocol::Dash, VideoTranscodeOptions::default())
                 .await
-                .err()
                 .unwrap();
             m.assert();
             m.delete();
 
-            assert!(matches!(error, plex_api::Error::TranscodeRefused));
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/ping")
+                    .query_param("session", session.session_id());
+                then.status(200);
+            });
+
+            session.ping().await.unwrap();
+            m.assert();
+            m.delete();
+
+            // The keepalive loop only ends when a ping fails.
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/ping")
+                    .query_param("session", session.session_id());
+                then.status(404);
+            });
+
+            let error = session
+                .keepalive(Duration::from_millis(10))
+                .await
+                .unwrap_err();
+            m.assert();
+            m.delete();
+
+            assert!(matches!(
+                error,
+                plex_api::Error::UnexpectedApiResponse {
+                    status_code: 404,
+                    ..
+                }
+            ));
         }
     }
 
@@ -1045,7 +1983,9 @@ mod offline {
                     });
                 then.status(200)
                     .header("content-type", "text/json")
-                    .body_from_file("tests/mocks/transcode/video_dash_h264_mp3.json");
+                    .body_from_file(
+                        "../plex-api-mock-server/mocks/transcode/video_dash_h264_mp3.json",
+                    );
             });
 
             part.create_streaming_session(
@@ -1111,6 +2051,32 @@ mod offline {
                 Some((Decision::Transcode, AudioCodec::Mp3))
             );
             assert_eq!(session.video_transcode(), None);
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/video/:/transcode/universal/start.mpd");
+                then.status(200)
+                    .header("content-type", "application/dash+xml")
+                    .body("<MPD/>");
+            });
+
+            let mut updates = Vec::new();
+            let mut buf = Vec::<u8>::new();
+            session
+                .download_with(
+                    &mut buf,
+                    Default::default(),
+                    |progress: plex_api::Progress| {
+                        updates.push((progress.transferred, progress.total))
+                    },
+                )
+                .await
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            assert_eq!(buf, b"<MPD/>");
+            assert_eq!(updates.last(), Some(&(6, Some(6))));
         }
     }
 
@@ -1118,7 +2084,7 @@ mod offline {
         use super::*;
         use plex_api::{
             library::{MetadataItem, Movie},
-            transcode::ArtTranscodeOptions,
+            transcode::{ArtTranscodeOptions, ImageFormat},
         };
 
         #[plex_api_test_helper::offline_test]
@@ -1129,7 +2095,7 @@ mod offline {
                 when.method(GET).path("/library/metadata/159637");
                 then.status(200)
                     .header("content-type", "text/json")
-                    .body_from_file("tests/mocks/transcode/metadata_159637.json");
+                    .body_from_file("../plex-api-mock-server/mocks/transcode/metadata_159637.json");
             });
 
             let item: Movie = server
@@ -1194,6 +2160,7 @@ mod offline {
                     ArtTranscodeOptions {
                         upscale: false,
                         min_size: false,
+                        ..Default::default()
                     },
                     &mut buf,
                 )
@@ -1204,6 +2171,68 @@ mod offline {
 
             assert_eq!(std::str::from_utf8(&buf).unwrap(), "foo");
         }
+
+        #[plex_api_test_helper::offline_test]
+        async fn photo_transcode_url(#[future] server_authenticated: Mocked<Server>) {
+            let (server, mock_server) = server_authenticated.split();
+
+            let options = ArtTranscodeOptions {
+                min_size: false,
+                blur: Some(20),
+                opacity: Some(60),
+                format: Some(ImageFormat::Png),
+                ..Default::default()
+            };
+
+            let url = server
+                .photo_transcode_url("/library/metadata/159637/art/1675330665", 640, 360, options)
+                .unwrap();
+            assert_eq!(url.path(), "/photo/:/transcode");
+            let mut params = url.query().unwrap().split('&').collect::<Vec<_>>();
+            params.sort();
+            assert_eq!(
+                params,
+                vec![
+                    "X-Plex-Token=fixture_auth_token",
+                    "blur=20",
+                    "format=png",
+                    "height=360",
+                    "minSize=0",
+                    "opacity=60",
+                    "upscale=1",
+                    "url=%2Flibrary%2Fmetadata%2F159637%2Fart%2F1675330665",
+                    "width=640",
+                ]
+            );
+
+            let mut m = mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/photo/:/transcode")
+                    .query_param("blur", "20")
+                    .query_param("opacity", "60")
+                    .query_param("format", "png")
+                    .query_param("url", "/library/metadata/159637/art/1675330665");
+                then.status(200)
+                    .header("content-type", "image/png")
+                    .body("foo");
+            });
+
+            let mut buf = Vec::<u8>::new();
+            server
+                .transcode_artwork(
+                    "/library/metadata/159637/art/1675330665",
+                    640,
+                    360,
+                    options,
+                    &mut buf,
+                )
+                .await
+                .unwrap();
+            m.assert();
+            m.delete();
+
+            assert_eq!(buf, b"foo");
+        }
     }
 }
 
@@ -2407,6 +3436,7 @@ mod online {
                     ArtTranscodeOptions {
                         upscale: false,
                         min_size: true,
+                        ..Default::default()
                     },
                     &mut buf,
                 )
@@ -2431,6 +3461,7 @@ mod online {
                     ArtTranscodeOptions {
                         upscale: false,
                         min_size: true,
+                        ..Default::default()
                     },
                     &mut buf,
                 )
@@ -2455,6 +3486,7 @@ mod online {
                     ArtTranscodeOptions {
                         upscale: false,
                         min_size: false,
+                        ..Default::default()
                     },
                     &mut buf,
                 )
@@ -2479,6 +3511,7 @@ mod online {
                     ArtTranscodeOptions {
                         upscale: true,
                         min_size: false,
+                        ..Default::default()
                     },
                     &mut buf,
                 )
@@ -0,0 +1,78 @@
+mod fixtures;
+
+mod offline {
+    use super::fixtures::offline::{server::*, Mocked};
+    use httpmock::Method::GET;
+    use plex_api::{media_container::server::library::MetadataMediaContainer, Server};
+    use std::path::Path;
+
+    /// Reads the same response in JSON and in XML, which the server sends
+    /// for some endpoints regardless of what was asked for. The XML mock is
+    /// found by the name of the JSON one.
+    #[plex_api_test_helper::offline_test]
+    #[case::movie_library("../plex-api-mock-server/mocks/server/media/movie_library.json")]
+    #[case::movie("tests/mocks/server/media/metadata_108.json")]
+    #[case::collection("tests/mocks/server/media/metadata_161.json")]
+    #[case::playlist("tests/mocks/server/media/metadata_168.json")]
+    #[case::show("tests/mocks/server/media/metadata_22.json")]
+    #[case::seasons("tests/mocks/server/media/tv_seasons.json")]
+    #[case::episodes("tests/mocks/server/media/tv_episodes.json")]
+    #[case::albums("tests/mocks/server/media/music_albums.json")]
+    #[case::tracks("tests/mocks/server/media/music_tracks.json")]
+    #[case::photos("tests/mocks/server/media/photo_album.json")]
+    #[case::numeric_titles("tests/mocks/server/media/numeric_titles.json")]
+    async fn metadata_from_xml(
+        #[future] server_authenticated: Mocked<Server>,
+        #[case] json_mock: &str,
+    ) {
+        let (server, mock_server) = server_authenticated.split();
+        let mock_name = Path::new(json_mock).file_stem().unwrap().to_str().unwrap();
+
+        let json = mock_server.mock(|when, then| {
+            when.method(GET).path("/json");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file(json_mock);
+        });
+        let xml = mock_server.mock(|when, then| {
+            when.method(GET).path("/xml");
+            then.status(200)
+                .header("content-type", "text/xml;charset=utf-8")
+                .body_from_file(format!("tests/mocks/server/media/xml/{mock_name}.xml"));
+        });
+
+        let from_json: MetadataMediaContainer =
+            server.raw().get("/json").media_container().await.unwrap();
+        json.assert();
+        let from_xml: MetadataMediaContainer =
+            server.raw().get("/xml").media_container().await.unwrap();
+        xml.assert();
+
+        assert_eq!(format!("{from_xml:#?}"), format!("{from_json:#?}"));
+    }
+
+    /// Titles that look like numbers are numbers in XML but must keep their
+    /// text.
+    #[plex_api_test_helper::offline_test]
+    async fn numeric_titles_from_xml(#[future] server_authenticated: Mocked<Server>) {
+        let (server, mock_server) = server_authenticated.split();
+
+        let xml = mock_server.mock(|when, then| {
+            when.method(GET).path("/xml");
+            then.status(200)
+                .header("content-type", "text/xml;charset=utf-8")
+                .body_from_file("tests/mocks/server/media/xml/numeric_titles.xml");
+        });
+
+        let container: MetadataMediaContainer =
+            server.raw().get("/xml").media_container().await.unwrap();
+        xml.assert();
+
+        assert_eq!(container.library_section_title.as_deref(), Some("2024"));
+        let movie = &container.metadata[0];
+        assert_eq!(movie.title, "2046");
+        assert_eq!(movie.edition_title.as_deref(), Some("2.0"));
+        assert_eq!(movie.library_section_title.as_deref(), Some("2024"));
+        assert_eq!(movie.tagline.as_deref(), Some("1"));
+    }
+}
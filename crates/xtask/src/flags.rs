@@ -44,6 +44,10 @@ xflags::xflags! {
             /// If passed, the online tests will run with the `tests_deny_unknown_fields` feature enabled.
             optional --deny-unknown-fields
 
+            /// Save the responses received by the online tests into `tests/mocks/recorded`,
+            /// to be used as mocks for offline tests.
+            optional --record-mocks
+
             /// Path where to store the data. See `plex-data` subcommand for details.
             optional --plex-data-path path: String
 
@@ -124,6 +128,7 @@ pub struct Test {
     pub online: bool,
     pub client_id: Option<String>,
     pub deny_unknown_fields: bool,
+    pub record_mocks: bool,
     pub plex_data_path: Option<String>,
     pub test_name: Option<String>,
     pub github_actions: bool,
@@ -355,6 +355,10 @@ impl flags::Test {
             features.push_str(",tests_deny_unknown_fields");
         }
 
+        if self.record_mocks {
+            features.push_str(",tests_record_mocks");
+        }
+
         if !server_auth_token.is_empty() && server_auth_token != auth_token {
             features.push_str(",tests_shared_server_access_token");
         }